@@ -15,6 +15,10 @@ pub type Ident = Arc<String>;
 
 pub(crate) type Snapshot = HashMap<Ident, u32>;
 pub(crate) type Snapshots = HashMap<Ident, Snapshot>;
+/// The versions of the mutable variables at an assertion,
+/// used to report the variables' values when the assertion fails.
+/// None until var_to_const assigns the versions.
+pub type AssertVersions = Option<Arc<HashMap<Ident, u32>>>;
 
 pub type Typ = Arc<TypX>;
 pub type Typs = Arc<Vec<Typ>>;
@@ -97,11 +101,14 @@ pub enum Quant {
 pub type Trigger = Exprs;
 pub type Triggers = Arc<Vec<Trigger>>;
 
+/// Optional name for a quantifier, passed to the SMT solver as :qid for profiling
+pub type Qid = Option<Ident>;
+
 pub type Bind = Arc<BindX>;
 #[derive(Clone, Debug)]
 pub enum BindX {
     Let(Binders<Expr>),
-    Quant(Quant, Binders<Typ>, Triggers),
+    Quant(Quant, Binders<Typ>, Triggers, Qid),
     Lambda(Binders<Typ>),
     // choose Binders s.t. Expr is true
     Choose(Binders<Typ>, Triggers, Expr),
@@ -126,7 +133,7 @@ pub enum ExprX {
     // Sometimes an axiom will have additional error messages. If an assert fails
     // and this axiom was relevant, then we append the error labels to the Error.
     LabeledAxiom(ErrorLabels, Expr),
-    LabeledAssertion(Error, Expr),
+    LabeledAssertion(AssertVersions, Error, Expr),
 }
 
 pub type Stmt = Arc<StmtX>;
@@ -134,7 +141,7 @@ pub type Stmts = Arc<Vec<Stmt>>;
 #[derive(Debug)]
 pub enum StmtX {
     Assume(Expr),
-    Assert(Error, Expr),
+    Assert(AssertVersions, Error, Expr),
     Havoc(Ident),
     Assign(Ident, Expr),
     // create a named snapshot of the state of the variables
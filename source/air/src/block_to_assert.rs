@@ -11,14 +11,18 @@ fn stmt_to_expr(label_n: &mut u64, locals: &mut Vec<Decl>, stmt: &Stmt, pred: Ex
             // wp((assume Q), P) = Q ==> P
             mk_implies(&expr, &pred)
         }
-        StmtX::Assert(span, expr) => {
+        StmtX::Assert(versions, span, expr) => {
             // wp((assert Q), P) = Q /\ P
-            let assertion = Arc::new(ExprX::LabeledAssertion(span.clone(), expr.clone()));
+            let assertion =
+                Arc::new(ExprX::LabeledAssertion(versions.clone(), span.clone(), expr.clone()));
             mk_and(&vec![assertion, pred])
         }
         StmtX::Havoc(_) => panic!("internal error: Havoc in block_to_assert"),
         StmtX::Assign(_, _) => panic!("internal error: Assign in block_to_assert"),
-        StmtX::Snapshot(_) => panic!("internal error: Snapshot in block_to_assert"),
+        StmtX::Snapshot(_) => {
+            // wp(snapshot, P) = P
+            pred
+        }
         StmtX::DeadEnd(stmt) => {
             // wp(deadend(s), P) = wp(s, true) /\ P
             let wps = stmt_to_expr(label_n, locals, stmt, mk_true());
@@ -57,6 +61,6 @@ pub(crate) fn lower_query(query: &Query) -> Query {
     let mut locals: Vec<Decl> = (*query.local).clone();
     let mut switch_label: u64 = 0;
     let expr = stmt_to_expr(&mut switch_label, &mut locals, &query.assertion, mk_true());
-    let assertion = Arc::new(StmtX::Assert(error_from_spans(vec![]), expr));
+    let assertion = Arc::new(StmtX::Assert(None, error_from_spans(vec![]), expr));
     Arc::new(QueryX { local: Arc::new(locals), assertion })
 }
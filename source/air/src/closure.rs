@@ -322,8 +322,12 @@ fn simplify_choose(
             }
             let call = Arc::new(ExprX::Apply(closure_fun.clone(), Arc::new(xholes)));
             let and = mk_and(&vec![cond.clone(), mk_eq(&call, &body)]);
-            let existsbind =
-                Arc::new(BindX::Quant(Quant::Exists, binders.clone(), Arc::new(new_triggers)));
+            let existsbind = Arc::new(BindX::Quant(
+                Quant::Exists,
+                binders.clone(),
+                Arc::new(new_triggers),
+                None,
+            ));
             let exists1 = Arc::new(ExprX::Bind(existsbind.clone(), cond));
             let exists2 = Arc::new(ExprX::Bind(existsbind, and));
             let imply = Arc::new(ExprX::Binary(BinaryOp::Implies, exists1, exists2));
@@ -506,7 +510,7 @@ fn simplify_expr(ctxt: &mut Context, state: &mut State, expr: &Expr) -> (Typ, Ex
                 let expr = Arc::new(ExprX::Bind(bind, e1.clone()));
                 (typ1, expr, t)
             }
-            BindX::Quant(quant, binders, triggers) => {
+            BindX::Quant(quant, binders, triggers, qid) => {
                 ctxt.typing.decls.push_scope(true);
                 let mut typs: Vec<Typ> = Vec::new();
                 for binder in binders.iter() {
@@ -540,7 +544,8 @@ fn simplify_expr(ctxt: &mut Context, state: &mut State, expr: &Expr) -> (Typ, Ex
                 }
                 let e1 = es[i].clone();
                 let typ = Arc::new(TypX::Bool);
-                let bind = BindX::Quant(*quant, binders.clone(), Arc::new(new_triggers));
+                let bind =
+                    BindX::Quant(*quant, binders.clone(), Arc::new(new_triggers), qid.clone());
                 (typ, Arc::new(ExprX::Bind(Arc::new(bind), e1)), t)
             }
             BindX::Lambda(binders) => simplify_lambda(ctxt, state, binders, e1),
@@ -548,11 +553,11 @@ fn simplify_expr(ctxt: &mut Context, state: &mut State, expr: &Expr) -> (Typ, Ex
                 simplify_choose(ctxt, state, binders, triggers, cond, e1)
             }
         },
-        ExprX::LabeledAssertion(l, e1) => {
+        ExprX::LabeledAssertion(v, l, e1) => {
             let (es, ts) = simplify_exprs_ref(ctxt, state, &vec![e1]);
             let (typ, _) = ts[0].clone();
             let (es, t) = enclose(state, App::LabeledAssertion, es, ts);
-            (typ, Arc::new(ExprX::LabeledAssertion(l.clone(), es[0].clone())), t)
+            (typ, Arc::new(ExprX::LabeledAssertion(v.clone(), l.clone(), es[0].clone())), t)
         }
         ExprX::LabeledAxiom(l, e1) => {
             let (es, ts) = simplify_exprs_ref(ctxt, state, &vec![e1]);
@@ -569,9 +574,9 @@ fn simplify_stmt_rec(ctxt: &mut Context, state: &mut State, stmt: &Stmt) -> Stmt
             let (_, expr, _) = simplify_expr(ctxt, state, expr);
             Arc::new(StmtX::Assume(expr))
         }
-        StmtX::Assert(span, expr) => {
+        StmtX::Assert(v, span, expr) => {
             let (_, expr, _) = simplify_expr(ctxt, state, expr);
-            Arc::new(StmtX::Assert(span.clone(), expr))
+            Arc::new(StmtX::Assert(v.clone(), span.clone(), expr))
         }
         StmtX::Havoc(_) => stmt.clone(),
         StmtX::Assign(x, expr) => {
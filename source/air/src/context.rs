@@ -1,20 +1,23 @@
-use crate::ast::{Command, CommandX, Decl, Ident, Query, Typ, TypeError, Typs};
+use crate::ast::{
+    AssertVersions, Command, CommandX, Decl, Expr, Ident, Query, Typ, TypeError, Typs,
+};
 use crate::closure::ClosureTerm;
 use crate::emitter::Emitter;
 use crate::errors::{Error, ErrorLabels};
-use crate::model::Model;
+use crate::model::{Model, ModelExpr, ModelExprX};
 use crate::node;
 use crate::printer::{macro_push_node, str_to_node};
 use crate::scope_map::ScopeMap;
 use crate::smt_manager::SmtManager;
 use crate::typecheck::Typing;
 use sise::Node;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub(crate) struct AssertionInfo {
+    pub(crate) versions: AssertVersions,
     pub(crate) error: Error,
     pub(crate) label: Ident,
     pub(crate) decl: Decl,
@@ -36,12 +39,25 @@ pub enum ValidityResult {
     UnexpectedSmtOutput(String),
 }
 
+/// Statistics about a single check-sat sent to the SMT solver
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QueryStats {
+    /// Time spent waiting for the SMT solver
+    pub time: Duration,
+    /// Resources consumed by the SMT solver, as measured by Z3's rlimit-count
+    pub rlimit_count: u64,
+    /// True if the solver gave up because it ran out of resources (e.g. the rlimit)
+    pub resource_exhausted: bool,
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum ContextState {
     NotStarted,
     ReadyForQuery,
     FoundResult,
-    FoundInvalid(Vec<AssertionInfo>, Model),
+    // The assertion infos, the model before the failed assertion is known,
+    // and the label of the failed assertion
+    FoundInvalid(Vec<AssertionInfo>, Model, Ident),
 }
 
 pub struct Context {
@@ -58,6 +74,10 @@ pub struct Context {
     pub(crate) debug: bool,
     pub(crate) ignore_unexpected_smt: bool,
     pub(crate) rlimit: u32,
+    pub(crate) z3_params: HashMap<String, String>,
+    pub(crate) rlimit_count: u64,
+    pub(crate) last_query_stats: QueryStats,
+    pub(crate) profile_quantifiers: bool,
     pub(crate) air_initial_log: Emitter,
     pub(crate) air_middle_log: Emitter,
     pub(crate) air_final_log: Emitter,
@@ -83,6 +103,10 @@ impl Context {
             debug: false,
             ignore_unexpected_smt: false,
             rlimit: 0,
+            z3_params: HashMap::new(),
+            rlimit_count: 0,
+            last_query_stats: QueryStats::default(),
+            profile_quantifiers: false,
             air_initial_log: Emitter::new(false, false, None),
             air_middle_log: Emitter::new(false, false, None),
             air_final_log: Emitter::new(false, false, None),
@@ -131,6 +155,49 @@ impl Context {
         (self.time_smt_init, self.time_smt_run)
     }
 
+    /// Statistics for the most recent call to check_valid or check_valid_again
+    pub fn get_last_query_stats(&self) -> QueryStats {
+        self.last_query_stats
+    }
+
+    /// Ask the SMT solver to count quantifier instantiations.
+    /// Must be called before any commands are sent to the SMT solver.
+    pub fn set_profile_quantifiers(&mut self, profile_quantifiers: bool) {
+        self.profile_quantifiers = profile_quantifiers;
+        self.smt_manager.set_profile_quantifiers(profile_quantifiers);
+        if profile_quantifiers {
+            self.set_z3_param_bool("smt.qi.profile", true, true);
+        }
+    }
+
+    /// Return the number of instantiations of each quantifier (identified by its :qid)
+    /// deleted from the SMT solver's context since the last call to take_quantifier_profile.
+    /// Quantifiers are deleted when the scope containing them is popped,
+    /// so the caller should pop the relevant scopes before calling this.
+    /// Quantifiers without a :qid are reported under Z3's generated names.
+    pub fn take_quantifier_profile(&mut self) -> Vec<(String, u64)> {
+        assert!(self.profile_quantifiers);
+        let lines = self
+            .smt_manager
+            .get_smt_process()
+            .take_quantifier_profile(self.smt_log.take_pipe_data());
+        let mut profile: Vec<(String, u64)> = Vec::new();
+        for line in lines {
+            // line has the form "qid : instances : max generation : max cost"
+            let fields: Vec<&str> = line.split(" : ").map(|f| f.trim()).collect();
+            if fields.len() >= 2 {
+                if let Ok(count) = fields[1].parse::<u64>() {
+                    profile.push((fields[0].to_string(), count));
+                }
+            }
+        }
+        profile
+    }
+
+    pub fn get_rlimit(&self) -> u32 {
+        self.rlimit
+    }
+
     pub fn set_rlimit(&mut self, rlimit: u32) {
         self.rlimit = rlimit;
         self.air_initial_log.log_set_option("rlimit", &rlimit.to_string());
@@ -155,6 +222,7 @@ impl Context {
     }
 
     fn log_set_z3_param(&mut self, option: &str, value: &str) {
+        self.z3_params.insert(option.to_string(), value.to_string());
         self.air_initial_log.log_set_option(option, value);
         self.air_middle_log.log_set_option(option, value);
         self.air_final_log.log_set_option(option, value);
@@ -211,6 +279,40 @@ impl Context {
         }
     }
 
+    /// Return the current value of an SMT solver option,
+    /// either as last set through this context or, if never set, as reported by the solver.
+    /// Returns None if the solver doesn't recognize the option.
+    /// (This does not apply to rlimit; see get_rlimit.)
+    pub fn get_z3_param(&mut self, option: &str) -> Option<String> {
+        if let Some(value) = self.z3_params.get(option) {
+            return Some(value.clone());
+        }
+        self.smt_log.log_get_option(option);
+        let smt_output =
+            self.smt_manager.get_smt_process().send_commands(self.smt_log.take_pipe_data());
+        match &smt_output[..] {
+            // The solver reports the value as a string literal
+            [value] if !value.starts_with("(error") && !value.starts_with("unsupported") => {
+                Some(value.trim().trim_matches('"').to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the SMT solver's version, as reported by (get-info :version)
+    pub fn get_smt_version(&mut self) -> String {
+        self.smt_log.log_get_info("version");
+        let smt_output =
+            self.smt_manager.get_smt_process().send_commands(self.smt_log.take_pipe_data());
+        match &smt_output[..] {
+            [line] => match crate::smt_verify::parse_info_line(line, "version") {
+                Some(version) => version.trim_matches('"').to_string(),
+                None => line.clone(),
+            },
+            _ => smt_output.join("\n"),
+        }
+    }
+
     pub(crate) fn push_name_scope(&mut self) {
         self.axiom_infos.push_scope(false);
         self.lambda_map.push_scope(false);
@@ -283,10 +385,10 @@ impl Context {
         };
         let (query, snapshots, local_vars) = crate::var_to_const::lower_query(&query);
         self.air_middle_log.log_query(&query);
+        let model = Model::new(snapshots, local_vars, query.assertion.clone());
         let query = crate::block_to_assert::lower_query(&query);
         self.air_final_log.log_query(&query);
 
-        let model = Model::new(snapshots, local_vars);
         let validity = crate::smt_verify::smt_check_query(self, &query, model);
 
         validity
@@ -297,8 +399,14 @@ impl Context {
     /// errors, with the goal of making sure that the earliest error gets reported.
     /// Once only_check_earlier is set, it remains set until finish_query is called.
     pub fn check_valid_again(&mut self, only_check_earlier: bool) -> ValidityResult {
-        if let ContextState::FoundInvalid(infos, air_model) = self.state.clone() {
-            crate::smt_verify::smt_check_assertion(self, infos, air_model, only_check_earlier)
+        if let ContextState::FoundInvalid(infos, air_model, label) = self.state.clone() {
+            crate::smt_verify::smt_check_assertion(
+                self,
+                infos,
+                air_model,
+                Some(&label),
+                only_check_earlier,
+            )
         } else {
             panic!("check_valid_again expected query to be ValidityResult::Invalid");
         }
@@ -320,6 +428,22 @@ impl Context {
         smt_output[0].clone()
     }
 
+    /// Evaluate expr in the model of the most recent query, which must have been invalid.
+    /// Returns None if the SMT solver cannot evaluate expr (e.g. because it is ill-typed).
+    pub fn eval_model_expr(&mut self, expr: &Expr) -> Option<ModelExpr> {
+        let node = crate::printer::Printer::new(true).expr_to_node(expr);
+        self.smt_log.log_eval_completion(node);
+        let smt_output =
+            self.smt_manager.get_smt_process().send_commands(self.smt_log.take_pipe_data());
+        let text = smt_output.join("\n");
+        let mut parser = sise::Parser::new(text.as_bytes());
+        let node = sise::read_into_tree(&mut parser).ok()?;
+        match &node {
+            Node::List(nodes) if nodes.first() == Some(&Node::Atom("error".to_string())) => None,
+            _ => Some(ModelExprX::from_node(&node)),
+        }
+    }
+
     pub fn command(&mut self, command: &Command) -> ValidityResult {
         match &**command {
             CommandX::Push => {
@@ -146,9 +146,28 @@ impl Emitter {
         }
     }
 
+    pub fn log_get_info(&mut self, info: &str) {
+        if !self.is_none() {
+            self.log_node(&nodes!(get-info {Node::Atom(":".to_owned() + info)}));
+        }
+    }
+
+    pub fn log_get_option(&mut self, option: &str) {
+        if !self.is_none() {
+            self.log_node(&nodes!(get-option {Node::Atom(":".to_owned() + option)}));
+        }
+    }
+
     pub fn log_eval(&mut self, expr: Node) {
         if !self.is_none() {
             self.log_node(&nodes!(eval { expr }));
         }
     }
+
+    /// Like log_eval, but asks the solver to pick values for symbols that the model leaves open
+    pub fn log_eval_completion(&mut self, expr: Node) {
+        if !self.is_none() {
+            self.log_node(&nodes!(eval { expr } {Node::Atom(":completion".to_owned())} true));
+        }
+    }
 }
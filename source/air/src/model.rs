@@ -1,12 +1,20 @@
 //! Provides an AIR-level interface to the model returned by the SMT solver
 //! when it reaches a SAT conclusion
 
-use crate::ast::{Binders, Decl, DeclX, Ident, Snapshots, Typ};
+use crate::ast::{AssertVersions, Binders, Decl, DeclX, Ident, Snapshots, Stmt, StmtX, Typ};
+use crate::context::Context;
+use sise::Node;
 use std::collections::HashSet;
+use std::fmt;
 use std::sync::Arc;
 
-/// For now, expressions are just strings, but we can later change this to a more detailed enum
-pub type ModelExpr = Arc<String>;
+/// A term from the SMT model, such as a literal, a constant, or an application
+pub type ModelExpr = Arc<ModelExprX>;
+#[derive(Debug)]
+pub enum ModelExprX {
+    Atom(String),
+    List(Vec<ModelExpr>),
+}
 
 /// Represent (define-fun f (...parameters...) return-type body) from SMT model
 /// (This includes constants, which have an empty parameter list.)
@@ -28,6 +36,60 @@ pub struct Model {
     id_snapshots: Snapshots,
     /// The list of paramters of the function
     parameters: HashSet<Ident>,
+    /// The versions of the mutable variables at the assertion that failed, if known
+    failed_versions: AssertVersions,
+    /// The query's assertion after converting mutable variables to constants,
+    /// which still contains the snapshots
+    assertion: Stmt,
+}
+
+impl ModelExprX {
+    pub fn from_node(node: &Node) -> ModelExpr {
+        match node {
+            Node::Atom(s) => Arc::new(ModelExprX::Atom(s.clone())),
+            Node::List(nodes) => {
+                Arc::new(ModelExprX::List(nodes.iter().map(ModelExprX::from_node).collect()))
+            }
+        }
+    }
+
+    pub fn is_atom(&self, s: &str) -> bool {
+        match self {
+            ModelExprX::Atom(a) => a == s,
+            ModelExprX::List(_) => false,
+        }
+    }
+
+    /// Integer literal, written either as n or as (- n)
+    pub fn as_int(&self) -> Option<String> {
+        match self {
+            ModelExprX::Atom(a) if a.chars().all(|c| c.is_ascii_digit()) && a.len() > 0 => {
+                Some(a.clone())
+            }
+            ModelExprX::List(es) if es.len() == 2 && es[0].is_atom("-") => {
+                es[1].as_int().filter(|n| !n.starts_with('-')).map(|n| format!("-{}", n))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ModelExprX {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelExprX::Atom(a) => write!(f, "{}", a),
+            ModelExprX::List(es) => {
+                write!(f, "(")?;
+                for (i, e) in es.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
 }
 
 impl Model {
@@ -35,7 +97,8 @@ impl Model {
     /// # Arguments
     /// * `model` - The model that Z3 returns
     /// * `snapshots` - Internal mapping of snapshot IDs to snapshots that map AIR variables to usage counts.
-    pub fn new(snapshots: Snapshots, params: Vec<Decl>) -> Model {
+    /// * `assertion` - The query's assertion after converting mutable variables to constants.
+    pub fn new(snapshots: Snapshots, params: Vec<Decl>, assertion: Stmt) -> Model {
         // println!("Creating a new model with {} snapshots", snapshots.len());
         // for (sid, snapshot) in &snapshots {
         //     println!("{:?}", sid);
@@ -51,7 +114,43 @@ impl Model {
             }
         }
 
-        Model { id_snapshots: snapshots, parameters }
+        Model { id_snapshots: snapshots, parameters, failed_versions: None, assertion }
+    }
+
+    /// Record the variable versions of the assertion that failed
+    pub(crate) fn set_failed_assertion(&mut self, versions: &AssertVersions) {
+        self.failed_versions = versions.clone();
+    }
+
+    /// The constants that hold the values of the AIR variables at the failed assertion,
+    /// as pairs (AIR variable, SMT constant), sorted by variable name.
+    /// Empty if the failed assertion is not known.
+    pub fn failed_assertion_vars(&self) -> Vec<(Ident, Ident)> {
+        let versions = match &self.failed_versions {
+            None => return vec![],
+            Some(versions) => versions,
+        };
+        let mut vars: Vec<(Ident, Ident)> = Vec::new();
+        for name in self.parameters.iter() {
+            vars.push((name.clone(), name.clone()));
+        }
+        for (name, version) in versions.iter() {
+            let name_at = Arc::new(crate::var_to_const::rename_var(name, *version));
+            vars.push((name.clone(), name_at));
+        }
+        vars.sort();
+        vars
+    }
+
+    /// The snapshots that the counterexample's execution of the query reaches, in execution order.
+    /// Execution reaches a statement if the model satisfies all the assumptions before it,
+    /// so that it only enters the cases of a switch whose assumptions hold.
+    /// Assumptions that the SMT solver cannot evaluate to true or false are treated as satisfied.
+    /// Must be called while context still holds the model of the failed query.
+    pub fn reached_snapshots(&self, context: &mut Context) -> Vec<Ident> {
+        let mut snapshots: Vec<Ident> = Vec::new();
+        reach_stmt(context, &self.assertion, &mut snapshots);
+        snapshots
     }
 
     pub fn translate_variable(&self, sid: &Ident, name: &Ident) -> Option<String> {
@@ -67,3 +166,37 @@ impl Model {
         None
     }
 }
+
+/// Append the snapshots in stmt that execution reaches to snapshots,
+/// and return true if execution reaches the end of stmt
+fn reach_stmt(context: &mut Context, stmt: &Stmt, snapshots: &mut Vec<Ident>) -> bool {
+    match &**stmt {
+        StmtX::Assume(expr) => {
+            let expr = crate::smt_verify::elim_zero_args_expr(expr);
+            match context.eval_model_expr(&expr) {
+                Some(value) => !value.is_atom("false"),
+                None => true,
+            }
+        }
+        StmtX::Assert(..) => true,
+        StmtX::Havoc(_) | StmtX::Assign(..) => {
+            panic!("internal error: Havoc or Assign after var_to_const")
+        }
+        StmtX::Snapshot(snap) => {
+            snapshots.push(snap.clone());
+            true
+        }
+        StmtX::DeadEnd(s) => {
+            reach_stmt(context, s, snapshots);
+            true
+        }
+        StmtX::Block(ss) => ss.iter().all(|s| reach_stmt(context, s, snapshots)),
+        StmtX::Switch(ss) => {
+            let mut reached = false;
+            for s in ss.iter() {
+                reached |= reach_stmt(context, s, snapshots);
+            }
+            reached
+        }
+    }
+}
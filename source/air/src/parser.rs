@@ -1,11 +1,11 @@
 use crate::ast::{
     BinaryOp, BindX, Binder, BinderX, Binders, Command, CommandX, Commands, Constant, Decl, DeclX,
-    Decls, Expr, ExprX, Exprs, MultiOp, Quant, QueryX, Span, Stmt, StmtX, Stmts, Trigger, Triggers,
-    Typ, TypX, UnaryOp,
+    Decls, Expr, ExprX, Exprs, MultiOp, Qid, Quant, QueryX, Span, Stmt, StmtX, Stmts, Trigger,
+    Triggers, Typ, TypX, UnaryOp,
 };
 use crate::errors::{error_from_labels, error_from_spans};
 use crate::errors::{ErrorLabel, ErrorLabels};
-use crate::model::{ModelDef, ModelDefX, ModelDefs};
+use crate::model::{ModelDef, ModelDefX, ModelDefs, ModelExprX};
 use crate::printer::node_to_string;
 use sise::Node;
 use std::io::Write;
@@ -120,7 +120,7 @@ impl Parser {
                     [Node::Atom(s), Node::List(nodes), e] if s.to_string() == "location" => {
                         let error = error_from_labels(self.nodes_to_labels(nodes)?);
                         let expr = self.node_to_expr(e)?;
-                        return Ok(Arc::new(ExprX::LabeledAssertion(error, expr)));
+                        return Ok(Arc::new(ExprX::LabeledAssertion(None, error, expr)));
                     }
                     [Node::Atom(s), Node::List(nodes), e] if s.to_string() == "axiom_location" => {
                         let labels = self.nodes_to_labels(nodes)?;
@@ -299,13 +299,22 @@ impl Parser {
         Ok(crate::ast_util::mk_let(&binders, &self.node_to_expr(expr)?))
     }
 
-    fn nodes_to_triggers(&self, nodes: &[Node]) -> Result<Triggers, String> {
+    fn nodes_to_triggers(&self, nodes: &[Node]) -> Result<(Triggers, Qid), String> {
         let mut triggers: Vec<Trigger> = Vec::new();
+        let mut qid: Qid = None;
         let mut expect_pattern = true;
+        let mut expect_qid = false;
         for node in nodes {
             match node {
                 Node::Atom(s) if s.to_string() == ":pattern" && expect_pattern => {}
-                Node::List(trigger_nodes) if !expect_pattern => {
+                Node::Atom(s) if s.to_string() == ":qid" && expect_pattern => {
+                    expect_qid = true;
+                }
+                Node::Atom(s) if expect_qid => {
+                    qid = Some(Arc::new(s.clone()));
+                    expect_qid = false;
+                }
+                Node::List(trigger_nodes) if !expect_pattern && !expect_qid => {
                     triggers.push(self.nodes_to_exprs(trigger_nodes)?);
                 }
                 _ => {
@@ -317,7 +326,7 @@ impl Parser {
             }
             expect_pattern = !expect_pattern;
         }
-        Ok(Arc::new(triggers))
+        Ok((Arc::new(triggers), qid))
     }
 
     fn node_to_quant_expr(
@@ -327,18 +336,18 @@ impl Parser {
         expr: &Node,
     ) -> Result<Expr, String> {
         let binders = self.nodes_to_binders(binder_nodes, &|n| self.node_to_typ(n))?;
-        let (expr, triggers) = match &expr {
+        let (expr, (triggers, qid)) = match &expr {
             Node::List(nodes) if nodes.len() >= 2 => match &nodes[0] {
                 Node::Atom(s) if s.to_string() == "!" => {
                     (&nodes[1], self.nodes_to_triggers(&nodes[2..])?)
                 }
-                _ => (expr, Arc::new(vec![])),
+                _ => (expr, (Arc::new(vec![]), None)),
             },
-            _ => (expr, Arc::new(vec![])),
+            _ => (expr, (Arc::new(vec![]), None)),
         };
         let expr = self.node_to_expr(expr)?;
         let (body, bind) = match quant_or_choose {
-            QuantOrChoose::Quant(quant) => (expr, BindX::Quant(quant, binders, triggers)),
+            QuantOrChoose::Quant(quant) => (expr, BindX::Quant(quant, binders, triggers, qid)),
             QuantOrChoose::Choose(body) => (body, BindX::Choose(binders, triggers, expr)),
         };
         Ok(Arc::new(ExprX::Bind(Arc::new(bind), body)))
@@ -353,7 +362,7 @@ impl Parser {
                 }
                 [Node::Atom(s), e] if s.to_string() == "assert" => {
                     let expr = self.node_to_expr(&e)?;
-                    Ok(Arc::new(StmtX::Assert(error_from_spans(vec![]), expr)))
+                    Ok(Arc::new(StmtX::Assert(None, error_from_spans(vec![]), expr)))
                 }
                 [Node::Atom(s), Node::Atom(x)] if s.to_string() == "havoc" && is_symbol(x) => {
                     Ok(Arc::new(StmtX::Havoc(Arc::new(x.clone()))))
@@ -371,7 +380,7 @@ impl Parser {
                     let labels = self.nodes_to_labels(nodes)?;
                     let error = error_from_labels(labels);
                     let expr = self.node_to_expr(&e)?;
-                    Ok(Arc::new(StmtX::Assert(error, expr)))
+                    Ok(Arc::new(StmtX::Assert(None, error, expr)))
                 }
                 [Node::Atom(s), e] if s.to_string() == "deadend" => {
                     let stmt = self.node_to_stmt(&e)?;
@@ -496,7 +505,7 @@ impl Parser {
                     let name = Arc::new(x.clone());
                     let params = self.nodes_to_binders(param_nodes, &|t| self.node_to_typ(t))?;
                     let ret = self.node_to_typ(t)?;
-                    let body = Arc::new(node_to_string(body));
+                    let body = ModelExprX::from_node(body);
                     Ok(Some(Arc::new(ModelDefX { name, params, ret, body })))
                 }
                 _ => Ok(None),
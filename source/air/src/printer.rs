@@ -1,6 +1,7 @@
 use crate::ast::{
-    BinaryOp, BindX, Binder, Binders, Constant, Datatypes, Decl, DeclX, Expr, ExprX, Exprs, Ident,
-    MultiOp, Quant, Query, QueryX, Stmt, StmtX, Triggers, Typ, TypX, Typs, UnaryOp,
+    BinaryOp, BindX, Binder, Binders, Command, CommandX, Constant, Datatypes, Decl, DeclX, Expr,
+    ExprX, Exprs, Ident, MultiOp, Qid, Quant, Query, QueryX, Stmt, StmtX, Triggers, Typ, TypX,
+    Typs, UnaryOp,
 };
 use crate::errors::all_msgs_from_error;
 use crate::util::vec_map;
@@ -220,8 +221,8 @@ impl Printer {
                 nodes!(ite {self.expr_to_node(expr1)} {self.expr_to_node(expr2)} {self.expr_to_node(expr3)})
             }
             ExprX::Bind(bind, expr) => {
-                let with_triggers = |expr: &Expr, triggers: &Triggers| {
-                    if triggers.len() == 0 {
+                let with_triggers = |expr: &Expr, triggers: &Triggers, qid: &Qid| {
+                    if triggers.len() == 0 && qid.is_none() {
                         self.expr_to_node(expr)
                     } else {
                         let mut nodes: Vec<Node> = Vec::new();
@@ -231,6 +232,10 @@ impl Printer {
                             nodes.push(str_to_node(":pattern"));
                             nodes.push(self.exprs_to_node(trigger));
                         }
+                        if let Some(qid) = qid {
+                            nodes.push(str_to_node(":qid"));
+                            nodes.push(str_to_node(qid));
+                        }
                         Node::List(nodes)
                     }
                 };
@@ -239,13 +244,13 @@ impl Printer {
                         let s_binders = self.binders_to_node(binders, &|e| self.expr_to_node(e));
                         nodes!(let {s_binders} {self.expr_to_node(expr)})
                     }
-                    BindX::Quant(quant, binders, triggers) => {
+                    BindX::Quant(quant, binders, triggers, qid) => {
                         let s_quant = match quant {
                             Quant::Forall => "forall",
                             Quant::Exists => "exists",
                         };
                         let s_binders = self.binders_to_node(binders, &|t| self.typ_to_node(t));
-                        let body = with_triggers(expr, triggers);
+                        let body = with_triggers(expr, triggers, qid);
                         nodes!({str_to_node(s_quant)} {s_binders} {body})
                     }
                     BindX::Lambda(binders) => {
@@ -253,7 +258,7 @@ impl Printer {
                     }
                     BindX::Choose(binders, triggers, expr_cond) => {
                         let s_binders = self.binders_to_node(binders, &|t| self.typ_to_node(t));
-                        let cond = with_triggers(expr_cond, triggers);
+                        let cond = with_triggers(expr_cond, triggers, &None);
                         let body = self.expr_to_node(expr);
                         nodes!(choose {s_binders} {cond} {body})
                     }
@@ -267,7 +272,7 @@ impl Printer {
                     nodes!(axiom_location {Node::List(spans)} {self.expr_to_node(expr)})
                 }
             }
-            ExprX::LabeledAssertion(error, expr) => {
+            ExprX::LabeledAssertion(_, error, expr) => {
                 let spans =
                     vec_map(&all_msgs_from_error(error), |s| Node::Atom(format!("\"{}\"", s)));
                 if spans.len() == 0 {
@@ -359,7 +364,7 @@ impl Printer {
     pub fn stmt_to_node(&self, stmt: &Stmt) -> Node {
         match &**stmt {
             StmtX::Assume(expr) => nodes!(assume {self.expr_to_node(expr)}),
-            StmtX::Assert(labels, expr) => {
+            StmtX::Assert(_, labels, expr) => {
                 let spans =
                     vec_map(&all_msgs_from_error(labels), |s| Node::Atom(format!("\"{}\"", s)));
                 if spans.len() == 0 {
@@ -397,6 +402,18 @@ impl Printer {
         nodes.push(self.stmt_to_node(assertion));
         Node::List(nodes)
     }
+
+    pub fn command_to_node(&self, command: &Command) -> Node {
+        match &**command {
+            CommandX::Push => nodes!(push),
+            CommandX::Pop => nodes!(pop),
+            CommandX::SetOption(option, value) => {
+                nodes!(set-option {Node::Atom(":".to_owned() + option)} {str_to_node(value)})
+            }
+            CommandX::Global(decl) => self.decl_to_node(decl),
+            CommandX::CheckValid(query) => self.query_to_node(query),
+        }
+    }
 }
 
 pub struct NodeWriter {}
@@ -3,6 +3,7 @@ use crate::smt_process::SmtProcess;
 pub struct SmtManager {
     smt_process: Option<SmtProcess>,
     smt_executable_name: String,
+    profile_quantifiers: bool,
 }
 
 impl SmtManager {
@@ -12,18 +13,25 @@ impl SmtManager {
         } else {
             if cfg!(windows) { "z3.exe" } else { "z3" }.to_string()
         };
-        SmtManager { smt_process: None, smt_executable_name }
+        SmtManager { smt_process: None, smt_executable_name, profile_quantifiers: false }
     }
 
     pub fn set_smt_executable_name(&mut self, name: String) {
         self.smt_executable_name = name;
     }
 
+    /// Must be called before the SMT process is launched
+    pub(crate) fn set_profile_quantifiers(&mut self, profile_quantifiers: bool) {
+        assert!(self.smt_process.is_none());
+        self.profile_quantifiers = profile_quantifiers;
+    }
+
     /// Launch the SMT process if it hasn't been started yet.
     /// Return the SmtProcess.
     pub(crate) fn get_smt_process(&mut self) -> &mut SmtProcess {
         if self.smt_process.is_none() {
-            self.smt_process = Some(SmtProcess::launch(&self.smt_executable_name));
+            self.smt_process =
+                Some(SmtProcess::launch(&self.smt_executable_name, self.profile_quantifiers));
         }
         self.smt_process.as_mut().unwrap()
     }
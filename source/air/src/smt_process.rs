@@ -1,13 +1,17 @@
 use std::io::{BufRead, BufReader, Write};
-use std::process::{ChildStdin, ChildStdout};
+use std::process::{ChildStderr, ChildStdin, ChildStdout};
 use std::sync::mpsc::{channel, Receiver, Sender};
 
 pub(crate) struct SmtProcess {
     requests: Sender<Vec<u8>>,
     smt_pipe_stdout: BufReader<ChildStdout>,
+    // Quantifier profiling lines from Z3's stderr (only when profiling quantifiers)
+    profile_lines: Option<Receiver<String>>,
 }
 
 const DONE: &str = "<<DONE>>";
+const PROFILE_DONE: &str = "<<PROFILE_DONE>>";
+const QUANTIFIER_INSTANCES: &str = "[quantifier_instances]";
 
 /// A separate thread writes data to the SMT solver over a pipe.
 /// (Rust's documentation says you need a separate thread; otherwise, it lets the pipes deadlock.)
@@ -25,19 +29,50 @@ fn writer_thread(requests: Receiver<Vec<u8>>, mut smt_pipe_stdin: ChildStdin) {
     // Exit when the other side closes the channel
 }
 
+/// When profiling quantifiers, Z3 prints the number of instantiations of each quantifier
+/// on stderr whenever the quantifier is deleted (e.g. when popping the scope containing it).
+/// A separate thread forwards these lines (and our PROFILE_DONE marker) to the SmtProcess,
+/// and passes any other stderr output through to our own stderr.
+fn profile_reader_thread(lines: Sender<String>, smt_pipe_stderr: ChildStderr) {
+    for line in BufReader::new(smt_pipe_stderr).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if line.starts_with(QUANTIFIER_INSTANCES) || line == PROFILE_DONE {
+            if lines.send(line).is_err() {
+                break;
+            }
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+}
+
 impl SmtProcess {
-    pub(crate) fn launch(smt_executable_name: &String) -> Self {
-        let mut child = std::process::Command::new(smt_executable_name)
+    pub(crate) fn launch(smt_executable_name: &String, profile_quantifiers: bool) -> Self {
+        let mut command = std::process::Command::new(smt_executable_name);
+        command
             .args(&["-smt2", "-in"])
             .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .spawn()
-            .expect("could not execute Z3 process");
+            .stdout(std::process::Stdio::piped());
+        if profile_quantifiers {
+            command.stderr(std::process::Stdio::piped());
+        }
+        let mut child = command.spawn().expect("could not execute Z3 process");
         let smt_pipe_stdout = BufReader::new(child.stdout.take().expect("take stdout"));
         let child_stdin = child.stdin.take().expect("take stdin");
         let (sender, receiver) = channel();
         std::thread::spawn(move || writer_thread(receiver, child_stdin));
-        SmtProcess { requests: sender, smt_pipe_stdout }
+        let profile_lines = if profile_quantifiers {
+            let child_stderr = child.stderr.take().expect("take stderr");
+            let (line_sender, line_receiver) = channel();
+            std::thread::spawn(move || profile_reader_thread(line_sender, child_stderr));
+            Some(line_receiver)
+        } else {
+            None
+        };
+        SmtProcess { requests: sender, smt_pipe_stdout, profile_lines }
     }
 
     /// Send commands to Z3, wait for Z3 to acknowledge commands, and return responses
@@ -60,4 +95,28 @@ impl SmtProcess {
             lines.push(line);
         }
     }
+
+    /// Send commands to Z3, then return the quantifier profiling lines
+    /// that Z3 has printed so far.
+    /// To know when we've received all the lines, we ask Z3 to echo PROFILE_DONE to stderr.
+    pub(crate) fn take_quantifier_profile(&mut self, mut commands: Vec<u8>) -> Vec<String> {
+        if self.profile_lines.is_none() {
+            panic!("internal error: quantifier profiling is not enabled");
+        }
+        writeln!(commands, "(set-option :regular-output-channel \"stderr\")").unwrap();
+        writeln!(commands, "(echo \"{}\")", PROFILE_DONE).unwrap();
+        writeln!(commands, "(set-option :regular-output-channel \"stdout\")").unwrap();
+        self.send_commands(commands);
+        let profile_lines = self.profile_lines.as_ref().unwrap();
+        let mut lines = Vec::new();
+        loop {
+            let line = profile_lines
+                .recv()
+                .expect("IO error: failure when receiving profile data from Z3 process");
+            if line == PROFILE_DONE {
+                return lines;
+            }
+            lines.push(line[QUANTIFIER_INSTANCES.len()..].to_string());
+        }
+    }
 }
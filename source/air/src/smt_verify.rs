@@ -2,7 +2,7 @@ use crate::ast::{
     BinaryOp, BindX, Decl, DeclX, Expr, ExprX, Ident, MultiOp, Quant, Query, StmtX, TypX, UnaryOp,
 };
 use crate::ast_util::{ident_var, mk_and, mk_implies, mk_not, str_ident, str_var};
-use crate::context::{AssertionInfo, AxiomInfo, Context, ContextState, ValidityResult};
+use crate::context::{AssertionInfo, AxiomInfo, Context, ContextState, QueryStats, ValidityResult};
 use crate::def::{GLOBAL_PREFIX_LABEL, PREFIX_LABEL, QUERY};
 use crate::errors::{Error, ErrorLabel};
 pub use crate::model::{Model, ModelDef};
@@ -34,18 +34,23 @@ fn label_asserts<'ctx>(
             Arc::new(ExprX::Multi(*op, Arc::new(exprs_vec)))
         }
         ExprX::Bind(bind, body) => match &**bind {
-            BindX::Quant(Quant::Forall, _, _) => Arc::new(ExprX::Bind(
+            BindX::Quant(Quant::Forall, _, _, _) => Arc::new(ExprX::Bind(
                 bind.clone(),
                 label_asserts(context, infos, axiom_infos, body),
             )),
             _ => expr.clone(),
         },
-        ExprX::LabeledAssertion(error, expr) => {
+        ExprX::LabeledAssertion(versions, error, expr) => {
             let label = Arc::new(PREFIX_LABEL.to_string() + &infos.len().to_string());
 
             let decl = Arc::new(DeclX::Const(label.clone(), Arc::new(TypX::Bool)));
-            let assertion_info =
-                AssertionInfo { error: error.clone(), label: label.clone(), decl, disabled: false };
+            let assertion_info = AssertionInfo {
+                versions: versions.clone(),
+                error: error.clone(),
+                label: label.clone(),
+                decl,
+                disabled: false,
+            };
             infos.push(assertion_info);
             let lhs = Arc::new(ExprX::Var(label));
             Arc::new(ExprX::Binary(
@@ -75,13 +80,23 @@ fn label_asserts<'ctx>(
 
 /// In SMT-LIB, functions applied to zero arguments are considered constants.
 /// REVIEW: maybe AIR should follow this design for consistency.
-fn elim_zero_args_expr(expr: &Expr) -> Expr {
+pub(crate) fn elim_zero_args_expr(expr: &Expr) -> Expr {
     crate::visitor::map_expr_visitor(expr, &mut |expr| match &**expr {
         ExprX::Apply(x, es) if es.len() == 0 => Arc::new(ExprX::Var(x.clone())),
         _ => expr.clone(),
     })
 }
 
+/// Parse a response to (get-info :info), which has the form (:info value)
+pub(crate) fn parse_info_line<'a>(line: &'a str, info: &str) -> Option<&'a str> {
+    let prefix = format!("(:{} ", info);
+    if line.starts_with(&prefix) && line.ends_with(")") {
+        Some(line[prefix.len()..line.len() - 1].trim())
+    } else {
+        None
+    }
+}
+
 pub(crate) fn smt_add_decl<'ctx>(context: &mut Context, decl: &Decl) {
     match &**decl {
         DeclX::Sort(_) | DeclX::Datatypes(_) | DeclX::Const(_, _) | DeclX::Fun(_, _, _) => {
@@ -110,8 +125,16 @@ pub(crate) fn smt_check_assertion<'ctx>(
     context: &mut Context,
     mut infos: Vec<AssertionInfo>,
     air_model: Model,
+    disable_label: Option<&Ident>,
     only_check_earlier: bool,
 ) -> ValidityResult {
+    context.last_query_stats = QueryStats::default();
+    if let Some(label) = disable_label {
+        // Disable the label of the previous error in this check-sat to get additional errors.
+        // (We can't do this right after finding the previous error,
+        // because any assertion discards the model that the caller may still be evaluating.)
+        context.smt_log.log_assert(&mk_not(&ident_var(label)));
+    }
     if only_check_earlier {
         // disable all labels that come after the first known error
         let mut disabled: Vec<Expr> = Vec::new();
@@ -143,6 +166,8 @@ pub(crate) fn smt_check_assertion<'ctx>(
     context.set_z3_param_u32("rlimit", context.rlimit, false);
 
     context.smt_log.log_word("check-sat");
+    // Z3's rlimit-count is cumulative, so we ask for it after every check-sat
+    context.smt_log.log_get_info("rlimit");
 
     // Run SMT solver
     let time0 = std::time::Instant::now();
@@ -152,16 +177,23 @@ pub(crate) fn smt_check_assertion<'ctx>(
     let time2 = std::time::Instant::now();
     context.time_smt_init += time1 - time0;
     context.time_smt_run += time2 - time1;
+    context.last_query_stats.time = time2 - time1;
 
     // Process SMT results
     let mut unsat = None;
+    let mut is_unknown = false;
     for line in smt_output {
-        if line == "unsat" {
+        if let Some(count) = parse_info_line(&line, "rlimit") {
+            let count = count.parse::<u64>().expect("could not parse rlimit-count");
+            context.last_query_stats.rlimit_count = count.saturating_sub(context.rlimit_count);
+            context.rlimit_count = count;
+        } else if line == "unsat" {
             assert!(unsat == None);
             unsat = Some(true);
         } else if line == "sat" || line == "unknown" {
             assert!(unsat == None);
             unsat = Some(false);
+            is_unknown = line == "unknown";
         } else if context.ignore_unexpected_smt {
             println!("warning: unexpected SMT output: {}", line);
         } else {
@@ -172,6 +204,19 @@ pub(crate) fn smt_check_assertion<'ctx>(
     context.smt_log.log_set_option("rlimit", "0");
     context.set_z3_param_u32("rlimit", 0, false);
 
+    if is_unknown {
+        context.smt_log.log_get_info("reason-unknown");
+        let smt_output =
+            context.smt_manager.get_smt_process().send_commands(context.smt_log.take_pipe_data());
+        for line in smt_output {
+            if let Some(reason) = parse_info_line(&line, "reason-unknown") {
+                context.last_query_stats.resource_exhausted = reason.contains("canceled")
+                    || reason.contains("timeout")
+                    || reason.contains("resource");
+            }
+        }
+    }
+
     match unsat {
         None => {
             panic!("expected sat/unsat/unknown from SMT solver");
@@ -191,15 +236,17 @@ pub(crate) fn smt_check_assertion<'ctx>(
             for def in model.iter() {
                 model_defs.insert(def.name.clone(), def.clone());
             }
+            let mut failed_model = air_model.clone();
+            let mut discovered_label: Option<Ident> = None;
             for info in infos.iter_mut() {
                 if let Some(def) = model_defs.get(&info.label) {
-                    if *def.body == "true" {
+                    if def.body.is_atom("true") {
                         discovered_error = Some(info.error.clone());
+                        failed_model.set_failed_assertion(&info.versions);
 
                         // Disable this label in subsequent check-sat calls to get additional errors
                         info.disabled = true;
-                        let disable_label = mk_not(&ident_var(&info.label));
-                        context.smt_log.log_assert(&disable_label);
+                        discovered_label = Some(info.label.clone());
 
                         break;
                     }
@@ -207,7 +254,7 @@ pub(crate) fn smt_check_assertion<'ctx>(
             }
             for (_, info) in context.axiom_infos.map().iter() {
                 if let Some(def) = model_defs.get(&info.label) {
-                    if *def.body == "true" {
+                    if def.body.is_atom("true") {
                         discovered_additional_info.append(&mut (*info.labels).clone());
                         break;
                     }
@@ -226,9 +273,10 @@ pub(crate) fn smt_check_assertion<'ctx>(
             // to the function precondition)
 
             let error = discovered_error.expect("discovered_error");
+            let label = discovered_label.expect("discovered_label");
             let e = error.append_labels(&discovered_additional_info);
-            context.state = ContextState::FoundInvalid(infos, air_model.clone());
-            ValidityResult::Invalid(air_model, e)
+            context.state = ContextState::FoundInvalid(infos, air_model, label);
+            ValidityResult::Invalid(failed_model, e)
         }
     }
 }
@@ -251,7 +299,7 @@ pub(crate) fn smt_check_query<'ctx>(
 
     // after lowering, there should be just one assertion
     let assertion = match &*query.assertion {
-        StmtX::Assert(_, expr) => expr,
+        StmtX::Assert(_, _, expr) => expr,
         _ => panic!("internal error: query not lowered"),
     };
     let assertion = elim_zero_args_expr(assertion);
@@ -272,7 +320,7 @@ pub(crate) fn smt_check_query<'ctx>(
     let not_expr = Arc::new(ExprX::Unary(UnaryOp::Not, labeled_assertion));
     context.smt_log.log_decl(&Arc::new(DeclX::Const(str_ident(QUERY), Arc::new(TypX::Bool))));
     context.smt_log.log_assert(&mk_implies(&str_var(QUERY), &not_expr));
-    let result = smt_check_assertion(context, infos, air_model, false);
+    let result = smt_check_assertion(context, infos, air_model, None, false);
 
     result
 }
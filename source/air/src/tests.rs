@@ -840,6 +840,32 @@ fn yes_forall5() {
     )
 }
 
+#[test]
+fn yes_forall_qid() {
+    yes!(
+        (declare-fun f (Int) Bool)
+        (declare-fun g (Int) Bool)
+        (axiom
+            (forall ((i Int)) (!
+                (=> (f i) (g i))
+                :pattern ((f i))
+                :qid f_implies_g
+            ))
+        )
+        (axiom
+            (forall ((i Int)) (!
+                (f i)
+                :qid all_f
+            ))
+        )
+        (check-valid
+            (assert
+                (g 10)
+            )
+        )
+    )
+}
+
 #[test]
 fn no_forall1() {
     no!(
@@ -356,7 +356,7 @@ fn check_expr(typing: &mut Typing, expr: &Expr) -> Result<Typ, TypeError> {
                     }
                     Arc::new(binders)
                 }
-                BindX::Quant(_, binders, _) => binders.clone(),
+                BindX::Quant(_, binders, _, _) => binders.clone(),
                 BindX::Lambda(binders) => binders.clone(),
                 BindX::Choose(binders, _, _) => binders.clone(),
             };
@@ -370,7 +370,7 @@ fn check_expr(typing: &mut Typing, expr: &Expr) -> Result<Typ, TypeError> {
             // Type-check triggers
             match &**bind {
                 BindX::Let(_) | BindX::Lambda(_) => {}
-                BindX::Quant(_, _, triggers) | BindX::Choose(_, triggers, _) => {
+                BindX::Quant(_, _, triggers, _) | BindX::Choose(_, triggers, _) => {
                     for trigger in triggers.iter() {
                         for expr in trigger.iter() {
                             check_expr(typing, expr)?;
@@ -392,7 +392,7 @@ fn check_expr(typing: &mut Typing, expr: &Expr) -> Result<Typ, TypeError> {
             let t1 = check_expr(typing, e1)?;
             let tb = match &**bind {
                 BindX::Let(_) => t1,
-                BindX::Quant(_, _, _) => {
+                BindX::Quant(_, _, _, _) => {
                     expect_typ(&t1, &bt(), "forall/exists body must have type bool")?;
                     t1
                 }
@@ -403,7 +403,7 @@ fn check_expr(typing: &mut Typing, expr: &Expr) -> Result<Typ, TypeError> {
             typing.decls.pop_scope();
             Ok(tb)
         }
-        ExprX::LabeledAssertion(_, expr) => check_expr(typing, expr),
+        ExprX::LabeledAssertion(_, _, expr) => check_expr(typing, expr),
         ExprX::LabeledAxiom(_, expr) => check_expr(typing, expr),
     };
     match result {
@@ -422,7 +422,7 @@ fn check_stmt(typing: &mut Typing, stmt: &Stmt) -> Result<(), TypeError> {
             &bt(),
             "assume statement expects expression of type bool",
         ),
-        StmtX::Assert(_, expr) => expect_typ(
+        StmtX::Assert(_, _, expr) => expect_typ(
             &check_expr(typing, expr)?,
             &bt(),
             "assert statement expects expression of type bool",
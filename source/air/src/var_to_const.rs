@@ -46,7 +46,11 @@ fn lower_stmt(
         lower_expr_visitor(versions, snapshots, e)
     });
     match &*stmt {
-        StmtX::Assume(_) | StmtX::Assert(_, _) => stmt,
+        StmtX::Assume(_) => stmt,
+        StmtX::Assert(_, error, expr) => {
+            let versions = Some(Arc::new(versions.clone()));
+            Arc::new(StmtX::Assert(versions, error.clone(), expr.clone()))
+        }
         StmtX::Havoc(x) | StmtX::Assign(x, _) => {
             let n = find_version(&versions, x);
             let typ = types[x].clone();
@@ -69,7 +73,9 @@ fn lower_stmt(
         StmtX::Snapshot(snap) => {
             snapshots.insert(snap.clone(), versions.clone());
             all_snapshots.insert(snap.clone(), versions.clone());
-            Arc::new(StmtX::Block(Arc::new(vec![])))
+            // Keep the snapshot so that the model can find the snapshots that the
+            // counterexample's execution reaches
+            stmt
         }
         StmtX::DeadEnd(s) => {
             let s = lower_stmt(decls, versions, version_decls, snapshots, all_snapshots, types, s);
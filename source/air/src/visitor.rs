@@ -59,7 +59,7 @@ pub(crate) fn map_expr_visitor<F: FnMut(&Expr) -> Expr>(expr: &Expr, f: &mut F)
                     }
                     BindX::Let(Arc::new(binders))
                 }
-                BindX::Quant(quant, binders, ts) => {
+                BindX::Quant(quant, binders, ts, qid) => {
                     let mut triggers: Vec<Trigger> = Vec::new();
                     for t in ts.iter() {
                         let mut exprs: Vec<Expr> = Vec::new();
@@ -68,7 +68,7 @@ pub(crate) fn map_expr_visitor<F: FnMut(&Expr) -> Expr>(expr: &Expr, f: &mut F)
                         }
                         triggers.push(Arc::new(exprs));
                     }
-                    BindX::Quant(*quant, binders.clone(), Arc::new(triggers))
+                    BindX::Quant(*quant, binders.clone(), Arc::new(triggers), qid.clone())
                 }
                 BindX::Lambda(binders) => BindX::Lambda(binders.clone()),
                 BindX::Choose(binders, ts, e2) => {
@@ -88,9 +88,9 @@ pub(crate) fn map_expr_visitor<F: FnMut(&Expr) -> Expr>(expr: &Expr, f: &mut F)
             let expr = Arc::new(ExprX::Bind(Arc::new(bind), e1));
             f(&expr)
         }
-        ExprX::LabeledAssertion(l, e1) => {
+        ExprX::LabeledAssertion(v, l, e1) => {
             let expr1 = map_expr_visitor(e1, f);
-            let expr = Arc::new(ExprX::LabeledAssertion(l.clone(), expr1));
+            let expr = Arc::new(ExprX::LabeledAssertion(v.clone(), l.clone(), expr1));
             f(&expr)
         }
         ExprX::LabeledAxiom(l, e1) => {
@@ -107,9 +107,9 @@ pub(crate) fn map_stmt_expr_visitor<F: FnMut(&Expr) -> Expr>(stmt: &Stmt, f: &mu
             let expr = map_expr_visitor(e, f);
             Arc::new(StmtX::Assume(f(&expr)))
         }
-        StmtX::Assert(span, e) => {
+        StmtX::Assert(v, span, e) => {
             let expr = map_expr_visitor(e, f);
-            Arc::new(StmtX::Assert(span.clone(), f(&expr)))
+            Arc::new(StmtX::Assert(v.clone(), span.clone(), f(&expr)))
         }
         StmtX::Havoc(_) => stmt.clone(),
         StmtX::Assign(x, e) => {
@@ -127,7 +127,7 @@ pub(crate) fn map_stmt_expr_visitor<F: FnMut(&Expr) -> Expr>(stmt: &Stmt, f: &mu
 pub(crate) fn map_stmt_visitor<F: FnMut(&Stmt) -> Stmt>(stmt: &Stmt, f: &mut F) -> Stmt {
     match &**stmt {
         StmtX::Assume(_) => f(stmt),
-        StmtX::Assert(_, _) => f(stmt),
+        StmtX::Assert(..) => f(stmt),
         StmtX::Havoc(_) => f(stmt),
         StmtX::Assign(_, _) => f(stmt),
         StmtX::Snapshot(_) => f(stmt),
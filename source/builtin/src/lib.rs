@@ -1,5 +1,6 @@
 #![feature(rustc_attrs)]
 #![feature(negative_impls)]
+#![feature(unboxed_closures)]
 
 #[proof]
 pub fn admit() {
@@ -18,7 +19,7 @@ pub fn requires<A>(_a: A) {
     unimplemented!();
 }
 
-// Can only appear at beginning of function body
+// Can only appear at beginning of function body or loop body
 #[proof]
 pub fn ensures<A>(_a: A) {
     unimplemented!();
@@ -119,6 +120,38 @@ pub fn old<A>(_: A) -> A {
     unimplemented!();
 }
 
+// fin(r): final value of a returned &mut r at the end of the borrow
+#[spec]
+pub fn fin<A>(_: &mut A) -> A {
+    unimplemented!();
+}
+
+// Preconditions and postconditions of exec closures, written f.requires((x, y))
+// and f.ensures((x, y), r), where the arguments are passed as a tuple
+pub trait FnWithSpecification<Args> {
+    type Output;
+
+    #[spec]
+    fn requires(&self, _args: Args) -> bool;
+
+    #[spec]
+    fn ensures(&self, _args: Args, _output: Self::Output) -> bool;
+}
+
+impl<Args, F: FnOnce<Args>> FnWithSpecification<Args> for F {
+    type Output = <F as FnOnce<Args>>::Output;
+
+    #[spec]
+    fn requires(&self, _args: Args) -> bool {
+        unimplemented!();
+    }
+
+    #[spec]
+    fn ensures(&self, _args: Args, _output: Self::Output) -> bool {
+        unimplemented!();
+    }
+}
+
 #[proof]
 pub fn assert_by(_: bool, _: ()) {
     unimplemented!();
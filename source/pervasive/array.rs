@@ -0,0 +1,82 @@
+//! Arrays `[A; N]` and slices `[A]` are viewed by the verifier as `Seq<A>`.
+//! For values of array type `[A; N]`, the verifier assumes that the length is `N`.
+//! In spec code, `a.len()`, `a[i]`, `&a[i..j]`, `[a0, a1, ...]`, and `[a; n]`
+//! are translated directly into `Seq` operations.
+//! In exec code, they are translated into calls to the exec functions below,
+//! whose requires/ensures specify the built-in Rust operations.
+//! These functions are never called by the compiled code, which still uses the built-in operations.
+
+#![allow(unused_variables)]
+
+#[allow(unused_imports)]
+use builtin::*;
+#[allow(unused_imports)]
+use builtin_macros::*;
+#[allow(unused_imports)]
+use crate::pervasive::*;
+#[allow(unused_imports)]
+use crate::pervasive::seq::*;
+
+#[spec]
+#[verifier(publish)]
+pub fn seq_repeat<A>(a: A, n: nat) -> Seq<A> {
+    Seq::new(n, |i: int| a)
+}
+
+/// Unsizing coercion from an array of length n to a slice
+#[verifier(external_body)]
+pub fn array_as_slice<A>(s: Seq<A>, n: usize) -> Seq<A> {
+    ensures(|r: Seq<A>| [
+        equal(r, s),
+        r.len() == n,
+    ]);
+
+    unimplemented!()
+}
+
+#[verifier(external_body)]
+pub fn slice_len<A>(s: Seq<A>) -> usize {
+    ensures(|l: usize| l == s.len());
+
+    unimplemented!()
+}
+
+#[verifier(external_body)]
+pub fn slice_index<A>(s: Seq<A>, i: usize) -> A {
+    requires(i < s.len());
+    ensures(|a: A| equal(a, s.index(i)));
+
+    unimplemented!()
+}
+
+#[verifier(external_body)]
+pub fn slice_subrange<A>(s: Seq<A>, i: usize, j: usize) -> Seq<A> {
+    requires([
+        i <= j,
+        j <= s.len(),
+    ]);
+    ensures(|r: Seq<A>| equal(r, s.subrange(i, j)));
+
+    unimplemented!()
+}
+
+#[verifier(external_body)]
+pub fn array_empty<A>() -> Seq<A> {
+    ensures(|r: Seq<A>| equal(r, Seq::empty()));
+
+    unimplemented!()
+}
+
+#[verifier(external_body)]
+pub fn array_push<A>(s: Seq<A>, a: A) -> Seq<A> {
+    ensures(|r: Seq<A>| equal(r, s.push(a)));
+
+    unimplemented!()
+}
+
+#[verifier(external_body)]
+pub fn array_repeat<A>(a: A, n: usize) -> Seq<A> {
+    ensures(|r: Seq<A>| equal(r, seq_repeat(a, n)));
+
+    unimplemented!()
+}
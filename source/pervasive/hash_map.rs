@@ -0,0 +1,83 @@
+#[allow(unused_imports)]
+use builtin::*;
+#[allow(unused_imports)]
+use builtin_macros::*;
+#[allow(unused_imports)]
+use crate::pervasive::*;
+#[allow(unused_imports)]
+use crate::pervasive::map::*;
+#[allow(unused_imports)]
+use crate::pervasive::option::*;
+
+/// Executable hash map, viewed as a Map.
+/// Keys must be Structural, so that the executable equality used by hashing
+/// coincides with the equality of the spec-level Map.
+/// The specifications below also trust that the keys' Hash is consistent with that equality;
+/// the verifier ensures this by rejecting Hash implementations that are not derived.
+#[verifier(external_body)]
+pub struct HashMap<#[verifier(maybe_negative)] K, #[verifier(strictly_positive)] V> {
+    pub map: std::collections::HashMap<K, V>,
+}
+
+impl<K: std::hash::Hash + Eq + Structural, V> HashMap<K, V> {
+    fndecl!(pub fn view(&self) -> Map<K, V>);
+
+    #[verifier(external_body)]
+    pub fn new() -> Self {
+        ensures(|m: Self| equal(m.view(), Map::empty()));
+
+        HashMap { map: std::collections::HashMap::new() }
+    }
+
+    #[verifier(external_body)]
+    pub fn insert(&mut self, k: K, v: V) {
+        ensures(equal(self.view(), old(self).view().insert(k, v)));
+
+        self.map.insert(k, v);
+    }
+
+    #[verifier(external_body)]
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        ensures(|r: Option<V>| [
+            equal(self.view(), old(self).view().remove(*k)),
+            r.is_Some() == old(self).view().dom().contains(*k),
+            r.is_Some() >>= equal(r.get_Some_0(), old(self).view().index(*k)),
+        ]);
+
+        match self.map.remove(k) {
+            Some(v) => Option::Some(v),
+            None => Option::None,
+        }
+    }
+
+    #[verifier(external_body)]
+    pub fn get(&self, k: &K) -> Option<&V> {
+        ensures(|r: Option<&V>| [
+            r.is_Some() == self.view().dom().contains(*k),
+            r.is_Some() >>= equal(*r.get_Some_0(), self.view().index(*k)),
+        ]);
+
+        match self.map.get(k) {
+            Some(v) => Option::Some(v),
+            None => Option::None,
+        }
+    }
+
+    #[verifier(external_body)]
+    pub fn contains_key(&self, k: &K) -> bool {
+        ensures(|r: bool| r == self.view().dom().contains(*k));
+
+        self.map.contains_key(k)
+    }
+
+    #[verifier(external_body)]
+    #[verifier(autoview)]
+    pub fn len(&self) -> usize {
+        ensures(|l: usize| [
+            self.view().dom().finite(),
+            l == self.view().len(),
+        ]);
+
+        self.map.len()
+    }
+}
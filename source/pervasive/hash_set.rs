@@ -0,0 +1,67 @@
+#[allow(unused_imports)]
+use builtin::*;
+#[allow(unused_imports)]
+use builtin_macros::*;
+#[allow(unused_imports)]
+use crate::pervasive::*;
+#[allow(unused_imports)]
+use crate::pervasive::set::*;
+
+/// Executable hash set, viewed as a Set.
+/// Elements must be Structural, so that the executable equality used by hashing
+/// coincides with the equality of the spec-level Set.
+/// The specifications below also trust that the elements' Hash is consistent with that equality;
+/// the verifier ensures this by rejecting Hash implementations that are not derived.
+#[verifier(external_body)]
+pub struct HashSet<#[verifier(maybe_negative)] K> {
+    pub set: std::collections::HashSet<K>,
+}
+
+impl<K: std::hash::Hash + Eq + Structural> HashSet<K> {
+    fndecl!(pub fn view(&self) -> Set<K>);
+
+    #[verifier(external_body)]
+    pub fn new() -> Self {
+        ensures(|s: Self| equal(s.view(), Set::empty()));
+
+        HashSet { set: std::collections::HashSet::new() }
+    }
+
+    #[verifier(external_body)]
+    pub fn insert(&mut self, k: K) -> bool {
+        ensures(|r: bool| [
+            equal(self.view(), old(self).view().insert(k)),
+            r == !old(self).view().contains(k),
+        ]);
+
+        self.set.insert(k)
+    }
+
+    #[verifier(external_body)]
+    pub fn remove(&mut self, k: &K) -> bool {
+        ensures(|r: bool| [
+            equal(self.view(), old(self).view().remove(*k)),
+            r == old(self).view().contains(*k),
+        ]);
+
+        self.set.remove(k)
+    }
+
+    #[verifier(external_body)]
+    pub fn contains(&self, k: &K) -> bool {
+        ensures(|r: bool| r == self.view().contains(*k));
+
+        self.set.contains(k)
+    }
+
+    #[verifier(external_body)]
+    #[verifier(autoview)]
+    pub fn len(&self) -> usize {
+        ensures(|l: usize| [
+            self.view().finite(),
+            l == self.view().len(),
+        ]);
+
+        self.set.len()
+    }
+}
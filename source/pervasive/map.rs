@@ -39,6 +39,33 @@ impl<K, V> Map<K, V> {
     pub fn contains_pair(self, k: K, v: V) -> bool {
         self.dom().contains(k) && equal(self.index(k), v)
     }
+
+    /// Every key of self is a key of m2, with the same value
+    #[spec] #[verifier(publish)]
+    pub fn submap_of(self, m2: Map<K, V>) -> bool {
+        forall(|k: K| self.dom().contains(k) >>=
+            m2.dom().contains(k) && equal(self.index(k), m2.index(k)))
+    }
+
+    /// Number of keys (only meaningful if dom() is finite)
+    #[spec] #[verifier(publish)]
+    pub fn len(self) -> nat {
+        self.dom().len()
+    }
+
+    #[spec] #[verifier(publish)]
+    pub fn values(self) -> Set<V> {
+        set_new(|v: V| exists(|k: K| self.contains_pair(k, v)))
+    }
+
+    /// Keys of both maps, taking the value from m2 when both maps contain the key
+    fndecl!(pub fn union_prefer_right(self, m2: Map<K, V>) -> Map<K, V>);
+
+    /// Keep only the keys in the given set
+    fndecl!(pub fn restrict(self, keys: Set<K>) -> Map<K, V>);
+
+    /// Drop all the keys in the given set
+    fndecl!(pub fn remove_keys(self, keys: Set<K>) -> Map<K, V>);
 }
 
 // Trusted axioms
@@ -93,6 +120,60 @@ pub fn axiom_map_remove_different<K, V>(m: Map<K, V>, key1: K, key2: K) {
     ensures(equal(m.remove(key2).index(key1), m.index(key1)));
 }
 
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_map_union_prefer_right_domain<K, V>(m1: Map<K, V>, m2: Map<K, V>) {
+    ensures(equal(#[trigger] m1.union_prefer_right(m2).dom(), m1.dom().union(m2.dom())));
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_map_union_prefer_right_index<K, V>(m1: Map<K, V>, m2: Map<K, V>, key: K) {
+    requires(m1.dom().contains(key) || m2.dom().contains(key));
+    ensures(equal(
+        #[trigger] m1.union_prefer_right(m2).index(key),
+        if m2.dom().contains(key) { m2.index(key) } else { m1.index(key) },
+    ));
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_map_restrict_domain<K, V>(m: Map<K, V>, keys: Set<K>) {
+    ensures(equal(#[trigger] m.restrict(keys).dom(), m.dom().intersect(keys)));
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_map_restrict_index<K, V>(m: Map<K, V>, keys: Set<K>, key: K) {
+    requires([
+        m.dom().contains(key),
+        keys.contains(key),
+    ]);
+    ensures(equal(#[trigger] m.restrict(keys).index(key), m.index(key)));
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_map_remove_keys_domain<K, V>(m: Map<K, V>, keys: Set<K>) {
+    ensures(equal(#[trigger] m.remove_keys(keys).dom(), m.dom().difference(keys)));
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_map_remove_keys_index<K, V>(m: Map<K, V>, keys: Set<K>, key: K) {
+    requires([
+        m.dom().contains(key),
+        !keys.contains(key),
+    ]);
+    ensures(equal(#[trigger] m.remove_keys(keys).index(key), m.index(key)));
+}
+
 #[proof]
 #[verifier(external_body)]
 #[verifier(broadcast_forall)]
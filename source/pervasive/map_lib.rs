@@ -0,0 +1,109 @@
+#[allow(unused_imports)]
+use builtin::*;
+#[allow(unused_imports)]
+use crate::pervasive::*;
+#[allow(unused_imports)]
+use crate::pervasive::set::*;
+#[allow(unused_imports)]
+use crate::pervasive::set_lib::*;
+#[allow(unused_imports)]
+use crate::pervasive::map::*;
+
+#[proof]
+pub fn lemma_map_empty_len<K, V>() {
+    ensures([
+        Map::<K, V>::empty().dom().finite(),
+        Map::<K, V>::empty().len() == 0,
+    ]);
+}
+
+#[proof]
+pub fn lemma_map_insert_len<K, V>(m: Map<K, V>, key: K, value: V) {
+    requires(m.dom().finite());
+    ensures([
+        m.insert(key, value).dom().finite(),
+        m.insert(key, value).len() == m.len() + (if m.dom().contains(key) { 0 } else { 1 }),
+    ]);
+}
+
+#[proof]
+pub fn lemma_map_remove_len<K, V>(m: Map<K, V>, key: K) {
+    requires(m.dom().finite());
+    ensures([
+        m.remove(key).dom().finite(),
+        m.len() == m.remove(key).len() + (if m.dom().contains(key) { 1 } else { 0 }),
+    ]);
+}
+
+#[proof]
+pub fn lemma_union_prefer_right_len<K, V>(m1: Map<K, V>, m2: Map<K, V>) {
+    requires([
+        m1.dom().finite(),
+        m2.dom().finite(),
+    ]);
+    ensures([
+        m1.union_prefer_right(m2).dom().finite(),
+        m1.union_prefer_right(m2).len() <= m1.len() + m2.len(),
+    ]);
+
+    lemma_len_union::<K>(m1.dom(), m2.dom());
+}
+
+#[proof]
+pub fn lemma_restrict_len<K, V>(m: Map<K, V>, keys: Set<K>) {
+    requires(m.dom().finite());
+    ensures([
+        m.restrict(keys).dom().finite(),
+        m.restrict(keys).len() <= m.len(),
+    ]);
+
+    lemma_len_intersect::<K>(m.dom(), keys);
+}
+
+#[proof]
+pub fn lemma_remove_keys_len<K, V>(m: Map<K, V>, keys: Set<K>) {
+    requires(m.dom().finite());
+    ensures([
+        m.remove_keys(keys).dom().finite(),
+        m.remove_keys(keys).len() <= m.len(),
+    ]);
+
+    lemma_len_difference::<K>(m.dom(), keys);
+}
+
+#[proof]
+pub fn lemma_submap_len<K, V>(m1: Map<K, V>, m2: Map<K, V>) {
+    requires([
+        m2.dom().finite(),
+        m1.submap_of(m2),
+    ]);
+    ensures([
+        m1.dom().finite(),
+        m1.len() <= m2.len(),
+    ]);
+
+    lemma_len_subset::<K>(m1.dom(), m2.dom());
+}
+
+#[proof]
+pub fn lemma_submap_union_prefer_right<K, V>(m1: Map<K, V>, m2: Map<K, V>) {
+    ensures(m2.submap_of(m1.union_prefer_right(m2)));
+}
+
+#[proof]
+pub fn lemma_submap_restrict<K, V>(m: Map<K, V>, keys: Set<K>) {
+    ensures(m.restrict(keys).submap_of(m));
+}
+
+#[proof]
+pub fn lemma_submap_remove_keys<K, V>(m: Map<K, V>, keys: Set<K>) {
+    ensures(m.remove_keys(keys).submap_of(m));
+}
+
+#[proof]
+pub fn lemma_values_contains<K, V>(m: Map<K, V>, key: K) {
+    requires(m.dom().contains(key));
+    ensures(m.values().contains(m.index(key)));
+
+    assert(m.contains_pair(key, m.index(key)));
+}
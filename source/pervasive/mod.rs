@@ -1,17 +1,23 @@
 pub mod map;
+pub mod map_lib;
 pub mod option;
 pub mod result;
 pub mod vec;
+pub mod array;
 pub mod seq;
 pub mod seq_lib;
 pub mod set;
 pub mod set_lib;
+pub mod hash_map;
+pub mod hash_set;
+pub mod string;
 pub mod cell;
 pub mod ptr;
 pub mod invariants;
 pub mod atomic;
 pub mod modes;
 pub mod multiset;
+pub mod multiset_lib;
 pub mod state_machine_internal;
 
 #[allow(unused_imports)]
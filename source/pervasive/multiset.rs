@@ -44,6 +44,22 @@ impl<V> Multiset<V> {
         forall(|v: V| self.count(v) == m2.count(v))
     }
 
+    /// Total number of occurrences (only meaningful if dom() is finite)
+    fndecl!(pub fn len(self) -> nat);
+
+    /// The set of values that occur at least once
+    #[spec] #[verifier(publish)]
+    pub fn dom(self) -> Set<V> {
+        set_new(|v: V| self.count(v) > 0)
+    }
+
+    fndecl!(pub fn filter<F: Fn(V) -> bool>(self, f: F) -> Self);
+
+    #[spec] #[verifier(publish)]
+    pub fn choose(self) -> V {
+        choose(|v: V| self.count(v) > 0)
+    }
+
     // TODO flesh out remaining proof-mode functions
 
     #[proof]
@@ -103,6 +119,71 @@ pub fn axiom_multiset_sub<V>(m1: Multiset<V>, m2: Multiset<V>, v: V) {
         if m1.count(v) >= m2.count(v) { m1.count(v) - m2.count(v) } else { 0 });
 }
 
+// Specification of `filter`
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_multiset_filter<V, F: Fn(V) -> bool>(m: Multiset<V>, f: F, v: V) {
+    ensures(#[trigger] m.filter(f).count(v) == if f(v) { m.count(v) } else { 0 });
+}
+
+// Specification of `len`
+// These only constrain multisets with a finite domain;
+// dom() finiteness itself follows from the Set axioms (see multiset_lib)
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_multiset_empty_len<V>() {
+    ensures(#[trigger] Multiset::<V>::empty().len() == 0);
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_multiset_singleton_len<V>(v: V) {
+    ensures(#[trigger] Multiset::singleton(v).len() == 1);
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_multiset_add_len<V>(m1: Multiset<V>, m2: Multiset<V>) {
+    requires([
+        m1.dom().finite(),
+        m2.dom().finite(),
+    ]);
+    ensures(#[trigger] m1.add(m2).len() == m1.len() + m2.len());
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_multiset_sub_len<V>(m1: Multiset<V>, m2: Multiset<V>) {
+    requires([
+        m1.dom().finite(),
+        m2.le(m1),
+    ]);
+    ensures(#[trigger] m1.sub(m2).len() == m1.len() - m2.len());
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_multiset_len_zero<V>(m: Multiset<V>) {
+    requires(m.dom().finite());
+    ensures((#[trigger] m.len() == 0) == equal(m, Multiset::empty()));
+}
+
+#[proof]
+#[verifier(external_body)]
+#[verifier(broadcast_forall)]
+pub fn axiom_multiset_count_le_len<V>(m: Multiset<V>, v: V) {
+    requires(m.dom().finite());
+    ensures(#[trigger] m.count(v) <= #[trigger] m.len());
+}
+
 // Extensional equality
 
 #[proof]
@@ -0,0 +1,139 @@
+#[allow(unused_imports)]
+use builtin::*;
+#[allow(unused_imports)]
+use crate::pervasive::*;
+#[allow(unused_imports)]
+use crate::pervasive::set::*;
+#[allow(unused_imports)]
+use crate::pervasive::set_lib::*;
+#[allow(unused_imports)]
+use crate::pervasive::multiset::*;
+
+#[proof]
+pub fn lemma_multiset_empty_dom<V>() {
+    ensures([
+        equal(Multiset::<V>::empty().dom(), Set::empty()),
+        Multiset::<V>::empty().dom().finite(),
+    ]);
+
+    assert(Multiset::<V>::empty().dom().ext_equal(Set::empty()));
+}
+
+#[proof]
+pub fn lemma_multiset_singleton_dom<V>(v: V) {
+    ensures([
+        equal(Multiset::singleton(v).dom(), Set::empty().insert(v)),
+        Multiset::singleton(v).dom().finite(),
+    ]);
+
+    assert(Multiset::singleton(v).dom().ext_equal(Set::empty().insert(v)));
+}
+
+#[proof]
+pub fn lemma_multiset_add_dom<V>(m1: Multiset<V>, m2: Multiset<V>) {
+    ensures(equal(m1.add(m2).dom(), m1.dom().union(m2.dom())));
+
+    assert(m1.add(m2).dom().ext_equal(m1.dom().union(m2.dom())));
+}
+
+#[proof]
+pub fn lemma_multiset_le_finite<V>(m1: Multiset<V>, m2: Multiset<V>) {
+    requires([
+        m2.dom().finite(),
+        m1.le(m2),
+    ]);
+    ensures([
+        m1.dom().finite(),
+        m1.dom().len() <= m2.dom().len(),
+    ]);
+
+    lemma_len_subset::<V>(m1.dom(), m2.dom());
+}
+
+#[proof]
+pub fn lemma_multiset_insert_len<V>(m: Multiset<V>, v: V) {
+    requires(m.dom().finite());
+    ensures([
+        m.insert(v).dom().finite(),
+        m.insert(v).len() == m.len() + 1,
+    ]);
+
+    lemma_multiset_singleton_dom::<V>(v);
+    lemma_multiset_add_dom::<V>(m, Multiset::singleton(v));
+}
+
+#[proof]
+pub fn lemma_multiset_remove_len<V>(m: Multiset<V>, v: V) {
+    requires([
+        m.dom().finite(),
+        m.count(v) > 0,
+    ]);
+    ensures([
+        m.remove(v).dom().finite(),
+        m.remove(v).len() == m.len() - 1,
+    ]);
+
+    assert(Multiset::singleton(v).le(m));
+    assert(m.remove(v).le(m));
+    lemma_multiset_le_finite::<V>(m.remove(v), m);
+}
+
+#[proof]
+pub fn lemma_multiset_filter_len<V, F: Fn(V) -> bool>(m: Multiset<V>, f: F) {
+    requires(m.dom().finite());
+    ensures([
+        m.filter(f).dom().finite(),
+        m.filter(f).len() <= m.len(),
+    ]);
+
+    assert(m.filter(f).le(m));
+    lemma_multiset_le_finite::<V>(m.filter(f), m);
+    assert(m.sub(m.filter(f)).len() == m.len() - m.filter(f).len());
+}
+
+#[proof]
+pub fn lemma_multiset_choose<V>(m: Multiset<V>) {
+    requires([
+        m.dom().finite(),
+        m.len() != 0,
+    ]);
+    ensures([
+        m.count(m.choose()) > 0,
+        m.dom().contains(m.choose()),
+    ]);
+
+    if !exists(|v: V| m.count(v) > 0) {
+        // derive contradiction:
+        assert(m.ext_equal(Multiset::empty()));
+    }
+}
+
+/// A multiset with a finite domain has at least as many occurrences as distinct values,
+/// and is empty exactly when its domain is empty
+#[proof]
+pub fn lemma_multiset_dom_len<V>(m: Multiset<V>) {
+    requires(m.dom().finite());
+    ensures([
+        m.dom().len() <= m.len(),
+        (m.len() == 0) == (m.dom().len() == 0),
+    ]);
+    decreases(m.len());
+
+    if m.len() == 0 {
+        assert(equal(m, Multiset::empty()));
+        lemma_multiset_empty_dom::<V>();
+    } else {
+        lemma_multiset_choose::<V>(m);
+        let v = m.choose();
+        lemma_multiset_remove_len::<V>(m, v);
+        lemma_multiset_dom_len::<V>(m.remove(v));
+        assert_forall_by(|w: V| {
+            requires(m.dom().remove(v).contains(w));
+            ensures(m.remove(v).dom().contains(w));
+            assert(!equal(w, v));
+            assert(m.count(w) > 0);
+        });
+        lemma_len_subset::<V>(m.dom().remove(v), m.remove(v).dom());
+        assert(m.dom().len() == m.dom().remove(v).len() + 1);
+    }
+}
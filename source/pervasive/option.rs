@@ -3,9 +3,115 @@ use builtin::*;
 use builtin_macros::*;
 #[allow(unused_imports)]
 use crate::pervasive::*;
+#[allow(unused_imports)]
+use crate::pervasive::result::*;
 
 #[is_variant]
 pub enum Option<A> {
     None,
     Some(A)
 }
+
+impl<A> Option<A> {
+    pub fn is_some(&self) -> bool {
+        ensures(|b: bool| b == self.is_Some());
+
+        match self {
+            Option::Some(_) => true,
+            Option::None => false,
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        ensures(|b: bool| b == self.is_None());
+
+        match self {
+            Option::Some(_) => false,
+            Option::None => true,
+        }
+    }
+
+    pub fn unwrap(self) -> A {
+        requires(self.is_Some());
+        ensures(|a: A| equal(a, self.get_Some_0()));
+
+        match self {
+            Option::Some(a) => a,
+            Option::None => unreached(),
+        }
+    }
+
+    pub fn unwrap_or(self, default: A) -> A {
+        ensures(|a: A| equal(a, if self.is_Some() { self.get_Some_0() } else { default }));
+
+        match self {
+            Option::Some(a) => a,
+            Option::None => default,
+        }
+    }
+
+    pub fn map<B, F: Fn(A) -> B>(self, f: F) -> Option<B> {
+        requires(self.is_Some() >>= f.requires((self.get_Some_0(),)));
+        ensures(|r: Option<B>| [
+            r.is_Some() == self.is_Some(),
+            self.is_Some() >>= f.ensures((self.get_Some_0(),), r.get_Some_0()),
+        ]);
+
+        match self {
+            Option::Some(a) => Option::Some(f(a)),
+            Option::None => Option::None,
+        }
+    }
+
+    pub fn and_then<B, F: Fn(A) -> Option<B>>(self, f: F) -> Option<B> {
+        requires(self.is_Some() >>= f.requires((self.get_Some_0(),)));
+        ensures(|r: Option<B>| [
+            self.is_None() >>= r.is_None(),
+            self.is_Some() >>= f.ensures((self.get_Some_0(),), r),
+        ]);
+
+        match self {
+            Option::Some(a) => f(a),
+            Option::None => Option::None,
+        }
+    }
+
+    pub fn ok_or<E>(self, err: E) -> Result<A, E> {
+        ensures(|r: Result<A, E>| [
+            r.is_Ok() == self.is_Some(),
+            self.is_Some() >>= equal(r.get_Ok_0(), self.get_Some_0()),
+            self.is_None() >>= equal(r.get_Err_0(), err),
+        ]);
+
+        match self {
+            Option::Some(a) => Result::Ok(a),
+            Option::None => Result::Err(err),
+        }
+    }
+}
+
+// Support for the ? operator.
+// The verifier translates e? directly into a match and an early return,
+// so these impls are only used by the compiled code.
+
+impl<A> core::ops::Try for Option<A> {
+    type Output = A;
+    type Residual = Option<core::convert::Infallible>;
+
+    fn from_output(output: A) -> Self {
+        Option::Some(output)
+    }
+
+    fn branch(self) -> core::ops::ControlFlow<Self::Residual, A> {
+        match self {
+            Option::Some(a) => core::ops::ControlFlow::Continue(a),
+            Option::None => core::ops::ControlFlow::Break(Option::None),
+        }
+    }
+}
+
+impl<A> core::ops::FromResidual for Option<A> {
+    fn from_residual(_residual: Option<core::convert::Infallible>) -> Self {
+        Option::None
+    }
+}
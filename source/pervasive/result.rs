@@ -1,9 +1,130 @@
 #[allow(unused_imports)]
 use builtin::*;
 use builtin_macros::*;
+#[allow(unused_imports)]
+use crate::pervasive::*;
+#[allow(unused_imports)]
+use crate::pervasive::option::*;
 
 #[is_variant]
 pub enum Result<T, E> {
     Ok(T),
     Err(E)
 }
+
+impl<T, E> Result<T, E> {
+    pub fn is_ok(&self) -> bool {
+        ensures(|b: bool| b == self.is_Ok());
+
+        match self {
+            Result::Ok(_) => true,
+            Result::Err(_) => false,
+        }
+    }
+
+    pub fn is_err(&self) -> bool {
+        ensures(|b: bool| b == self.is_Err());
+
+        match self {
+            Result::Ok(_) => false,
+            Result::Err(_) => true,
+        }
+    }
+
+    pub fn unwrap(self) -> T {
+        requires(self.is_Ok());
+        ensures(|t: T| equal(t, self.get_Ok_0()));
+
+        match self {
+            Result::Ok(t) => t,
+            Result::Err(_) => unreached(),
+        }
+    }
+
+    pub fn unwrap_err(self) -> E {
+        requires(self.is_Err());
+        ensures(|e: E| equal(e, self.get_Err_0()));
+
+        match self {
+            Result::Ok(_) => unreached(),
+            Result::Err(e) => e,
+        }
+    }
+
+    pub fn unwrap_or(self, default: T) -> T {
+        ensures(|t: T| equal(t, if self.is_Ok() { self.get_Ok_0() } else { default }));
+
+        match self {
+            Result::Ok(t) => t,
+            Result::Err(_) => default,
+        }
+    }
+
+    pub fn map<U, F: Fn(T) -> U>(self, f: F) -> Result<U, E> {
+        requires(self.is_Ok() >>= f.requires((self.get_Ok_0(),)));
+        ensures(|r: Result<U, E>| [
+            r.is_Ok() == self.is_Ok(),
+            self.is_Ok() >>= f.ensures((self.get_Ok_0(),), r.get_Ok_0()),
+            self.is_Err() >>= equal(r.get_Err_0(), self.get_Err_0()),
+        ]);
+
+        match self {
+            Result::Ok(t) => Result::Ok(f(t)),
+            Result::Err(e) => Result::Err(e),
+        }
+    }
+
+    pub fn and_then<U, F: Fn(T) -> Result<U, E>>(self, f: F) -> Result<U, E> {
+        requires(self.is_Ok() >>= f.requires((self.get_Ok_0(),)));
+        ensures(|r: Result<U, E>| [
+            self.is_Ok() >>= f.ensures((self.get_Ok_0(),), r),
+            self.is_Err() >>= r.is_Err() && equal(r.get_Err_0(), self.get_Err_0()),
+        ]);
+
+        match self {
+            Result::Ok(t) => f(t),
+            Result::Err(e) => Result::Err(e),
+        }
+    }
+
+    pub fn ok(self) -> Option<T> {
+        ensures(|r: Option<T>| [
+            r.is_Some() == self.is_Ok(),
+            self.is_Ok() >>= equal(r.get_Some_0(), self.get_Ok_0()),
+        ]);
+
+        match self {
+            Result::Ok(t) => Option::Some(t),
+            Result::Err(_) => Option::None,
+        }
+    }
+}
+
+// Support for the ? operator.
+// The verifier translates e? directly into a match and an early return,
+// so these impls are only used by the compiled code.
+
+impl<T, E> core::ops::Try for Result<T, E> {
+    type Output = T;
+    type Residual = Result<core::convert::Infallible, E>;
+
+    fn from_output(output: T) -> Self {
+        Result::Ok(output)
+    }
+
+    fn branch(self) -> core::ops::ControlFlow<Self::Residual, T> {
+        match self {
+            Result::Ok(t) => core::ops::ControlFlow::Continue(t),
+            Result::Err(e) => core::ops::ControlFlow::Break(Result::Err(e)),
+        }
+    }
+}
+
+impl<T, E> core::ops::FromResidual for Result<T, E> {
+    fn from_residual(residual: Result<core::convert::Infallible, E>) -> Self {
+        match residual {
+            Result::Ok(i) => match i {},
+            Result::Err(e) => Result::Err(e),
+        }
+    }
+}
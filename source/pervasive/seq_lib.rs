@@ -4,10 +4,542 @@ use builtin::*;
 use crate::pervasive::*;
 #[allow(unused_imports)]
 use crate::pervasive::seq::*;
+#[allow(unused_imports)]
+use crate::pervasive::set::*;
+#[allow(unused_imports)]
+use crate::pervasive::multiset::*;
+#[allow(unused_imports)]
+use crate::pervasive::multiset_lib::*;
 
 impl<A> Seq<A> {
     #[spec] #[verifier(publish)]
     pub fn map<B, F: Fn(int, A) -> B>(self, f: F) -> Seq<B> {
         Seq::new(self.len(), |i: int| f(i, self.index(i)))
     }
+
+    #[spec] #[verifier(publish)]
+    pub fn contains(self, needle: A) -> bool {
+        exists(|i: int| 0 <= i && i < self.len() && equal(self.index(i), needle))
+    }
+
+    /// Some index at which needle appears (arbitrary if the sequence does not contain needle)
+    #[spec] #[verifier(publish)]
+    pub fn index_of(self, needle: A) -> int {
+        choose(|i: int| 0 <= i && i < self.len() && equal(self.index(i), needle))
+    }
+
+    #[spec] #[verifier(publish)]
+    pub fn first(self) -> A {
+        self.index(0)
+    }
+
+    #[spec] #[verifier(publish)]
+    pub fn last(self) -> A {
+        self.index(self.len() - 1)
+    }
+
+    #[spec] #[verifier(publish)]
+    pub fn drop_last(self) -> Seq<A> {
+        self.subrange(0, self.len() - 1)
+    }
+
+    #[spec] #[verifier(publish)]
+    pub fn reverse(self) -> Seq<A> {
+        Seq::new(self.len(), |i: int| self.index(self.len() - 1 - i))
+    }
+
+    /// Keep the elements satisfying pred, in their original order
+    #[spec] #[verifier(publish)]
+    pub fn filter<F: Fn(A) -> bool>(self, pred: F) -> Seq<A> {
+        decreases(self.len());
+
+        if self.len() == 0 {
+            Seq::empty()
+        } else if pred(self.last()) {
+            self.drop_last().filter(pred).push(self.last())
+        } else {
+            self.drop_last().filter(pred)
+        }
+    }
+
+    /// f(...f(f(b, s[0]), s[1])..., s[n - 1])
+    #[spec] #[verifier(publish)]
+    pub fn fold_left<B, F: Fn(B, A) -> B>(self, b: B, f: F) -> B {
+        decreases(self.len());
+
+        if self.len() == 0 {
+            b
+        } else {
+            f(self.drop_last().fold_left(b, f), self.last())
+        }
+    }
+
+    /// f(s[0], f(s[1], ...f(s[n - 1], b)...))
+    #[spec] #[verifier(publish)]
+    pub fn fold_right<B, F: Fn(A, B) -> B>(self, f: F, b: B) -> B {
+        decreases(self.len());
+
+        if self.len() == 0 {
+            b
+        } else {
+            self.drop_last().fold_right(f, f(self.last(), b))
+        }
+    }
+
+    #[spec] #[verifier(publish)]
+    pub fn to_set(self) -> Set<A> {
+        set_new(|a: A| self.contains(a))
+    }
+
+    #[spec] #[verifier(publish)]
+    pub fn to_multiset(self) -> Multiset<A> {
+        decreases(self.len());
+
+        if self.len() == 0 {
+            Multiset::empty()
+        } else {
+            self.drop_last().to_multiset().insert(self.last())
+        }
+    }
+
+    #[spec] #[verifier(publish)]
+    pub fn no_duplicates(self) -> bool {
+        forall(|i: int, j: int|
+            0 <= i && i < self.len() && 0 <= j && j < self.len() && i != j
+            >>= !equal(self.index(i), self.index(j)))
+    }
+
+    /// leq(s[i], s[j]) for all i < j
+    #[spec] #[verifier(publish)]
+    pub fn sorted_by<F: Fn(A, A) -> bool>(self, leq: F) -> bool {
+        forall(|i: int, j: int|
+            0 <= i && i < j && j < self.len() >>= leq(self.index(i), self.index(j)))
+    }
+
+    /// Same elements with the same number of occurrences, in any order
+    #[spec] #[verifier(publish)]
+    pub fn is_permutation(self, s2: Seq<A>) -> bool {
+        equal(self.to_multiset(), s2.to_multiset())
+    }
+}
+
+#[proof]
+pub fn lemma_index_of<A>(s: Seq<A>, a: A) {
+    requires(s.contains(a));
+    ensures([
+        0 <= s.index_of(a),
+        s.index_of(a) < s.len(),
+        equal(s.index(s.index_of(a)), a),
+    ]);
+}
+
+#[proof]
+pub fn lemma_drop_last<A>(s: Seq<A>) {
+    requires(s.len() >= 1);
+    ensures([
+        s.drop_last().len() == s.len() - 1,
+        equal(s.drop_last().push(s.last()), s),
+    ]);
+
+    assert(s.drop_last().push(s.last()).ext_equal(s));
+}
+
+#[proof]
+pub fn lemma_drop_last_push<A>(s: Seq<A>, a: A) {
+    ensures([
+        equal(s.push(a).last(), a),
+        equal(s.push(a).drop_last(), s),
+    ]);
+
+    assert(s.push(a).drop_last().ext_equal(s));
+}
+
+#[proof]
+pub fn lemma_push_contains<A>(s: Seq<A>, a: A, b: A) {
+    ensures(s.push(a).contains(b) == (s.contains(b) || equal(a, b)));
+
+    if s.contains(b) {
+        let i = s.index_of(b);
+        assert(equal(s.push(a).index(i), b));
+    }
+    assert(equal(s.push(a).index(s.len()), a));
+}
+
+#[proof]
+pub fn lemma_add_empty<A>(s: Seq<A>) {
+    ensures([
+        equal(s.add(Seq::empty()), s),
+        equal(Seq::empty().add(s), s),
+    ]);
+
+    assert(s.add(Seq::empty()).ext_equal(s));
+    assert(Seq::empty().add(s).ext_equal(s));
+}
+
+#[proof]
+pub fn lemma_add_push<A>(s1: Seq<A>, s2: Seq<A>, a: A) {
+    ensures(equal(s1.add(s2).push(a), s1.add(s2.push(a))));
+
+    assert(s1.add(s2).push(a).ext_equal(s1.add(s2.push(a))));
+}
+
+#[proof]
+pub fn lemma_add_contains<A>(s1: Seq<A>, s2: Seq<A>, a: A) {
+    ensures(s1.add(s2).contains(a) == (s1.contains(a) || s2.contains(a)));
+
+    if s1.contains(a) {
+        let i = s1.index_of(a);
+        assert(equal(s1.add(s2).index(i), a));
+    }
+    if s2.contains(a) {
+        let i = s2.index_of(a);
+        assert(equal(s1.add(s2).index(s1.len() + i), a));
+    }
+}
+
+#[proof]
+pub fn lemma_subrange_add<A>(s: Seq<A>, i: int) {
+    requires([
+        0 <= i,
+        i <= s.len(),
+    ]);
+    ensures(equal(s.subrange(0, i).add(s.subrange(i, s.len())), s));
+
+    assert(s.subrange(0, i).add(s.subrange(i, s.len())).ext_equal(s));
+}
+
+#[proof]
+pub fn lemma_subrange_push<A>(s: Seq<A>, i: int) {
+    requires([
+        0 <= i,
+        i < s.len(),
+    ]);
+    ensures(equal(s.subrange(0, i).push(s.index(i)), s.subrange(0, i + 1)));
+
+    assert(s.subrange(0, i).push(s.index(i)).ext_equal(s.subrange(0, i + 1)));
+}
+
+#[proof]
+pub fn lemma_subrange_contains<A>(s: Seq<A>, i: int, j: int, a: A) {
+    requires([
+        0 <= i,
+        i <= j,
+        j <= s.len(),
+        s.subrange(i, j).contains(a),
+    ]);
+    ensures(s.contains(a));
+
+    let k = s.subrange(i, j).index_of(a);
+    lemma_index_of::<A>(s.subrange(i, j), a);
+    assert(equal(s.index(k + i), a));
+}
+
+#[proof]
+pub fn lemma_reverse<A>(s: Seq<A>) {
+    ensures([
+        s.reverse().len() == s.len(),
+        equal(s.reverse().reverse(), s),
+    ]);
+
+    assert(s.reverse().reverse().ext_equal(s));
+}
+
+#[proof]
+pub fn lemma_reverse_index<A>(s: Seq<A>, i: int) {
+    requires([
+        0 <= i,
+        i < s.len(),
+    ]);
+    ensures(equal(s.reverse().index(i), s.index(s.len() - 1 - i)));
+}
+
+#[proof]
+pub fn lemma_filter_len<A, F: Fn(A) -> bool>(s: Seq<A>, pred: F) {
+    ensures(s.filter(pred).len() <= s.len());
+    decreases(s.len());
+
+    if s.len() != 0 {
+        lemma_filter_len::<A, F>(s.drop_last(), pred);
+    }
+}
+
+#[proof]
+pub fn lemma_filter_pred<A, F: Fn(A) -> bool>(s: Seq<A>, pred: F, i: int) {
+    requires([
+        0 <= i,
+        i < s.filter(pred).len(),
+    ]);
+    ensures(pred(s.filter(pred).index(i)));
+    decreases(s.len());
+
+    if i < s.drop_last().filter(pred).len() {
+        lemma_filter_pred::<A, F>(s.drop_last(), pred, i);
+    }
+}
+
+#[proof]
+pub fn lemma_filter_push<A, F: Fn(A) -> bool>(s: Seq<A>, pred: F, a: A) {
+    ensures(equal(
+        s.push(a).filter(pred),
+        if pred(a) { s.filter(pred).push(a) } else { s.filter(pred) },
+    ));
+
+    lemma_drop_last_push::<A>(s, a);
+}
+
+#[proof]
+pub fn lemma_fold_left_push<A, B, F: Fn(B, A) -> B>(s: Seq<A>, b: B, f: F, a: A) {
+    ensures(equal(s.push(a).fold_left(b, f), f(s.fold_left(b, f), a)));
+
+    lemma_drop_last_push::<A>(s, a);
+}
+
+#[proof]
+pub fn lemma_fold_right_push<A, B, F: Fn(A, B) -> B>(s: Seq<A>, f: F, b: B, a: A) {
+    ensures(equal(s.push(a).fold_right(f, b), s.fold_right(f, f(a, b))));
+
+    lemma_drop_last_push::<A>(s, a);
+}
+
+#[proof]
+pub fn lemma_fold_right_add<A, B, F: Fn(A, B) -> B>(s1: Seq<A>, s2: Seq<A>, f: F, b: B) {
+    ensures(equal(s1.add(s2).fold_right(f, b), s1.fold_right(f, s2.fold_right(f, b))));
+    decreases(s2.len());
+
+    if s2.len() == 0 {
+        lemma_add_empty::<A>(s1);
+        assert(s2.ext_equal(Seq::empty()));
+    } else {
+        let s = s2.drop_last();
+        let a = s2.last();
+        lemma_drop_last::<A>(s2);
+        lemma_add_push::<A>(s1, s, a);
+        lemma_fold_right_push::<A, B, F>(s1.add(s), f, b, a);
+        lemma_fold_right_push::<A, B, F>(s, f, b, a);
+        lemma_fold_right_add::<A, B, F>(s1, s, f, f(a, b));
+    }
+}
+
+#[proof]
+pub fn lemma_to_set_push<A>(s: Seq<A>, a: A) {
+    ensures(equal(s.push(a).to_set(), s.to_set().insert(a)));
+
+    assert_forall_by(|b: A| {
+        ensures(s.push(a).to_set().contains(b) == s.to_set().insert(a).contains(b));
+        lemma_push_contains::<A>(s, a, b);
+    });
+    assert(s.push(a).to_set().ext_equal(s.to_set().insert(a)));
+}
+
+#[proof]
+pub fn lemma_no_duplicates_push<A>(s: Seq<A>, a: A) {
+    requires([
+        s.no_duplicates(),
+        !s.contains(a),
+    ]);
+    ensures(s.push(a).no_duplicates());
+
+    assert_forall_by(|i: int, j: int| {
+        requires(0 <= i && i < s.len() + 1 && 0 <= j && j < s.len() + 1 && i != j);
+        ensures(!equal(s.push(a).index(i), s.push(a).index(j)));
+        if i < s.len() {
+            assert(equal(s.push(a).index(i), s.index(i)));
+        }
+        if j < s.len() {
+            assert(equal(s.push(a).index(j), s.index(j)));
+        }
+    });
+}
+
+#[proof]
+pub fn lemma_to_multiset_push<A>(s: Seq<A>, a: A) {
+    ensures(equal(s.push(a).to_multiset(), s.to_multiset().insert(a)));
+
+    lemma_drop_last_push::<A>(s, a);
+}
+
+#[proof]
+pub fn lemma_to_multiset_len<A>(s: Seq<A>) {
+    ensures([
+        s.to_multiset().dom().finite(),
+        s.to_multiset().len() == s.len(),
+    ]);
+    decreases(s.len());
+
+    if s.len() == 0 {
+        lemma_multiset_empty_dom::<A>();
+    } else {
+        lemma_to_multiset_len::<A>(s.drop_last());
+        lemma_multiset_insert_len::<A>(s.drop_last().to_multiset(), s.last());
+    }
+}
+
+#[proof]
+pub fn lemma_to_multiset_count<A>(s: Seq<A>, a: A) {
+    ensures([
+        (s.to_multiset().count(a) > 0) == s.contains(a),
+        s.to_multiset().count(a) <= s.len(),
+    ]);
+    decreases(s.len());
+
+    lemma_to_multiset_len::<A>(s);
+    if s.len() != 0 {
+        lemma_drop_last::<A>(s);
+        lemma_to_multiset_count::<A>(s.drop_last(), a);
+        lemma_push_contains::<A>(s.drop_last(), s.last(), a);
+    }
+}
+
+#[proof]
+pub fn lemma_to_multiset_add<A>(s1: Seq<A>, s2: Seq<A>) {
+    ensures(equal(s1.add(s2).to_multiset(), s1.to_multiset().add(s2.to_multiset())));
+    decreases(s2.len());
+
+    if s2.len() == 0 {
+        lemma_add_empty::<A>(s1);
+        assert(s2.ext_equal(Seq::empty()));
+        assert(s1.to_multiset().add(s2.to_multiset()).ext_equal(s1.to_multiset()));
+    } else {
+        let s = s2.drop_last();
+        let a = s2.last();
+        lemma_drop_last::<A>(s2);
+        lemma_add_push::<A>(s1, s, a);
+        lemma_to_multiset_push::<A>(s1.add(s), a);
+        lemma_to_multiset_push::<A>(s, a);
+        lemma_to_multiset_add::<A>(s1, s);
+        assert(s1.add(s2).to_multiset().ext_equal(s1.to_multiset().add(s2.to_multiset())));
+    }
+}
+
+#[proof]
+pub fn lemma_to_multiset_update<A>(s: Seq<A>, i: int, a: A) {
+    requires([
+        0 <= i,
+        i < s.len(),
+    ]);
+    ensures([
+        s.to_multiset().count(s.index(i)) > 0,
+        equal(s.update(i, a).to_multiset(), s.to_multiset().insert(a).remove(s.index(i))),
+    ]);
+
+    let s1 = s.subrange(0, i);
+    let s2 = s.subrange(i + 1, s.len());
+    assert(s.ext_equal(s1.push(s.index(i)).add(s2)));
+    assert(s.update(i, a).ext_equal(s1.push(a).add(s2)));
+    lemma_to_multiset_add::<A>(s1.push(s.index(i)), s2);
+    lemma_to_multiset_add::<A>(s1.push(a), s2);
+    lemma_to_multiset_push::<A>(s1, s.index(i));
+    lemma_to_multiset_push::<A>(s1, a);
+    assert(s.update(i, a).to_multiset().ext_equal(s.to_multiset().insert(a).remove(s.index(i))));
+}
+
+#[proof]
+pub fn lemma_swap_permutation<A>(s: Seq<A>, i: int, j: int) {
+    requires([
+        0 <= i,
+        i < s.len(),
+        0 <= j,
+        j < s.len(),
+    ]);
+    ensures(s.update(i, s.index(j)).update(j, s.index(i)).is_permutation(s));
+
+    let s1 = s.update(i, s.index(j));
+    lemma_to_multiset_update::<A>(s, i, s.index(j));
+    lemma_to_multiset_update::<A>(s1, j, s.index(i));
+    assert(equal(s1.index(j), s.index(j)));
+    assert(s1.update(j, s.index(i)).to_multiset().ext_equal(s.to_multiset()));
+}
+
+#[proof]
+pub fn lemma_add_permutation<A>(s1: Seq<A>, s2: Seq<A>, t1: Seq<A>, t2: Seq<A>) {
+    requires([
+        s1.is_permutation(t1),
+        s2.is_permutation(t2),
+    ]);
+    ensures(s1.add(s2).is_permutation(t1.add(t2)));
+
+    lemma_to_multiset_add::<A>(s1, s2);
+    lemma_to_multiset_add::<A>(t1, t2);
+}
+
+#[proof]
+pub fn lemma_insert_permutation<A>(s: Seq<A>, i: int, a: A) {
+    requires([
+        0 <= i,
+        i <= s.len(),
+    ]);
+    ensures(s.subrange(0, i).push(a).add(s.subrange(i, s.len())).is_permutation(s.push(a)));
+
+    let s1 = s.subrange(0, i);
+    let s2 = s.subrange(i, s.len());
+    lemma_subrange_add::<A>(s, i);
+    lemma_to_multiset_add::<A>(s1, s2);
+    lemma_to_multiset_add::<A>(s1.push(a), s2);
+    lemma_to_multiset_push::<A>(s1, a);
+    lemma_to_multiset_push::<A>(s, a);
+    assert(s1.push(a).add(s2).to_multiset().ext_equal(s.push(a).to_multiset()));
+}
+
+#[proof]
+pub fn lemma_sorted_subrange<A, F: Fn(A, A) -> bool>(s: Seq<A>, leq: F, i: int, j: int) {
+    requires([
+        s.sorted_by(leq),
+        0 <= i,
+        i <= j,
+        j <= s.len(),
+    ]);
+    ensures(s.subrange(i, j).sorted_by(leq));
+
+    assert_forall_by(|k: int, l: int| {
+        requires(0 <= k && k < l && l < j - i);
+        ensures(leq(s.subrange(i, j).index(k), s.subrange(i, j).index(l)));
+        assert(equal(s.subrange(i, j).index(k), s.index(k + i)));
+        assert(equal(s.subrange(i, j).index(l), s.index(l + i)));
+    });
+}
+
+#[proof]
+pub fn lemma_sorted_add<A, F: Fn(A, A) -> bool>(s1: Seq<A>, s2: Seq<A>, leq: F) {
+    requires([
+        s1.sorted_by(leq),
+        s2.sorted_by(leq),
+        forall(|i: int, j: int| 0 <= i && i < s1.len() && 0 <= j && j < s2.len()
+            >>= leq(s1.index(i), s2.index(j))),
+    ]);
+    ensures(s1.add(s2).sorted_by(leq));
+
+    assert_forall_by(|k: int, l: int| {
+        requires(0 <= k && k < l && l < s1.len() + s2.len());
+        ensures(leq(s1.add(s2).index(k), s1.add(s2).index(l)));
+        if l < s1.len() {
+            assert(equal(s1.add(s2).index(l), s1.index(l)));
+        } else {
+            assert(equal(s1.add(s2).index(l), s2.index(l - s1.len())));
+        }
+        if k < s1.len() {
+            assert(equal(s1.add(s2).index(k), s1.index(k)));
+        } else {
+            assert(equal(s1.add(s2).index(k), s2.index(k - s1.len())));
+        }
+    });
+}
+
+#[proof]
+pub fn lemma_sorted_push<A, F: Fn(A, A) -> bool>(s: Seq<A>, leq: F, a: A) {
+    requires([
+        s.sorted_by(leq),
+        forall(|i: int| 0 <= i && i < s.len() >>= leq(s.index(i), a)),
+    ]);
+    ensures(s.push(a).sorted_by(leq));
+
+    assert_forall_by(|k: int, l: int| {
+        requires(0 <= k && k < l && l < s.len() + 1);
+        ensures(leq(s.push(a).index(k), s.push(a).index(l)));
+        assert(equal(s.push(a).index(k), s.index(k)));
+        if l < s.len() {
+            assert(equal(s.push(a).index(l), s.index(l)));
+        } else {
+            assert(equal(s.push(a).index(l), a));
+        }
+    });
 }
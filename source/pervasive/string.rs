@@ -0,0 +1,103 @@
+#[allow(unused_imports)]
+use builtin::*;
+#[allow(unused_imports)]
+use builtin_macros::*;
+#[allow(unused_imports)]
+use crate::pervasive::*;
+#[allow(unused_imports)]
+use crate::pervasive::seq::*;
+
+/// Executable owned string, viewed as the sequence of its chars.
+/// Lengths and indices are measured in chars, not in UTF-8 bytes.
+#[verifier(external_body)]
+pub struct String {
+    pub inner: std::string::String,
+}
+
+/// Executable borrowed string slice, viewed as the sequence of its chars.
+#[verifier(external_body)]
+pub struct StrSlice<'a> {
+    pub inner: &'a str,
+}
+
+impl String {
+    fndecl!(pub fn view(&self) -> Seq<char>);
+
+    #[verifier(external_body)]
+    pub fn new() -> String {
+        ensures(|s: String| equal(s.view(), Seq::empty()));
+
+        String { inner: std::string::String::new() }
+    }
+
+    #[verifier(external_body)]
+    #[verifier(autoview)]
+    pub fn len(&self) -> usize {
+        ensures(|l: usize| l == self.view().len());
+
+        self.inner.chars().count()
+    }
+
+    #[verifier(external_body)]
+    pub fn get_char(&self, i: usize) -> char {
+        requires(i < self.view().len());
+        ensures(|c: char| c == self.view().index(i));
+
+        self.inner.chars().nth(i).unwrap()
+    }
+
+    #[verifier(external_body)]
+    pub fn push(&mut self, c: char) {
+        ensures(equal(self.view(), old(self).view().push(c)));
+
+        self.inner.push(c);
+    }
+
+    #[verifier(external_body)]
+    pub fn as_str<'a>(&'a self) -> StrSlice<'a> {
+        ensures(|s: StrSlice<'a>| equal(s.view(), self.view()));
+
+        StrSlice { inner: self.inner.as_str() }
+    }
+
+    #[verifier(external_body)]
+    pub fn eq(&self, other: &String) -> bool {
+        ensures(|b: bool| b == equal(self.view(), other.view()));
+
+        self.inner == other.inner
+    }
+}
+
+impl<'a> StrSlice<'a> {
+    fndecl!(pub fn view(&self) -> Seq<char>);
+
+    #[verifier(external_body)]
+    #[verifier(autoview)]
+    pub fn len(&self) -> usize {
+        ensures(|l: usize| l == self.view().len());
+
+        self.inner.chars().count()
+    }
+
+    #[verifier(external_body)]
+    pub fn get_char(&self, i: usize) -> char {
+        requires(i < self.view().len());
+        ensures(|c: char| c == self.view().index(i));
+
+        self.inner.chars().nth(i).unwrap()
+    }
+
+    #[verifier(external_body)]
+    pub fn to_string(&self) -> String {
+        ensures(|s: String| equal(s.view(), self.view()));
+
+        String { inner: self.inner.to_string() }
+    }
+
+    #[verifier(external_body)]
+    pub fn eq(&self, other: &StrSlice) -> bool {
+        ensures(|b: bool| b == equal(self.view(), other.view()));
+
+        self.inner == other.inner
+    }
+}
@@ -15,6 +15,94 @@ pub struct Vec<#[verifier(strictly_positive)] A> {
 impl<A> Vec<A> {
     fndecl!(pub fn view(&self) -> Seq<A>);
 
+    #[verifier(external_body)]
+    pub fn new() -> Vec<A> {
+        ensures(|v: Vec<A>| equal(v.view(), Seq::empty()));
+
+        Vec { vec: std::vec::Vec::new() }
+    }
+
+    #[verifier(external_body)]
+    pub fn with_capacity(capacity: usize) -> Vec<A> {
+        ensures(|v: Vec<A>| equal(v.view(), Seq::empty()));
+
+        Vec { vec: std::vec::Vec::with_capacity(capacity) }
+    }
+
+    #[verifier(external_body)]
+    pub fn push(&mut self, value: A) {
+        ensures(equal(self.view(), old(self).view().push(value)));
+
+        self.vec.push(value);
+    }
+
+    #[verifier(external_body)]
+    pub fn pop(&mut self) -> A {
+        requires(old(self).view().len() > 0);
+        ensures(|value: A| [
+            equal(value, old(self).view().index(old(self).view().len() - 1)),
+            equal(self.view(), old(self).view().subrange(0, old(self).view().len() - 1)),
+        ]);
+
+        self.vec.pop().unwrap()
+    }
+
+    #[verifier(external_body)]
+    pub fn insert(&mut self, i: usize, a: A) {
+        requires(i <= old(self).view().len());
+        ensures(equal(
+            self.view(),
+            old(self).view().subrange(0, i).push(a).add(old(self).view().subrange(i, old(self).view().len())),
+        ));
+
+        self.vec.insert(i, a);
+    }
+
+    #[verifier(external_body)]
+    pub fn remove(&mut self, i: usize) -> A {
+        requires(i < old(self).view().len());
+        ensures(|a: A| [
+            equal(a, old(self).view().index(i)),
+            equal(
+                self.view(),
+                old(self).view().subrange(0, i).add(old(self).view().subrange(i + 1, old(self).view().len())),
+            ),
+        ]);
+
+        self.vec.remove(i)
+    }
+
+    #[verifier(external_body)]
+    pub fn clear(&mut self) {
+        ensures(equal(self.view(), Seq::empty()));
+
+        self.vec.clear();
+    }
+
+    #[verifier(external_body)]
+    pub fn truncate(&mut self, len: usize) {
+        ensures([
+            len <= old(self).view().len() >>= equal(self.view(), old(self).view().subrange(0, len)),
+            len > old(self).view().len() >>= equal(self.view(), old(self).view()),
+        ]);
+
+        self.vec.truncate(len);
+    }
+
+    #[verifier(external_body)]
+    pub fn swap(&mut self, i: usize, j: usize) {
+        requires([
+            i < old(self).view().len(),
+            j < old(self).view().len(),
+        ]);
+        ensures(equal(
+            self.view(),
+            old(self).view().update(i, old(self).view().index(j)).update(j, old(self).view().index(i)),
+        ));
+
+        self.vec.swap(i, j);
+    }
+
     #[verifier(external_body)]
     #[verifier(autoview)]
     pub fn index(&self, i: usize) -> &A {
@@ -25,7 +113,16 @@ impl<A> Vec<A> {
     }
 
     #[verifier(external_body)]
-    pub fn set(self, i: usize, a: A) -> Vec<A> {
+    pub fn set(&mut self, i: usize, a: A) {
+        requires(i < old(self).view().len());
+        ensures(equal(self.view(), old(self).view().update(i, a)));
+
+        self.vec[i] = a;
+    }
+
+    /// By-value version of set (set used to take self by value and return the updated vector)
+    #[verifier(external_body)]
+    pub fn update(self, i: usize, a: A) -> Vec<A> {
         requires(i < self.view().len());
         ensures(|v2: Vec<A>| equal(v2.view(), self.view().update(i, a)));
 
@@ -34,6 +131,17 @@ impl<A> Vec<A> {
         v2
     }
 
+    #[verifier(external_body)]
+    pub fn get_mut(&mut self, i: usize) -> &mut A {
+        requires(i < old(self).view().len());
+        ensures(|r: &mut A| [
+            equal(*r, old(self).view().index(i)),
+            equal(self.view(), old(self).view().update(i, fin(r))),
+        ]);
+
+        &mut self.vec[i]
+    }
+
     #[verifier(external_body)]
     #[verifier(autoview)]
     pub fn len(&self) -> usize {
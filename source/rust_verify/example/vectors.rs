@@ -53,8 +53,8 @@ fn reverse(v1: Vec<u64>) -> Vec<u64> {
 
         let x = *v2.index(n);
         let y = *v2.index(length - 1 - n);
-        v2 = v2.set(n, y);
-        v2 = v2.set(length - 1 - n, x);
+        v2.set(n, y);
+        v2.set(length - 1 - n, x);
 
         n = n + 1;
     }
@@ -128,6 +128,10 @@ pub(crate) enum Attr {
     InvariantBlock,
     // an enum variant is_Variant
     IsVariant,
+    // SMT resource limit for the function's queries
+    Rlimit(u32),
+    // SMT solver option for the function's queries
+    SmtOption(String, String),
 }
 
 fn get_trigger_arg(span: Span, attr_tree: &AttrTree) -> Result<u64, VirErr> {
@@ -144,6 +148,14 @@ fn get_trigger_arg(span: Span, attr_tree: &AttrTree) -> Result<u64, VirErr> {
     }
 }
 
+// AIR passes option values to the SMT solver as Booleans, integers, or decimals
+fn is_smt_option_value(value: &str) -> bool {
+    value == "true"
+        || value == "false"
+        || value.parse::<u32>().is_ok()
+        || (value.contains(".") && value.parse::<f64>().is_ok())
+}
+
 pub(crate) fn parse_attrs(attrs: &[Attribute]) -> Result<Vec<Attr>, VirErr> {
     let mut v: Vec<Attr> = Vec::new();
     for attr in attrs_to_trees(attrs) {
@@ -209,6 +221,32 @@ pub(crate) fn parse_attrs(attrs: &[Attribute]) -> Result<Vec<Attr>, VirErr> {
                 Some(box [AttrTree::Fun(_, arg, None)]) if arg == "bit_vector" => {
                     v.push(Attr::BitVector)
                 }
+                Some(box [AttrTree::Fun(_, arg, Some(box [AttrTree::Fun(_, n, None)]))])
+                    if arg == "rlimit" =>
+                {
+                    match n.parse::<u32>() {
+                        Ok(n) if crate::config::rlimit_to_smt(n).is_some() => {
+                            v.push(Attr::Rlimit(n))
+                        }
+                        Ok(_) => return err_span_str(span, "rlimit(...) is too large"),
+                        Err(_) => return err_span_str(span, "expected integer in rlimit(...)"),
+                    }
+                }
+                Some(
+                    box [AttrTree::Fun(
+                        _,
+                        arg,
+                        Some(box [AttrTree::Fun(_, option, None), AttrTree::Fun(_, value, None)]),
+                    )],
+                ) if arg == "smt_option" => {
+                    if !is_smt_option_value(value) {
+                        return err_span_str(
+                            span,
+                            "expected smt_option value to be true, false, or a number",
+                        );
+                    }
+                    v.push(Attr::SmtOption(option.clone(), value.clone()))
+                }
                 Some(box [AttrTree::Fun(_, arg, Some(box [AttrTree::Fun(_, name, None)]))])
                     if arg == "returns" && name == "spec" =>
                 {
@@ -308,6 +346,8 @@ pub(crate) struct VerifierAttrs {
     pub(crate) unforgeable: bool,
     pub(crate) atomic: bool,
     pub(crate) is_variant: bool,
+    pub(crate) rlimit: Option<u32>,
+    pub(crate) smt_options: Vec<(String, String)>,
 }
 
 pub(crate) fn get_verifier_attrs(attrs: &[Attribute]) -> Result<VerifierAttrs, VirErr> {
@@ -326,6 +366,8 @@ pub(crate) fn get_verifier_attrs(attrs: &[Attribute]) -> Result<VerifierAttrs, V
         unforgeable: false,
         atomic: false,
         is_variant: false,
+        rlimit: None,
+        smt_options: Vec::new(),
     };
     for attr in parse_attrs(attrs)? {
         match attr {
@@ -343,6 +385,8 @@ pub(crate) fn get_verifier_attrs(attrs: &[Attribute]) -> Result<VerifierAttrs, V
             Attr::Unforgeable => vs.unforgeable = true,
             Attr::Atomic => vs.atomic = true,
             Attr::IsVariant => vs.is_variant = true,
+            Attr::Rlimit(n) => vs.rlimit = Some(n),
+            Attr::SmtOption(option, value) => vs.smt_options.push((option, value)),
             _ => {}
         }
     }
@@ -0,0 +1,127 @@
+//! Persistent cache of Function-Def queries that a previous run already proved valid.
+//!
+//! Each entry is an empty file whose name is the fingerprint of a query.
+//! The fingerprint is a SHA-256 digest of:
+//! - the verifier build (a digest of the verifier's own executable) and the SMT solver version,
+//! - the SMT options,
+//! - the AIR commands that establish the module's context
+//!   (prelude, datatypes, function declarations and axioms generated from the pruned crate),
+//! - the function's own SMT settings and AIR commands.
+//! Only valid results are cached, so a cache hit can never hide an error;
+//! a stale or corrupted cache at worst causes functions to be re-checked.
+
+use crate::config::Args;
+use air::ast::Command;
+use air::printer::{NodeWriter, Printer};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use vir::ast::FunctionAttrsX;
+
+pub const DEFAULT_CACHE_DIR: &str = "target/verus-cache";
+
+pub type Fingerprint = String;
+
+pub struct VerificationCache {
+    dir: PathBuf,
+    /// Digest of the verifier executable and the SMT solver version
+    build_id: Vec<u8>,
+}
+
+fn hash_str(hasher: &mut Sha256, s: &str) {
+    // Length-prefix each string so that different sequences of strings never hash the same bytes
+    hasher.update(&(s.len() as u64).to_le_bytes());
+    hasher.update(s.as_bytes());
+}
+
+fn hash_smt_options(hasher: &mut Sha256, rlimit: Option<u32>, options: &Vec<(String, String)>) {
+    match rlimit {
+        None => hasher.update(&[0]),
+        Some(rlimit) => {
+            hasher.update(&[1]);
+            hasher.update(&rlimit.to_le_bytes());
+        }
+    }
+    hasher.update(&(options.len() as u64).to_le_bytes());
+    for (option, value) in options.iter() {
+        hash_str(hasher, option);
+        hash_str(hasher, value);
+    }
+}
+
+fn hash_commands(hasher: &mut Sha256, commands: &[Command]) {
+    let printer = Printer::new(false);
+    let mut node_writer = NodeWriter::new();
+    let indent = "".to_string();
+    hasher.update(&(commands.len() as u64).to_le_bytes());
+    for command in commands.iter() {
+        let s = node_writer.node_to_string_indent(&indent, &printer.command_to_node(command));
+        hash_str(hasher, &s);
+    }
+}
+
+/// Identify the verifier build by a digest of its own executable,
+/// so that entries recorded by a different build of the verifier are never used
+fn verifier_build_digest() -> std::io::Result<Vec<u8>> {
+    let exe = std::fs::read(std::env::current_exe()?)?;
+    Ok(Sha256::digest(&exe).to_vec())
+}
+
+impl VerificationCache {
+    /// Open the cache in directory dir, creating the directory if necessary.
+    /// smt_version is the version reported by the SMT solver that will run the queries.
+    /// Returns None (after printing a warning) if the directory cannot be created
+    /// or the verifier build cannot be identified.
+    pub fn new(dir: &str, smt_version: &str) -> Option<Self> {
+        let build_digest = match verifier_build_digest() {
+            Ok(digest) => digest,
+            Err(err) => {
+                eprintln!(
+                    "warning: verification cache disabled: cannot read the verifier: {}",
+                    err
+                );
+                return None;
+            }
+        };
+        if let Err(err) = std::fs::create_dir_all(dir) {
+            eprintln!("warning: could not create verification cache directory {}: {}", dir, err);
+            return None;
+        }
+        let mut hasher = Sha256::new();
+        hash_str(&mut hasher, env!("CARGO_PKG_VERSION"));
+        hasher.update(&build_digest);
+        hash_str(&mut hasher, smt_version);
+        Some(VerificationCache { dir: PathBuf::from(dir), build_id: hasher.finalize().to_vec() })
+    }
+
+    /// Hash the verifier build, the SMT configuration,
+    /// and the commands shared by all queries in a module
+    pub fn context_hasher(&self, args: &Args, context_commands: &[Command]) -> Sha256 {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.build_id);
+        hash_smt_options(&mut hasher, Some(args.rlimit), &args.smt_options);
+        hash_commands(&mut hasher, context_commands);
+        hasher
+    }
+
+    /// Extend the module's context hash with a function's own SMT settings and commands
+    pub fn fingerprint(
+        context_hasher: &Sha256,
+        attrs: &FunctionAttrsX,
+        commands: &[Command],
+    ) -> Fingerprint {
+        let mut hasher = context_hasher.clone();
+        hash_smt_options(&mut hasher, attrs.rlimit, &attrs.smt_options);
+        hash_commands(&mut hasher, commands);
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn is_verified(&self, fingerprint: &Fingerprint) -> bool {
+        self.dir.join(fingerprint).is_file()
+    }
+
+    pub fn record_verified(&self, fingerprint: &Fingerprint) {
+        if let Err(err) = std::fs::File::create(self.dir.join(fingerprint)) {
+            eprintln!("warning: could not write verification cache entry: {}", err);
+        }
+    }
+}
@@ -1,17 +1,22 @@
 use getopts::Options;
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Args {
     pub pervasive_path: Option<String>,
     pub verify_root: bool,
     pub verify_module: Option<String>,
+    pub verify_function: Option<String>,
     pub verify_pervasive: bool,
     pub external_body: bool,
     pub no_lifetime: bool,
     pub time: bool,
+    pub time_functions: bool,
+    pub time_functions_csv: Option<String>,
     pub rlimit: u32,
     pub smt_options: Vec<(String, String)>,
     pub multiple_errors: u32,
+    pub num_threads: u32,
+    pub cache_dir: Option<String>,
     pub log_vir: Option<String>,
     pub log_vir_simple: Option<String>,
     pub log_vir_poly: Option<String>,
@@ -19,32 +24,60 @@ pub struct Args {
     pub log_air_final: Option<String>,
     pub log_smt: Option<String>,
     pub log_triggers: Option<String>,
+    pub output_json: Option<String>,
     pub show_triggers: bool,
+    pub profile_quantifiers: bool,
     pub print_erased: bool,
     pub print_erased_spec: bool,
     pub ignore_unexpected_smt: bool,
     pub debug: bool,
+    pub report_counterexamples: bool,
     pub compile: bool,
 }
 
-pub fn enable_default_features(rustc_args: &mut Vec<String>) {
-    for feature in &["stmt_expr_attributes", "box_syntax", "box_patterns", "negative_impls"] {
+/// Convert a resource limit, given with --rlimit or #[verifier(rlimit(...))],
+/// to the SMT solver's units; returns None if the limit is too large
+pub fn rlimit_to_smt(rlimit: u32) -> Option<u32> {
+    rlimit.checked_mul(1000000)
+}
+
+fn enable_features(rustc_args: &mut Vec<String>, features: &[&str]) {
+    for feature in features {
         rustc_args.push("-Z".to_string());
         rustc_args.push(format!("enable_feature={}", feature));
     }
 }
 
+pub fn enable_default_features(rustc_args: &mut Vec<String>) {
+    enable_features(
+        rustc_args,
+        &["stmt_expr_attributes", "box_syntax", "box_patterns", "negative_impls"],
+    );
+}
+
+/// Features needed by the pervasive library, enabled only for crates built with pervasive
+/// (pervasive implements core::ops::Try for its Option and Result)
+pub fn enable_pervasive_features(rustc_args: &mut Vec<String>) {
+    enable_features(rustc_args, &["try_trait_v2"]);
+}
+
 pub fn parse_args(program: &String, args: impl Iterator<Item = String>) -> (Args, Vec<String>) {
     const OPT_PERVASIVE_PATH: &str = "pervasive-path";
     const OPT_VERIFY_ROOT: &str = "verify-root";
     const OPT_VERIFY_MODULE: &str = "verify-module";
+    const OPT_VERIFY_FUNCTION: &str = "verify-function";
     const OPT_VERIFY_PERVASIVE: &str = "verify-pervasive";
     const OPT_NO_VERIFY: &str = "no-verify";
     const OPT_NO_LIFETIME: &str = "no-lifetime";
     const OPT_TIME: &str = "time";
+    const OPT_TIME_FUNCTIONS: &str = "time-functions";
+    const OPT_TIME_FUNCTIONS_CSV: &str = "time-functions-csv";
     const OPT_RLIMIT: &str = "rlimit";
     const OPT_SMT_OPTION: &str = "smt-option";
     const OPT_MULTIPLE_ERRORS: &str = "multiple-errors";
+    const OPT_NUM_THREADS: &str = "num-threads";
+    const OPT_CACHE_DIR: &str = "cache-dir";
+    const OPT_NO_CACHE: &str = "no-cache";
     const OPT_LOG_VIR: &str = "log-vir";
     const OPT_LOG_VIR_SIMPLE: &str = "log-vir-simple";
     const OPT_LOG_VIR_POLY: &str = "log-vir-poly";
@@ -52,11 +85,14 @@ pub fn parse_args(program: &String, args: impl Iterator<Item = String>) -> (Args
     const OPT_LOG_AIR_FINAL: &str = "log-air-final";
     const OPT_LOG_SMT: &str = "log-smt";
     const OPT_LOG_TRIGGERS: &str = "log-triggers";
+    const OPT_OUTPUT_JSON: &str = "output-json";
     const OPT_TRIGGERS: &str = "triggers";
+    const OPT_PROFILE_QUANTIFIERS: &str = "profile-quantifiers";
     const OPT_PRINT_ERASED: &str = "print-erased";
     const OPT_PRINT_ERASED_SPEC: &str = "print-erased-spec";
     const OPT_IGNORE_UNEXPECTED_SMT: &str = "ignore-unexpected-smt";
     const OPT_DEBUG: &str = "debug";
+    const OPT_REPORT_COUNTEREXAMPLES: &str = "report-counterexamples";
     const OPT_COMPILE: &str = "compile";
 
     let mut opts = Options::new();
@@ -68,13 +104,46 @@ pub fn parse_args(program: &String, args: impl Iterator<Item = String>) -> (Args
         "Verify just one submodule within crate (e.g. 'foo' or 'foo::bar')",
         "MODULE",
     );
+    opts.optopt(
+        "",
+        OPT_VERIFY_FUNCTION,
+        "Verify just one function within crate (e.g. 'foo::bar' or 'Type::method')",
+        "PATH",
+    );
     opts.optflag("", OPT_VERIFY_PERVASIVE, "Verify trusted pervasive modules");
     opts.optflag("", OPT_NO_VERIFY, "Do not run verification");
     opts.optflag("", OPT_NO_LIFETIME, "Do not run lifetime checking on proofs");
     opts.optflag("", OPT_TIME, "Measure and report time taken");
+    opts.optflag(
+        "",
+        OPT_TIME_FUNCTIONS,
+        "Report time and SMT resources (rlimit-count) used by the slowest functions",
+    );
+    opts.optopt(
+        "",
+        OPT_TIME_FUNCTIONS_CSV,
+        "Write time and SMT resources used by each function as CSV",
+        "FILENAME",
+    );
     opts.optopt("", OPT_RLIMIT, "Set SMT resource limit (roughly in seconds)", "INTEGER");
     opts.optmulti("", OPT_SMT_OPTION, "Set an SMT option (e.g. smt.random_seed=7)", "OPTION=VALUE");
     opts.optopt("", OPT_MULTIPLE_ERRORS, "If 0, look for at most one error per function; if > 0, always find first error in function and make extra queries to find more errors (default: 2)", "INTEGER");
+    opts.optopt(
+        "",
+        OPT_NUM_THREADS,
+        "Number of SMT processes used to verify function bodies in parallel (default: 1)",
+        "INTEGER",
+    );
+    opts.optopt(
+        "",
+        OPT_CACHE_DIR,
+        &format!(
+            "Directory for caching functions that were already verified (default: {})",
+            crate::cache::DEFAULT_CACHE_DIR
+        ),
+        "PATH",
+    );
+    opts.optflag("", OPT_NO_CACHE, "Do not use or update the verification cache");
     opts.optopt("", OPT_LOG_VIR, "Log VIR", "FILENAME");
     opts.optopt("", OPT_LOG_VIR_SIMPLE, "Log simplified VIR", "FILENAME");
     opts.optopt(
@@ -88,10 +157,26 @@ pub fn parse_args(program: &String, args: impl Iterator<Item = String>) -> (Args
     opts.optopt("", OPT_LOG_SMT, "Log SMT queries", "FILENAME");
     opts.optopt("", OPT_LOG_TRIGGERS, "Log automatically chosen triggers", "FILENAME");
     opts.optflag("", OPT_TRIGGERS, "Show automatically chosen triggers");
+    opts.optflag(
+        "",
+        OPT_PROFILE_QUANTIFIERS,
+        "Report the most frequently instantiated quantifiers for each function",
+    );
+    opts.optopt(
+        "",
+        OPT_OUTPUT_JSON,
+        "Write the verification outcome of each function as JSON",
+        "FILENAME",
+    );
     opts.optflag("", OPT_PRINT_ERASED, "Print code after erasing spec/proof (requires --compile)");
     opts.optflag("", OPT_PRINT_ERASED_SPEC, "Print code after erasing spec");
     opts.optflag("", OPT_IGNORE_UNEXPECTED_SMT, "Ignore unexpected SMT output");
     opts.optflag("", OPT_DEBUG, "Enable debugging of proof failures");
+    opts.optflag(
+        "",
+        OPT_REPORT_COUNTEREXAMPLES,
+        "Report the values of variables at each line leading to a proof failure",
+    );
     opts.optflag("", OPT_COMPILE, "Run Rustc compiler after verification");
     opts.optflag("h", "help", "print this help menu");
 
@@ -126,10 +211,13 @@ pub fn parse_args(program: &String, args: impl Iterator<Item = String>) -> (Args
         pervasive_path: matches.opt_str(OPT_PERVASIVE_PATH),
         verify_root: matches.opt_present(OPT_VERIFY_ROOT),
         verify_module: matches.opt_str(OPT_VERIFY_MODULE),
+        verify_function: matches.opt_str(OPT_VERIFY_FUNCTION),
         verify_pervasive: matches.opt_present(OPT_VERIFY_PERVASIVE),
         external_body: matches.opt_present(OPT_NO_VERIFY),
         no_lifetime: matches.opt_present(OPT_NO_LIFETIME),
         time: matches.opt_present(OPT_TIME),
+        time_functions: matches.opt_present(OPT_TIME_FUNCTIONS),
+        time_functions_csv: matches.opt_str(OPT_TIME_FUNCTIONS_CSV),
         rlimit: matches
             .opt_get::<u32>(OPT_RLIMIT)
             .unwrap_or_else(|_| error("expected integer after rlimit".to_string()))
@@ -150,6 +238,19 @@ pub fn parse_args(program: &String, args: impl Iterator<Item = String>) -> (Args
             .opt_get::<u32>(OPT_MULTIPLE_ERRORS)
             .unwrap_or_else(|_| error("expected integer after multiple-errors".to_string()))
             .unwrap_or(2),
+        num_threads: matches
+            .opt_get::<u32>(OPT_NUM_THREADS)
+            .unwrap_or_else(|_| error("expected integer after num-threads".to_string()))
+            .unwrap_or(1),
+        cache_dir: if matches.opt_present(OPT_NO_CACHE) {
+            None
+        } else {
+            Some(
+                matches
+                    .opt_str(OPT_CACHE_DIR)
+                    .unwrap_or(crate::cache::DEFAULT_CACHE_DIR.to_string()),
+            )
+        },
         log_vir: matches.opt_str(OPT_LOG_VIR),
         log_vir_simple: matches.opt_str(OPT_LOG_VIR_SIMPLE),
         log_vir_poly: matches.opt_str(OPT_LOG_VIR_POLY),
@@ -157,13 +258,37 @@ pub fn parse_args(program: &String, args: impl Iterator<Item = String>) -> (Args
         log_air_final: matches.opt_str(OPT_LOG_AIR_FINAL),
         log_smt: matches.opt_str(OPT_LOG_SMT),
         log_triggers: matches.opt_str(OPT_LOG_TRIGGERS),
+        output_json: matches.opt_str(OPT_OUTPUT_JSON),
         show_triggers: matches.opt_present(OPT_TRIGGERS),
+        profile_quantifiers: matches.opt_present(OPT_PROFILE_QUANTIFIERS),
         print_erased: matches.opt_present(OPT_PRINT_ERASED),
         print_erased_spec: matches.opt_present(OPT_PRINT_ERASED_SPEC),
         ignore_unexpected_smt: matches.opt_present(OPT_IGNORE_UNEXPECTED_SMT),
         debug: matches.opt_present(OPT_DEBUG),
+        report_counterexamples: matches.opt_present(OPT_REPORT_COUNTEREXAMPLES),
         compile: matches.opt_present(OPT_COMPILE),
     };
 
+    if rlimit_to_smt(args.rlimit).is_none() {
+        error(format!("rlimit must be at most {}", u32::MAX / 1000000));
+    }
+    if args.num_threads == 0 {
+        error("num-threads must be at least 1".to_string());
+    }
+    if args.num_threads > 1 && args.debug {
+        error("--debug is not supported with --num-threads greater than 1".to_string());
+    }
+    if args.num_threads > 1 && args.report_counterexamples {
+        error(
+            "--report-counterexamples is not supported with --num-threads greater than 1"
+                .to_string(),
+        );
+    }
+    if args.num_threads > 1 && args.profile_quantifiers {
+        error(
+            "--profile-quantifiers is not supported with --num-threads greater than 1".to_string(),
+        );
+    }
+
     (args, unmatched)
 }
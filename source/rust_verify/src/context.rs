@@ -2,7 +2,7 @@ use crate::erase::ResolvedCall;
 use rustc_hir::{Crate, HirId};
 use rustc_middle::ty::{TyCtxt, TypeckResults};
 use rustc_span::SpanData;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use vir::ast::{Expr, Mode, Pattern, Typ};
 
@@ -22,6 +22,8 @@ pub struct ContextX<'tcx> {
     pub(crate) krate: &'tcx Crate<'tcx>,
     pub(crate) erasure_info: ErasureInfoRef,
     pub(crate) autoviewed_call_typs: HashMap<HirId, Typ>,
+    /// Expressions that typecheck found in a spec context (e.g. arguments to requires/assert)
+    pub(crate) exprs_in_spec: HashSet<HirId>,
 }
 
 #[derive(Clone)]
@@ -0,0 +1,167 @@
+//! Non-interactive counterexample reports for --report-counterexamples.
+//! For a failed assertion, we report the values of the function's parameters and locals
+//! at each line, leading up to the failure, that has a snapshot in the snap_map.
+//! Only the snapshots that the counterexample's execution reaches are reported,
+//! so that lines in branches that the execution does not take are omitted.
+
+use crate::json::Json;
+use crate::util::from_raw_span;
+use air::ast::Ident;
+use air::errors::Error;
+use air::model::Model;
+use rustc_span::source_map::SourceMap;
+use rustc_span::{FileName, Span};
+use std::collections::{BTreeMap, HashMap};
+use vir::ast::Function;
+use vir::def::{SnapPos, SpanKind};
+use vir::model_to_rust::ModelValue;
+use vir::sst::SourceVar;
+
+/// The values of the variables at one line of the source code
+pub struct CounterexampleLine {
+    /// 1-based line number
+    pub line: usize,
+    /// (variable name, value), in order of declaration
+    pub values: Vec<(Ident, String)>,
+}
+
+/// The values of the variables at each line leading to a failed assertion
+pub struct Counterexample {
+    pub message: String,
+    pub file: String,
+    /// 1-based line number of the failure
+    pub line: usize,
+    pub lines: Vec<CounterexampleLine>,
+}
+
+impl Counterexample {
+    /// Must be called while air_context still holds the model of the failed query.
+    /// Returns None if the error has no location in the source code.
+    pub fn new(
+        source_map: &SourceMap,
+        ctx: &vir::context::Ctx,
+        air_context: &mut air::context::Context,
+        air_model: &Model,
+        function: &Function,
+        snap_map: &Vec<(air::ast::Span, SnapPos)>,
+        source_vars: &Vec<SourceVar>,
+        error: &Error,
+    ) -> Option<Counterexample> {
+        let error_span: Span = from_raw_span(&error.spans.first()?.raw_span);
+        let error_loc = source_map.lookup_char_pos(error_span.lo());
+        // An error inside the body (e.g. an assertion) is reached by the statements before it;
+        // an error outside the body (e.g. a postcondition) may be reached by the whole body
+        let in_body = match &function.x.body {
+            Some(body) => from_raw_span(&body.span.raw_span).contains(error_span),
+            None => false,
+        };
+
+        // Position of each snapshot in the counterexample's execution;
+        // snapshots on branches that the execution does not take have no position
+        let mut positions: HashMap<Ident, usize> = HashMap::new();
+        for (i, sid) in air_model.reached_snapshots(air_context).into_iter().enumerate() {
+            positions.insert(sid, i);
+        }
+
+        // For each line, the last executed snapshot mapped to it
+        let mut line_snapshots: BTreeMap<usize, (usize, Ident, Span)> = BTreeMap::new();
+        for (air_span, SnapPos { snapshot_id, kind }) in snap_map {
+            let position = match positions.get(snapshot_id) {
+                Some(position) => *position,
+                None => continue,
+            };
+            let span: Span = from_raw_span(&air_span.raw_span);
+            if in_body && span.lo() > error_span.hi() {
+                continue;
+            }
+            let (start, end) = match source_map.is_valid_span(span) {
+                Ok(pos) => pos,
+                Err(_) => continue,
+            };
+            if start.file.name != error_loc.file.name {
+                continue;
+            }
+            let line = match kind {
+                SpanKind::Start | SpanKind::Full => start.line,
+                SpanKind::End => end.line,
+            };
+            match line_snapshots.get(&line) {
+                Some((p, _, _)) if *p > position => {}
+                _ => {
+                    line_snapshots.insert(line, (position, snapshot_id.clone(), span));
+                }
+            }
+        }
+
+        let mut lines: Vec<CounterexampleLine> = Vec::new();
+        for (line, (_, sid, span)) in line_snapshots {
+            let values =
+                vir::model_to_rust::snapshot_values(ctx, air_context, air_model, source_vars, &sid);
+            let mut line_values: Vec<(Ident, String)> = Vec::new();
+            for ModelValue { name, span: decl_span, value } in values {
+                // Skip variables that are not yet declared at this line
+                if let Some(value) = value {
+                    if from_raw_span(&decl_span.raw_span).lo() <= span.hi() {
+                        line_values.push((name, value));
+                    }
+                }
+            }
+            // Skip lines at which no variable has a value
+            if line_values.len() > 0 {
+                lines.push(CounterexampleLine { line, values: line_values });
+            }
+        }
+
+        Some(Counterexample {
+            message: error.msg.clone(),
+            file: match &error_loc.file.name {
+                FileName::Real(rfn) => match rfn.local_path() {
+                    Some(path) => path.to_string_lossy().to_string(),
+                    None => format!("{:?}", rfn),
+                },
+                name => format!("{:?}", name),
+            },
+            line: error_loc.line,
+            lines,
+        })
+    }
+
+    /// The lines of the printed report
+    pub fn report_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Counterexample for \"{}\" at {}:{}:",
+            self.message, self.file, self.line
+        )];
+        for CounterexampleLine { line, values } in self.lines.iter() {
+            let values: Vec<String> =
+                values.iter().map(|(name, value)| format!("{} = {}", name, value)).collect();
+            lines.push(format!("  line {}: {}", line, values.join(", ")));
+        }
+        lines
+    }
+
+    pub fn to_json(&self) -> Json {
+        let lines = self
+            .lines
+            .iter()
+            .map(|CounterexampleLine { line, values }| {
+                let values = values
+                    .iter()
+                    .map(|(name, value)| {
+                        Json::object(vec![("name", Json::str(name)), ("value", Json::str(value))])
+                    })
+                    .collect();
+                Json::object(vec![
+                    ("line", Json::Int(*line as i128)),
+                    ("values", Json::Array(values)),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("message", Json::str(&self.message)),
+            ("file", Json::str(&self.file)),
+            ("line", Json::Int(self.line as i128)),
+            ("lines", Json::Array(lines)),
+        ])
+    }
+}
@@ -149,7 +149,14 @@ impl Debugger {
     fn eval_expr(&self, context: &mut air::context::Context, expr: &[u8]) {
         let mut parser = sise::Parser::new(expr);
         let node = sise::read_into_tree(&mut parser).unwrap();
-        let expr = self.rewrite_eval_expr(&node).unwrap();
+        let expr = match self.rewrite_eval_expr(&node) {
+            Some(expr) => expr,
+            None => {
+                let expr = String::from_utf8_lossy(expr);
+                println!("cannot evaluate {} at line {}", expr, self.line);
+                return;
+            }
+        };
         let result = context.eval_expr(expr);
         println!("{}", result);
     }
@@ -69,7 +69,8 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use vir::ast::{
-    Datatype, ExprX, Fun, Function, GenericBoundX, Krate, Mode, Path, Pattern, PatternX, UnaryOpr,
+    Datatype, ExprX, Fun, Function, GenericBoundX, Krate, Mode, Path, Pattern, PatternX, TypX,
+    UnaryOpr,
 };
 use vir::ast_util::get_field;
 use vir::modes::{mode_join, ErasureModes};
@@ -362,6 +363,13 @@ fn erase_arm(ctxt: &Ctxt, mctxt: &mut MCtxt, expect: Mode, arm: &Arm) -> Arm {
     Arm { attrs: attrs.clone(), pat, guard, body, span, id, is_placeholder }
 }
 
+// Is the Fn-bounded type parameter x the type of a parameter that survives erasure?
+fn keep_fn_typ_param(ctxt: &Ctxt, f: &Function, x: &vir::ast::Ident) -> bool {
+    f.x.params
+        .iter()
+        .any(|p| keep_mode(ctxt, p.x.mode) && matches!(&*p.x.typ, TypX::TypParam(y) if y == x))
+}
+
 fn erase_call(
     ctxt: &Ctxt,
     mctxt: &mut MCtxt,
@@ -380,13 +388,22 @@ fn erase_call(
                     for arg in args.args.iter() {
                         match arg {
                             AngleBracketedArg::Arg(GenericArg::Type(_)) => {
-                                let (_, bounds) =
+                                let (x, bounds) =
                                     typ_bounds_iter.next().expect("missing typ_bound");
                                 match &**bounds {
                                     GenericBoundX::Traits(_) => new_args.push(arg.clone()),
+                                    GenericBoundX::FnSpec(..) if keep_fn_typ_param(ctxt, f, x) => {
+                                        new_args.push(arg.clone())
+                                    }
                                     GenericBoundX::FnSpec(..) => {}
+                                    // the parser treats a path like N in f::<N> as a type
+                                    GenericBoundX::Const(_) => new_args.push(arg.clone()),
                                 }
                             }
+                            AngleBracketedArg::Arg(GenericArg::Const(_)) => {
+                                typ_bounds_iter.next().expect("missing typ_bound");
+                                new_args.push(arg.clone());
+                            }
                             AngleBracketedArg::Arg(GenericArg::Lifetime(_)) => {
                                 new_args.push(arg.clone());
                             }
@@ -417,6 +434,21 @@ fn erase_call(
     }
 }
 
+/// Erase a range start..end
+fn erase_range(ctxt: &Ctxt, mctxt: &mut MCtxt, expect: Mode, range: &Expr) -> Expr {
+    match &range.kind {
+        ExprKind::Range(Some(start), Some(end), limits) => {
+            let start = erase_expr(ctxt, mctxt, expect, start);
+            let end = erase_expr(ctxt, mctxt, expect, end);
+            let Expr { id, span, .. } = *range; // for asymptotic efficiency, don't call range.clone()
+            let kind = ExprKind::Range(Some(P(start)), Some(P(end)), *limits);
+            let attrs = range.attrs.clone();
+            Expr { id, kind, span, attrs, tokens: range.tokens.clone() }
+        }
+        _ => unsupported!("unsupported range", range),
+    }
+}
+
 fn erase_expr(ctxt: &Ctxt, mctxt: &mut MCtxt, expect: Mode, expr: &Expr) -> Expr {
     erase_expr_opt(ctxt, mctxt, expect, expr).expect("erase_expr")
 }
@@ -611,6 +643,44 @@ fn erase_expr_opt(ctxt: &Ctxt, mctxt: &mut MCtxt, expect: Mode, expr: &Expr) ->
                 _ => panic!("internal error: MethodCall ResolvedCall {:?}", call),
             }
         }
+        ExprKind::Array(exprs) => {
+            if keep_mode(ctxt, expect) {
+                ExprKind::Array(vec_map(exprs, |e| P(erase_expr(ctxt, mctxt, expect, e))))
+            } else {
+                let exprs = vec_map(exprs, |e| erase_expr_opt(ctxt, mctxt, expect, e));
+                return replace_with_exprs(mctxt, expr, exprs);
+            }
+        }
+        ExprKind::Repeat(e1, len) => {
+            if keep_mode(ctxt, expect) {
+                let e1 = erase_expr(ctxt, mctxt, expect, e1);
+                ExprKind::Repeat(P(e1), len.clone())
+            } else {
+                let e1 = erase_expr_opt(ctxt, mctxt, expect, e1);
+                return replace_with_exprs(mctxt, expr, vec![e1]);
+            }
+        }
+        ExprKind::Index(e1, e2) => {
+            // Indexing on arrays and slices; a[i..j] is only supported for ranges i..j
+            if keep_mode(ctxt, expect) {
+                let e1 = erase_expr(ctxt, mctxt, expect, e1);
+                let e2 = match &e2.kind {
+                    ExprKind::Range(..) => erase_range(ctxt, mctxt, expect, e2),
+                    _ => erase_expr(ctxt, mctxt, expect, e2),
+                };
+                ExprKind::Index(P(e1), P(e2))
+            } else {
+                let mut exprs = vec![erase_expr_opt(ctxt, mctxt, expect, e1)];
+                match &e2.kind {
+                    ExprKind::Range(Some(start), Some(end), _) => {
+                        exprs.push(erase_expr_opt(ctxt, mctxt, expect, start));
+                        exprs.push(erase_expr_opt(ctxt, mctxt, expect, end));
+                    }
+                    _ => exprs.push(erase_expr_opt(ctxt, mctxt, expect, e2)),
+                }
+                return replace_with_exprs(mctxt, expr, exprs);
+            }
+        }
         ExprKind::Tup(exprs) => {
             if keep_mode(ctxt, expect) {
                 ExprKind::Tup(vec_map(exprs, |e| P(erase_expr(ctxt, mctxt, expect, e))))
@@ -690,7 +760,23 @@ fn erase_expr_opt(ctxt: &Ctxt, mctxt: &mut MCtxt, expect: Mode, expr: &Expr) ->
                 return replace_with_exprs(mctxt, expr, vec![e1]);
             }
         }
-        ExprKind::Closure(..) => return None,
+        ExprKind::Closure(capture, asyncness, movability, decl, body, span) => {
+            // Exec closures are kept (see ExprX::ExecClosure); spec closures are erased
+            match mctxt.find_span_opt(&ctxt.var_modes, expr.span) {
+                Some(Mode::Exec) if keep_mode(ctxt, expect) => {
+                    let body = erase_expr(ctxt, mctxt, Mode::Exec, body);
+                    ExprKind::Closure(
+                        *capture,
+                        *asyncness,
+                        *movability,
+                        decl.clone(),
+                        P(body),
+                        *span,
+                    )
+                }
+                _ => return None,
+            }
+        }
         ExprKind::If(eb, e1, e2_opt) => {
             let modeb = *mctxt.find_span(&ctxt.condition_modes, expr.span);
             let eb_erase = match &eb.kind {
@@ -794,12 +880,36 @@ fn erase_expr_opt(ctxt: &Ctxt, mctxt: &mut MCtxt, expect: Mode, expr: &Expr) ->
                 }
             }
         }
-        ExprKind::While(eb, block, None) => {
+        ExprKind::Try(e1) => {
+            // e1? is an early return, so we keep it whenever the enclosing code is kept
+            if keep_mode(ctxt, expect) {
+                let e1 = erase_expr(ctxt, mctxt, expect, e1);
+                ExprKind::Try(P(e1))
+            } else {
+                let e1 = erase_expr_opt(ctxt, mctxt, expect, e1);
+                return replace_with_exprs(mctxt, expr, vec![e1]);
+            }
+        }
+        ExprKind::While(eb, block, label) => {
             // The mode checker only allows While for Mode::Exec
             let eb = erase_expr(ctxt, mctxt, Mode::Exec, eb);
             let block = erase_block(ctxt, mctxt, Mode::Exec, block);
-            ExprKind::While(P(eb), P(block), None)
+            ExprKind::While(P(eb), P(block), *label)
+        }
+        ExprKind::Loop(block, label) => {
+            // The mode checker only allows Loop for Mode::Exec
+            let block = erase_block(ctxt, mctxt, Mode::Exec, block);
+            ExprKind::Loop(P(block), *label)
+        }
+        ExprKind::ForLoop(pat, range, block, label) => {
+            // The mode checker only allows for loops over ranges start..end, for Mode::Exec
+            let range = erase_range(ctxt, mctxt, Mode::Exec, range);
+            let pat = erase_pat(ctxt, mctxt, pat);
+            let block = erase_block(ctxt, mctxt, Mode::Exec, block);
+            ExprKind::ForLoop(P(pat), P(range), P(block), *label)
         }
+        ExprKind::Break(label, None) => ExprKind::Break(*label, None),
+        ExprKind::Continue(label) => ExprKind::Continue(*label),
         ExprKind::Ret(None) => ExprKind::Ret(None),
         ExprKind::Ret(Some(e1)) => {
             let e1 = erase_expr(ctxt, mctxt, mctxt.ret_mode.expect("erase: ret_mode"), e1);
@@ -1019,7 +1129,7 @@ fn erase_fn(
     let mut num_typ_params = 0;
     for param in params.iter() {
         match &param.kind {
-            GenericParamKind::Type { .. } => {
+            GenericParamKind::Type { .. } | GenericParamKind::Const { .. } => {
                 num_typ_params += 1;
             }
             _ => {}
@@ -1034,15 +1144,23 @@ fn erase_fn(
                 new_params.push(param.clone());
             }
             GenericParamKind::Type { .. } => {
-                let (_, bound) = typ_bounds_iter.next().expect("missing typ_bound");
+                let (x, bound) = typ_bounds_iter.next().expect("missing typ_bound");
                 // erase Fn trait bounds, since the type checker won't be able to infer them
+                // (unless the Fn type is used by an exec closure parameter that we keep)
                 // TODO: also erase other type parameters left unused after erasure
                 match &**bound {
                     GenericBoundX::Traits(_) => new_params.push(param.clone()),
+                    GenericBoundX::FnSpec(..) if keep_fn_typ_param(ctxt, f_vir, x) => {
+                        new_params.push(param.clone())
+                    }
                     GenericBoundX::FnSpec(..) => {}
+                    GenericBoundX::Const(_) => panic!("const generic bound on type parameter"),
                 }
             }
-            _ => {}
+            GenericParamKind::Const { .. } => {
+                typ_bounds_iter.next().expect("missing typ_bound");
+                new_params.push(param.clone());
+            }
         }
     }
     let generics =
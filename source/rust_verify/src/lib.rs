@@ -22,14 +22,17 @@ extern crate rustc_typeck;
 extern crate smallvec;
 
 mod attributes;
+pub mod cache;
 pub mod config;
 pub mod context;
+pub mod counterexample;
 pub mod debugger;
 pub mod def;
 pub mod driver;
 pub mod erase;
 mod erase_rewrite;
 pub mod file_loader;
+pub mod json;
 mod lifetime;
 pub mod rust_to_vir;
 pub mod rust_to_vir_adts;
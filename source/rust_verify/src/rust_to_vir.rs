@@ -11,8 +11,9 @@ use crate::context::Context;
 use crate::def::is_get_variant_fn_name;
 use crate::rust_to_vir_adts::{check_item_enum, check_item_struct};
 use crate::rust_to_vir_base::{
-    check_generics_bounds, def_id_to_vir_path, fn_item_hir_id_to_self_def_id, hack_get_def_name,
-    ident_to_var, mk_visibility, ty_to_vir, typ_path_and_ident_to_vir_path,
+    check_generics_bounds, check_generics_bounds_fun, def_id_to_vir_path,
+    fn_item_hir_id_to_self_def_id, hack_get_def_name, ident_to_var, is_lifetime_bound,
+    mk_visibility, ty_to_vir, typ_path_and_ident_to_vir_path,
 };
 use crate::rust_to_vir_func::{check_foreign_item_fn, check_item_fn};
 use crate::util::{err_span_str, spanned_new, unsupported_err_span};
@@ -27,8 +28,9 @@ use rustc_hir::{
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use vir::ast::{Fun, FunX, FunctionKind, Krate, KrateX, Mode, Path, TypX, VirErr};
+use vir::ast::{AssocTypeImplX, Fun, FunX, FunctionKind, Krate, KrateX, Mode, Path, TypX, VirErr};
 use vir::ast_util::path_as_rust_name;
+use vir::def::{option_type_path, result_type_path};
 
 fn check_item<'tcx>(
     ctxt: &Context<'tcx>,
@@ -92,6 +94,8 @@ fn check_item<'tcx>(
         ItemKind::Impl(impll) => {
             if let Some(TraitRef { path, hir_ref_id: _ }) = impll.of_trait {
                 let path_name = path_as_rust_name(&def_id_to_vir_path(ctxt.tcx, path.res.def_id()));
+                let is_try = path_name == "core::ops::try_trait::Try"
+                    || path_name == "core::ops::try_trait::FromResidual";
                 let ignore = if path_name == "builtin::Structural" {
                     let ty = {
                         // TODO extract to rust_to_vir_base, or use
@@ -140,6 +144,37 @@ fn check_item<'tcx>(
                 {
                     // TODO SOUNDNESS additional checks of the implementation
                     true
+                } else if path_name == "core::hash::Hash" {
+                    // pervasive::hash_map::HashMap and pervasive::hash_set::HashSet trust that
+                    // hashing agrees with the equality of Structural keys,
+                    // which holds for derived implementations
+                    let attrs = ctxt.tcx.hir().attrs(item.hir_id());
+                    unsupported_err_unless!(
+                        attrs.iter().any(|a| a.has_name(rustc_span::sym::automatically_derived)),
+                        item.span,
+                        "implementing Hash other than with #[derive(Hash)]"
+                    );
+                    true
+                } else if is_try {
+                    // try_to_vir translates ? directly for pervasive Option and Result,
+                    // so their implementations are only used by the compiled code;
+                    // the verifier would ignore the implementations for any other type
+                    let self_path = match impll.self_ty.kind {
+                        TyKind::Path(QPath::Resolved(
+                            None,
+                            rustc_hir::Path {
+                                res: rustc_hir::def::Res::Def(_, self_def_id), ..
+                            },
+                        )) => Some(def_id_to_vir_path(ctxt.tcx, *self_def_id)),
+                        _ => None,
+                    };
+                    unsupported_err_unless!(
+                        self_path == Some(option_type_path())
+                            || self_path == Some(result_type_path()),
+                        item.span,
+                        "implementing Try or FromResidual for types other than pervasive Option and Result"
+                    );
+                    true
                 } else {
                     false
                 };
@@ -147,7 +182,8 @@ fn check_item<'tcx>(
                 if ignore {
                     for impl_item_ref in impll.items {
                         match impl_item_ref.kind {
-                            AssocItemKind::Fn { has_self } if has_self => {
+                            // Try::from_output and FromResidual::from_residual have no self
+                            AssocItemKind::Fn { has_self } if has_self || is_try => {
                                 if let ImplItemKind::Fn(sig, _) =
                                     &ctxt.tcx.hir().impl_item(impl_item_ref.id).kind
                                 {
@@ -296,8 +332,31 @@ fn check_item<'tcx>(
                                 }
                             }
                             AssocItemKind::Type => {
-                                let _impl_item = ctxt.tcx.hir().impl_item(impl_item_ref.id);
-                                // the type system handles this for Trait impls
+                                let impl_item = ctxt.tcx.hir().impl_item(impl_item_ref.id);
+                                // rustc normalizes the associated types of datatypes,
+                                // but generic code sees them as projections,
+                                // so record their definitions in impls of our own traits
+                                match (&impl_item.kind, trait_path_typ_args.clone()) {
+                                    (
+                                        ImplItemKind::TyAlias(ty),
+                                        Some((trait_path, trait_typ_args)),
+                                    ) if trait_path.krate.is_none() => {
+                                        let name = Arc::new(impl_item_ref.ident.to_string());
+                                        let typ_params =
+                                            check_generics_bounds_fun(ctxt.tcx, &impll.generics)?;
+                                        let assocx = AssocTypeImplX {
+                                            name,
+                                            typ_params,
+                                            self_typ: self_typ.clone(),
+                                            trait_path,
+                                            trait_typ_args,
+                                            typ: ty_to_vir(ctxt.tcx, ty),
+                                        };
+                                        let assoc = spanned_new(impl_item.span, assocx);
+                                        vir.assoc_type_impls.push(assoc);
+                                    }
+                                    _ => {}
+                                }
                             }
                             _ => unsupported_err!(
                                 item.span,
@@ -336,12 +395,10 @@ fn check_item<'tcx>(
             unsupported_err_unless!(bounds.len() == 0, item.span, "trait generic bounds");
             let trait_path = def_id_to_vir_path(ctxt.tcx, item.def_id.to_def_id());
             let mut methods: Vec<Fun> = Vec::new();
+            let mut assoc_typs: Vec<vir::ast::Ident> = Vec::new();
             for trait_item_ref in *trait_items {
                 let trait_item = ctxt.tcx.hir().trait_item(trait_item_ref.id);
-                let TraitItem { ident: _, def_id, generics: item_generics, kind, span } =
-                    trait_item;
-                let generics_bnds = check_generics_bounds(ctxt.tcx, item_generics, false)?;
-                unsupported_err_unless!(generics_bnds.len() == 0, *span, "trait generics");
+                let TraitItem { ident, def_id, generics: item_generics, kind, span } = trait_item;
                 match kind {
                     TraitItemKind::Fn(sig, fun) => {
                         let body_id = match fun {
@@ -377,6 +434,19 @@ fn check_item<'tcx>(
                             methods.push(fun);
                         }
                     }
+                    TraitItemKind::Type(bounds, None) => {
+                        unsupported_err_unless!(
+                            item_generics.params.len() == 0,
+                            *span,
+                            "generic associated types"
+                        );
+                        unsupported_err_unless!(
+                            bounds.iter().all(is_lifetime_bound),
+                            *span,
+                            "associated type bounds"
+                        );
+                        assoc_typs.push(Arc::new(ident.to_string()));
+                    }
                     _ => {
                         unsupported_err!(item.span, "unsupported item", item);
                     }
@@ -386,6 +456,7 @@ fn check_item<'tcx>(
                 name: trait_path,
                 methods: Arc::new(methods),
                 typ_params: Arc::new(generics_bnds),
+                assoc_typs: Arc::new(assoc_typs),
             };
             vir.traits.push(spanned_new(item.span, traitx));
         }
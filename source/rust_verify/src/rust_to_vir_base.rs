@@ -8,7 +8,7 @@ use rustc_hir::definitions::DefPath;
 use rustc_hir::{
     GenericBound, GenericParam, GenericParamKind, Generics, HirId, ItemKind, LifetimeParamKind,
     ParamName, PathSegment, PolyTraitRef, PrimTy, QPath, TraitBoundModifier, TraitFn,
-    TraitItemKind, Ty, Visibility, VisibilityKind,
+    TraitItemKind, Ty, Visibility, VisibilityKind, WherePredicate,
 };
 use rustc_infer::infer::TyCtxtInferExt;
 use rustc_middle::ty::{AdtDef, TyCtxt, TyKind};
@@ -16,6 +16,7 @@ use rustc_span::def_id::{DefId, LOCAL_CRATE};
 use rustc_span::symbol::{kw, Ident};
 use rustc_span::Span;
 use rustc_trait_selection::infer::InferCtxtExt;
+use std::collections::HashMap;
 use std::sync::Arc;
 use vir::ast::{GenericBoundX, IntRange, Path, PathX, Typ, TypBounds, TypX, Typs, VirErr};
 use vir::ast_util::{path_as_rust_name, types_equal};
@@ -140,6 +141,11 @@ pub(crate) fn def_id_to_datatype_typ_args<'tcx, 'hir>(
             .filter_map(|a| match a {
                 rustc_hir::GenericArg::Type(t) => Some(ty_to_vir(tcx, &t)),
                 rustc_hir::GenericArg::Lifetime(_) => None,
+                rustc_hir::GenericArg::Const(c) => {
+                    let def_id = tcx.hir().local_def_id(c.value.hir_id);
+                    let cnst = rustc_middle::ty::Const::from_anon_const(tcx, def_id);
+                    Some(const_arg_in_type_to_vir(tcx, cnst))
+                }
                 _ => panic!("unexpected type arguments"),
             })
             .collect(),
@@ -211,6 +217,7 @@ pub(crate) fn mk_range<'tcx>(ty: rustc_middle::ty::Ty<'tcx>) -> IntRange {
         TyKind::Int(rustc_middle::ty::IntTy::I64) => IntRange::I(64),
         TyKind::Int(rustc_middle::ty::IntTy::I128) => IntRange::I(128),
         TyKind::Int(rustc_middle::ty::IntTy::Isize) => IntRange::ISize,
+        TyKind::Char => IntRange::Char,
         _ => panic!("mk_range {:?}", ty),
     }
 }
@@ -248,7 +255,7 @@ pub(crate) fn mid_ty_to_vir<'tcx>(
 ) -> Typ {
     match ty.kind() {
         TyKind::Bool => Arc::new(TypX::Bool),
-        TyKind::Uint(_) | TyKind::Int(_) => Arc::new(TypX::Int(mk_range(ty))),
+        TyKind::Uint(_) | TyKind::Int(_) | TyKind::Char => Arc::new(TypX::Int(mk_range(ty))),
         TyKind::Ref(_, tys, rustc_ast::Mutability::Not) => mid_ty_to_vir(tcx, tys, allow_mut_ref),
         TyKind::Ref(_, tys, rustc_ast::Mutability::Mut) if allow_mut_ref => {
             mid_ty_to_vir(tcx, tys, allow_mut_ref)
@@ -274,16 +281,7 @@ pub(crate) fn mid_ty_to_vir<'tcx>(
             } else if s == crate::typecheck::BUILTIN_NAT {
                 TypX::Int(IntRange::Nat)
             } else {
-                let typ_args: Vec<Typ> = args
-                    .iter()
-                    .filter_map(|arg| match arg.unpack() {
-                        rustc_middle::ty::subst::GenericArgKind::Type(t) => {
-                            Some(mid_ty_to_vir(tcx, t, allow_mut_ref))
-                        }
-                        rustc_middle::ty::subst::GenericArgKind::Lifetime(_) => None,
-                        _ => panic!("unexpected type argument"),
-                    })
-                    .collect();
+                let typ_args = mid_generic_args_to_vir(tcx, args, allow_mut_ref);
                 if Some(*did) == tcx.lang_items().owned_box() && typ_args.len() == 2 {
                     return typ_args[0].clone();
                 }
@@ -301,12 +299,86 @@ pub(crate) fn mid_ty_to_vir<'tcx>(
             let ret = mid_ty_to_vir(tcx, sig.output().skip_binder(), allow_mut_ref);
             Arc::new(TypX::Lambda(Arc::new(args), ret))
         }
+        // arrays and slices are viewed as pervasive::seq::Seq;
+        // array types also keep their length, which the verifier assumes for array values
+        TyKind::Array(t, len) => Arc::new(TypX::Array(
+            mid_ty_to_vir(tcx, t, allow_mut_ref),
+            const_arg_in_type_to_vir(tcx, len),
+        )),
+        TyKind::Slice(t) => {
+            let typ_args = Arc::new(vec![mid_ty_to_vir(tcx, t, allow_mut_ref)]);
+            Arc::new(TypX::Datatype(vir::def::seq_type_path(), typ_args))
+        }
+        TyKind::Projection(proj) => {
+            // associated type <Self as T<...>>::Name that rustc could not normalize
+            let trait_path = def_id_to_vir_path(tcx, proj.trait_def_id(tcx));
+            let typ_args = mid_generic_args_to_vir(tcx, proj.substs, allow_mut_ref);
+            let name = Arc::new(tcx.item_name(proj.item_def_id).to_string());
+            Arc::new(TypX::Projection(trait_path, Arc::new(typ_args), name))
+        }
         _ => {
             unsupported!(format!("type {:?}", ty))
         }
     }
 }
 
+// type arguments and const generic arguments, skipping lifetime arguments
+fn mid_generic_args_to_vir<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    args: rustc_middle::ty::subst::SubstsRef<'tcx>,
+    allow_mut_ref: bool,
+) -> Vec<Typ> {
+    args.iter()
+        .filter_map(|arg| match arg.unpack() {
+            rustc_middle::ty::subst::GenericArgKind::Type(t) => {
+                Some(mid_ty_to_vir(tcx, t, allow_mut_ref))
+            }
+            rustc_middle::ty::subst::GenericArgKind::Lifetime(_) => None,
+            rustc_middle::ty::subst::GenericArgKind::Const(c) => {
+                Some(const_arg_in_type_to_vir(tcx, c))
+            }
+        })
+        .collect()
+}
+
+// const generic argument, such as 3 in S<3> or N in S<N>
+// returns None for const generic arguments that the verifier does not support
+fn const_arg_to_vir<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    cnst: &'tcx rustc_middle::ty::Const<'tcx>,
+) -> Option<Typ> {
+    if let rustc_middle::ty::ConstKind::Param(param) = cnst.val {
+        return Some(Arc::new(TypX::TypParam(Arc::new(param.name.to_string()))));
+    }
+    if !matches!(cnst.ty.kind(), TyKind::Uint(_)) {
+        return None;
+    }
+    let bits = cnst.try_eval_bits(tcx, rustc_middle::ty::ParamEnv::empty(), cnst.ty)?;
+    Some(Arc::new(TypX::ConstInt(Arc::new(bits.to_string()))))
+}
+
+pub(crate) fn mid_const_to_vir<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    span: Span,
+    cnst: &'tcx rustc_middle::ty::Const<'tcx>,
+) -> Result<Typ, VirErr> {
+    match const_arg_to_vir(tcx, cnst) {
+        Some(typ) => Ok(typ),
+        None => unsupported_err!(span, format!("const generic argument {:?}", cnst)),
+    }
+}
+
+// const generic argument inside a type, reported like other unsupported types
+fn const_arg_in_type_to_vir<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    cnst: &'tcx rustc_middle::ty::Const<'tcx>,
+) -> Typ {
+    match const_arg_to_vir(tcx, cnst) {
+        Some(typ) => typ,
+        None => unsupported!(format!("const generic argument {:?}", cnst)),
+    }
+}
+
 // TODO remove if unused
 pub(crate) fn _ty_resolved_path_to_debug_path(_tcx: TyCtxt<'_>, ty: &Ty) -> String {
     let Ty { hir_id: _, kind, span: _ } = ty;
@@ -336,8 +408,18 @@ pub(crate) fn impl_def_id_to_self_ty<'tcx>(tcx: TyCtxt<'tcx>, impl_def_id: DefId
 }
 
 pub(crate) fn ty_to_vir<'tcx>(tcx: TyCtxt<'tcx>, ty: &Ty) -> Typ {
-    let Ty { hir_id: _, kind, span } = ty;
+    let Ty { hir_id, kind, span } = ty;
     match kind {
+        rustc_hir::TyKind::Path(QPath::TypeRelative(..))
+        | rustc_hir::TyKind::Path(QPath::Resolved(Some(_), _)) => {
+            // associated types, such as Self::Item, A::Item, or <A as T>::Item;
+            // normalize them in the environment of the enclosing item,
+            // so that only associated types of type parameters remain as projections
+            let item_id = tcx.hir().local_def_id(tcx.hir().get_parent_item(*hir_id));
+            let param_env = tcx.param_env(item_id);
+            let mid_ty = rustc_typeck::hir_ty_to_ty(tcx, ty);
+            mid_ty_to_vir(tcx, tcx.normalize_erasing_regions(param_env, mid_ty), false)
+        }
         rustc_hir::TyKind::Tup(tys) => {
             Arc::new(TypX::Tuple(Arc::new(tys.iter().map(|t| ty_to_vir(tcx, t)).collect())))
         }
@@ -359,6 +441,7 @@ pub(crate) fn ty_to_vir<'tcx>(tcx: TyCtxt<'tcx>, ty: &Ty) -> Typ {
             Res::PrimTy(PrimTy::Int(IntTy::I64)) => TypX::Int(IntRange::I(64)),
             Res::PrimTy(PrimTy::Int(IntTy::I128)) => TypX::Int(IntRange::I(128)),
             Res::PrimTy(PrimTy::Int(IntTy::Isize)) => TypX::Int(IntRange::ISize),
+            Res::PrimTy(PrimTy::Char) => TypX::Int(IntRange::Char),
             Res::Def(DefKind::TyParam, def_id) => {
                 let path = def_id_to_vir_path(tcx, def_id);
                 let name = path.segments.last().unwrap();
@@ -392,6 +475,15 @@ pub(crate) fn ty_to_vir<'tcx>(tcx: TyCtxt<'tcx>, ty: &Ty) -> Typ {
                 unsupported!(format!("type {:#?} {:?} {:?}", kind, path.res, span))
             }
         }),
+        rustc_hir::TyKind::Array(t, len) => {
+            let def_id = tcx.hir().local_def_id(len.hir_id);
+            let len = rustc_middle::ty::Const::from_anon_const(tcx, def_id);
+            Arc::new(TypX::Array(ty_to_vir(tcx, t), const_arg_in_type_to_vir(tcx, len)))
+        }
+        rustc_hir::TyKind::Slice(t) => {
+            let typ_args = Arc::new(vec![ty_to_vir(tcx, t)]);
+            Arc::new(TypX::Datatype(vir::def::seq_type_path(), typ_args))
+        }
         _ => {
             unsupported!(format!("type {:#?} {:?}", kind, span))
         }
@@ -465,10 +557,30 @@ pub(crate) fn is_smt_arith<'tcx>(bctx: &BodyCtxt<'tcx>, id1: &HirId, id2: &HirId
     }
 }
 
+fn generic_bound_trait_name<'tcx>(tcx: TyCtxt<'tcx>, bound: &GenericBound<'tcx>) -> Option<String> {
+    match bound {
+        GenericBound::Trait(PolyTraitRef { trait_ref, .. }, _) => match trait_ref.path.res {
+            Res::Def(_, def_id) => Some(path_as_rust_name(&def_id_to_vir_path(tcx, def_id))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Structural carries no specification, so it can be dropped from VIR.
+// For a Structural type parameter, Eq and Hash agree with the builtin equality
+// (Structural requires a derived Eq, and Hash impls must be derived), so they can be dropped too
+// (e.g. K: Hash + Eq + Structural for the keys of pervasive::hash_map::HashMap)
+fn is_ignored_trait_bound(f_name: &str, structural: bool) -> bool {
+    f_name == "builtin::Structural"
+        || (structural && (f_name == "core::cmp::Eq" || f_name == "core::hash::Hash"))
+}
+
 pub(crate) fn check_generic_bound<'tcx>(
     tcx: TyCtxt<'tcx>,
     span: Span,
     bound: &'tcx GenericBound<'tcx>,
+    structural: bool,
 ) -> Result<vir::ast::GenericBound, VirErr> {
     match bound {
         GenericBound::Trait(
@@ -481,7 +593,10 @@ pub(crate) fn check_generic_bound<'tcx>(
                 _ => return unsupported_err!(span, "generic bounds"),
             };
             let f_name = path_as_rust_name(&def_id_to_vir_path(tcx, def_id));
-            if f_name == "core::ops::function::Fn" {
+            if is_ignored_trait_bound(&f_name, structural) {
+                // rustc still checks these; VIR doesn't need to know about them
+                Ok(Arc::new(GenericBoundX::Traits(vec![])))
+            } else if f_name == "core::ops::function::Fn" {
                 let args = &path.segments.last().expect("last segment").args.expect("GenericArgs");
                 unsupported_err_unless!(args.args.len() == 1, span, "generic bounds");
                 unsupported_err_unless!(args.bindings.len() == 1, span, "generic bounds");
@@ -524,6 +639,29 @@ pub(crate) fn check_generics_bounds<'tcx>(
     check_that_external_body_datatype_declares_positivity: bool,
 ) -> Result<Vec<(vir::ast::Ident, vir::ast::GenericBound, bool)>, VirErr> {
     let Generics { params, where_clause, span: _ } = generics;
+
+    // where clauses of the form A: T1 + T2 are treated as additional bounds on A
+    let mut where_bounds: HashMap<String, Vec<&'tcx GenericBound<'tcx>>> = HashMap::new();
+    for predicate in where_clause.predicates.iter() {
+        match predicate {
+            WherePredicate::BoundPredicate(pred) if pred.bound_generic_params.len() == 0 => {
+                match &pred.bounded_ty.kind {
+                    rustc_hir::TyKind::Path(QPath::Resolved(None, path))
+                        if matches!(path.res, Res::Def(DefKind::TyParam, _))
+                            && path.segments.len() == 1 =>
+                    {
+                        let name = path.segments[0].ident.name.as_str().to_string();
+                        where_bounds.entry(name).or_insert(Vec::new()).extend(pred.bounds.iter());
+                    }
+                    _ => unsupported_err!(pred.span, "where clause on non-type-parameter"),
+                }
+            }
+            // lifetime bounds can be ignored for VIR
+            WherePredicate::RegionPredicate(..) => {}
+            _ => unsupported_err!(generics.span, "where clause"),
+        }
+    }
+
     let mut typ_params: Vec<(vir::ast::Ident, vir::ast::GenericBound, bool)> = Vec::new();
     for param in params.iter() {
         let vattrs = get_verifier_attrs(tcx.hir().attrs(param.hir_id))?;
@@ -547,17 +685,26 @@ pub(crate) fn check_generics_bounds<'tcx>(
         unsupported_err_unless!(!pure_wrt_drop, *span, "generic pure_wrt_drop");
         match (name, kind) {
             (ParamName::Plain(id), GenericParamKind::Type { default: None, synthetic: false }) => {
+                let param_name = id.name.as_str().to_string();
+                let where_bounds = where_bounds.remove(&param_name).unwrap_or(Vec::new());
                 // lifetime bounds can be ignored for VIR, since they are only relevant
                 // for rustc's borrow-checking pass
-                let bounds: Vec<&GenericBound> =
-                    bounds.iter().filter(|bound| !is_lifetime_bound(bound)).collect();
+                let bounds: Vec<&GenericBound> = bounds
+                    .iter()
+                    .chain(where_bounds.into_iter())
+                    .filter(|bound| !is_lifetime_bound(bound))
+                    .collect();
+
+                let structural = bounds.iter().any(|bound| {
+                    generic_bound_trait_name(tcx, bound).as_deref() == Some("builtin::Structural")
+                });
 
                 // trait/function bounds
                 let ident = Arc::new(id.name.as_str().to_string());
                 let mut trait_bounds: Vec<Path> = Vec::new();
                 let mut fn_bounds: Vec<vir::ast::GenericBound> = Vec::new();
                 for bound in bounds {
-                    let vir_bound = check_generic_bound(tcx, *span, bound)?;
+                    let vir_bound = check_generic_bound(tcx, *span, bound, structural)?;
                     match &*vir_bound {
                         GenericBoundX::Traits(ts) => {
                             trait_bounds.extend(ts.clone());
@@ -578,6 +725,15 @@ pub(crate) fn check_generics_bounds<'tcx>(
                 };
                 typ_params.push((ident, bound, strictly_positive));
             }
+            (ParamName::Plain(id), GenericParamKind::Const { ty, default: None }) => {
+                let typ = ty_to_vir(tcx, ty);
+                match &*typ {
+                    TypX::Int(IntRange::U(_) | IntRange::USize) => {}
+                    _ => unsupported_err!(*span, "const generic parameters of non-unsigned type"),
+                }
+                let ident = Arc::new(id.name.as_str().to_string());
+                typ_params.push((ident, Arc::new(GenericBoundX::Const(typ)), strictly_positive));
+            }
             (
                 ParamName::Plain(_id),
                 GenericParamKind::Lifetime { kind: LifetimeParamKind::Explicit },
@@ -589,7 +745,12 @@ pub(crate) fn check_generics_bounds<'tcx>(
             _ => unsupported_err!(*span, "complex generics", generics),
         }
     }
-    unsupported_err_unless!(where_clause.predicates.len() == 0, generics.span, "where clause");
+    // e.g. a method's where clause on one of the impl's type parameters
+    unsupported_err_unless!(
+        where_bounds.len() == 0,
+        generics.span,
+        "where clause on type parameter of enclosing item"
+    );
     Ok(typ_params)
 }
 
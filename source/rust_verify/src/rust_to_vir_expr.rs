@@ -4,8 +4,8 @@ use crate::def::is_get_variant_fn_name;
 use crate::erase::ResolvedCall;
 use crate::rust_to_vir_base::{
     def_id_to_vir_path, def_to_path_ident, get_function_def, get_range, hack_get_def_name,
-    ident_to_var, is_smt_arith, is_smt_equality, mid_ty_simplify, mid_ty_to_vir, mk_range,
-    typ_of_node, typ_of_node_expect_mut_ref, typ_path_and_ident_to_vir_path,
+    ident_to_var, is_smt_arith, is_smt_equality, mid_const_to_vir, mid_ty_simplify, mid_ty_to_vir,
+    mk_range, typ_of_node, typ_of_node_expect_mut_ref, typ_path_and_ident_to_vir_path,
 };
 use crate::util::{
     err_span_str, err_span_string, slice_vec_map_result, spanned_new, spanned_typed_new,
@@ -17,8 +17,8 @@ use air::ast_util::str_ident;
 use rustc_ast::{Attribute, BorrowKind, Mutability};
 use rustc_hir::def::{DefKind, Res};
 use rustc_hir::{
-    BinOpKind, BindingAnnotation, Block, Destination, Expr, ExprKind, Guard, Local, LoopSource,
-    Node, Pat, PatKind, QPath, Stmt, StmtKind, UnOp,
+    Arm, BinOpKind, BindingAnnotation, Block, Destination, Expr, ExprKind, Guard, LangItem, Local,
+    LoopSource, MatchSource, Node, Pat, PatKind, QPath, Stmt, StmtKind, UnOp,
 };
 use rustc_middle::ty::subst::GenericArgKind;
 use rustc_middle::ty::{PredicateKind, TyCtxt, TyKind};
@@ -26,12 +26,14 @@ use rustc_span::def_id::DefId;
 use rustc_span::Span;
 use std::sync::Arc;
 use vir::ast::{
-    ArmX, BinaryOp, CallTarget, Constant, ExprX, FunX, HeaderExpr, HeaderExprX, Ident, IntRange,
-    InvAtomicity, Mode, PatternX, SpannedTyped, StmtX, Stmts, Typ, TypX, UnaryOp, UnaryOpr, VarAt,
-    VirErr,
+    ArmX, BinaryOp, CallTarget, ClosureSpec, Constant, ExprX, FunX, HeaderExpr, HeaderExprX, Ident,
+    IntRange, InvAtomicity, Mode, PatternX, SpannedTyped, StmtX, Stmts, Typ, TypX, UnaryOp,
+    UnaryOpr, VarAt, VirErr,
 };
 use vir::ast_util::{ident_binder, path_as_rust_name};
-use vir::def::positional_field_ident;
+use vir::def::{
+    option_type_path, positional_field_ident, result_type_path, TRY_RESIDUAL, TRY_VALUE,
+};
 
 pub(crate) fn pat_to_var<'tcx>(pat: &Pat) -> String {
     let Pat { hir_id: _, kind, span: _, default_binding_modes } = pat;
@@ -84,7 +86,11 @@ fn get_ensures_arg<'tcx>(
     }
 }
 
-fn closure_param_typs<'tcx>(bctx: &BodyCtxt<'tcx>, expr: &Expr<'tcx>) -> Vec<Typ> {
+fn closure_param_typs<'tcx>(
+    bctx: &BodyCtxt<'tcx>,
+    expr: &Expr<'tcx>,
+    allow_mut_ref: bool,
+) -> Vec<Typ> {
     let node_type = bctx.types.node_type(expr.hir_id);
     match node_type.kind() {
         TyKind::Closure(_def, substs) => {
@@ -93,7 +99,7 @@ fn closure_param_typs<'tcx>(bctx: &BodyCtxt<'tcx>, expr: &Expr<'tcx>) -> Vec<Typ
                 .inputs()
                 .skip_binder()
                 .iter()
-                .map(|t| mid_ty_to_vir(bctx.ctxt.tcx, t, false /* allow_mut_ref */))
+                .map(|t| mid_ty_to_vir(bctx.ctxt.tcx, t, allow_mut_ref))
                 .collect();
             assert!(args.len() == 1);
             match &*args[0] {
@@ -112,7 +118,8 @@ fn extract_ensures<'tcx>(
     let tcx = bctx.ctxt.tcx;
     match &expr.kind {
         ExprKind::Closure(_, _fn_decl, body_id, _, _) => {
-            let typs: Vec<Typ> = closure_param_typs(bctx, expr);
+            // the return value may be an &mut
+            let typs: Vec<Typ> = closure_param_typs(bctx, expr, true);
             let body = tcx.hir().body(*body_id);
             let xs: Vec<String> = body.params.iter().map(|param| pat_to_var(param.pat)).collect();
             let expr = &body.value;
@@ -141,7 +148,7 @@ fn extract_quant<'tcx>(
     match &expr.kind {
         ExprKind::Closure(_, _fn_decl, body_id, _, _) => {
             let body = tcx.hir().body(*body_id);
-            let typs = closure_param_typs(bctx, expr);
+            let typs = closure_param_typs(bctx, expr, false);
             assert!(typs.len() == body.params.len());
             let binders: Vec<Binder<Typ>> = body
                 .params
@@ -174,7 +181,7 @@ fn extract_assert_forall_by<'tcx>(
     match &expr.kind {
         ExprKind::Closure(_, _fn_decl, body_id, _, _) => {
             let body = tcx.hir().body(*body_id);
-            let typs = closure_param_typs(bctx, expr);
+            let typs = closure_param_typs(bctx, expr, false);
             assert!(body.params.len() == typs.len());
             let binders: Vec<Binder<Typ>> = body
                 .params
@@ -222,7 +229,7 @@ fn extract_choose<'tcx>(
             let closure_body = tcx.hir().body(*body_id);
             let mut params: Vec<Binder<Typ>> = Vec::new();
             let mut vars: Vec<vir::ast::Expr> = Vec::new();
-            let typs = closure_param_typs(bctx, expr);
+            let typs = closure_param_typs(bctx, expr, false);
             assert!(closure_body.params.len() == typs.len());
             for (x, typ) in closure_body.params.iter().zip(typs) {
                 let name = Arc::new(pat_to_var(x.pat));
@@ -321,6 +328,93 @@ fn get_fn_path<'tcx>(bctx: &BodyCtxt<'tcx>, expr: &Expr<'tcx>) -> Result<vir::as
     }
 }
 
+// Mode of a local variable or parameter, from the attributes on its let statement or parameter
+fn local_var_mode<'tcx>(bctx: &BodyCtxt<'tcx>, hir_id: rustc_hir::HirId) -> Mode {
+    let hir = bctx.ctxt.tcx.hir();
+    let mut id = hir_id;
+    loop {
+        id = hir.get_parent_node(id);
+        match hir.get(id) {
+            Node::Pat(..) => {}
+            Node::Param(param) => return get_var_mode(bctx.mode, hir.attrs(param.hir_id)),
+            Node::Local(local) => return get_var_mode(bctx.mode, hir.attrs(local.hir_id)),
+            _ => return get_var_mode(bctx.mode, &[]),
+        }
+    }
+}
+
+// Functions returning &mut are supported in limited forms (see ExprX::Assign and VarAt::Final):
+// - a returned &mut must be immediately written to, as in *f(...) = e
+// - a function returning &mut may return &mut to a field of an &mut parameter,
+//   or the result of calling another function that returns &mut
+fn is_mut_ref_assign_lhs<'tcx>(tcx: TyCtxt<'tcx>, hir_id: rustc_hir::HirId) -> bool {
+    let hir = tcx.hir();
+    let deref_id = hir.get_parent_node(hir_id);
+    match hir.get(deref_id) {
+        Node::Expr(Expr { kind: ExprKind::Unary(UnOp::Deref, _), .. }) => {
+            match hir.get(hir.get_parent_node(deref_id)) {
+                Node::Expr(Expr { kind: ExprKind::Assign(lhs, _, _), .. }) => {
+                    lhs.hir_id == deref_id
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+// Is the expression returned, via block tails, if/else branches, match arms, or return,
+// from a function that returns &mut?
+fn is_mut_ref_return_position<'tcx>(tcx: TyCtxt<'tcx>, hir_id: rustc_hir::HirId) -> bool {
+    let hir = tcx.hir();
+    let mut id = hir_id;
+    let mut returned = false;
+    loop {
+        let parent = hir.get_parent_node(id);
+        let node = hir.get(parent);
+        let sig = match node {
+            Node::Item(rustc_hir::Item { kind: rustc_hir::ItemKind::Fn(sig, ..), .. }) => Some(sig),
+            Node::ImplItem(rustc_hir::ImplItem {
+                kind: rustc_hir::ImplItemKind::Fn(sig, _),
+                ..
+            }) => Some(sig),
+            Node::TraitItem(rustc_hir::TraitItem {
+                kind: rustc_hir::TraitItemKind::Fn(sig, _),
+                ..
+            }) => Some(sig),
+            _ => None,
+        };
+        if let Some(sig) = sig {
+            return match sig.decl.output {
+                rustc_hir::FnRetTy::Return(rustc_hir::Ty {
+                    kind:
+                        rustc_hir::TyKind::Rptr(
+                            _,
+                            rustc_hir::MutTy { mutbl: rustc_hir::Mutability::Mut, .. },
+                        ),
+                    ..
+                }) => true,
+                _ => false,
+            };
+        }
+        match node {
+            Node::Expr(Expr { kind: ExprKind::Closure(..), .. }) => return false,
+            _ if returned => {}
+            Node::Expr(e) => match &e.kind {
+                ExprKind::Ret(_) => returned = true,
+                ExprKind::Block(..) | ExprKind::DropTemps(..) => {}
+                ExprKind::If(cond, ..) if cond.hir_id != id => {}
+                ExprKind::Match(scrutinee, ..) if scrutinee.hir_id != id => {}
+                _ => return false,
+            },
+            Node::Block(block) if block.expr.map(|e| e.hir_id) == Some(id) => {}
+            Node::Arm(arm) if arm.body.hir_id == id => {}
+            _ => return false,
+        }
+        id = parent;
+    }
+}
+
 const BUILTIN_INV_LOCAL_BEGIN: &str = "crate::pervasive::invariants::open_local_invariant_begin";
 const BUILTIN_INV_BEGIN: &str = "crate::pervasive::invariants::open_invariant_begin";
 const BUILTIN_INV_END: &str = "crate::pervasive::invariants::open_invariant_end";
@@ -395,6 +489,7 @@ fn fn_call_to_vir<'tcx>(
     let is_assert_forall_by = f_name == "builtin::assert_forall_by";
     let is_assert_bit_vector = f_name == "builtin::assert_bit_vector";
     let is_old = f_name == "builtin::old";
+    let is_fin = f_name == "builtin::fin";
     let is_eq = f_name == "core::cmp::PartialEq::eq";
     let is_ne = f_name == "core::cmp::PartialEq::ne";
     let is_le = f_name == "core::cmp::PartialOrd::le";
@@ -459,6 +554,16 @@ fn fn_call_to_vir<'tcx>(
     );
     let name = Arc::new(FunX { path: path.clone(), trait_path: None });
 
+    if let TyKind::Ref(_, _, Mutability::Mut) = bctx.types.node_type(expr.hir_id).kind() {
+        if !is_mut_ref_assign_lhs(tcx, expr.hir_id) && !is_mut_ref_return_position(tcx, expr.hir_id)
+        {
+            return err_span_str(
+                expr.span,
+                "a returned &mut must be immediately assigned to (*f(...) = e) or returned",
+            );
+        }
+    }
+
     record_fun(
         &bctx.ctxt,
         fn_span,
@@ -472,12 +577,14 @@ fn fn_call_to_vir<'tcx>(
             || is_assert_forall_by
             || is_assert_bit_vector
             || is_old
+            || is_fin
             || is_get_variant.is_some(),
         is_implies,
     );
 
     let len = args.len();
-    let expr_typ = || typ_of_node(bctx, &expr.hir_id, false);
+    // a call may return an &mut (see is_mut_ref_assign_lhs)
+    let expr_typ = || typ_of_node(bctx, &expr.hir_id, true);
     let mk_expr = |x: ExprX| spanned_typed_new(expr.span, &expr_typ(), x);
     let mk_expr_span = |span: Span, x: ExprX| spanned_typed_new(span, &expr_typ(), x);
 
@@ -563,6 +670,19 @@ fn fn_call_to_vir<'tcx>(
             "only a variable binding is allowed as the argument to old",
         );
     }
+    if is_fin {
+        if let ExprKind::Path(QPath::Resolved(None, rustc_hir::Path { res: Res::Local(id), .. })) =
+            &args[0].kind
+        {
+            if let Node::Binding(pat) = tcx.hir().get(*id) {
+                return Ok(mk_expr(ExprX::VarAt(Arc::new(pat_to_var(pat)), VarAt::Final)));
+            }
+        }
+        return err_span_str(
+            expr.span,
+            "only a variable binding is allowed as the argument to fin",
+        );
+    }
 
     if is_extra_dependency || is_hide || is_reveal {
         unsupported_err_unless!(len == 1, expr.span, "expected hide/reveal", &args);
@@ -768,7 +888,9 @@ fn fn_call_to_vir<'tcx>(
                     typ_args.push(mid_ty_to_vir(tcx, ty, false));
                 }
                 GenericArgKind::Lifetime(_) => {}
-                _ => unsupported_err!(expr.span, format!("const type arguments"), expr),
+                GenericArgKind::Const(cnst) => {
+                    typ_args.push(mid_const_to_vir(tcx, expr.span, cnst)?);
+                }
             }
         }
         let target = CallTarget::Static(name, Arc::new(typ_args));
@@ -962,6 +1084,367 @@ pub fn attrs_is_invariant_block(attrs: &[Attribute]) -> Result<bool, VirErr> {
     Ok(false)
 }
 
+/// Translate `'label: for pat in start..end { body }`, which rustc desugars into:
+///   match IntoIterator::into_iter(start..end) {
+///       mut iter => 'label: loop {
+///           match Iterator::next(&mut iter) { None => break, Some(pat) => body };
+///       }
+///   }
+fn for_loop_to_vir<'tcx>(
+    bctx: &BodyCtxt<'tcx>,
+    expr: &Expr<'tcx>,
+    head: &Expr<'tcx>,
+    arm: &Arm<'tcx>,
+    modifier: ExprModifier,
+) -> Result<ExprX, VirErr> {
+    let range = match &head.kind {
+        ExprKind::Call(_, [range]) => range.peel_drop_temps(),
+        _ => unsupported_err!(expr.span, "for loop desugaring", head),
+    };
+    let (start, end) = match range_start_end(range) {
+        Some(start_end) => start_end,
+        None => {
+            unsupported_err!(range.span, "for loops over anything other than a range start..end")
+        }
+    };
+    let (block, label) = match &arm.body.kind {
+        ExprKind::Loop(block, label, LoopSource::ForLoop, _) => (block, label),
+        _ => unsupported_err!(expr.span, "for loop desugaring", arm.body),
+    };
+    let next_match = match block {
+        Block {
+            stmts: [Stmt { kind: StmtKind::Expr(e) | StmtKind::Semi(e), .. }],
+            expr: None,
+            ..
+        } => e,
+        _ => unsupported_err!(expr.span, "for loop desugaring", block),
+    };
+    let some_arm = match &next_match.kind {
+        ExprKind::Match(_, [_none_arm, some_arm], MatchSource::ForLoopDesugar) => some_arm,
+        _ => unsupported_err!(expr.span, "for loop desugaring", next_match),
+    };
+    let pat = match &some_arm.pat.kind {
+        PatKind::TupleStruct(_, [pat], _) => pat,
+        _ => unsupported_err!(expr.span, "for loop desugaring", some_arm.pat),
+    };
+    match &pat.kind {
+        PatKind::Binding(BindingAnnotation::Unannotated, _, _, None) => {}
+        _ => unsupported_err!(pat.span, "for loop pattern other than a single immutable variable"),
+    }
+    let typ = typ_of_node(bctx, &pat.hir_id, false);
+    match &*typ {
+        TypX::Int(IntRange::U(_) | IntRange::USize) => {}
+        _ => unsupported_err!(range.span, "for loops over ranges of non-unsigned-integer types"),
+    }
+    let var = Arc::new(BinderX { name: Arc::new(pat_to_var(pat)), a: typ });
+    let start = expr_to_vir(bctx, start, modifier)?;
+    let end = expr_to_vir(bctx, end, modifier)?;
+    let mut body = expr_to_vir(bctx, &some_arm.body, modifier)?;
+    let invs = vir::headers::read_loop_header(&mut body)?;
+    let label = label.map(|l| Arc::new(l.ident.to_string()));
+    Ok(ExprX::ForRange { label, var, start, end, body, invs })
+}
+
+/// Translate `e?`, which rustc desugars into:
+///   match Try::branch(e) {
+///       ControlFlow::Continue(val) => val,
+///       ControlFlow::Break(residual) => return FromResidual::from_residual(residual),
+///   }
+/// For pervasive::option::Option and pervasive::result::Result, we translate this directly into:
+///   match e { Some(val) => val, None => return None }
+///   match e { Ok(val) => val, Err(err) => return Err(err) }
+fn try_to_vir<'tcx>(
+    bctx: &BodyCtxt<'tcx>,
+    expr: &Expr<'tcx>,
+    head: &Expr<'tcx>,
+    break_arm: &Arm<'tcx>,
+    modifier: ExprModifier,
+) -> Result<ExprX, VirErr> {
+    let arg = match &head.kind {
+        ExprKind::Call(_, [arg]) => arg,
+        _ => unsupported_err!(expr.span, "? desugaring", head),
+    };
+    let residual = match &break_arm.body.kind {
+        ExprKind::Ret(Some(residual)) => residual,
+        _ => unsupported_err!(expr.span, "? inside try blocks", break_arm.body),
+    };
+    let arg_typ = typ_of_node(bctx, &arg.hir_id, false);
+    let ret_typ = typ_of_node(bctx, &residual.hir_id, false);
+    let (arg_path, typ_args, ret_path) = match (&*arg_typ, &*ret_typ) {
+        (TypX::Datatype(arg_path, typ_args), TypX::Datatype(ret_path, _)) => {
+            (arg_path.clone(), typ_args.clone(), ret_path.clone())
+        }
+        _ => unsupported_err!(expr.span, "? on types other than pervasive Option and Result"),
+    };
+    let is_option = arg_path == option_type_path() && ret_path == option_type_path();
+    let is_result = arg_path == result_type_path() && ret_path == result_type_path();
+    if !is_option && !is_result {
+        unsupported_err!(expr.span, "? on types other than pervasive Option and Result");
+    }
+
+    let span = expr.span;
+    let var = |x: &str, typ: &Typ| spanned_typed_new(span, typ, ExprX::Var(str_ident(x)));
+    let field_pattern = |x: &str, typ: &Typ| {
+        let pattern = PatternX::Var { name: str_ident(x), mutable: false };
+        let pattern = spanned_typed_new(span, typ, pattern);
+        Arc::new(vec![ident_binder(&positional_field_ident(0), &pattern)])
+    };
+    let mk_arm = |pattern: PatternX, body: vir::ast::Expr| {
+        let pattern = spanned_typed_new(span, &arg_typ, pattern);
+        let guard = ExprX::Const(Constant::Bool(true));
+        let guard = spanned_typed_new(span, &Arc::new(TypX::Bool), guard);
+        spanned_new(span, ArmX { pattern, guard, body })
+    };
+
+    let (val_variant, residual_variant) = if is_option { ("Some", "None") } else { ("Ok", "Err") };
+
+    // Some(val) => val or Ok(val) => val
+    let val_typ = &typ_args[0];
+    let val_fields = field_pattern(TRY_VALUE, val_typ);
+    let val_pattern = PatternX::Constructor(arg_path.clone(), str_ident(val_variant), val_fields);
+    let val_arm = mk_arm(val_pattern, var(TRY_VALUE, val_typ));
+
+    // None => return None or Err(err) => return Err(err)
+    let (residual_pattern_fields, residual_fields) = if is_option {
+        (Arc::new(vec![]), Arc::new(vec![]))
+    } else {
+        let err_typ = &typ_args[1];
+        let field = ident_binder(&positional_field_ident(0), &var(TRY_RESIDUAL, err_typ));
+        (field_pattern(TRY_RESIDUAL, err_typ), Arc::new(vec![field]))
+    };
+    let residual_variant = str_ident(residual_variant);
+    let residual_pattern =
+        PatternX::Constructor(arg_path, residual_variant.clone(), residual_pattern_fields);
+    let residual_ctor = ExprX::Ctor(ret_path, residual_variant, residual_fields, None);
+    let residual_ctor = spanned_typed_new(span, &ret_typ, residual_ctor);
+    let ret_typ_never = typ_of_node(bctx, &break_arm.body.hir_id, false);
+    let ret = spanned_typed_new(span, &ret_typ_never, ExprX::Return(Some(residual_ctor)));
+    let residual_arm = mk_arm(residual_pattern, ret);
+
+    let vir_arg = expr_to_vir(bctx, arg, modifier)?;
+    Ok(ExprX::Match(vir_arg, Arc::new(vec![val_arm, residual_arm])))
+}
+
+/// Match a range expression start..end
+fn range_start_end<'tcx>(range: &Expr<'tcx>) -> Option<(&'tcx Expr<'tcx>, &'tcx Expr<'tcx>)> {
+    match &range.kind {
+        ExprKind::Struct(QPath::LangItem(LangItem::Range, ..), fields, None) => {
+            let field = |name: &str| fields.iter().find(|f| f.ident.as_str() == name);
+            match (field("start"), field("end")) {
+                (Some(start), Some(end)) => Some((start.expr, end.expr)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// Arrays [T; N] and slices [T] are represented as pervasive::seq::Seq<T>.
+// In spec code, operations on arrays and slices become Seq operations.
+// In exec code, they become calls to the exec functions in pervasive::array,
+// which specify the Rust operations (e.g. bounds checks) in terms of Seq.
+
+/// If ty is an array or slice (possibly behind references), return the element type
+/// and, for arrays of known length, the length
+fn array_or_slice_ty<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ty: rustc_middle::ty::Ty<'tcx>,
+) -> Option<(rustc_middle::ty::Ty<'tcx>, Option<u64>)> {
+    match ty.kind() {
+        TyKind::Ref(_, t, _) => array_or_slice_ty(tcx, *t),
+        TyKind::Array(t, len) => {
+            Some((*t, len.try_eval_usize(tcx, rustc_middle::ty::ParamEnv::empty())))
+        }
+        TyKind::Slice(t) => Some((*t, None)),
+        _ => None,
+    }
+}
+
+fn is_spec_context<'tcx>(bctx: &BodyCtxt<'tcx>, expr: &Expr<'tcx>) -> bool {
+    bctx.mode != Mode::Exec || bctx.ctxt.exprs_in_spec.contains(&expr.hir_id)
+}
+
+fn mk_seq_call(
+    span: Span,
+    typ: &Typ,
+    fun: vir::ast::Fun,
+    elem_typ: &Typ,
+    args: Vec<vir::ast::Expr>,
+) -> vir::ast::Expr {
+    let target = CallTarget::Static(fun, Arc::new(vec![elem_typ.clone()]));
+    spanned_typed_new(span, typ, ExprX::Call(target, Arc::new(args)))
+}
+
+fn mk_usize_const(span: Span, n: u64) -> vir::ast::Expr {
+    let typ = Arc::new(TypX::Int(IntRange::USize));
+    spanned_typed_new(span, &typ, ExprX::Const(Constant::Nat(Arc::new(n.to_string()))))
+}
+
+/// In exec code, coerce an array to a slice, remembering the array's length
+fn array_as_slice(
+    span: Span,
+    array: vir::ast::Expr,
+    len: Option<u64>,
+    elem_typ: &Typ,
+) -> vir::ast::Expr {
+    match len {
+        None => array,
+        Some(n) => {
+            let fun = vir::def::fn_array_name("array_as_slice");
+            let typ = array.typ.clone();
+            mk_seq_call(span, &typ, fun, elem_typ, vec![array, mk_usize_const(span, n)])
+        }
+    }
+}
+
+/// a[i] and a[i..j] for arrays and slices
+fn array_index_to_vir<'tcx>(
+    bctx: &BodyCtxt<'tcx>,
+    expr: &Expr<'tcx>,
+    tgt_expr: &Expr<'tcx>,
+    idx_expr: &Expr<'tcx>,
+    modifier: ExprModifier,
+) -> Result<Option<vir::ast::Expr>, VirErr> {
+    let tcx = bctx.ctxt.tcx;
+    let (elem_ty, len) = match array_or_slice_ty(tcx, bctx.types.expr_ty(tgt_expr)) {
+        Some(elem_len) => elem_len,
+        None => return Ok(None),
+    };
+    let elem_typ = mid_ty_to_vir(tcx, elem_ty, false);
+    let typ = typ_of_node(bctx, &expr.hir_id, false);
+    let in_spec = is_spec_context(bctx, expr);
+    let mut tgt = expr_to_vir(bctx, tgt_expr, modifier)?;
+    if !in_spec {
+        tgt = array_as_slice(tgt_expr.span, tgt, len, &elem_typ);
+    }
+    let (name, args) = if let Some((start, end)) = range_start_end(idx_expr.peel_drop_temps()) {
+        let start = expr_to_vir(bctx, start, modifier)?;
+        let end = expr_to_vir(bctx, end, modifier)?;
+        let name = if in_spec { "subrange" } else { "slice_subrange" };
+        (name, vec![tgt, start, end])
+    } else {
+        let idx = expr_to_vir(bctx, idx_expr, modifier)?;
+        let name = if in_spec { "index" } else { "slice_index" };
+        (name, vec![tgt, idx])
+    };
+    let fun = if in_spec { vir::def::fn_seq_name(name) } else { vir::def::fn_array_name(name) };
+    Ok(Some(mk_seq_call(expr.span, &typ, fun, &elem_typ, args)))
+}
+
+/// Methods of [T] (only len is supported)
+fn slice_method_to_vir<'tcx>(
+    bctx: &BodyCtxt<'tcx>,
+    expr: &Expr<'tcx>,
+    f: DefId,
+    fn_span: Span,
+    args: &'tcx [Expr<'tcx>],
+) -> Result<Option<vir::ast::Expr>, VirErr> {
+    let tcx = bctx.ctxt.tcx;
+    match tcx.impl_of_method(f).map(|impl_def_id| tcx.type_of(impl_def_id).kind()) {
+        Some(TyKind::Slice(_)) => {}
+        _ => return Ok(None),
+    }
+    let method_name = tcx.item_name(f);
+    if method_name.as_str() != "len" || args.len() != 1 {
+        unsupported_err!(expr.span, format!("slice method {}", method_name))
+    }
+    let (elem_ty, len) =
+        array_or_slice_ty(tcx, bctx.types.expr_ty(&args[0])).expect("slice method receiver");
+    let elem_typ = mid_ty_to_vir(tcx, elem_ty, false);
+    let typ = typ_of_node(bctx, &expr.hir_id, false);
+    let in_spec = is_spec_context(bctx, expr);
+    let tgt = expr_to_vir(bctx, &args[0], ExprModifier::REGULAR)?;
+    let (fun, tgt) = if in_spec {
+        (vir::def::fn_seq_name("len"), tgt)
+    } else {
+        (vir::def::fn_array_name("slice_len"), array_as_slice(args[0].span, tgt, len, &elem_typ))
+    };
+    record_fun(&bctx.ctxt, fn_span, &fun, in_spec, false);
+    Ok(Some(mk_seq_call(expr.span, &typ, fun, &elem_typ, vec![tgt])))
+}
+
+/// f.requires((args)) and f.ensures((args), ret) for exec closures f
+fn closure_spec_method_to_vir<'tcx>(
+    bctx: &BodyCtxt<'tcx>,
+    expr: &Expr<'tcx>,
+    f: DefId,
+    fn_span: Span,
+    args: &'tcx [Expr<'tcx>],
+) -> Result<Option<vir::ast::Expr>, VirErr> {
+    let tcx = bctx.ctxt.tcx;
+    let trait_id = tcx
+        .trait_of_item(f)
+        .or_else(|| tcx.impl_of_method(f).and_then(|impl_id| tcx.trait_id_of_impl(impl_id)));
+    match trait_id.map(|t| path_as_rust_name(&def_id_to_vir_path(tcx, t))) {
+        Some(name) if name == "builtin::FnWithSpecification" => {}
+        _ => return Ok(None),
+    }
+    let spec = match (tcx.item_name(f).as_str(), args.len()) {
+        ("requires", 2) => ClosureSpec::Requires,
+        ("ensures", 3) => ClosureSpec::Ensures,
+        _ => panic!("unexpected FnWithSpecification method"),
+    };
+    let fun = expr_to_vir(bctx, &args[0], ExprModifier::REGULAR)?;
+    let mut vir_args = match &args[1].kind {
+        ExprKind::Tup(es) => {
+            slice_vec_map_result(es, |e| expr_to_vir(bctx, e, ExprModifier::REGULAR))?
+        }
+        _ => {
+            return err_span_str(
+                args[1].span,
+                "expected a tuple of arguments, e.g. f.requires((x,))",
+            );
+        }
+    };
+    if spec == ClosureSpec::Ensures {
+        vir_args.push(expr_to_vir(bctx, &args[2], ExprModifier::REGULAR)?);
+    }
+    let mut erasure_info = bctx.ctxt.erasure_info.borrow_mut();
+    erasure_info.resolved_calls.push((fn_span.data(), ResolvedCall::Spec));
+    let closure_spec = ExprX::ClosureSpec(spec, fun, Arc::new(vir_args));
+    Ok(Some(spanned_typed_new(expr.span, &Arc::new(TypX::Bool), closure_spec)))
+}
+
+/// Array literals [e1, ..., en] and repeat expressions [e; n]
+fn array_expr_to_vir<'tcx>(
+    bctx: &BodyCtxt<'tcx>,
+    expr: &Expr<'tcx>,
+    modifier: ExprModifier,
+) -> Result<vir::ast::Expr, VirErr> {
+    let tcx = bctx.ctxt.tcx;
+    let (elem_ty, len) =
+        array_or_slice_ty(tcx, bctx.types.expr_ty(expr)).expect("array expression type");
+    let elem_typ = mid_ty_to_vir(tcx, elem_ty, false);
+    let typ = typ_of_node(bctx, &expr.hir_id, false);
+    let in_spec = is_spec_context(bctx, expr);
+    match &expr.kind {
+        ExprKind::Array(elems) => {
+            let (empty, push) = if in_spec {
+                (vir::def::fn_seq_name("empty"), vir::def::fn_seq_name("push"))
+            } else {
+                (vir::def::fn_array_name("array_empty"), vir::def::fn_array_name("array_push"))
+            };
+            let mut seq = mk_seq_call(expr.span, &typ, empty, &elem_typ, vec![]);
+            for elem in elems.iter() {
+                let elem = expr_to_vir(bctx, elem, modifier)?;
+                seq = mk_seq_call(expr.span, &typ, push.clone(), &elem_typ, vec![seq, elem]);
+            }
+            Ok(seq)
+        }
+        ExprKind::Repeat(elem, _) => {
+            let len = match len {
+                Some(len) => mk_usize_const(expr.span, len),
+                None => unsupported_err!(expr.span, "array repeat expression with unknown length"),
+            };
+            let elem = expr_to_vir(bctx, elem, modifier)?;
+            let name = if in_spec { "seq_repeat" } else { "array_repeat" };
+            let fun = vir::def::fn_array_name(name);
+            Ok(mk_seq_call(expr.span, &typ, fun, &elem_typ, vec![elem, len]))
+        }
+        _ => panic!("array_expr_to_vir expects an array expression"),
+    }
+}
+
 /// Check for the #[verifier(invariant_block)] attribute on a block
 fn is_invariant_block(bctx: &BodyCtxt, expr: &Expr) -> Result<bool, VirErr> {
     let attrs = bctx.ctxt.tcx.hir().attrs(expr.hir_id);
@@ -1211,7 +1694,8 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
             typ_of_node_expect_mut_ref(bctx, &expr.hir_id, expr.span)
                 .expect("unexpected non-mut-ref type here")
         } else {
-            typ_of_node(bctx, &expr.hir_id, false)
+            // &mut values may be passed to &mut parameters and returned
+            typ_of_node(bctx, &expr.hir_id, true)
         }
     };
     let mk_expr = move |x: ExprX| spanned_typed_new(expr.span, &expr_typ(), x);
@@ -1252,15 +1736,7 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
             if is_invariant_block(bctx, expr)? {
                 invariant_block_to_vir(bctx, expr, modifier)
             } else {
-                let vir_stmts: Stmts = Arc::new(
-                    slice_vec_map_result(body.stmts, |stmt| stmt_to_vir(bctx, stmt))?
-                        .into_iter()
-                        .flatten()
-                        .collect(),
-                );
-                let vir_expr =
-                    body.expr.map(|expr| expr_to_vir(bctx, &expr, modifier)).transpose()?;
-                Ok(mk_expr(ExprX::Block(vir_stmts, vir_expr)))
+                block_to_vir(bctx, body, expr.span, &expr_typ(), modifier)
             }
         }
         ExprKind::Call(fun, args_slice) => {
@@ -1312,14 +1788,26 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
                     if bctx.external_body {
                         return Ok(mk_expr(ExprX::Block(Arc::new(vec![]), None)));
                     }
+                    let is_exec = match &fun.kind {
+                        ExprKind::Path(QPath::Resolved(
+                            None,
+                            rustc_hir::Path { res: Res::Local(id), .. },
+                        )) => {
+                            !is_spec_context(bctx, expr) && local_var_mode(bctx, *id) == Mode::Exec
+                        }
+                        _ => false,
+                    };
                     let vir_fun = expr_to_vir(bctx, fun, modifier)?;
                     let args: Vec<&'tcx Expr<'tcx>> = args_slice.iter().collect();
                     let vir_args = vec_map_result(&args, |arg| expr_to_vir(bctx, arg, modifier))?;
                     let expr_typ = typ_of_node(bctx, &expr.hir_id, false);
-                    let target = CallTarget::FnSpec(vir_fun);
+                    let (target, resolved_call) = if is_exec {
+                        (CallTarget::FnExec(vir_fun), ResolvedCall::CompilableOperator)
+                    } else {
+                        (CallTarget::FnSpec(vir_fun), ResolvedCall::Spec)
+                    };
                     let mut erasure_info = bctx.ctxt.erasure_info.borrow_mut();
-                    // Only spec closures are currently supported:
-                    erasure_info.resolved_calls.push((fun.span.data(), ResolvedCall::Spec));
+                    erasure_info.resolved_calls.push((fun.span.data(), resolved_call));
                     Ok(spanned_typed_new(
                         expr.span,
                         &expr_typ,
@@ -1333,6 +1821,7 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
                 exprs.iter().map(|e| expr_to_vir(bctx, e, modifier)).collect();
             Ok(mk_expr(ExprX::Tuple(Arc::new(args?))))
         }
+        ExprKind::Array(..) | ExprKind::Repeat(..) => array_expr_to_vir(bctx, expr, modifier),
         ExprKind::Lit(lit) => match lit.node {
             rustc_ast::LitKind::Bool(b) => {
                 let c = vir::ast::Constant::Bool(b);
@@ -1341,6 +1830,10 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
             rustc_ast::LitKind::Int(i, _) => {
                 mk_lit_int(false, i, typ_of_node(bctx, &expr.hir_id, false))
             }
+            rustc_ast::LitKind::Char(c) => {
+                let c = vir::ast::Constant::Nat(Arc::new((c as u32).to_string()));
+                Ok(mk_expr(ExprX::Const(c)))
+            }
             _ => {
                 panic!("unexpected constant: {:?}", lit)
             }
@@ -1351,6 +1844,14 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
         ExprKind::AddrOf(BorrowKind::Ref, Mutability::Not, e) => {
             expr_to_vir_inner(bctx, e, ExprModifier::REGULAR)
         }
+        ExprKind::AddrOf(BorrowKind::Ref, Mutability::Mut, e) => {
+            // &mut arguments are handled by fn_call_to_vir
+            if !is_mut_ref_return_position(tcx, expr.hir_id) {
+                unsupported_err!(expr.span, "&mut outside of arguments and return values")
+            }
+            let loc = expr_to_vir(bctx, e, ExprModifier::ADDR_OF)?;
+            Ok(spanned_typed_new(expr.span, &loc.typ.clone(), ExprX::Loc(loc)))
+        }
         ExprKind::Box(e) => expr_to_vir_inner(bctx, e, ExprModifier::REGULAR),
         ExprKind::Unary(op, arg) => match op {
             UnOp::Not => {
@@ -1436,6 +1937,7 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
                             // Non-Euclidean division, which will need more encoding
                             unsupported_err!(expr.span, "div/mod on signed finite-width integers")
                         }
+                        IntRange::Char => err_span_str(expr.span, "div/mod on char"),
                     }
                 }
                 _ => Ok(mk_expr(ExprX::Binary(vop, vlhs, vrhs))),
@@ -1475,6 +1977,10 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
                         let name = hack_get_def_name(tcx, id); // TODO: proper handling of paths
                         Ok(mk_expr(ExprX::Var(Arc::new(name))))
                     }
+                    DefKind::ConstParam => {
+                        let name = tcx.item_name(id).to_string();
+                        Ok(mk_expr(ExprX::ConstParam(Arc::new(name))))
+                    }
                     DefKind::Ctor(_, _ctor_kind) => expr_tuple_datatype_ctor_to_vir(
                         bctx,
                         expr,
@@ -1494,6 +2000,11 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
             if let ExprKind::Field(_, _) = lhs.kind {
                 unsupported_err!(expr.span, format!("field updates"), lhs);
             }
+            if let ExprKind::Index(tgt, _) = lhs.kind {
+                if array_or_slice_ty(tcx, bctx.types.expr_ty(tgt)).is_some() {
+                    unsupported_err!(expr.span, "assignment to array or slice elements", lhs);
+                }
+            }
             Ok(mk_expr(ExprX::Assign(
                 expr_to_vir(bctx, lhs, ExprModifier::ADDR_OF)?,
                 expr_to_vir(bctx, rhs, modifier)?,
@@ -1592,6 +2103,12 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
                 }
             }
         }
+        ExprKind::Match(head, [arm], MatchSource::ForLoopDesugar) => {
+            Ok(mk_expr(for_loop_to_vir(bctx, expr, head, arm, modifier)?))
+        }
+        ExprKind::Match(head, [_continue_arm, break_arm], MatchSource::TryDesugar) => {
+            Ok(mk_expr(try_to_vir(bctx, expr, head, break_arm, modifier)?))
+        }
         ExprKind::Match(expr, arms, _match_source) => {
             let vir_expr = expr_to_vir(bctx, expr, modifier)?;
             let mut vir_arms: Vec<vir::ast::Arm> = Vec::new();
@@ -1612,7 +2129,7 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
             Block {
                 stmts: [], expr: Some(Expr { kind: ExprKind::If(cond, body, other), .. }), ..
             },
-            None,
+            label,
             LoopSource::While,
             _span,
         ) => {
@@ -1646,11 +2163,27 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
             } else {
                 unsupported!("loop else", expr);
             }
-            let cond = expr_to_vir(bctx, cond, modifier)?;
+            let label = label.map(|l| Arc::new(l.ident.to_string()));
+            let cond = Some(expr_to_vir(bctx, cond, modifier)?);
             let mut body = expr_to_vir(bctx, body, modifier)?;
-            let header = vir::headers::read_header(&mut body)?;
-            let invs = header.invariant;
-            Ok(mk_expr(ExprX::While { cond, body, invs }))
+            let invs = vir::headers::read_loop_header(&mut body)?;
+            Ok(mk_expr(ExprX::Loop { label, cond, body, step: None, invs }))
+        }
+        ExprKind::Loop(block, label, LoopSource::Loop, _span) => {
+            let label = label.map(|l| Arc::new(l.ident.to_string()));
+            let typ = Arc::new(TypX::Tuple(Arc::new(vec![])));
+            let mut body = block_to_vir(bctx, block, expr.span, &typ, modifier)?;
+            let invs = vir::headers::read_loop_header(&mut body)?;
+            Ok(mk_expr(ExprX::Loop { label, cond: None, body, step: None, invs }))
+        }
+        ExprKind::Break(Destination { label, .. }, None) => {
+            let label = label.map(|l| Arc::new(l.ident.to_string()));
+            Ok(mk_expr(ExprX::BreakOrContinue { label, is_break: true }))
+        }
+        ExprKind::Break(_, Some(_)) => unsupported_err!(expr.span, "break with a value"),
+        ExprKind::Continue(Destination { label, .. }) => {
+            let label = label.map(|l| Arc::new(l.ident.to_string()));
+            Ok(mk_expr(ExprX::BreakOrContinue { label, is_break: false }))
         }
         ExprKind::Ret(expr) => {
             let expr = match expr {
@@ -1704,6 +2237,16 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
                 .types
                 .type_dependent_def_id(expr.hir_id)
                 .expect("def id of the method definition");
+            if let Some(vir_expr) =
+                slice_method_to_vir(bctx, expr, fn_def_id, *call_span_1, all_args)?
+            {
+                return Ok(vir_expr);
+            }
+            if let Some(vir_expr) =
+                closure_spec_method_to_vir(bctx, expr, fn_def_id, *call_span_1, all_args)?
+            {
+                return Ok(vir_expr);
+            }
             match tcx.hir().get_if_local(fn_def_id).expect("fn def for method in hir") {
                 rustc_hir::Node::ImplItem(rustc_hir::ImplItem {
                     kind: rustc_hir::ImplItemKind::Fn(..),
@@ -1728,7 +2271,7 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
         }
         ExprKind::Closure(_, _fn_decl, body_id, _, _) => {
             let body = tcx.hir().body(*body_id);
-            let typs = closure_param_typs(bctx, expr);
+            let typs = closure_param_typs(bctx, expr, false);
             assert!(typs.len() == body.params.len());
             let params: Vec<Binder<Typ>> = body
                 .params
@@ -1736,11 +2279,45 @@ pub(crate) fn expr_to_vir_inner<'tcx>(
                 .zip(typs.clone())
                 .map(|(x, t)| Arc::new(BinderX { name: Arc::new(pat_to_var(x.pat)), a: t }))
                 .collect();
-            let body = expr_to_vir(bctx, &body.value, modifier)?;
+            let mut body = expr_to_vir(bctx, &body.value, modifier)?;
             let typ = Arc::new(TypX::Lambda(Arc::new(typs), body.typ.clone()));
-            Ok(spanned_typed_new(expr.span, &typ, ExprX::Closure(Arc::new(params), body)))
+            if is_spec_context(bctx, expr) {
+                let closurex = ExprX::Closure(Arc::new(params), body);
+                return Ok(spanned_typed_new(expr.span, &typ, closurex));
+            }
+            // exec closure, optionally beginning with requires/ensures
+            let header = vir::headers::read_header(&mut body)?;
+            if header.invariant.len() + header.decrease.len() > 0 {
+                return err_span_str(expr.span, "exec closures cannot have invariants/decreases");
+            }
+            let ret = match header.ensure_id_typ {
+                Some((x, t)) => {
+                    if !vir::ast_util::types_equal(&t, &body.typ) {
+                        return err_span_str(
+                            expr.span,
+                            "ensures type must match the closure's return type",
+                        );
+                    }
+                    Arc::new(BinderX { name: x, a: t })
+                }
+                None => Arc::new(BinderX {
+                    name: Arc::new(vir::def::RETURN_VALUE.to_string()),
+                    a: body.typ.clone(),
+                }),
+            };
+            let closurex = ExprX::ExecClosure {
+                params: Arc::new(params),
+                require: header.require,
+                ensure: header.ensure,
+                ret,
+                body,
+            };
+            Ok(spanned_typed_new(expr.span, &typ, closurex))
         }
         ExprKind::Index(tgt_expr, idx_expr) => {
+            if let Some(vir_expr) = array_index_to_vir(bctx, expr, tgt_expr, idx_expr, modifier)? {
+                return Ok(vir_expr);
+            }
             let tgt_vir = expr_to_vir(bctx, tgt_expr, modifier)?;
             if let TypX::Datatype(path, _dt_typs) = &*tgt_vir.typ {
                 let tgt_index_path = {
@@ -1777,12 +2354,29 @@ pub(crate) fn let_stmt_to_vir<'tcx>(
     initializer: &Option<&Expr<'tcx>>,
     attrs: &[Attribute],
 ) -> Result<Vec<vir::ast::Stmt>, VirErr> {
+    let init = initializer.map(|e| expr_to_vir(bctx, e, ExprModifier::REGULAR)).transpose()?;
     let vir_pattern = pattern_to_vir(bctx, pattern)?;
     let mode = get_var_mode(bctx.mode, attrs);
-    let init = initializer.map(|e| expr_to_vir(bctx, e, ExprModifier::REGULAR)).transpose()?;
     Ok(vec![spanned_new(pattern.span, StmtX::Decl { pattern: vir_pattern, mode, init })])
 }
 
+fn block_to_vir<'tcx>(
+    bctx: &BodyCtxt<'tcx>,
+    block: &Block<'tcx>,
+    span: Span,
+    ty: &Typ,
+    modifier: ExprModifier,
+) -> Result<vir::ast::Expr, VirErr> {
+    let vir_stmts: Stmts = Arc::new(
+        slice_vec_map_result(block.stmts, |stmt| stmt_to_vir(bctx, stmt))?
+            .into_iter()
+            .flatten()
+            .collect(),
+    );
+    let vir_expr = block.expr.map(|expr| expr_to_vir(bctx, &expr, modifier)).transpose()?;
+    Ok(spanned_typed_new(span, ty, ExprX::Block(vir_stmts, vir_expr)))
+}
+
 pub(crate) fn stmt_to_vir<'tcx>(
     bctx: &BodyCtxt<'tcx>,
     stmt: &Stmt<'tcx>,
@@ -54,7 +54,7 @@ fn check_fn_decl<'tcx>(
     self_typ: Option<Typ>,
     attrs: &[Attribute],
     mode: Mode,
-) -> Result<Option<(Typ, Mode)>, VirErr> {
+) -> Result<Option<(Typ, Mode, bool)>, VirErr> {
     let FnDecl { inputs: _, output, c_variadic, implicit_self } = decl;
     unsupported_unless!(!c_variadic, "c_variadic");
     match implicit_self {
@@ -70,12 +70,13 @@ fn check_fn_decl<'tcx>(
         // so we always return the default mode.
         // The current workaround is to return a struct if the default doesn't work.
         rustc_hir::FnRetTy::Return(ty) => {
+            let is_mut = is_mut_ty(*ty);
             let typ = if is_self_or_self_ref(*sig_span, &ty)? {
                 self_typ.expect("a param is Self, so this must be an impl")
             } else {
-                ty_to_vir(tcx, ty)
+                ty_to_vir(tcx, is_mut.unwrap_or(*ty))
             };
-            Ok(Some((typ, get_ret_mode(mode, attrs))))
+            Ok(Some((typ, get_ret_mode(mode, attrs), is_mut.is_some())))
         }
     }
 }
@@ -196,7 +197,7 @@ pub(crate) fn check_item_fn<'tcx>(
             (Some(_), None) => {
                 return err_span_str(sig.span, "ensures clause cannot be a closure");
             }
-            (Some((_, typ)), Some((ret_typ, _))) => {
+            (Some((_, typ)), Some((ret_typ, _, _))) => {
                 if !vir::ast_util::types_equal(&typ, &ret_typ) {
                     return err_span_string(
                         sig.span,
@@ -210,17 +211,22 @@ pub(crate) fn check_item_fn<'tcx>(
         }
     }
     let params = Arc::new(vir_params);
-    let (ret_name, ret_typ, ret_mode) = match (header.ensure_id_typ, ret_typ_mode) {
-        (None, None) => {
-            (Arc::new(RETURN_VALUE.to_string()), Arc::new(TypX::Tuple(Arc::new(vec![]))), mode)
+    let (ret_name, ret_typ, ret_mode, ret_is_mut) = match (header.ensure_id_typ, ret_typ_mode) {
+        (None, None) => (
+            Arc::new(RETURN_VALUE.to_string()),
+            Arc::new(TypX::Tuple(Arc::new(vec![]))),
+            mode,
+            false,
+        ),
+        (None, Some((typ, mode, is_mut))) => {
+            (Arc::new(RETURN_VALUE.to_string()), typ, mode, is_mut)
         }
-        (None, Some((typ, mode))) => (Arc::new(RETURN_VALUE.to_string()), typ, mode),
-        (Some((x, _)), Some((typ, mode))) => (x, typ, mode),
+        (Some((x, _)), Some((typ, mode, is_mut))) => (x, typ, mode, is_mut),
         _ => panic!("internal error: ret_typ"),
     };
     let ret = spanned_new(
         sig.span,
-        ParamX { name: ret_name, typ: ret_typ, mode: ret_mode, is_mut: false },
+        ParamX { name: ret_name, typ: ret_typ, mode: ret_mode, is_mut: ret_is_mut },
     );
     let typ_bounds = {
         let mut typ_bounds: Vec<(vir::ast::Ident, vir::ast::GenericBound)> = Vec::new();
@@ -243,6 +249,8 @@ pub(crate) fn check_item_fn<'tcx>(
         bit_vector: vattrs.bit_vector,
         autoview: vattrs.autoview,
         atomic: vattrs.atomic,
+        rlimit: vattrs.rlimit,
+        smt_options: vattrs.smt_options,
     };
     let func = FunctionX {
         name: name.clone(),
@@ -357,15 +365,15 @@ pub(crate) fn check_foreign_item_fn<'tcx>(
     let path = def_id_to_vir_path(ctxt.tcx, id);
     let name = Arc::new(FunX { path, trait_path: None });
     let params = Arc::new(vir_params);
-    let (ret_typ, ret_mode) = match ret_typ_mode {
-        None => (Arc::new(TypX::Tuple(Arc::new(vec![]))), mode),
-        Some((typ, mode)) => (typ, mode),
+    let (ret_typ, ret_mode, ret_is_mut) = match ret_typ_mode {
+        None => (Arc::new(TypX::Tuple(Arc::new(vec![]))), mode, false),
+        Some((typ, mode, is_mut)) => (typ, mode, is_mut),
     };
     let ret_param = ParamX {
         name: Arc::new(RETURN_VALUE.to_string()),
         typ: ret_typ,
         mode: ret_mode,
-        is_mut: false,
+        is_mut: ret_is_mut,
     };
     let ret = spanned_new(span, ret_param);
     let func = FunctionX {
@@ -1,27 +1,34 @@
-use crate::config::Args;
+use crate::cache::{Fingerprint, VerificationCache};
+use crate::config::{rlimit_to_smt, Args};
 use crate::context::{ContextX, ErasureInfo};
+use crate::counterexample::Counterexample;
 use crate::debugger::Debugger;
+use crate::driver::Stats;
+use crate::json::Json;
 use crate::unsupported;
 use crate::util::{from_raw_span, signalling};
 use air::ast::{Command, CommandX, Commands};
-use air::context::ValidityResult;
+use air::context::{QueryStats, ValidityResult};
 use air::errors::{Error, ErrorLabel};
+use air::model::Model;
 use rustc_hir::OwnerNode;
 use rustc_interface::interface::Compiler;
 
 use rustc_middle::ty::TyCtxt;
 use rustc_span::source_map::SourceMap;
 use rustc_span::{CharPos, FileName, MultiSpan, Span};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs::File;
 use std::io::Write;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use vir::ast::{Fun, Function, Krate, VirErr, Visibility};
-use vir::ast_util::{fun_as_rust_dbg, is_visible_to};
+use vir::ast::{Fun, Function, FunctionAttrs, Krate, Mode, Path, VirErr, Visibility};
+use vir::ast_util::{err_string, fun_as_rust_dbg, is_visible_to};
 use vir::def::SnapPos;
+use vir::model_to_rust::ModelValue;
 use vir::recursion::Node;
+use vir::sst::SourceVar;
 
 pub struct VerifierCallbacks {
     pub verifier: Arc<Mutex<Verifier>>,
@@ -33,6 +40,8 @@ pub struct Verifier {
     pub encountered_vir_error: bool,
     pub count_verified: u64,
     pub count_errors: u64,
+    pub count_cached_functions: u64,
+    pub count_checked_functions: u64,
     pub errors: Vec<Vec<ErrorSpan>>,
     pub args: Args,
     pub test_capture_output: Option<std::sync::Arc<std::sync::Mutex<Vec<u8>>>>,
@@ -46,6 +55,78 @@ pub struct Verifier {
 
     pub vir_crate: Option<Krate>,
     pub air_no_span: Option<air::ast::Span>,
+    pub cache: Option<VerificationCache>,
+    pub function_reports: Vec<FunctionReport>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FunctionOutcome {
+    Verified,
+    Failed,
+    TimedOut,
+    Skipped,
+}
+
+impl FunctionOutcome {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            FunctionOutcome::Verified => "verified",
+            FunctionOutcome::Failed => "failed",
+            FunctionOutcome::TimedOut => "timed-out",
+            FunctionOutcome::Skipped => "skipped",
+        }
+    }
+}
+
+/// The result of verifying one function's body (or termination, for spec functions)
+pub struct FunctionReport {
+    pub function: Fun,
+    pub outcome: FunctionOutcome,
+    /// True if the result came from the verification cache rather than from the SMT solver
+    pub cached: bool,
+    pub errors: Vec<Error>,
+    /// Counterexamples for the errors, for --report-counterexamples
+    pub counterexamples: Vec<Counterexample>,
+    /// Number of check-valid queries issued for the function
+    pub num_queries: u64,
+    /// Wall-clock time spent on the function's queries
+    pub time: Duration,
+    /// Part of time spent waiting for the SMT solver
+    pub time_smt: Duration,
+    pub rlimit_count: u64,
+}
+
+impl FunctionReport {
+    fn new(function: &Fun, outcome: FunctionOutcome) -> Self {
+        FunctionReport {
+            function: function.clone(),
+            outcome,
+            cached: false,
+            errors: Vec::new(),
+            counterexamples: Vec::new(),
+            num_queries: 0,
+            time: Duration::new(0, 0),
+            time_smt: Duration::new(0, 0),
+            rlimit_count: 0,
+        }
+    }
+
+    fn add_query(&mut self, result: &QueryResult, errors: &Vec<Error>) {
+        if let QueryStatus::Invalid = result.status {
+            if result.stats.resource_exhausted && self.outcome != FunctionOutcome::Failed {
+                self.outcome = FunctionOutcome::TimedOut;
+            } else {
+                self.outcome = FunctionOutcome::Failed;
+            }
+        }
+        if let QueryStatus::Valid | QueryStatus::Invalid = result.status {
+            self.num_queries += 1;
+        }
+        self.errors.extend(errors.iter().cloned());
+        self.time += result.time;
+        self.time_smt += result.stats.time;
+        self.rlimit_count += result.stats.rlimit_count;
+    }
 }
 
 #[derive(Debug)]
@@ -105,6 +186,79 @@ fn report_error(compiler: &Compiler, error: &Error) {
     compiler.session().parse_sess.span_diagnostic.span_err(multispan, &error.msg);
 }
 
+/// Label error with the values of the function's parameters and locals (source_vars)
+/// at the failed assertion, each at the variable's declaration.
+/// Variables that are declared after the failed assertion, or whose values cannot be rendered,
+/// are omitted.
+fn error_with_counterexample(
+    ctx: &vir::context::Ctx,
+    air_context: &mut air::context::Context,
+    air_model: &Model,
+    source_vars: &Vec<SourceVar>,
+    error: &Error,
+) -> Error {
+    let error_span: Span = match error.spans.first() {
+        None => return error.clone(),
+        Some(sp) => from_raw_span(&sp.raw_span),
+    };
+    let values =
+        vir::model_to_rust::failed_assertion_values(ctx, air_context, air_model, source_vars);
+    let mut labels: Vec<ErrorLabel> = Vec::new();
+    for ModelValue { name, span, value } in values {
+        if let Some(value) = value {
+            if from_raw_span(&span.raw_span).lo() <= error_span.hi() {
+                labels.push(ErrorLabel { msg: format!("{} = {}", name, value), span });
+            }
+        }
+    }
+    error.append_labels(&labels)
+}
+
+fn error_spans(compiler: &Compiler, error: &Error) -> Vec<ErrorSpan> {
+    let mut errors = vec![ErrorSpan::new_from_air_span(
+        compiler.session().source_map(),
+        &error.msg,
+        &error.spans[0],
+    )];
+    for ErrorLabel { msg, span } in &error.labels {
+        errors.push(ErrorSpan::new_from_air_span(compiler.session().source_map(), msg, span));
+    }
+    errors
+}
+
+fn span_to_json(source_map: &SourceMap, span: &air::ast::Span, label: Option<&String>) -> Json {
+    let ErrorSpan { span_data: (file, (line_start, col_start), (line_end, col_end)), .. } =
+        ErrorSpan::new_from_air_span(source_map, &"".to_string(), span);
+    Json::object(vec![
+        ("file", Json::String(file)),
+        ("line_start", Json::Int(line_start as i128)),
+        ("column_start", Json::Int(col_start.0 as i128 + 1)),
+        ("line_end", Json::Int(line_end as i128)),
+        ("column_end", Json::Int(col_end.0 as i128 + 1)),
+        ("label", label.map(|l| Json::str(l)).unwrap_or(Json::Null)),
+    ])
+}
+
+fn error_to_json(source_map: &SourceMap, error: &Error) -> Json {
+    Json::object(vec![
+        ("message", Json::str(&error.msg)),
+        (
+            "spans",
+            Json::Array(error.spans.iter().map(|s| span_to_json(source_map, s, None)).collect()),
+        ),
+        (
+            "labels",
+            Json::Array(
+                error
+                    .labels
+                    .iter()
+                    .map(|ErrorLabel { msg, span }| span_to_json(source_map, span, Some(msg)))
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
 fn report_chosen_triggers(
     compiler: &Compiler,
     air_span: &air::ast::Span,
@@ -115,12 +269,265 @@ fn report_chosen_triggers(
     compiler.session().parse_sess.span_diagnostic.span_note_without_error(span, &msg);
 }
 
+#[derive(Clone, Copy, Debug)]
+enum QueryStatus {
+    NotQuery,
+    Valid,
+    Invalid,
+}
+
+/// The status of a command run by check_query, along with the solver statistics
+/// accumulated over all the check-sat calls made for the command
+#[derive(Clone, Copy, Debug)]
+struct QueryResult {
+    status: QueryStatus,
+    stats: QueryStats,
+    /// Wall-clock time for the command, including AIR processing
+    time: Duration,
+}
+
+/// The results of running one function's Function-Def commands on a worker thread
+type WorkerResults = Vec<(QueryResult, Vec<Error>)>;
+
+/// Create an AIR context configured according to the command-line arguments.
+/// log_suffix is appended to the names of any log files.
+fn new_air_context(args: &Args, log_suffix: &str) -> air::context::Context {
+    let mut air_context = air::context::Context::new(air::smt_manager::SmtManager::new());
+    air_context.set_ignore_unexpected_smt(args.ignore_unexpected_smt);
+    air_context.set_debug(args.debug);
+
+    if let Some(filename) = &args.log_air_initial {
+        let filename = filename.to_string() + log_suffix;
+        let file = File::create(&filename).expect(&format!("could not open file {}", filename));
+        air_context.set_air_initial_log(Box::new(file));
+    }
+    if let Some(filename) = &args.log_air_final {
+        let filename = filename.to_string() + log_suffix;
+        let file = File::create(&filename).expect(&format!("could not open file {}", filename));
+        air_context.set_air_final_log(Box::new(file));
+    }
+    if let Some(filename) = &args.log_smt {
+        let filename = filename.to_string() + log_suffix;
+        let file = File::create(&filename).expect(&format!("could not open file {}", filename));
+        air_context.set_smt_log(Box::new(file));
+    }
+
+    // air_recommended_options causes AIR to apply a preset collection of Z3 options
+    air_context.set_z3_param("air_recommended_options", "true");
+    air_context.set_rlimit(rlimit_to_smt(args.rlimit).expect("rlimit checked by parse_args"));
+    for (option, value) in args.smt_options.iter() {
+        air_context.set_z3_param(&option, &value);
+    }
+    air_context
+}
+
+/// Module name in a form suitable for use in file names
+fn module_name_for_files(module: &Path) -> String {
+    if module.segments.len() > 0 {
+        module.segments.iter().map(|s| s.to_string()).collect::<Vec<_>>().join("__")
+    } else {
+        "root".to_string()
+    }
+}
+
+/// Does the function match the name given to --verify-function?
+/// The name may be any suffix of the function's path, so that 'Type::method' matches
+/// 'module::Type::method'.
+fn fun_matches_name(fun: &Fun, name: &str) -> bool {
+    let name = name.strip_prefix("crate::").unwrap_or(name);
+    let name_segments: Vec<&str> = name.split("::").collect();
+    let segments = &fun.path.segments;
+    name_segments.len() <= segments.len()
+        && segments[segments.len() - name_segments.len()..]
+            .iter()
+            .zip(name_segments.iter())
+            .all(|(segment, name_segment)| segment.as_str() == *name_segment)
+}
+
+/// SMT settings to restore after running a function's queries
+struct SavedSmtOptions {
+    rlimit: u32,
+    options: Vec<(String, String)>,
+}
+
+/// Check that the SMT solver recognizes each of the function's #[verifier(smt_option(...))]
+/// options, so that push_function_smt_options can save the option's current value
+/// and pop_function_smt_options can restore it.
+/// (The solver has no way to unset an option, so without the current value,
+/// the function's setting would remain in effect for later functions.)
+fn check_function_smt_options(
+    air_context: &mut air::context::Context,
+    function: &Function,
+) -> Result<(), VirErr> {
+    for (option, _) in function.x.attrs.smt_options.iter() {
+        if air_context.get_z3_param(option).is_none() {
+            return err_string(
+                &function.span,
+                format!("smt_option: the SMT solver does not recognize option {}", option),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Apply a function's #[verifier(rlimit(...))] and #[verifier(smt_option(...))] attributes
+/// in a new scope for the function's queries.
+/// SMT solver options are not restored by pop, so we save the previous values,
+/// to be restored by pop_function_smt_options.
+/// The options must have been checked by check_function_smt_options.
+fn push_function_smt_options(
+    air_context: &mut air::context::Context,
+    attrs: &FunctionAttrs,
+) -> Option<SavedSmtOptions> {
+    if attrs.rlimit.is_none() && attrs.smt_options.len() == 0 {
+        return None;
+    }
+    air_context.push();
+    let rlimit = air_context.get_rlimit();
+    if let Some(rlimit) = attrs.rlimit {
+        air_context.set_rlimit(rlimit_to_smt(rlimit).expect("rlimit checked by attributes"));
+    }
+    let mut options: Vec<(String, String)> = Vec::new();
+    for (option, value) in attrs.smt_options.iter() {
+        let prev_value = air_context
+            .get_z3_param(option)
+            .expect("internal error: smt_option not checked by check_function_smt_options");
+        options.push((option.clone(), prev_value));
+        air_context.set_z3_param(option, value);
+    }
+    Some(SavedSmtOptions { rlimit, options })
+}
+
+fn pop_function_smt_options(
+    air_context: &mut air::context::Context,
+    saved: Option<SavedSmtOptions>,
+) {
+    if let Some(SavedSmtOptions { rlimit, options }) = saved {
+        for (option, value) in options.iter().rev() {
+            air_context.set_z3_param(option, value);
+        }
+        air_context.set_rlimit(rlimit);
+        air_context.pop();
+    }
+}
+
+/// Maximum number of quantifiers listed for each function by --profile-quantifiers
+const MAX_PROFILED_QUANTIFIERS: usize = 10;
+
+/// Create an AIR context for --profile-quantifiers
+fn new_profile_air_context(args: &Args, module: &Path) -> air::context::Context {
+    let log_suffix = format!("-{}-profile", module_name_for_files(module));
+    let mut air_context = new_air_context(args, &log_suffix);
+    air_context.set_profile_quantifiers(true);
+    air_context
+}
+
+/// Run a command that may be a query.
+/// If the query fails, call on_error for the first error and for each additional error found
+/// (up to multiple_errors additional queries).
+fn check_query(
+    air_context: &mut air::context::Context,
+    command: &Command,
+    multiple_errors: u32,
+    on_error: &mut dyn FnMut(&mut air::context::Context, Model, &Error),
+) -> QueryResult {
+    let time0 = Instant::now();
+    let is_check_valid = matches!(**command, CommandX::CheckValid(_));
+    let mut result = air_context.command(&command);
+    let mut status = if is_check_valid { QueryStatus::Valid } else { QueryStatus::NotQuery };
+    let mut stats = QueryStats::default();
+    if is_check_valid {
+        stats = air_context.get_last_query_stats();
+    }
+    let mut checks_remaining = multiple_errors;
+    let mut only_check_earlier = false;
+    loop {
+        match result {
+            ValidityResult::Valid => {
+                break;
+            }
+            ValidityResult::TypeError(err) => {
+                panic!("internal error: generated ill-typed AIR code: {}", err);
+            }
+            ValidityResult::Invalid(air_model, error) => {
+                status = QueryStatus::Invalid;
+                on_error(air_context, air_model, &error);
+
+                if multiple_errors == 0 {
+                    break;
+                }
+                if !only_check_earlier {
+                    checks_remaining -= 1;
+                    if checks_remaining == 0 {
+                        only_check_earlier = true;
+                    }
+                }
+
+                result = air_context.check_valid_again(only_check_earlier);
+                // Only the first check determines whether the query ran out of resources
+                let again_stats = air_context.get_last_query_stats();
+                stats.time += again_stats.time;
+                stats.rlimit_count += again_stats.rlimit_count;
+            }
+            ValidityResult::UnexpectedSmtOutput(err) => {
+                panic!("unexpected SMT output: {}", err);
+            }
+        }
+    }
+
+    if is_check_valid {
+        air_context.finish_query();
+    }
+    QueryResult { status, stats, time: time0.elapsed() }
+}
+
+/// Worker thread for --num-threads:
+/// replay the shared commands into a fresh AIR context (with its own SMT process),
+/// then run Function-Def queries from the job queue until the queue is empty.
+/// Returns the time spent in SMT.
+fn verify_worker(
+    args: Args,
+    log_suffix: String,
+    shared_commands: Arc<Vec<Command>>,
+    jobs: Arc<Mutex<VecDeque<(usize, FunctionAttrs, Commands)>>>,
+    results: std::sync::mpsc::Sender<(usize, WorkerResults)>,
+) -> (Duration, Duration) {
+    let mut air_context = new_air_context(&args, &log_suffix);
+    for command in shared_commands.iter() {
+        Verifier::check_internal_result(air_context.command(&command));
+    }
+    loop {
+        let job = jobs.lock().expect("verifier job queue mutex").pop_front();
+        let (index, attrs, commands) = match job {
+            None => break,
+            Some(job) => job,
+        };
+        let saved_options = push_function_smt_options(&mut air_context, &attrs);
+        let mut function_results: WorkerResults = Vec::new();
+        for command in commands.iter() {
+            let mut errors: Vec<Error> = Vec::new();
+            let result =
+                check_query(&mut air_context, command, args.multiple_errors, &mut |_, _, error| {
+                    errors.push(error.clone())
+                });
+            function_results.push((result, errors));
+        }
+        pop_function_smt_options(&mut air_context, saved_options);
+        results
+            .send((index, function_results))
+            .expect("internal error: failed to send verification results");
+    }
+    air_context.get_time()
+}
+
 impl Verifier {
     pub fn new(args: Args) -> Verifier {
         Verifier {
             encountered_vir_error: false,
             count_verified: 0,
             count_errors: 0,
+            count_cached_functions: 0,
+            count_checked_functions: 0,
             errors: Vec::new(),
             args,
             test_capture_output: None,
@@ -134,6 +541,8 @@ impl Verifier {
 
             vir_crate: None,
             air_no_span: None,
+            cache: None,
+            function_reports: Vec::new(),
         }
     }
 
@@ -150,84 +559,259 @@ impl Verifier {
         }
     }
 
+    /// Version of the --output-json schema; increment when making incompatible changes
+    const JSON_SCHEMA_VERSION: i128 = 1;
+
+    /// Report for --output-json, with the form:
+    /// { "version": 1, "verified": n, "errors": n, "functions": [
+    ///   { "function": name, "outcome": "verified" | "failed" | "timed-out" | "skipped",
+    ///     "cached": bool, "smt_time_ms": n, "rlimit_count": n,
+    ///     "errors": [ { "message": msg, "spans": [span], "labels": [span] } ],
+    ///     "counterexamples": [counterexample] } ] }
+    /// where each span is
+    ///   { "file": path, "line_start": n, "column_start": n, "line_end": n, "column_end": n,
+    ///     "label": msg or null }
+    /// and each counterexample (only present with --report-counterexamples) is
+    ///   { "message": msg, "file": path, "line": n,
+    ///     "lines": [ { "line": n, "values": [ { "name": x, "value": v } ] } ] }
+    /// Lines and columns are 1-based.
+    fn json_report(&self, source_map: &SourceMap) -> Json {
+        let functions = self
+            .function_reports
+            .iter()
+            .map(|report| {
+                Json::object(vec![
+                    ("function", Json::String(fun_as_rust_dbg(&report.function))),
+                    ("outcome", Json::str(report.outcome.to_str())),
+                    ("cached", Json::Bool(report.cached)),
+                    ("smt_time_ms", Json::Int(report.time_smt.as_millis() as i128)),
+                    ("rlimit_count", Json::Int(report.rlimit_count as i128)),
+                    (
+                        "errors",
+                        Json::Array(
+                            report.errors.iter().map(|e| error_to_json(source_map, e)).collect(),
+                        ),
+                    ),
+                    (
+                        "counterexamples",
+                        Json::Array(report.counterexamples.iter().map(|c| c.to_json()).collect()),
+                    ),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("version", Json::Int(Self::JSON_SCHEMA_VERSION)),
+            ("verified", Json::Int(self.count_verified as i128)),
+            ("errors", Json::Int(self.count_errors as i128)),
+            ("functions", Json::Array(functions)),
+        ])
+    }
+
+    /// Print lines of a report to stdout, or to the captured output when testing
+    fn print_report(&self, lines: &[String]) {
+        match &self.test_capture_output {
+            Some(output) => {
+                let mut output =
+                    output.lock().expect("internal error: cannot lock captured output");
+                for line in lines {
+                    writeln!(output, "{}", line).expect("internal error: cannot write output");
+                }
+            }
+            None => {
+                for line in lines {
+                    println!("{}", line);
+                }
+            }
+        }
+    }
+
+    /// Print the verification results, followed by the reports requested by --time and
+    /// --time-functions.
+    /// stats is None if compilation or verification failed.
+    pub fn print_summary(&self, stats: Option<&Stats>, total_time: Duration) {
+        let mut lines: Vec<String> = Vec::new();
+        if !self.encountered_vir_error {
+            lines.push(format!(
+                "Verification results:: verified: {} errors: {}",
+                self.count_verified, self.count_errors
+            ));
+            if self.cache.is_some() {
+                lines.push(format!(
+                    "Verification cache:: cached: {} checked: {}",
+                    self.count_cached_functions, self.count_checked_functions
+                ));
+            }
+        }
+        if let (true, Some(stats)) = (self.args.time, stats) {
+            let verify = stats.time_verify;
+            let mut vir = self.time_vir;
+            let vir_rust_to_vir = self.time_vir_rust_to_vir;
+            let mut vir_verify = self.time_vir_verify;
+            let mut air = self.time_air;
+            let smt_init = self.time_smt_init;
+            let smt_run = self.time_smt_run;
+            let lifetime = stats.time_lifetime;
+            let compile = stats.time_compile;
+            let erasure = stats.time_erasure;
+            let rust_init = verify.saturating_sub(vir);
+            let rust = rust_init + lifetime + compile;
+            // With --num-threads, the SMT time is summed over the worker threads,
+            // so it can exceed the AIR time measured on the main thread
+            vir_verify = vir_verify.saturating_sub(air);
+            vir = vir.saturating_sub(air);
+            air = air.saturating_sub(smt_init + smt_run);
+            lines.push(format!("total-time:      {:>10} ms", total_time.as_millis()));
+            lines.push(format!("    rust-time:       {:>10} ms", rust.as_millis()));
+            lines.push(format!("        init-and-types:  {:>10} ms", rust_init.as_millis()));
+            lines.push(format!("        lifetime-time:   {:>10} ms", lifetime.as_millis()));
+            lines.push(format!("        compile-time:    {:>10} ms", compile.as_millis()));
+            lines.push(format!("    vir-time:        {:>10} ms", vir.as_millis()));
+            lines.push(format!("        rust-to-vir:     {:>10} ms", vir_rust_to_vir.as_millis()));
+            lines.push(format!("        verify:          {:>10} ms", vir_verify.as_millis()));
+            lines.push(format!("        erase:           {:>10} ms", erasure.as_millis()));
+            lines.push(format!("    air-time:        {:>10} ms", air.as_millis()));
+            lines.push(format!("    smt-time:        {:>10} ms", (smt_init + smt_run).as_millis()));
+            lines.push(format!("        smt-init:        {:>10} ms", smt_init.as_millis()));
+            lines.push(format!("        smt-run:         {:>10} ms", smt_run.as_millis()));
+        }
+        // The per-function times are reported even if verification failed
+        if self.args.time_functions {
+            lines.extend(self.function_times(20));
+        }
+        self.print_report(&lines);
+    }
+
+    /// Functions that issued queries, slowest first
+    fn function_reports_by_time(&self) -> Vec<&FunctionReport> {
+        let mut reports: Vec<&FunctionReport> =
+            self.function_reports.iter().filter(|r| r.num_queries > 0).collect();
+        reports.sort_by(|r1, r2| r2.time.cmp(&r1.time));
+        reports
+    }
+
+    /// Table of the slowest functions, for --time-functions
+    fn function_times(&self, max_rows: usize) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{:>10} {:>10} {:>14} {:>7} {:>10}  {}",
+            "time-ms", "smt-ms", "rlimit-count", "queries", "outcome", "function"
+        )];
+        for report in self.function_reports_by_time().iter().take(max_rows) {
+            lines.push(format!(
+                "{:>10} {:>10} {:>14} {:>7} {:>10}  {}",
+                report.time.as_millis(),
+                report.time_smt.as_millis(),
+                report.rlimit_count,
+                report.num_queries,
+                report.outcome.to_str(),
+                fun_as_rust_dbg(&report.function),
+            ));
+        }
+        lines
+    }
+
+    fn write_function_times_csv(&self, w: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(w, "function,outcome,queries,time_ms,smt_time_ms,rlimit_count")?;
+        for report in self.function_reports_by_time() {
+            writeln!(
+                w,
+                "\"{}\",{},{},{},{},{}",
+                fun_as_rust_dbg(&report.function),
+                report.outcome.to_str(),
+                report.num_queries,
+                report.time.as_millis(),
+                report.time_smt.as_millis(),
+                report.rlimit_count
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Should we issue queries for this function's body and termination?
+    fn is_selected_function(&self, f: &Fun) -> bool {
+        match &self.args.verify_function {
+            None => true,
+            Some(name) => fun_matches_name(f, name),
+        }
+    }
+
+    /// Does the module own any function selected by --verify-function?
+    /// (Always true if --verify-function is not set.)
+    fn module_has_selected_function(&self, krate: &Krate, module: &Path) -> bool {
+        self.args.verify_function.is_none()
+            || krate.functions.iter().any(|function| {
+                function.x.visibility.owning_module.as_ref() == Some(module)
+                    && self.is_selected_function(&function.x.name)
+            })
+    }
+
+    fn record_query_status(&mut self, status: QueryStatus) {
+        match status {
+            QueryStatus::NotQuery => {}
+            QueryStatus::Valid => self.count_verified += 1,
+            QueryStatus::Invalid => self.count_errors += 1,
+        }
+    }
+
     /// Check the result of a query that was based on user input.
     /// Success/failure will (eventually) be communicated back to the user.
     fn check_result_validity(
         &mut self,
         compiler: &Compiler,
+        ctx: &vir::context::Ctx,
         air_context: &mut air::context::Context,
+        function: &Function,
         assign_map: &HashMap<*const air::ast::Span, HashSet<Arc<std::string::String>>>,
         snap_map: &Vec<(air::ast::Span, SnapPos)>,
+        source_vars: &Vec<SourceVar>,
         command: &Command,
-    ) {
-        let is_check_valid = matches!(**command, CommandX::CheckValid(_));
-        let mut result = air_context.command(&command);
-        let mut is_first_check = true;
-        let mut checks_remaining = self.args.multiple_errors;
-        let mut only_check_earlier = false;
-        loop {
-            match result {
-                ValidityResult::Valid => {
-                    if is_check_valid && is_first_check {
-                        self.count_verified += 1;
-                    }
-                    break;
-                }
-                ValidityResult::TypeError(err) => {
-                    panic!("internal error: generated ill-typed AIR code: {}", err);
-                }
-                ValidityResult::Invalid(air_model, error) => {
-                    if is_first_check {
-                        self.count_errors += 1;
-                    }
-                    report_error(compiler, &error);
-
-                    let mut errors = vec![ErrorSpan::new_from_air_span(
+    ) -> (QueryResult, Vec<Error>, Vec<Counterexample>) {
+        let debug = self.args.debug;
+        let report_counterexamples = self.args.report_counterexamples;
+        let mut errors: Vec<Error> = Vec::new();
+        let mut counterexamples: Vec<Counterexample> = Vec::new();
+        let result = check_query(
+            air_context,
+            command,
+            self.args.multiple_errors,
+            &mut |air_context, air_model, error| {
+                let error =
+                    error_with_counterexample(ctx, air_context, &air_model, source_vars, error);
+                report_error(compiler, &error);
+                if report_counterexamples {
+                    let counterexample = Counterexample::new(
                         compiler.session().source_map(),
-                        &error.msg,
-                        &error.spans[0],
-                    )];
-                    for ErrorLabel { msg, span } in &error.labels {
-                        errors.push(ErrorSpan::new_from_air_span(
-                            compiler.session().source_map(),
-                            msg,
-                            span,
-                        ));
-                    }
-
-                    self.errors.push(errors);
-                    if self.args.debug {
-                        let mut debugger = Debugger::new(
-                            air_model,
-                            assign_map,
-                            snap_map,
-                            compiler.session().source_map(),
-                        );
-                        debugger.start_shell(air_context);
-                    }
-
-                    if self.args.multiple_errors == 0 {
-                        break;
+                        ctx,
+                        air_context,
+                        &air_model,
+                        function,
+                        snap_map,
+                        source_vars,
+                        &error,
+                    );
+                    if let Some(counterexample) = counterexample {
+                        counterexamples.push(counterexample);
                     }
-                    is_first_check = false;
-                    if !only_check_earlier {
-                        checks_remaining -= 1;
-                        if checks_remaining == 0 {
-                            only_check_earlier = true;
-                        }
-                    }
-
-                    result = air_context.check_valid_again(only_check_earlier);
                 }
-                ValidityResult::UnexpectedSmtOutput(err) => {
-                    panic!("unexpected SMT output: {}", err);
+                errors.push(error);
+                if debug {
+                    let mut debugger = Debugger::new(
+                        air_model,
+                        assign_map,
+                        snap_map,
+                        compiler.session().source_map(),
+                    );
+                    debugger.start_shell(air_context);
                 }
-            }
+            },
+        );
+        for error in errors.iter() {
+            self.errors.push(error_spans(compiler, error));
         }
-
-        if is_check_valid {
-            air_context.finish_query();
+        for counterexample in counterexamples.iter() {
+            self.print_report(&counterexample.report_lines());
         }
+        self.record_query_status(result.status);
+        (result, errors, counterexamples)
     }
 
     fn run_commands(
@@ -248,24 +832,193 @@ impl Verifier {
         }
     }
 
+    /// Run the queries for function, recording a FunctionReport if there are any queries.
     fn run_commands_queries(
         &mut self,
         compiler: &Compiler,
+        ctx: &vir::context::Ctx,
         air_context: &mut air::context::Context,
+        function: &Function,
         commands: &Vec<Command>,
         assign_map: &HashMap<*const air::ast::Span, HashSet<Arc<String>>>,
         snap_map: &Vec<(air::ast::Span, SnapPos)>,
+        source_vars: &Vec<SourceVar>,
         comment: &str,
-    ) {
-        if commands.len() > 0 {
-            air_context.blank_line();
-            air_context.comment(comment);
+    ) -> FunctionOutcome {
+        if commands.len() == 0 {
+            return FunctionOutcome::Verified;
         }
+        air_context.blank_line();
+        air_context.comment(comment);
+        let saved_options = push_function_smt_options(air_context, &function.x.attrs);
+        let mut report = FunctionReport::new(&function.x.name, FunctionOutcome::Verified);
         for command in commands.iter() {
             let time0 = Instant::now();
-            self.check_result_validity(compiler, air_context, assign_map, snap_map, &command);
+            let (result, errors, counterexamples) = self.check_result_validity(
+                compiler,
+                ctx,
+                air_context,
+                function,
+                assign_map,
+                snap_map,
+                source_vars,
+                &command,
+            );
             let time1 = Instant::now();
             self.time_air += time1 - time0;
+            report.add_query(&result, &errors);
+            report.counterexamples.extend(counterexamples);
+        }
+        pop_function_smt_options(air_context, saved_options);
+        let outcome = report.outcome;
+        self.function_reports.push(report);
+        outcome
+    }
+
+    /// Run function's queries for --profile-quantifiers.
+    /// The queries run in a fresh scope of profile_context into which we replay shared_commands
+    /// (the prelude and the module's declarations and axioms),
+    /// so that the instantiation counts that the SMT solver reports when the scope is popped
+    /// cover exactly the work done for function's queries.
+    fn run_commands_queries_profiled(
+        &mut self,
+        compiler: &Compiler,
+        profile_context: &mut air::context::Context,
+        ctx: &vir::context::Ctx,
+        shared_commands: &Vec<Command>,
+        function: &Function,
+        commands: &Vec<Command>,
+        snap_map: &Vec<(air::ast::Span, SnapPos)>,
+        source_vars: &Vec<SourceVar>,
+        comment: &str,
+    ) -> FunctionOutcome {
+        profile_context.push();
+        self.run_commands(profile_context, shared_commands, "Module context for profiling");
+        let outcome = self.run_commands_queries(
+            compiler,
+            ctx,
+            profile_context,
+            function,
+            commands,
+            &HashMap::new(),
+            snap_map,
+            source_vars,
+            comment,
+        );
+        profile_context.pop();
+        let profile = profile_context.take_quantifier_profile();
+        self.report_quantifier_profile(compiler, &ctx.global, &function.x.name, profile);
+        outcome
+    }
+
+    /// Print the most frequently instantiated quantifiers for function f.
+    /// Quantifiers written by the user are also reported as notes at their source location.
+    fn report_quantifier_profile(
+        &self,
+        compiler: &Compiler,
+        global_ctx: &vir::context::GlobalCtx,
+        f: &Fun,
+        profile: Vec<(String, u64)>,
+    ) {
+        // The same quantifier may be reported more than once (e.g. once per scope)
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for (qid, count) in profile {
+            *counts.entry(qid).or_insert(0) += count;
+        }
+        let mut counts: Vec<(String, u64)> = counts.into_iter().filter(|(_, n)| *n > 0).collect();
+        if counts.len() == 0 {
+            return;
+        }
+        counts.sort_by(|(q1, n1), (q2, n2)| n2.cmp(n1).then(q1.cmp(q2)));
+        let source_map = compiler.session().source_map();
+        let diagnostic = &compiler.session().parse_sess.span_diagnostic;
+        let f_name = fun_as_rust_dbg(f);
+        let mut lines = vec![format!("Quantifier instantiations while verifying {}:", f_name)];
+        for (qid, count) in counts.iter().take(MAX_PROFILED_QUANTIFIERS) {
+            match global_ctx.get_qid_span(qid) {
+                Some(air_span) if vir::def::is_user_qid(qid) => {
+                    let ErrorSpan { span_data: (file, (line, col), _), .. } =
+                        ErrorSpan::new_from_air_span(source_map, &"".to_string(), &air_span);
+                    lines.push(format!("{:>10}  {}:{}:{}", count, file, line, col.0 + 1));
+                    let span: Span = from_raw_span(&air_span.raw_span);
+                    let msg = format!(
+                        "quantifier instantiated {} times while verifying {}",
+                        count, f_name
+                    );
+                    diagnostic.span_note_without_error(span, &msg);
+                }
+                _ => lines.push(format!("{:>10}  {}", count, qid)),
+            }
+        }
+        self.print_report(&lines);
+    }
+
+    /// Run the Function-Def queries for a module on a pool of worker threads.
+    /// Each worker owns a separate AIR context and SMT process,
+    /// into which it first replays shared_commands (the prelude and the module's declarations
+    /// and axioms).
+    /// Results are reported in the order of queries, regardless of which worker finishes first.
+    fn run_queries_parallel(
+        &mut self,
+        compiler: &Compiler,
+        module: &Path,
+        shared_commands: Vec<Command>,
+        queries: Vec<(Function, Commands, Option<Fingerprint>)>,
+    ) {
+        let num_workers = std::cmp::min(self.args.num_threads as usize, queries.len());
+        let shared_commands = Arc::new(shared_commands);
+        let jobs: VecDeque<(usize, FunctionAttrs, Commands)> = queries
+            .iter()
+            .enumerate()
+            .map(|(i, (function, commands, _))| (i, function.x.attrs.clone(), commands.clone()))
+            .collect();
+        let jobs = Arc::new(Mutex::new(jobs));
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut workers = Vec::new();
+        for worker_id in 0..num_workers {
+            let args = self.args.clone();
+            let log_suffix = format!("-{}-worker{}", module_name_for_files(module), worker_id);
+            let shared_commands = shared_commands.clone();
+            let jobs = jobs.clone();
+            let sender = sender.clone();
+            workers.push(std::thread::spawn(move || {
+                verify_worker(args, log_suffix, shared_commands, jobs, sender)
+            }));
+        }
+        drop(sender);
+
+        let mut results: Vec<Option<WorkerResults>> = queries.iter().map(|_| None).collect();
+        for (index, function_results) in receiver.iter() {
+            results[index] = Some(function_results);
+        }
+        for worker in workers {
+            let (time_smt_init, time_smt_run) =
+                worker.join().expect("internal error: verification worker thread panicked");
+            self.time_smt_init += time_smt_init;
+            self.time_smt_run += time_smt_run;
+        }
+
+        for ((function, _, fingerprint), function_results) in
+            queries.iter().zip(results.into_iter())
+        {
+            let f = &function.x.name;
+            let function_results = function_results
+                .expect(&format!("internal error: missing results for {}", fun_as_rust_dbg(f)));
+            let mut report = FunctionReport::new(f, FunctionOutcome::Verified);
+            for (result, errors) in function_results {
+                for error in errors.iter() {
+                    report_error(compiler, error);
+                    self.errors.push(error_spans(compiler, error));
+                }
+                self.record_query_status(result.status);
+                report.add_query(&result, &errors);
+            }
+            if let (FunctionOutcome::Verified, Some(cache), Some(fingerprint)) =
+                (report.outcome, &self.cache, fingerprint)
+            {
+                cache.record_verified(fingerprint);
+            }
+            self.function_reports.push(report);
         }
     }
 
@@ -278,11 +1031,27 @@ impl Verifier {
         ctx: &mut vir::context::Ctx,
     ) -> Result<(), VirErr> {
         let module = &ctx.module();
+        let parallel = self.args.num_threads > 1;
+        let profile = self.args.profile_quantifiers;
+        // Commands that establish the module's context for the Function-Def queries.
+        // These are replayed into each worker's AIR context when verifying in parallel
+        // and before each function's queries when profiling quantifiers,
+        // and are part of each query's fingerprint for the verification cache.
+        let record_shared = parallel || profile || self.cache.is_some();
+        let mut shared_commands: Vec<Command> = Vec::new();
+        if record_shared {
+            shared_commands.extend(vir::context::Ctx::prelude().iter().cloned());
+        }
+
         air_context.blank_line();
         air_context.comment("Fuel");
-        for command in ctx.fuel().iter() {
+        let fuel_commands = ctx.fuel();
+        for command in fuel_commands.iter() {
             Self::check_internal_result(air_context.command(&command));
         }
+        if record_shared {
+            shared_commands.extend(fuel_commands.iter().cloned());
+        }
 
         let datatype_commands = vir::datatype_to_air::datatypes_to_air(
             ctx,
@@ -294,6 +1063,15 @@ impl Verifier {
                 .collect(),
         );
         self.run_commands(air_context, &datatype_commands, &("Datatypes".to_string()));
+        if record_shared {
+            shared_commands.extend(datatype_commands.iter().cloned());
+        }
+
+        let trait_commands = vir::traits::traits_to_air(ctx, krate);
+        self.run_commands(air_context, &trait_commands, &("Traits".to_string()));
+        if record_shared {
+            shared_commands.extend(trait_commands.iter().cloned());
+        }
 
         // Declare the function symbols
         for function in &krate.functions {
@@ -306,6 +1084,9 @@ impl Verifier {
                 &commands,
                 &("Function-Decl ".to_string() + &fun_as_rust_dbg(&function.x.name)),
             );
+            if record_shared {
+                shared_commands.extend(commands.iter().cloned());
+            }
         }
 
         // Collect function definitions
@@ -348,9 +1129,17 @@ impl Verifier {
                 if Some(module.clone()) != function.x.visibility.owning_module {
                     continue;
                 }
+                if !self.is_selected_function(f) {
+                    continue;
+                }
+                if check_commands.len() > 0 {
+                    check_function_smt_options(air_context, function)?;
+                }
                 self.run_commands_queries(
                     compiler,
+                    ctx,
                     air_context,
+                    function,
                     &check_commands,
                     &HashMap::new(),
                     &vec![],
@@ -369,25 +1158,101 @@ impl Verifier {
                     &decl_commands,
                     &("Function-Axioms ".to_string() + &fun_as_rust_dbg(f)),
                 );
+                if record_shared {
+                    shared_commands.extend(decl_commands.iter().cloned());
+                }
                 fun_decls.remove(f);
             }
         }
         assert!(fun_decls.len() == 0);
 
         // Create queries to check the validity of proof/exec function bodies
+        let context_hasher =
+            self.cache.as_ref().map(|cache| cache.context_hasher(&self.args, &shared_commands));
+        let mut parallel_queries: Vec<(Function, Commands, Option<Fingerprint>)> = Vec::new();
+        let mut profile_context: Option<air::context::Context> = None;
         for function in &krate.functions {
             if Some(module.clone()) != function.x.visibility.owning_module {
                 continue;
             }
-            let (commands, snap_map) = vir::func_to_air::func_def_to_air(ctx, &function)?;
-            self.run_commands_queries(
-                compiler,
-                air_context,
-                &commands,
-                &HashMap::new(),
-                &snap_map,
-                &("Function-Def ".to_string() + &fun_as_rust_dbg(&function.x.name)),
-            );
+            if !self.is_selected_function(&function.x.name) {
+                if function.x.mode != Mode::Spec && function.x.body.is_some() {
+                    let report = FunctionReport::new(&function.x.name, FunctionOutcome::Skipped);
+                    self.function_reports.push(report);
+                }
+                continue;
+            }
+            let (commands, snap_map, source_vars) =
+                vir::func_to_air::func_def_to_air(ctx, &function)?;
+            if commands.len() == 0 {
+                continue;
+            }
+            check_function_smt_options(air_context, function)?;
+            let fingerprint = context_hasher
+                .as_ref()
+                .map(|hasher| VerificationCache::fingerprint(hasher, &function.x.attrs, &commands));
+            if let (Some(cache), Some(fingerprint)) = (&self.cache, &fingerprint) {
+                if cache.is_verified(fingerprint) {
+                    // Skip the SMT queries, but count them as if they had been run
+                    self.count_cached_functions += 1;
+                    let mut report =
+                        FunctionReport::new(&function.x.name, FunctionOutcome::Verified);
+                    report.cached = true;
+                    self.function_reports.push(report);
+                    for command in commands.iter() {
+                        if let CommandX::CheckValid(_) = &**command {
+                            self.count_verified += 1;
+                        }
+                    }
+                    continue;
+                }
+                self.count_checked_functions += 1;
+            }
+            if parallel {
+                parallel_queries.push((function.clone(), commands, fingerprint));
+                continue;
+            }
+            let comment = "Function-Def ".to_string() + &fun_as_rust_dbg(&function.x.name);
+            let outcome = if profile {
+                let profile_context = profile_context
+                    .get_or_insert_with(|| new_profile_air_context(&self.args, module));
+                self.run_commands_queries_profiled(
+                    compiler,
+                    profile_context,
+                    ctx,
+                    &shared_commands,
+                    function,
+                    &commands,
+                    &snap_map,
+                    &source_vars,
+                    &comment,
+                )
+            } else {
+                self.run_commands_queries(
+                    compiler,
+                    ctx,
+                    air_context,
+                    function,
+                    &commands,
+                    &HashMap::new(),
+                    &snap_map,
+                    &source_vars,
+                    &comment,
+                )
+            };
+            if let (FunctionOutcome::Verified, Some(cache), Some(fingerprint)) =
+                (outcome, &self.cache, &fingerprint)
+            {
+                cache.record_verified(fingerprint);
+            }
+        }
+        if parallel_queries.len() > 0 {
+            self.run_queries_parallel(compiler, module, shared_commands, parallel_queries);
+        }
+        if let Some(profile_context) = profile_context {
+            let (time_smt_init, time_smt_run) = profile_context.get_time();
+            self.time_smt_init += time_smt_init;
+            self.time_smt_run += time_smt_run;
         }
 
         Ok(())
@@ -401,28 +1266,11 @@ impl Verifier {
         #[cfg(debug_assertions)]
         vir::check_ast_flavor::check_krate(&krate);
 
-        let mut air_context = air::context::Context::new(air::smt_manager::SmtManager::new());
-        air_context.set_ignore_unexpected_smt(self.args.ignore_unexpected_smt);
-        air_context.set_debug(self.args.debug);
-
-        if let Some(filename) = &self.args.log_air_initial {
-            let file = File::create(filename).expect(&format!("could not open file {}", filename));
-            air_context.set_air_initial_log(Box::new(file));
-        }
-        if let Some(filename) = &self.args.log_air_final {
-            let file = File::create(filename).expect(&format!("could not open file {}", filename));
-            air_context.set_air_final_log(Box::new(file));
-        }
-        if let Some(filename) = &self.args.log_smt {
-            let file = File::create(filename).expect(&format!("could not open file {}", filename));
-            air_context.set_smt_log(Box::new(file));
-        }
-
-        // air_recommended_options causes AIR to apply a preset collection of Z3 options
-        air_context.set_z3_param("air_recommended_options", "true");
-        air_context.set_rlimit(self.args.rlimit * 1000000);
-        for (option, value) in self.args.smt_options.iter() {
-            air_context.set_z3_param(&option, &value);
+        let mut air_context = new_air_context(&self.args, "");
+        // Profiling quantifiers requires actually running every query, so don't use the cache
+        if let (false, Some(dir)) = (self.args.profile_quantifiers, &self.args.cache_dir) {
+            let smt_version = air_context.get_smt_version();
+            self.cache = VerificationCache::new(dir, &smt_version);
         }
 
         let mut global_ctx = vir::context::GlobalCtx::new(&krate, air_no_span.clone())?;
@@ -445,6 +1293,7 @@ impl Verifier {
         }
 
         let verify_entire_crate = !self.args.verify_root && self.args.verify_module.is_none();
+        let mut found_function = false;
         for module in &krate.module_ids {
             let module_name =
                 module.segments.iter().map(|s| s.to_string()).collect::<Vec<_>>().join("::");
@@ -452,6 +1301,9 @@ impl Verifier {
                 if !verify_entire_crate && !self.args.verify_root {
                     continue;
                 }
+                if !self.module_has_selected_function(&krate, module) {
+                    continue;
+                }
                 println!("Verifying root module");
             } else {
                 if !verify_entire_crate && self.args.verify_module != Some(module_name.clone()) {
@@ -462,6 +1314,9 @@ impl Verifier {
                 if !self.args.verify_pervasive && is_pervasive {
                     continue;
                 }
+                if !self.module_has_selected_function(&krate, module) {
+                    continue;
+                }
                 println!("Verifying module {}", &module_name);
             }
             air_context.blank_line();
@@ -475,15 +1330,11 @@ impl Verifier {
                 module.clone(),
                 mono_abstract_datatypes,
                 lambda_types,
-                self.args.debug,
+                self.args.debug || self.args.report_counterexamples,
             )?;
             let poly_krate = vir::poly::poly_krate_for_module(&mut ctx, &pruned_krate);
             if let Some(filename) = &self.args.log_vir_poly {
-                let module_name_os = if module.segments.len() > 0 {
-                    module.segments.iter().map(|s| s.to_string()).collect::<Vec<_>>().join("__")
-                } else {
-                    "root".to_string()
-                };
+                let module_name_os = module_name_for_files(&module);
                 let mut file = File::create(filename.to_string() + "-" + &module_name_os)
                     .expect(&format!("could not open file {}", filename));
                 vir::printer::write_krate(&mut file, &poly_krate);
@@ -491,6 +1342,14 @@ impl Verifier {
             self.verify_module(compiler, &poly_krate, &mut air_context, &mut ctx)?;
             global_ctx = ctx.free();
             air_context.pop();
+            found_function = true;
+        }
+
+        if let Some(name) = &self.args.verify_function {
+            if !found_function {
+                let no_span = self.air_no_span.as_ref().expect("air_no_span should be initialized");
+                return err_string(no_span, format!("could not find function {} to verify", name));
+            }
         }
 
         if let Some(filename) = &self.args.log_triggers {
@@ -510,17 +1369,35 @@ impl Verifier {
         }
 
         let (time_smt_init, time_smt_run) = air_context.get_time();
-        self.time_smt_init = time_smt_init;
-        self.time_smt_run = time_smt_run;
+        self.time_smt_init += time_smt_init;
+        self.time_smt_run += time_smt_run;
+
         Ok(())
     }
 
+    /// Write the per-function reports requested by --time-functions-csv and --output-json
+    fn write_function_reports(&self, compiler: &Compiler) {
+        if let Some(filename) = &self.args.time_functions_csv {
+            let mut file =
+                File::create(filename).expect(&format!("could not open file {}", filename));
+            self.write_function_times_csv(&mut file)
+                .expect(&format!("error writing to file {}", filename));
+        }
+        if let Some(filename) = &self.args.output_json {
+            let mut file =
+                File::create(filename).expect(&format!("could not open file {}", filename));
+            let json = self.json_report(compiler.session().source_map());
+            writeln!(file, "{}", json).expect(&format!("error writing to file {}", filename));
+        }
+    }
+
     fn vir<'tcx>(&mut self, tcx: TyCtxt<'tcx>) -> Result<bool, VirErr> {
         let autoviewed_call_typs = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let exprs_in_spec = Arc::new(std::sync::Mutex::new(HashSet::new()));
         let _ = tcx.formal_verifier_callback.replace(Some(Box::new(crate::typecheck::Typecheck {
             int_ty_id: None,
             nat_ty_id: None,
-            exprs_in_spec: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            exprs_in_spec: exprs_in_spec.clone(),
             autoviewed_calls: HashSet::new(),
             autoviewed_call_typs: autoviewed_call_typs.clone(),
         })));
@@ -532,6 +1409,7 @@ impl Verifier {
         }
         let autoviewed_call_typs =
             autoviewed_call_typs.lock().expect("get autoviewed_call_typs").clone();
+        let exprs_in_spec = exprs_in_spec.lock().expect("get exprs_in_spec").clone();
 
         let time0 = Instant::now();
 
@@ -544,8 +1422,13 @@ impl Verifier {
             ignored_functions: vec![],
         };
         let erasure_info = std::rc::Rc::new(std::cell::RefCell::new(erasure_info));
-        let ctxt =
-            Arc::new(ContextX { tcx, krate: hir.krate(), erasure_info, autoviewed_call_typs });
+        let ctxt = Arc::new(ContextX {
+            tcx,
+            krate: hir.krate(),
+            erasure_info,
+            autoviewed_call_typs,
+            exprs_in_spec,
+        });
 
         // Convert HIR -> VIR
         let time1 = Instant::now();
@@ -608,7 +1491,10 @@ impl Verifier {
         // Verify crate
         let time3 = Instant::now();
         if !self.args.external_body {
-            self.verify_crate(&compiler)?;
+            let result = self.verify_crate(&compiler);
+            // Report the functions verified so far, even if verification stopped with an error
+            self.write_function_reports(compiler);
+            result?;
         }
         let time4 = Instant::now();
 
@@ -0,0 +1,159 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+test_verify_one_file! {
+    #[test] test_array_index code! {
+        fn sum3(a: [u64; 3]) -> u64 {
+            requires(forall(|i: usize| i < 3 >>= a[i] < 100));
+            ensures(|r: u64| r == a[0] + a[1] + a[2]);
+
+            a[0] + a[1] + a[2]
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_array_index_fails code! {
+        fn get(a: &[u64; 3], i: usize) -> u64 {
+            a[i] // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_array_literal code! {
+        fn test() {
+            let a = [10u64, 20, 30];
+            assert(a.len() == 3);
+            assert(a[1] == 20);
+            let x = a[2];
+            assert(x == 30);
+            assert(a[0] == 20); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_array_repeat code! {
+        fn test() {
+            let a = [7u8; 64];
+            let n = a.len();
+            assert(n == 64);
+            let x = a[63];
+            assert(x == 7);
+            assert(forall(|i: usize| i < 64 >>= a[i] == 7));
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_slice_loop code! {
+        fn sum(s: &[u64]) -> u64 {
+            requires(forall(|i: usize| i < s.len() >>= s[i] <= 1));
+            ensures(|r: u64| r <= s.len());
+
+            let mut total: u64 = 0;
+            let mut i: usize = 0;
+            while i < s.len() {
+                invariant([
+                    i <= s.len(),
+                    total <= i,
+                    forall(|j: usize| j < s.len() >>= s[j] <= 1),
+                ]);
+                total = total + s[i];
+                i = i + 1;
+            }
+            total
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_slice_index_fails code! {
+        fn last(s: &[u64]) -> u64 {
+            s[s.len() - 1] // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_slice_range code! {
+        fn tail(s: &[u64]) -> &[u64] {
+            requires(s.len() > 0);
+            ensures(|r: &[u64]| r.len() + 1 == s.len());
+
+            &s[1..s.len()]
+        }
+
+        fn test(a: &[u64; 4]) {
+            let u = &a[1..3];
+            assert(u.len() == 2);
+            assert(u[0] == a[1]);
+            let t = tail(a);
+            assert(t.len() == 3);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_slice_range_fails code! {
+        fn test(s: &[u64]) -> &[u64] {
+            &s[0..2] // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_slice_unsupported_method code! {
+        fn test(s: &[u64]) -> bool {
+            s.is_empty()
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_array_len_from_type code! {
+        struct S {
+            a: [u64; 2],
+        }
+
+        #[proof]
+        fn lemma_param(a: [u64; 3]) {
+            ensures(a.len() == 3);
+        }
+
+        #[proof]
+        fn lemma_field(s: S) {
+            ensures(s.a.len() == 2);
+        }
+
+        fn make() -> [u64; 2] {
+            [1, 2]
+        }
+
+        fn test() {
+            let a = make();
+            assert(a.len() == 2);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_array_len_from_type_fails code! {
+        #[proof]
+        fn lemma_param(a: [u64; 3]) {
+            ensures(a.len() == 4); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_array_assign_unsupported code! {
+        fn test(a: [u64; 3]) {
+            let mut b = a;
+            b[0] = 5;
+        }
+    } => Err(err) => assert_vir_error(err)
+}
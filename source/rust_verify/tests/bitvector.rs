@@ -165,3 +165,12 @@ test_verify_one_file! {
 //         }
 //     } => Err(err) => assert_one_fails(err)
 // }
+
+test_verify_one_file! {
+    #[test] test_char_unsupported code! {
+        #[proof]
+        fn test(c: char) {
+            assert_bit_vector(c == c);
+        }
+    } => Err(err) => assert_vir_error(err)
+}
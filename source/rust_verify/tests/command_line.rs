@@ -0,0 +1,390 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+const FUNCTIONS: &str = code_str! {
+    #[proof]
+    fn lemma1(x: int) {
+        ensures(x + 1 > x);
+    }
+
+    #[proof]
+    fn lemma2(x: int) {
+        ensures(x + 2 > x);
+    }
+
+    fn exec1(a: u64) -> u64 {
+        requires(a < 100);
+        ensures(|r: u64| r == a + 1);
+        a + 1
+    }
+};
+
+#[test]
+fn test_time_num_threads() {
+    let (result, output) =
+        verify_one_file_with_options(FUNCTIONS.to_string(), &["--time", "--num-threads", "2"]);
+    assert!(result.is_ok());
+    assert!(output.contains("Verification results:: verified: 3 errors: 0"));
+    assert!(output.contains("air-time:"));
+    assert!(output.contains("smt-time:"));
+}
+
+/// The description and source line of each span of each error, in the order reported
+fn error_spans(err: &TestErr) -> Vec<Vec<(Option<String>, String)>> {
+    err.errors
+        .iter()
+        .map(|spans| {
+            spans.iter().map(|s| (s.description.clone(), s.test_span_line.clone())).collect()
+        })
+        .collect()
+}
+
+/// The function names and outcomes listed in an --output-json file, in order
+fn json_functions(json: &str) -> Vec<&str> {
+    json.split(r#"{"function":"#).skip(1).map(|f| f.split(r#","cached""#).next().unwrap()).collect()
+}
+
+#[test]
+fn test_num_threads_failures() {
+    let failing = FUNCTIONS.replace("x + 1 > x", "x + 1 < x").replace("x + 2 > x", "x + 2 < x");
+    let path = new_temp_path("num-threads-sequential.json");
+    let (result, _) =
+        verify_one_file_with_options(failing.clone(), &["--output-json", path.as_str()]);
+    let sequential_errors = error_spans(&result.unwrap_err());
+    let sequential_json = std::fs::read_to_string(&path).expect("JSON output file");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(sequential_errors.len(), 2);
+    let expected = [
+        r#""crate::lemma1","outcome":"failed""#,
+        r#""crate::lemma2","outcome":"failed""#,
+        r#""crate::exec1","outcome":"verified""#,
+    ];
+    assert_eq!(json_functions(&sequential_json), expected);
+    for threads in ["2", "3"] {
+        let path = new_temp_path(&format!("num-threads-{}.json", threads));
+        let options = ["--num-threads", threads, "--output-json", path.as_str()];
+        let (result, output) = verify_one_file_with_options(failing.clone(), &options);
+        assert_eq!(error_spans(&result.unwrap_err()), sequential_errors);
+        assert!(output.contains("Verification results:: verified: 1 errors: 2"));
+        let json = std::fs::read_to_string(&path).expect("JSON output file");
+        assert_eq!(json_functions(&json), json_functions(&sequential_json));
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+#[test]
+fn test_time_functions_failure() {
+    let failing = FUNCTIONS.replace("x + 2 > x", "x + 2 < x");
+    let (result, output) = verify_one_file_with_options(failing, &["--time-functions"]);
+    assert_eq!(result.unwrap_err().errors.len(), 1);
+    assert!(output.contains("rlimit-count"));
+    assert!(output.contains("failed  crate::lemma2"));
+    assert!(output.contains("verified  crate::exec1"));
+}
+
+#[test]
+fn test_time_functions_csv() {
+    let path = new_temp_path("time-functions.csv");
+    let failing = FUNCTIONS.replace("x + 2 > x", "x + 2 < x");
+    let (result, _) =
+        verify_one_file_with_options(failing, &["--time-functions-csv", path.as_str()]);
+    assert_eq!(result.unwrap_err().errors.len(), 1);
+    let csv = std::fs::read_to_string(&path).expect("CSV output file");
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("function,outcome,queries,time_ms,smt_time_ms,rlimit_count"));
+    let mut rows: Vec<(&str, &str)> = lines
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            assert_eq!(fields.len(), 6, "{}", line);
+            for field in &fields[2..] {
+                assert!(field.parse::<u64>().is_ok(), "{}", line);
+            }
+            (fields[0], fields[1])
+        })
+        .collect();
+    // The rows are sorted by time, so sort them by name before comparing
+    rows.sort();
+    assert_eq!(
+        rows,
+        vec![
+            (r#""crate::exec1""#, "verified"),
+            (r#""crate::lemma1""#, "verified"),
+            (r#""crate::lemma2""#, "failed"),
+        ]
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+/// A fresh path for a test's output files or verification cache
+fn new_temp_path(test_name: &str) -> String {
+    let path =
+        std::env::temp_dir().join(format!("verus-test-{}-{}", test_name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    let _ = std::fs::remove_file(&path);
+    path.to_str().expect("temp path").to_string()
+}
+
+#[test]
+fn test_cache_hit() {
+    let dir = new_temp_path("cache-hit");
+    let options = ["--cache-dir", dir.as_str()];
+    let (result, output) = verify_one_file_with_options(FUNCTIONS.to_string(), &options);
+    assert!(result.is_ok());
+    assert!(output.contains("Verification cache:: cached: 0 checked: 3"));
+    let (result, output) = verify_one_file_with_options(FUNCTIONS.to_string(), &options);
+    assert!(result.is_ok());
+    assert!(output.contains("Verification results:: verified: 3 errors: 0"));
+    assert!(output.contains("Verification cache:: cached: 3 checked: 0"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_cache_miss() {
+    let dir = new_temp_path("cache-miss");
+    let options = ["--cache-dir", dir.as_str()];
+    let (result, _) = verify_one_file_with_options(FUNCTIONS.to_string(), &options);
+    assert!(result.is_ok());
+    // Changing a function's body changes only that function's query
+    let changed = FUNCTIONS.replace("a + 1\n", "1 + a\n");
+    assert!(changed != FUNCTIONS);
+    let (result, output) = verify_one_file_with_options(changed, &options);
+    assert!(result.is_ok());
+    assert!(output.contains("Verification cache:: cached: 2 checked: 1"));
+    // Failures are never cached
+    let failing = FUNCTIONS.replace("a + 1\n", "a + 2\n");
+    for _ in 0..2 {
+        let (result, output) = verify_one_file_with_options(failing.clone(), &options);
+        assert_eq!(result.unwrap_err().errors.len(), 1);
+        assert!(output.contains("Verification cache:: cached: 2 checked: 1"));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_cache_invalidation() {
+    let dir = new_temp_path("cache-invalidation");
+    let options = ["--cache-dir", dir.as_str()];
+    let (result, _) = verify_one_file_with_options(FUNCTIONS.to_string(), &options);
+    assert!(result.is_ok());
+    // Changing a function's specification changes the context of all queries in the module
+    let changed = FUNCTIONS.replace("x + 2 > x", "x + 3 > x");
+    assert!(changed != FUNCTIONS);
+    let (result, output) = verify_one_file_with_options(changed, &options);
+    assert!(result.is_ok());
+    assert!(output.contains("Verification cache:: cached: 0 checked: 3"));
+    // Changing the SMT configuration invalidates all entries
+    let options = ["--cache-dir", dir.as_str(), "--smt-option", "smt.random_seed=3"];
+    let (result, output) = verify_one_file_with_options(FUNCTIONS.to_string(), &options);
+    assert!(result.is_ok());
+    assert!(output.contains("Verification cache:: cached: 0 checked: 3"));
+    let options = ["--cache-dir", dir.as_str(), "--rlimit", "5"];
+    let (result, output) = verify_one_file_with_options(FUNCTIONS.to_string(), &options);
+    assert!(result.is_ok());
+    assert!(output.contains("Verification cache:: cached: 0 checked: 3"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_output_json_failure() {
+    let path = new_temp_path("output-json-failure.json");
+    let failing = FUNCTIONS.replace("x + 2 > x", "x + 2 < x");
+    let (result, _) = verify_one_file_with_options(failing, &["--output-json", path.as_str()]);
+    assert_eq!(result.unwrap_err().errors.len(), 1);
+    let json = std::fs::read_to_string(&path).expect("JSON output file");
+    assert!(json.contains(r#""verified":2,"errors":1"#));
+    assert!(json.contains(r#"{"function":"crate::lemma2","outcome":"failed""#));
+    assert!(json.contains(r#"{"function":"crate::exec1","outcome":"verified""#));
+    assert!(json.contains(r#""message":"postcondition not satisfied""#));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_output_json_vir_error() {
+    let path = new_temp_path("output-json-vir-error.json");
+    let (result, _) = verify_one_file_with_options(
+        FUNCTIONS.to_string(),
+        &["--output-json", path.as_str(), "--verify-function", "no_such_function"],
+    );
+    assert_vir_error(result.unwrap_err());
+    let json = std::fs::read_to_string(&path).expect("JSON output file");
+    assert!(json.contains(r#""verified":0,"errors":0,"functions":[]"#));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_verify_function() {
+    let failing = FUNCTIONS.replace("x + 2 > x", "x + 2 < x");
+    let (result, output) =
+        verify_one_file_with_options(failing.clone(), &["--verify-function", "lemma1"]);
+    assert!(result.is_ok());
+    assert!(output.contains("Verification results:: verified: 1 errors: 0"));
+    let (result, _) = verify_one_file_with_options(failing, &["--verify-function", "lemma2"]);
+    assert_eq!(result.unwrap_err().errors.len(), 1);
+}
+
+const METHODS: &str = code_str! {
+    mod m {
+        pub struct S { pub x: u64 }
+        pub struct T { pub x: u64 }
+
+        impl S {
+            pub fn get(&self) -> u64 {
+                ensures(|r: u64| r == self.x);
+                self.x
+            }
+        }
+
+        impl T {
+            pub fn get(&self) -> u64 {
+                ensures(|r: u64| r == self.x + 1);
+                self.x
+            }
+        }
+    }
+};
+
+#[test]
+fn test_verify_function_method() {
+    for name in ["S::get", "m::S::get", "crate::m::S::get"] {
+        let (result, output) =
+            verify_one_file_with_options(METHODS.to_string(), &["--verify-function", name]);
+        assert!(result.is_ok(), "{}", name);
+        assert!(output.contains("Verification results:: verified: 1 errors: 0"), "{}", name);
+    }
+    // 'get' matches both methods
+    for name in ["T::get", "get"] {
+        let (result, _) =
+            verify_one_file_with_options(METHODS.to_string(), &["--verify-function", name]);
+        assert_eq!(result.unwrap_err().errors.len(), 1, "{}", name);
+    }
+    // A suffix must match whole path segments
+    let (result, _) =
+        verify_one_file_with_options(METHODS.to_string(), &["--verify-function", "et"]);
+    assert_vir_error(result.unwrap_err());
+}
+
+#[test]
+fn test_profile_quantifiers() {
+    let code = code! {
+        #[spec] #[verifier(external_body)]
+        fn g(i: int) -> int { unimplemented!() }
+
+        #[proof]
+        fn test(s: int) {
+            requires(forall(|i: int| g(i) > 0));
+            assert(g(s) > 0);
+            assert(g(s + 1) + g(s + 2) > 0);
+        }
+    };
+    let (result, output) = verify_one_file_with_options(code, &["--profile-quantifiers"]);
+    assert!(result.is_ok());
+    let report: Vec<&str> = output
+        .lines()
+        .skip_while(|l| *l != "Quantifier instantiations while verifying crate::test:")
+        .skip(1)
+        .take_while(|l| l.starts_with("  "))
+        .collect();
+    // The user's quantifier is reported at its source location,
+    // and the verifier's own quantifiers are reported by their internal ids
+    assert!(report.iter().any(|l| l.contains("test.rs:")), "{:?}", report);
+    for line in report {
+        let qid = line.trim_start().splitn(2, "  ").nth(1).expect("qid");
+        assert!(qid.contains(".rs:") || qid.starts_with("internal_"), "{}", qid);
+    }
+    assert!(output.contains("quantifier instantiated"));
+}
+
+const BRANCHES: &str = code_str! {
+    fn test(c: bool) {
+        requires(c);
+        let mut x: u64 = 1;
+        if c {
+            x = x + 10;
+        } else {
+            x = x + 20;
+        }
+        assert(x == 1); // FAILS
+    }
+};
+
+const MUT_ARG: &str = code_str! {
+    fn add_ten(x: &mut u64) {
+        requires(*old(x) < 100);
+        ensures(*x == *old(x) + 10);
+
+        *x = *x + 10;
+    }
+
+    fn test(a: u64) {
+        requires(a == 1);
+        let mut x: u64 = a;
+        add_ten(&mut x);
+        assert(x == 1); // FAILS
+    }
+};
+
+/// The 1-based line number, in the file verified by verify_one_file_with_options,
+/// of the first line of code that contains s
+fn code_line(code: &str, s: &str) -> usize {
+    let file = format!("{}\n\n{}", USE_PRELUDE, code);
+    file.lines().position(|l| l.contains(s)).expect("line") + 1
+}
+
+/// The lines of the first counterexample report in output
+fn counterexample_report(output: &str) -> Vec<&str> {
+    let mut lines = output.lines().skip_while(|l| !l.starts_with("Counterexample for "));
+    let first = lines.next().into_iter();
+    first.chain(lines.take_while(|l| l.starts_with("  line "))).collect()
+}
+
+#[test]
+fn test_report_counterexamples() {
+    let (result, output) =
+        verify_one_file_with_options(BRANCHES.to_string(), &["--report-counterexamples"]);
+    assert_one_fails(result.unwrap_err());
+    let report = counterexample_report(&output);
+    let fails_line = code_line(BRANCHES, "FAILS");
+    let header = format!("Counterexample for \"assertion failed\" at test.rs:{}:", fails_line);
+    assert_eq!(report.first(), Some(&header.as_str()), "{}", output);
+    let then_line = format!("  line {}: c = true, x = 11u64", code_line(BRANCHES, "x + 10"));
+    assert!(report.contains(&then_line.as_str()), "{:?}", report);
+    // The else branch is not taken, so it is not reported
+    let else_line = format!("  line {}:", code_line(BRANCHES, "x + 20"));
+    assert!(report.iter().all(|l| !l.starts_with(&else_line)), "{:?}", report);
+}
+
+#[test]
+fn test_report_counterexamples_json() {
+    let path = new_temp_path("counterexamples.json");
+    let options = ["--report-counterexamples", "--output-json", path.as_str()];
+    let (result, _) = verify_one_file_with_options(BRANCHES.to_string(), &options);
+    assert_one_fails(result.unwrap_err());
+    let json = std::fs::read_to_string(&path).expect("JSON output file");
+    assert!(json.contains(r#""counterexamples":[{"message":"assertion failed""#), "{}", json);
+    let then_line = format!(
+        r#"{{"line":{},"values":[{{"name":"c","value":"true"}},{{"name":"x","value":"11u64"}}]}}"#,
+        code_line(BRANCHES, "x + 10")
+    );
+    assert!(json.contains(&then_line), "{}", json);
+    let else_line = format!(r#"{{"line":{},"values":"#, code_line(BRANCHES, "x + 20"));
+    assert!(!json.contains(&else_line), "{}", json);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_report_counterexamples_mut_arg() {
+    let (result, output) =
+        verify_one_file_with_options(MUT_ARG.to_string(), &["--report-counterexamples"]);
+    assert_one_fails(result.unwrap_err());
+    let report = counterexample_report(&output);
+    let call_line = format!("  line {}: a = 1u64, x = 11u64", code_line(MUT_ARG, "add_ten(&mut"));
+    assert!(report.contains(&call_line.as_str()), "{:?}", report);
+}
+
+#[test]
+fn test_debug_mut_arg() {
+    let (result, _) = verify_one_file_with_options(MUT_ARG.to_string(), &["--debug"]);
+    assert_one_fails(result.unwrap_err());
+}
@@ -5,7 +5,7 @@ extern crate rustc_span;
 pub use rust_verify::verifier::ErrorSpan;
 pub use rust_verify_test_macros::{code, code_str};
 
-use rust_verify::config::{enable_default_features, parse_args, Args};
+use rust_verify::config::{enable_default_features, enable_pervasive_features, parse_args, Args};
 use rust_verify::verifier::Verifier;
 
 use rustc_span::source_map::FileLoader;
@@ -64,6 +64,19 @@ pub fn verify_files_and_pervasive(
     entry_file: String,
     verify_pervasive: bool,
 ) -> Result<(), TestErr> {
+    verify_files_with_options(files, entry_file, verify_pervasive, &[]).0
+}
+
+/// Run the verifier with the given command-line options,
+/// returning the result along with the captured diagnostics and reports.
+/// The verification cache is disabled unless the options contain --cache-dir.
+#[allow(dead_code)]
+pub fn verify_files_with_options(
+    files: impl IntoIterator<Item = (String, String)>,
+    entry_file: String,
+    verify_pervasive: bool,
+    options: &[&str],
+) -> (Result<(), TestErr>, String) {
     let mut rustc_args = vec![
         "../../rust/install/bin/rust_verify".to_string(),
         "--edition".to_string(),
@@ -107,14 +120,18 @@ pub fn verify_files_and_pervasive(
     ]);
 
     enable_default_features(&mut rustc_args);
+    // TestFileLoader always provides pervasive
+    enable_pervasive_features(&mut rustc_args);
 
     rustc_args.push(entry_file);
     let our_args = {
-        let mut our_args: Args = if let Ok(extra_args) = std::env::var("VERIFY_EXTRA_ARGS") {
-            let (args, rest) = parse_args(
-                &"test".to_string(),
-                extra_args.split(" ").map(|x| x.to_string()).chain(Some("test".to_string())),
-            );
+        let mut args: Vec<String> = options.iter().map(|x| x.to_string()).collect();
+        if let Ok(extra_args) = std::env::var("VERIFY_EXTRA_ARGS") {
+            args.extend(extra_args.split(" ").map(|x| x.to_string()));
+        }
+        let mut our_args: Args = if args.len() > 0 {
+            let (args, rest) =
+                parse_args(&"test".to_string(), args.into_iter().chain(Some("test".to_string())));
             if rest.len() != 2 {
                 eprintln!("warning: unparsed extra arguments from VERIFY_EXTRA_ARGS");
             }
@@ -122,6 +139,9 @@ pub fn verify_files_and_pervasive(
         } else {
             Default::default()
         };
+        if !options.iter().any(|x| x.starts_with("--cache-dir")) {
+            our_args.cache_dir = None;
+        }
         if let Ok(path) = std::env::var("VERIFY_LOG_IR_PATH") {
             let path = std::path::Path::new(&path);
             if !path.is_dir() {
@@ -145,24 +165,25 @@ pub fn verify_files_and_pervasive(
     let captured_output = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
     let captured_output_1 = captured_output.clone();
     let result = std::panic::catch_unwind(move || {
+        let time0 = std::time::Instant::now();
         let mut verifier = Verifier::new(our_args);
         verifier.test_capture_output = Some(captured_output_1);
         let file_loader: TestFileLoader = TestFileLoader { files };
         let (verifier, status) = rust_verify::driver::run(verifier, rustc_args, file_loader);
+        verifier.print_summary(status.as_ref().ok(), time0.elapsed());
         status.map(|_| ()).map_err(|_| TestErr {
             errors: verifier.errors,
             has_vir_error: verifier.encountered_vir_error,
         })
     });
-    eprintln!(
-        "{}",
-        std::str::from_utf8(
-            &captured_output.lock().expect("internal error: cannot lock captured output")
-        )
-        .expect("captured output is invalid utf8")
-    );
+    let output = std::str::from_utf8(
+        &captured_output.lock().expect("internal error: cannot lock captured output"),
+    )
+    .expect("captured output is invalid utf8")
+    .to_string();
+    eprintln!("{}", output);
     match result {
-        Ok(result) => result,
+        Ok(result) => (result, output),
         Err(_) => {
             panic!(
                 "The compiler panicked. This may be due to rustc not being available in the `rust` directory in the project root. Check the README for more information."
@@ -185,6 +206,17 @@ pub fn verify_one_file(code: String) -> Result<(), TestErr> {
     verify_files(files, "test.rs".to_string())
 }
 
+/// Verify code with the given command-line options,
+/// returning the result along with the captured diagnostics and reports
+#[allow(dead_code)]
+pub fn verify_one_file_with_options(
+    code: String,
+    options: &[&str],
+) -> (Result<(), TestErr>, String) {
+    let files = vec![("test.rs".to_string(), format!("{}\n\n{}", USE_PRELUDE, code.as_str()))];
+    verify_files_with_options(files, "test.rs".to_string(), false, options)
+}
+
 #[macro_export]
 macro_rules! test_verify_one_file {
     ($(#[$attrs:meta])* $name:ident $body:expr => $result:pat => $assertions:expr ) => {
@@ -0,0 +1,184 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+const FUNS: &str = code_str! {
+    #[spec]
+    fn f<const N: usize>() -> usize {
+        N
+    }
+
+    fn g<const N: usize>(x: u64) -> usize {
+        requires(N > 2);
+        ensures(|r: usize| r == N && r == f::<N>());
+        assert(N >= 3);
+        N
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_fun_ok FUNS.to_string() + code_str! {
+        fn test() {
+            let n = g::<7>(3);
+            assert(n == 7);
+            assert(f::<7>() == 7);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_fun_fails FUNS.to_string() + code_str! {
+        fn test1() {
+            let n = g::<2>(3); // FAILS
+        }
+
+        fn test2() {
+            let n = g::<7>(3);
+            assert(n == 8); // FAILS
+        }
+
+        fn test3<const M: usize>() {
+            assert(M < 1000); // FAILS
+        }
+    } => Err(err) => assert_fails(err, 3)
+}
+
+const RING_BUFFER: &str = code_str! {
+    struct RingBuffer<const N: usize> {
+        head: usize,
+    }
+
+    impl<const N: usize> RingBuffer<N> {
+        #[spec]
+        fn well_formed(&self) -> bool {
+            self.head < N
+        }
+
+        fn new() -> Self {
+            requires(N > 0);
+            ensures(|r: Self| r.well_formed());
+            RingBuffer { head: 0 }
+        }
+
+        fn advance(&mut self) {
+            requires(old(self).well_formed());
+            ensures(self.well_formed());
+            if self.head + 1 < N {
+                self.head = self.head + 1;
+            } else {
+                self.head = 0;
+            }
+        }
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_struct_ok RING_BUFFER.to_string() + code_str! {
+        fn test() {
+            let mut r = RingBuffer::<4>::new();
+            r.advance();
+            r.advance();
+            assert(r.head < 4);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_struct_fails RING_BUFFER.to_string() + code_str! {
+        fn test1() {
+            let mut r = RingBuffer::<4>::new();
+            r.advance();
+            assert(r.head < 3); // FAILS
+        }
+
+        fn test2() {
+            let r = RingBuffer::<0>::new(); // FAILS
+        }
+    } => Err(err) => assert_fails(err, 2)
+}
+
+const ABSTRACT: &str = code_str! {
+    mod M {
+        use builtin::*;
+
+        pub struct Buf<const N: usize> {
+            len: usize,
+        }
+
+        impl<const N: usize> Buf<N> {
+            pub fn new() -> Self {
+                Buf { len: 0 }
+            }
+
+            pub fn capacity(&self) -> usize {
+                ensures(|r: usize| r == N);
+                N
+            }
+        }
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_abstract_ok ABSTRACT.to_string() + code_str! {
+        fn test() {
+            let b = M::Buf::<3>::new();
+            let c = b.capacity();
+            assert(c == 3);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_abstract_fails ABSTRACT.to_string() + code_str! {
+        fn test(b1: &M::Buf<3>, b2: &M::Buf<4>) {
+            let c1 = b1.capacity();
+            let c2 = b2.capacity();
+            assert(c1 + 1 == c2);
+            assert(c1 == c2); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bool_param_unsupported code! {
+        fn f<const B: bool>() {
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_signed_param_unsupported code! {
+        fn f<const N: i32>() {
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_array_len_param code! {
+        #[proof]
+        fn lemma_len<const N: usize>(a: [u8; N]) {
+            ensures(a.len() == N);
+        }
+
+        fn first<const N: usize>(a: [u8; N]) -> u8 {
+            requires(N > 0);
+            ensures(|r: u8| r == a[0]);
+            a[0]
+        }
+
+        fn test(a: [u8; 4]) {
+            let x = first::<4>(a);
+            assert(x == a[0]);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_array_len_param_fails code! {
+        #[proof]
+        fn lemma_len<const N: usize>(a: [u8; N]) {
+            ensures(a.len() == N + 1); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
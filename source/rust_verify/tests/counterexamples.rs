@@ -0,0 +1,88 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+/// Assert that there is one failure, on a line containing "FAILS",
+/// and that the failure is labeled with each of the given variable values
+fn assert_one_fails_with_values(err: TestErr, values: &[&str]) {
+    let labels: Vec<String> =
+        err.errors[0].iter().skip(1).filter_map(|e| e.description.clone()).collect();
+    for value in values {
+        assert!(labels.iter().any(|l| l == value), "{} not found in {:?}", value, labels);
+    }
+    assert_one_fails(err);
+}
+
+test_verify_one_file! {
+    #[test] test_int_values code! {
+        fn test(a: u64, b: i8) {
+            requires([a == 7, b == -2]);
+            let mut x: u32 = 1;
+            x = x + 2;
+            let c = 'z';
+            assert(a < 5 || x == 4); // FAILS
+        }
+    } => Err(err) => assert_one_fails_with_values(err, &["a = 7u64", "b = -2i8", "x = 3u32", "c = 'z'"])
+}
+
+test_verify_one_file! {
+    #[test] test_datatype_values code! {
+        use crate::pervasive::option::*;
+
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        enum Shape {
+            Dot(Point),
+            Empty,
+        }
+
+        fn test(p: Point) {
+            requires(p.x == -3 && p.y == 4);
+            let s = Shape::Dot(Point { x: 1, y: 2 });
+            let o: Option<u8> = Option::Some(5);
+            let t = (10u16, true);
+            assert(p.x > 0); // FAILS
+        }
+    } => Err(err) => assert_one_fails_with_values(err, &[
+        "p = Point { x: -3i32, y: 4i32 }",
+        "s = Shape::Dot(Point { x: 1i32, y: 2i32 })",
+        "o = Option::Some(5u8)",
+        "t = (10u16, true)",
+    ])
+}
+
+test_verify_one_file! {
+    #[test] test_later_locals_omitted code! {
+        fn test(a: u64) {
+            requires(a == 1);
+            assert(a == 2); // FAILS
+            let b: u64 = 3;
+        }
+    } => Err(err) => {
+        assert!(err.errors[0].iter().all(|e| e.description != Some("b = 3u64".to_string())));
+        assert_one_fails_with_values(err, &["a = 1u64"])
+    }
+}
+
+test_verify_one_file! {
+    #[test] test_shadowed_locals code! {
+        fn test(a: u64) {
+            requires(a == 1);
+            assert_forall_by(|x: int| {
+                ensures(x + 0 == x);
+            });
+            let x: u64 = 4;
+            let y: u64 = {
+                let mut x: u64 = 2;
+                x = x + 1;
+                x
+            };
+            let x: u64 = x + 1;
+            assert(y == 5); // FAILS
+        }
+    } => Err(err) => assert_one_fails_with_values(err, &["x = 4u64", "x = 3u64", "y = 3u64", "x = 5u64"])
+}
@@ -0,0 +1,135 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+const APPLY: &str = code_str! {
+    fn apply<F: Fn(u64) -> u64>(f: F, x: u64) -> u64 {
+        requires(f.requires((x,)));
+        ensures(|r: u64| f.ensures((x,), r));
+
+        f(x)
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_apply APPLY.to_string() + code_str! {
+        fn test() {
+            let y = apply(|x: u64| {
+                requires(x < 10);
+                ensures(|r: u64| r == x + 1);
+
+                x + 1
+            }, 5);
+            assert(y == 6);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_apply_requires_fails APPLY.to_string() + code_str! {
+        fn test() {
+            let y = apply(|x: u64| {
+                requires(x < 10);
+                ensures(|r: u64| r == x + 1);
+
+                x + 1
+            }, 20); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_closure_ensures_fails code! {
+        fn test() {
+            let f = |x: u64| {
+                requires(x < 10);
+                ensures(|r: u64| r == x + 2); // FAILS
+
+                x + 1
+            };
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_closure_overflow_fails code! {
+        fn test() {
+            let f = |x: u64| {
+                ensures(|r: u64| r == x + 1);
+
+                x + 1 // FAILS
+            };
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_let_closure code! {
+        fn test(a: u64) {
+            requires(a < 100);
+
+            let f = |x: u64| {
+                requires(x < 100);
+                ensures(|r: u64| r == x + a);
+
+                x + a
+            };
+            let b = f(3);
+            assert(b == a + 3);
+            assert(f.requires((99,)));
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_let_closure_fails code! {
+        fn test() {
+            let f = |x: u64| {
+                requires(x < 100);
+                ensures(|r: u64| r == x + 1);
+
+                x + 1
+            };
+            let b = f(100); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_unknown_ensures_fails APPLY.to_string() + code_str! {
+        fn test() {
+            let y = apply(|x: u64| {
+                requires(x < 10);
+
+                x + 1
+            }, 5);
+            assert(y == 6); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_mutate_captured code! {
+        fn test() {
+            let mut a: u64 = 0;
+            let f = |x: u64| {
+                a = x;
+                x
+            };
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_early_return code! {
+        fn test() {
+            let f = |x: u64| {
+                if x == 0 {
+                    return 1;
+                }
+                x
+            };
+        }
+    } => Err(err) => assert_vir_error(err)
+}
@@ -0,0 +1,123 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+test_verify_one_file! {
+    #[test] test_hash_map code! {
+        use crate::pervasive::map::*;
+        use crate::pervasive::option::*;
+        use crate::pervasive::hash_map::*;
+
+        fn test() {
+            let mut m: HashMap<u64, u64> = HashMap::new();
+            m.insert(1, 10);
+            m.insert(2, 20);
+            assert(m.view().dom().contains(1));
+            assert(m.view().index(2) == 20);
+            let b = m.contains_key(&1);
+            assert(b);
+            let r = m.get(&2);
+            assert(r.is_Some());
+            let x = m.remove(&1);
+            assert(x.is_Some() && x.get_Some_0() == 10);
+            assert(!m.view().dom().contains(1));
+            let y = m.remove(&1);
+            assert(y.is_None());
+        }
+
+        fn test_len(m: &HashMap<u64, bool>) {
+            let l = m.len();
+            assert(m.view().dom().finite());
+            assert(l == m.len());
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_hash_map_fails code! {
+        use crate::pervasive::map::*;
+        use crate::pervasive::hash_map::*;
+
+        fn test() {
+            let mut m: HashMap<u64, u64> = HashMap::new();
+            m.insert(1, 10);
+            m.insert(1, 20);
+            assert(m.view().index(1) == 10); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_hash_set code! {
+        use crate::pervasive::set::*;
+        use crate::pervasive::hash_set::*;
+
+        fn test() {
+            let mut s: HashSet<u32> = HashSet::new();
+            let b1 = s.insert(3);
+            assert(b1);
+            let b2 = s.insert(3);
+            assert(!b2);
+            s.insert(4);
+            assert(s.view().contains(4));
+            let c = s.contains(&3);
+            assert(c);
+            let r = s.remove(&3);
+            assert(r);
+            assert(!s.view().contains(3));
+            let l = s.len();
+            assert(l == s.view().len());
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_hash_set_fails code! {
+        use crate::pervasive::set::*;
+        use crate::pervasive::hash_set::*;
+
+        fn test() {
+            let mut s: HashSet<u32> = HashSet::new();
+            s.insert(3);
+            let r = s.remove(&4);
+            assert(r); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_hash_map_struct_key code! {
+        use crate::pervasive::map::*;
+        use crate::pervasive::hash_map::*;
+
+        #[derive(PartialEq, Eq, Structural, Hash)]
+        struct Key {
+            a: u64,
+            b: bool,
+        }
+
+        fn test() {
+            let mut m: HashMap<Key, u64> = HashMap::new();
+            m.insert(Key { a: 1, b: true }, 10);
+            let b = m.contains_key(&Key { a: 1, b: true });
+            assert(b);
+            let c = m.contains_key(&Key { a: 1, b: false });
+            assert(!c);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_hash_manual_impl code! {
+        #[derive(PartialEq, Eq, Structural)]
+        struct Key {
+            a: u64,
+        }
+
+        impl std::hash::Hash for Key {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
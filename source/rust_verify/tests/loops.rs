@@ -133,16 +133,18 @@ test_verify_one_file! {
 }
 
 test_verify_one_file! {
-    // TODO: support break in loops?
-    #[test] #[ignore] break_test code! {
-        fn test1(a: int, b: int) {
+    #[test] break_test code! {
+        fn test1(a: u64, b: u64) {
             let mut i = a;
             while i >= 1 {
+                invariant(i <= a);
+                ensures(i >= 1 ==> a % i == 0 && b % i == 0);
                 if a % i == 0 && b % i == 0 {
                     break;
                 }
+                i = i - 1;
             }
-            assert(a % i == 0);
+            assert(i >= 1 ==> a % i == 0);
         }
     } => Ok(())
 }
@@ -249,3 +251,367 @@ test_verify_one_file! {
         }
     } => Ok(())
 }
+
+test_verify_one_file! {
+    #[test] loop_break_ensures code! {
+        fn test1(n: u64) {
+            let mut i: u64 = 0;
+            loop {
+                invariant(i <= n);
+                ensures(i == n);
+                if i == n {
+                    break;
+                }
+                i = i + 1;
+            }
+            assert(i == n);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] loop_break_ensures_fail code! {
+        fn test1(n: u64) {
+            let mut i: u64 = 0;
+            loop {
+                invariant(i <= n);
+                ensures(i == n);
+                if i >= 5 {
+                    break; // FAILS
+                }
+                if i == n {
+                    break;
+                }
+                i = i + 1;
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] loop_no_break_never_exits code! {
+        fn test1() {
+            let mut i: u64 = 0;
+            loop {
+                invariant(i <= 10);
+                if i < 10 {
+                    i = i + 1;
+                }
+            }
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] while_ensures code! {
+        fn test1(n: u64) {
+            let mut i: u64 = 0;
+            while i < n {
+                invariant(i <= n);
+                ensures(i == n);
+                i = i + 1;
+            }
+            assert(i == n);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] while_break code! {
+        fn test1(n: u64) {
+            let mut i: u64 = 0;
+            let mut found = false;
+            while i < n {
+                invariant([
+                    i <= n,
+                    found ==> i == 7,
+                ]);
+                if i == 7 {
+                    found = true;
+                    break;
+                }
+                i = i + 1;
+            }
+            assert(i <= n);
+            assert(found ==> i == 7);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] while_break_no_neg_cond_fail code! {
+        fn test1(n: u64) {
+            let mut i: u64 = 0;
+            while i < n {
+                invariant(i <= n);
+                if i == 7 {
+                    break;
+                }
+                i = i + 1;
+            }
+            assert(i == n); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] while_break_invariant_fail code! {
+        fn test1(n: u64) {
+            let mut i: u64 = 0;
+            while i < n {
+                invariant(i <= n);
+                i = i + 1;
+                if i == 7 {
+                    i = 100;
+                    break; // FAILS
+                }
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] while_continue code! {
+        fn test1() {
+            let mut i: u64 = 0;
+            let mut evens: u64 = 0;
+            while i < 10 {
+                invariant([
+                    i <= 10,
+                    evens <= i,
+                ]);
+                i = i + 1;
+                if i % 2 == 1 {
+                    continue;
+                }
+                evens = evens + 1;
+            }
+            assert(evens <= 10);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] while_continue_fail code! {
+        fn test1() {
+            let mut i: u64 = 0;
+            let mut evens: u64 = 0;
+            while i < 10 {
+                invariant([
+                    i <= 10,
+                    evens <= i,
+                ]);
+                i = i + 1;
+                if i % 2 == 1 {
+                    evens = evens + 2;
+                    continue; // FAILS
+                }
+                evens = evens + 1;
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] labeled_continue code! {
+        fn test1() {
+            let mut i: u64 = 0;
+            'outer: loop {
+                invariant(i <= 10);
+                ensures(i == 10);
+                if i == 10 {
+                    break;
+                }
+                let mut j: u64 = 0;
+                loop {
+                    invariant([
+                        i < 10,
+                        j <= 5,
+                    ]);
+                    if j == 5 {
+                        i = i + 1;
+                        continue 'outer;
+                    }
+                    j = j + 1;
+                }
+            }
+            assert(i == 10);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] labeled_break_fail code! {
+        fn test1() {
+            let mut i: u64 = 0;
+            'outer: loop {
+                invariant(i <= 10);
+                ensures(i == 10);
+                let mut j: u64 = 0;
+                loop {
+                    invariant([
+                        i <= 10,
+                        j <= 5,
+                    ]);
+                    if j == 5 {
+                        break 'outer; // FAILS
+                    }
+                    j = j + 1;
+                }
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] break_in_ghost_code code! {
+        fn test1(#[spec] b: bool) {
+            let mut i: u64 = 0;
+            while i < 10 {
+                if b {
+                    break;
+                }
+                i = i + 1;
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] for_range code! {
+        fn test1(n: u64) {
+            requires(n <= 100);
+            let mut sum: u64 = 0;
+            for i in 0..n {
+                invariant([
+                    n <= 100,
+                    sum == 2 * i,
+                ]);
+                sum = sum + 2;
+            }
+            assert(sum == 2 * n);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] for_range_bounds code! {
+        fn test1(a: u32, b: usize) {
+            for i in a..10 {
+                assert(a <= i);
+                assert(i < 10);
+            }
+            for j in 3..b {
+                assert(3 <= j);
+                assert(j < b);
+            }
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] for_range_empty code! {
+        fn test1() {
+            for i in 5..3 {
+                assert(false);
+            }
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] for_range_invariant_entry_fail code! {
+        fn test1() {
+            let mut sum: u64 = 0;
+            for i in 0..10 {
+                invariant(sum == i + 1); // FAILS
+                sum = sum + 1;
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] for_range_invariant_maintain_fail code! {
+        fn test1() {
+            let mut sum: u64 = 0;
+            for i in 0..10 {
+                invariant(sum == i); // FAILS
+                sum = sum + 2;
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] for_range_continue code! {
+        fn test1() {
+            let mut evens: u64 = 0;
+            for i in 0..10 {
+                invariant(evens <= i);
+                if i % 2 == 1 {
+                    continue;
+                }
+                evens = evens + 1;
+            }
+            assert(evens <= 10);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] for_range_continue_fail code! {
+        fn test1() {
+            let mut evens: u64 = 0;
+            for i in 0..10 {
+                invariant(evens == i);
+                if i % 2 == 1 {
+                    continue; // FAILS
+                }
+                evens = evens + 1;
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] for_range_break_ensures code! {
+        fn test1() {
+            let mut j: u64 = 0;
+            'outer: for i in 0..10 {
+                invariant(j == i);
+                ensures(j <= 10);
+                for k in 0..i {
+                    invariant([
+                        i < 10,
+                        j == i,
+                    ]);
+                    if k == 5 {
+                        break 'outer;
+                    }
+                }
+                j = j + 1;
+            }
+            assert(j <= 10);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] for_range_signed code! {
+        fn test1(n: i32) {
+            for i in 0..n {
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] for_range_mut_pattern code! {
+        fn test1() {
+            for mut i in 0..10 {
+                i = i + 1;
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
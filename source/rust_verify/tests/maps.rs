@@ -94,3 +94,68 @@ test_verify_one_file! {
         }
     } => Err(_)
 }
+
+test_verify_one_file! {
+    #[test] test_map_lib code! {
+        use crate::pervasive::set::*;
+        use crate::pervasive::map::*;
+        use crate::pervasive::map_lib::*;
+
+        #[proof]
+        fn test_union_restrict() {
+            let m1 = Map::<int, int>::empty().insert(1, 10).insert(2, 20);
+            let m2 = Map::<int, int>::empty().insert(2, 200).insert(3, 300);
+            let u = m1.union_prefer_right(m2);
+            assert(u.dom().contains(1) && u.dom().contains(3));
+            assert(u.index(1) == 10);
+            assert(u.index(2) == 200);
+            lemma_submap_union_prefer_right::<int, int>(m1, m2);
+            assert(m2.submap_of(u));
+
+            let r = u.restrict(Set::empty().insert(1).insert(5));
+            assert(r.dom().contains(1) && !r.dom().contains(2) && !r.dom().contains(5));
+            assert(r.index(1) == 10);
+            let d = u.remove_keys(Set::empty().insert(1));
+            assert(!d.dom().contains(1) && d.dom().contains(3));
+            assert(d.index(3) == 300);
+        }
+
+        #[proof]
+        fn test_len(m: Map<int, int>, keys: Set<int>) {
+            requires([
+                m.dom().finite(),
+                m.len() == 5,
+                !m.dom().contains(7),
+            ]);
+            lemma_map_insert_len::<int, int>(m, 7, 70);
+            assert(m.insert(7, 70).len() == 6);
+            lemma_restrict_len::<int, int>(m, keys);
+            assert(m.restrict(keys).len() <= 5);
+            lemma_submap_remove_keys::<int, int>(m, keys);
+            lemma_submap_len::<int, int>(m.remove_keys(keys), m);
+            assert(m.remove_keys(keys).len() <= m.len());
+        }
+
+        #[proof]
+        fn test_values(m: Map<int, int>) {
+            requires(m.dom().contains(3));
+            lemma_values_contains::<int, int>(m, 3);
+            assert(m.values().contains(m.index(3)));
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_map_lib_fails code! {
+        use crate::pervasive::set::*;
+        use crate::pervasive::map::*;
+
+        #[proof]
+        fn test_union() {
+            let m1 = Map::<int, int>::empty().insert(1, 10).insert(2, 20);
+            let m2 = Map::<int, int>::empty().insert(2, 200).insert(3, 300);
+            let u = m1.union_prefer_right(m2);
+            assert(u.index(2) == 20); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
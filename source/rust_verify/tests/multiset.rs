@@ -100,3 +100,85 @@ test_verify_one_file! {
         }
     } => Err(err) => assert_one_fails(err)
 }
+
+test_verify_one_file! {
+    #[test] multiset_len code! {
+        use crate::pervasive::multiset::*;
+        use crate::pervasive::multiset_lib::*;
+
+        #[proof]
+        pub fn len3(a: int, b: int) {
+            lemma_multiset_empty_dom::<int>();
+            let m0 = Multiset::<int>::empty();
+            lemma_multiset_insert_len::<int>(m0, a);
+            lemma_multiset_insert_len::<int>(m0.insert(a), b);
+            lemma_multiset_insert_len::<int>(m0.insert(a).insert(b), a);
+            let m = m0.insert(a).insert(b).insert(a);
+            assert(m.len() == 3);
+            assert(m.count(a) >= 2);
+            assert(m.dom().contains(b));
+            lemma_multiset_remove_len::<int>(m, b);
+            assert(m.remove(b).len() == 2);
+            lemma_multiset_filter_len(m, |x: int| x > 0);
+            assert(m.filter(|x: int| x > 0).len() <= 3);
+        }
+
+        #[proof]
+        pub fn nonempty<V>(m: Multiset<V>) {
+            requires([
+                m.dom().finite(),
+                m.len() > 0,
+            ]);
+            lemma_multiset_choose::<V>(m);
+            assert(m.count(m.choose()) > 0);
+            assert(m.count(m.choose()) <= m.len());
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] multiset_len_fails code! {
+        use crate::pervasive::multiset::*;
+        use crate::pervasive::multiset_lib::*;
+
+        #[proof]
+        pub fn len_filter<V, F: Fn(V) -> bool>(m: Multiset<V>, f: F) {
+            requires(m.dom().finite());
+            lemma_multiset_filter_len(m, f);
+            assert(m.filter(f).len() < m.len()); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] multiset_dom_len code! {
+        use crate::pervasive::multiset::*;
+        use crate::pervasive::multiset_lib::*;
+
+        #[proof]
+        pub fn dom_len<V>(m: Multiset<V>, v: V) {
+            requires([
+                m.dom().finite(),
+                m.count(v) > 0,
+            ]);
+            lemma_multiset_dom_len::<V>(m);
+            assert(m.dom().len() <= m.len());
+            assert(m.len() != 0);
+            assert(m.dom().len() != 0);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] multiset_dom_len_fails code! {
+        use crate::pervasive::multiset::*;
+        use crate::pervasive::multiset_lib::*;
+
+        #[proof]
+        pub fn dom_len<V>(m: Multiset<V>) {
+            requires(m.dom().finite());
+            lemma_multiset_dom_len::<V>(m);
+            assert(m.dom().len() == m.len()); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
@@ -0,0 +1,178 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+test_verify_one_file! {
+    #[test] test_vec_get_mut code! {
+        use crate::pervasive::vec::*;
+
+        fn test() {
+            let mut v: Vec<u64> = Vec::new();
+            v.push(1);
+            v.push(2);
+            *v.get_mut(0) = 5;
+            assert(v.len() == 2);
+            assert(v.index(0) == 5 && v.index(1) == 2);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_vec_get_mut_fails code! {
+        use crate::pervasive::vec::*;
+
+        fn test() {
+            let mut v: Vec<u64> = Vec::new();
+            v.push(1);
+            v.push(2);
+            *v.get_mut(1) = 5;
+            assert(v.index(0) == 1);
+            assert(v.index(1) == 2); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_vec_get_mut_bounds_fails code! {
+        use crate::pervasive::vec::*;
+
+        fn test() {
+            let mut v: Vec<u64> = Vec::new();
+            v.push(1);
+            *v.get_mut(1) = 5; // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+const PAIR: &str = code_str! {
+    struct Pair {
+        a: u64,
+        b: u64,
+    }
+
+    impl Pair {
+        fn a_mut(&mut self) -> &mut u64 {
+            ensures(|r: &mut u64| [
+                *r == old(self).a,
+                self.a == fin(r),
+                self.b == old(self).b,
+            ]);
+
+            &mut self.a
+        }
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_field_mut_return PAIR.to_string() + code_str! {
+        fn test() {
+            let mut p = Pair { a: 1, b: 2 };
+            *p.a_mut() = 5;
+            assert(p.a == 5 && p.b == 2);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_field_mut_return_wrong_ensures code! {
+        struct Pair {
+            a: u64,
+            b: u64,
+        }
+
+        fn b_mut(p: &mut Pair) -> &mut u64 {
+            ensures(|r: &mut u64| p.a == fin(r)); // FAILS
+
+            &mut p.b
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_field_mut_return_branches PAIR.to_string() + code_str! {
+        fn pick(p: &mut Pair, first: bool) -> &mut u64 {
+            ensures(|r: &mut u64| [
+                first >>= p.a == fin(r) && p.b == old(p).b,
+                !first >>= p.b == fin(r) && p.a == old(p).a,
+            ]);
+
+            if first { p.a_mut() } else { &mut p.b }
+        }
+
+        fn test() {
+            let mut p = Pair { a: 1, b: 2 };
+            *pick(&mut p, false) = 7;
+            assert(p.a == 1 && p.b == 7);
+            *pick(&mut p, true) = 8;
+            assert(p.a == 8 && p.b == 7);
+            assert(p.a == 1); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_field_borrow_arg PAIR.to_string() + code_str! {
+        fn inc(x: &mut u64) {
+            requires(*old(x) < 100);
+            ensures(*x == *old(x) + 1);
+
+            *x = *x + 1;
+        }
+
+        fn inc_a(p: &mut Pair) {
+            requires(old(p).a < 100);
+            ensures([
+                p.a == old(p).a + 1,
+                p.b == old(p).b,
+            ]);
+
+            inc(&mut p.a);
+        }
+
+        fn test() {
+            let mut p = Pair { a: 1, b: 2 };
+            inc_a(&mut p);
+            inc(&mut p.b);
+            assert(p.a == 2 && p.b == 3);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_field_borrow_arg_fails PAIR.to_string() + code_str! {
+        fn zero(x: &mut u64) {
+            ensures(*x == 0);
+
+            *x = 0;
+        }
+
+        fn test() {
+            let mut p = Pair { a: 1, b: 2 };
+            zero(&mut p.a);
+            assert(p.b == 2);
+            assert(p.a == 1); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_mut_return_let_unsupported code! {
+        use crate::pervasive::vec::*;
+
+        fn test(v: &mut Vec<u64>) {
+            requires(old(v).len() > 0);
+            let r = v.get_mut(0);
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_fin_in_requires code! {
+        fn f(x: &mut u64) -> &mut u64 {
+            requires(fin(x) == 0);
+
+            x
+        }
+    } => Err(err) => assert_vir_error(err)
+}
@@ -0,0 +1,178 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+const DIGITS: &str = code_str! {
+    use crate::pervasive::option::*;
+    use crate::pervasive::result::*;
+
+    fn parse_digit(c: char) -> Option<u32> {
+        ensures(|r: Option<u32>| [
+            r.is_Some() == ('0' <= c && c <= '9'),
+            r.is_Some() >>= r.get_Some_0() < 10,
+        ]);
+
+        if '0' <= c && c <= '9' {
+            Option::Some(c as u32 - '0' as u32)
+        } else {
+            Option::None
+        }
+    }
+
+    fn check_small(a: u64) -> Result<u64, bool> {
+        ensures(|r: Result<u64, bool>| [
+            r.is_Ok() == (a < 10),
+            r.is_Ok() >>= r.get_Ok_0() == a,
+            r.is_Err() >>= r.get_Err_0() == (a == 10),
+        ]);
+
+        if a < 10 {
+            Result::Ok(a)
+        } else {
+            Result::Err(a == 10)
+        }
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_option_combinators code! {
+        use crate::pervasive::option::*;
+        use crate::pervasive::result::*;
+
+        fn test() {
+            let x: Option<u64> = Option::Some(5);
+            let b = x.is_some();
+            assert(b);
+            let y = x.map(|a: u64| {
+                requires(a < 100);
+                ensures(|r: u64| r == a + 1);
+
+                a + 1
+            });
+            assert(y.is_Some() && y.get_Some_0() == 6);
+            let z = y.unwrap();
+            assert(z == 6);
+
+            let w: Option<u64> = Option::Some(8);
+            let h = w.and_then(|a: u64| {
+                ensures(|r: Option<u64>| [
+                    r.is_Some() == (a % 2 == 0),
+                    r.is_Some() >>= r.get_Some_0() == a / 2,
+                ]);
+
+                if a % 2 == 0 { Option::Some(a / 2) } else { Option::None }
+            });
+            assert(h.is_Some() && h.get_Some_0() == 4);
+
+            let n1: Option<u64> = Option::None;
+            let b1 = n1.is_none();
+            assert(b1);
+            let d = n1.unwrap_or(7);
+            assert(d == 7);
+            let n2: Option<u64> = Option::None;
+            let r = n2.ok_or(false);
+            assert(r.is_Err() && !r.get_Err_0());
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_result_combinators code! {
+        use crate::pervasive::option::*;
+        use crate::pervasive::result::*;
+
+        fn test() {
+            let x: Result<u64, bool> = Result::Ok(5);
+            let b = x.is_ok();
+            assert(b);
+            let y = x.map(|a: u64| {
+                requires(a < 100);
+                ensures(|r: u64| r == a * 2);
+
+                a * 2
+            });
+            let z = y.unwrap();
+            assert(z == 10);
+
+            let e: Result<u64, bool> = Result::Err(true);
+            let o = e.ok();
+            assert(o.is_None());
+            let e2: Result<u64, bool> = Result::Err(true);
+            let v = e2.unwrap_or(3);
+            assert(v == 3);
+            let e3: Result<u64, bool> = Result::Err(true);
+            let err = e3.unwrap_err();
+            assert(err);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_unwrap_none_fails code! {
+        use crate::pervasive::option::*;
+
+        fn test() {
+            let x: Option<u64> = Option::None;
+            let y = x.unwrap(); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_try_operator DIGITS.to_string() + code_str! {
+        fn parse_two(a: char, b: char) -> Option<u32> {
+            ensures(|r: Option<u32>| [
+                r.is_Some() == ('0' <= a && a <= '9' && '0' <= b && b <= '9'),
+                r.is_Some() >>= r.get_Some_0() < 100,
+            ]);
+
+            let x = parse_digit(a)?;
+            let y = parse_digit(b)?;
+            Option::Some(x * 10 + y)
+        }
+
+        fn sum_small(a: u64, b: u64) -> Result<u64, bool> {
+            ensures(|r: Result<u64, bool>| [
+                r.is_Ok() == (a < 10 && b < 10),
+                r.is_Ok() >>= r.get_Ok_0() == a + b,
+                a == 10 >>= r.is_Err() && r.get_Err_0(),
+            ]);
+
+            let x = check_small(a)?;
+            let y = check_small(b)?;
+            Result::Ok(x + y)
+        }
+
+        fn test() {
+            let r = parse_two('4', '2');
+            assert(r.is_Some());
+            let s = sum_small(10, 3);
+            assert(s.is_Err());
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_try_operator_fails DIGITS.to_string() + code_str! {
+        fn parse_two(a: char, b: char) -> Option<u32> {
+            ensures(|r: Option<u32>| r.is_Some() >>= r.get_Some_0() < 10); // FAILS
+
+            let x = parse_digit(a)?;
+            let y = parse_digit(b)?;
+            Option::Some(x * 10 + y)
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_try_user_impl_unsupported code! {
+        enum Tri { Yes(u64), No }
+
+        impl core::ops::FromResidual<Tri> for Tri {
+            fn from_residual(residual: Tri) -> Self {
+                residual
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
@@ -69,3 +69,130 @@ test_verify_one_file! {
         }
     } => Err(err) => assert_one_fails(err)
 }
+
+test_verify_one_file! {
+    #[test] test_seq_lib code! {
+        use crate::pervasive::seq::*;
+        use crate::pervasive::seq_lib::*;
+
+        #[proof]
+        fn test_seq_lib() {
+            let s = Seq::<int>::empty().push(1).push(2).push(3);
+            assert(s.first() == 1);
+            assert(s.last() == 3);
+            lemma_drop_last_push::<int>(Seq::<int>::empty().push(1).push(2), 3);
+            assert(equal(s.drop_last(), Seq::<int>::empty().push(1).push(2)));
+            assert(s.index(1) == 2);
+            assert(s.contains(2));
+            lemma_index_of::<int>(s, 2);
+            assert(s.index(s.index_of(2)) == 2);
+            let r = s.reverse();
+            assert(r.len() == 3);
+            assert(r.index(0) == 3);
+            lemma_reverse::<int>(s);
+            assert(equal(r.reverse(), s));
+            assert(s.to_set().contains(3));
+        }
+
+        #[proof]
+        fn test_filter(s: Seq<int>) {
+            lemma_filter_len(s, |x: int| x > 0);
+            assert(s.filter(|x: int| x > 0).len() <= s.len());
+            if s.filter(|x: int| x > 0).len() > 0 {
+                lemma_filter_pred(s, |x: int| x > 0, 0);
+                assert(s.filter(|x: int| x > 0).index(0) > 0);
+            }
+        }
+
+        #[proof]
+        fn test_fold(s: Seq<int>, a: int) {
+            lemma_fold_left_push(s, 0, |b: int, x: int| b + x, a);
+            assert(s.push(a).fold_left(0, |b: int, x: int| b + x)
+                == s.fold_left(0, |b: int, x: int| b + x) + a);
+        }
+
+        #[proof]
+        fn test_add(s1: Seq<int>, s2: Seq<int>) {
+            requires(s1.contains(5));
+            lemma_add_contains::<int>(s1, s2, 5);
+            assert(s1.add(s2).contains(5));
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_seq_lib_lemmas code! {
+        use crate::pervasive::seq::*;
+        use crate::pervasive::seq_lib::*;
+
+        #[proof]
+        fn test_no_duplicates(s: Seq<int>) {
+            requires([
+                s.no_duplicates(),
+                forall(|i: int| 0 <= i && i < s.len() >>= s.index(i) < 10),
+            ]);
+            lemma_no_duplicates_push::<int>(s, 10);
+            assert(s.push(10).no_duplicates());
+        }
+
+        #[proof]
+        fn test_to_multiset(s: Seq<int>) {
+            requires(s.contains(3));
+            lemma_to_multiset_len::<int>(s);
+            assert(s.to_multiset().len() == s.len());
+            lemma_to_multiset_count::<int>(s, 3);
+            assert(s.to_multiset().count(3) > 0);
+            assert(s.to_multiset().count(3) <= s.len());
+        }
+
+        #[proof]
+        fn test_fold_right(s1: Seq<int>, s2: Seq<int>) {
+            lemma_fold_right_add(s1, s2, |x: int, b: int| x + b, 0);
+            assert(s1.add(s2).fold_right(|x: int, b: int| x + b, 0)
+                == s1.fold_right(|x: int, b: int| x + b, s2.fold_right(|x: int, b: int| x + b, 0)));
+        }
+
+        #[proof]
+        fn test_reverse(s: Seq<int>) {
+            requires(s.len() > 2);
+            lemma_reverse_index::<int>(s, 0);
+            assert(s.reverse().index(0) == s.last());
+        }
+
+        #[proof]
+        fn test_subrange_contains(s: Seq<int>) {
+            requires([
+                s.len() > 2,
+                s.subrange(1, 2).contains(7),
+            ]);
+            lemma_subrange_contains::<int>(s, 1, 2, 7);
+            assert(s.contains(7));
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_seq_lib_lemmas_fails code! {
+        use crate::pervasive::seq::*;
+        use crate::pervasive::seq_lib::*;
+
+        #[proof]
+        fn test_to_multiset(s: Seq<int>) {
+            lemma_to_multiset_count::<int>(s, 3);
+            assert(s.to_multiset().count(3) < s.len()); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_seq_lib_fails code! {
+        use crate::pervasive::seq::*;
+        use crate::pervasive::seq_lib::*;
+
+        #[proof]
+        fn test_filter(s: Seq<int>) {
+            lemma_filter_len(s, |x: int| x > 0);
+            assert(s.filter(|x: int| x > 0).len() < s.len()); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
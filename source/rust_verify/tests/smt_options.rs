@@ -0,0 +1,106 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+test_verify_one_file! {
+    #[test] test_rlimit code! {
+        #[proof]
+        #[verifier(rlimit(20))]
+        fn lemma_with_rlimit(x: int, y: int) {
+            requires(x < y);
+            ensures(x + 1 <= y);
+        }
+
+        #[proof]
+        fn uses_lemma(a: int) {
+            lemma_with_rlimit(a, a + 1);
+            assert(a + 1 <= a + 1);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_smt_option code! {
+        #[proof]
+        #[verifier(smt_option("smt.random_seed", "7"))]
+        #[verifier(smt_option("smt.case_split", "1"))]
+        fn lemma_with_options(x: int) {
+            ensures(x + x == 2 * x);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_options_do_not_hide_errors code! {
+        #[proof]
+        #[verifier(rlimit(20))]
+        #[verifier(smt_option("smt.random_seed", "7"))]
+        fn lemma_with_options(x: int) {
+            assert(x + 1 > x);
+            assert(x > 0); // FAILS
+        }
+
+        #[proof]
+        fn lemma_without_options(x: int) {
+            assert(x + 1 > x);
+            assert(x < 0); // FAILS
+        }
+    } => Err(err) => assert_fails(err, 2)
+}
+
+test_verify_one_file! {
+    #[test] test_bad_rlimit code! {
+        #[proof]
+        #[verifier(rlimit(lots))]
+        fn lemma(x: int) {
+            ensures(x + 1 > x);
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bad_smt_option_value code! {
+        #[proof]
+        #[verifier(smt_option("smt.random_seed", "seven"))]
+        fn lemma(x: int) {
+            ensures(x + 1 > x);
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_options_restored code! {
+        #[proof]
+        #[verifier(smt_option("smt.arith.nl", "true"))]
+        #[verifier(smt_option("smt.random_seed", "7"))]
+        fn lemma_with_nl(x: int) {
+            ensures(x * x >= 0);
+        }
+
+        #[proof]
+        fn lemma_without_nl(x: int) {
+            ensures(x * x >= 0); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_unrecognized_smt_option code! {
+        #[proof]
+        #[verifier(smt_option("smt.no_such_option", "1"))]
+        fn lemma(x: int) {
+            ensures(x + 1 > x);
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_rlimit_too_large code! {
+        #[proof]
+        #[verifier(rlimit(5000))]
+        fn lemma(x: int) {
+            ensures(x + 1 > x);
+        }
+    } => Err(err) => assert_vir_error(err)
+}
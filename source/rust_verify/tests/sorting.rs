@@ -0,0 +1,214 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+const SORTED: &str = code_str! {
+    use crate::pervasive::vec::*;
+    use crate::pervasive::seq::*;
+    use crate::pervasive::seq_lib::*;
+
+    #[spec]
+    fn sorted(s: Seq<u64>) -> bool {
+        s.sorted_by(|x: u64, y: u64| x <= y)
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_lemmas SORTED.to_string() + code_str! {
+        #[proof]
+        fn test_swap(s: Seq<u64>) {
+            requires(s.len() == 3);
+            lemma_swap_permutation::<u64>(s, 0, 2);
+            assert(s.update(0, s.index(2)).update(2, s.index(0)).is_permutation(s));
+        }
+
+        #[proof]
+        fn test_sorted(s: Seq<u64>, a: u64) {
+            requires([
+                sorted(s),
+                forall(|i: int| 0 <= i && i < s.len() >>= s.index(i) <= a),
+            ]);
+            lemma_sorted_push(s, |x: u64, y: u64| x <= y, a);
+            assert(sorted(s.push(a)));
+            lemma_sorted_subrange(s, |x: u64, y: u64| x <= y, 0, s.len() as int);
+            assert(sorted(s.subrange(0, s.len() as int)));
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_insertion_sort SORTED.to_string() + code_str! {
+        fn insertion_sort(v: &mut Vec<u64>) {
+            ensures([
+                sorted(v.view()),
+                v.view().is_permutation(old(v).view()),
+            ]);
+
+            #[spec] let v0 = v.view();
+            let len = v.len();
+            let mut i: usize = 0;
+            while i < len {
+                invariant([
+                    len == v.len(),
+                    i <= len,
+                    forall(|a: int, b: int| 0 <= a && a < b && b < i >>= v.index(a) <= v.index(b)),
+                    v.view().is_permutation(v0),
+                ]);
+
+                let mut j: usize = i;
+                while j > 0 && *v.index(j - 1) > *v.index(j) {
+                    invariant([
+                        len == v.len(),
+                        i < len,
+                        j <= i,
+                        forall(|a: int, b: int| 0 <= a && a < b && b <= i && a != j && b != j
+                            >>= v.index(a) <= v.index(b)),
+                        forall(|b: int| j < b && b <= i >>= v.index(j) <= v.index(b)),
+                        v.view().is_permutation(v0),
+                    ]);
+
+                    lemma_swap_permutation::<u64>(v.view(), j as int - 1, j as int);
+                    v.swap(j - 1, j);
+                    j = j - 1;
+                }
+                i = i + 1;
+            }
+        }
+
+        fn test() {
+            let mut v: Vec<u64> = Vec::new();
+            v.push(3);
+            v.push(1);
+            v.push(2);
+            insertion_sort(&mut v);
+            assert(v.len() == 3);
+            assert(v.index(0) <= v.index(1));
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_merge_sort SORTED.to_string() + code_str! {
+        fn merge(a: &Vec<u64>, b: &Vec<u64>) -> Vec<u64> {
+            requires([
+                sorted(a.view()),
+                sorted(b.view()),
+            ]);
+            ensures(|r: Vec<u64>| [
+                sorted(r.view()),
+                r.view().is_permutation(a.view().add(b.view())),
+            ]);
+
+            let mut r: Vec<u64> = Vec::new();
+            let mut i: usize = 0;
+            let mut j: usize = 0;
+            while i < a.len() || j < b.len() {
+                invariant([
+                    sorted(a.view()),
+                    sorted(b.view()),
+                    i <= a.len(),
+                    j <= b.len(),
+                    r.len() == i + j,
+                    sorted(r.view()),
+                    forall(|k: int, l: int| 0 <= k && k < r.len() && i <= l && l < a.len()
+                        >>= r.index(k) <= a.index(l)),
+                    forall(|k: int, l: int| 0 <= k && k < r.len() && j <= l && l < b.len()
+                        >>= r.index(k) <= b.index(l)),
+                    r.view().is_permutation(a.view().subrange(0, i).add(b.view().subrange(0, j))),
+                ]);
+
+                #[spec] let a0 = a.view().subrange(0, i);
+                #[spec] let b0 = b.view().subrange(0, j);
+                lemma_to_multiset_add::<u64>(a0, b0);
+                if j >= b.len() || (i < a.len() && *a.index(i) <= *b.index(j)) {
+                    let x = *a.index(i);
+                    lemma_subrange_push::<u64>(a.view(), i as int);
+                    lemma_to_multiset_push::<u64>(r.view(), x);
+                    lemma_to_multiset_push::<u64>(a0, x);
+                    lemma_to_multiset_add::<u64>(a0.push(x), b0);
+                    r.push(x);
+                    i = i + 1;
+                    assert(r.view().to_multiset().ext_equal(a0.push(x).add(b0).to_multiset()));
+                } else {
+                    let x = *b.index(j);
+                    lemma_subrange_push::<u64>(b.view(), j as int);
+                    lemma_to_multiset_push::<u64>(r.view(), x);
+                    lemma_to_multiset_push::<u64>(b0, x);
+                    lemma_to_multiset_add::<u64>(a0, b0.push(x));
+                    r.push(x);
+                    j = j + 1;
+                    assert(r.view().to_multiset().ext_equal(a0.add(b0.push(x)).to_multiset()));
+                }
+            }
+            assert(a.view().subrange(0, i).ext_equal(a.view()));
+            assert(b.view().subrange(0, j).ext_equal(b.view()));
+            r
+        }
+
+        fn merge_sort(v: Vec<u64>) -> Vec<u64> {
+            ensures(|r: Vec<u64>| [
+                sorted(r.view()),
+                r.view().is_permutation(v.view()),
+            ]);
+            decreases(v.len());
+
+            let len = v.len();
+            if len <= 1 {
+                return v;
+            }
+            let mid = len / 2;
+
+            let mut left: Vec<u64> = Vec::new();
+            let mut k: usize = 0;
+            while k < mid {
+                invariant([
+                    mid <= v.len(),
+                    k <= mid,
+                    equal(left.view(), v.view().subrange(0, k)),
+                ]);
+                lemma_subrange_push::<u64>(v.view(), k as int);
+                left.push(*v.index(k));
+                k = k + 1;
+            }
+
+            let mut right: Vec<u64> = Vec::new();
+            let mut j: usize = mid;
+            while j < len {
+                invariant([
+                    len == v.len(),
+                    mid <= j,
+                    j <= len,
+                    equal(right.view(), v.view().subrange(mid, j)),
+                ]);
+                right.push(*v.index(j));
+                assert(right.view().ext_equal(v.view().subrange(mid, j + 1)));
+                j = j + 1;
+            }
+
+            let sorted_left = merge_sort(left);
+            let sorted_right = merge_sort(right);
+            lemma_subrange_add::<u64>(v.view(), mid as int);
+            lemma_add_permutation::<u64>(
+                sorted_left.view(),
+                sorted_right.view(),
+                v.view().subrange(0, mid),
+                v.view().subrange(mid, len),
+            );
+            merge(&sorted_left, &sorted_right)
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_not_sorted_fails SORTED.to_string() + code_str! {
+        fn bogus_sort(v: Vec<u64>) -> Vec<u64> {
+            ensures(|r: Vec<u64>| [
+                sorted(r.view()), // FAILS
+                r.view().is_permutation(v.view()),
+            ]);
+
+            v
+        }
+    } => Err(err) => assert_one_fails(err)
+}
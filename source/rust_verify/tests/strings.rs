@@ -0,0 +1,138 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+const DIGITS: &str = code_str! {
+    use crate::pervasive::seq::*;
+    use crate::pervasive::string::*;
+
+    #[spec]
+    fn is_digit(c: char) -> bool {
+        '0' <= c && c <= '9'
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_chars code! {
+        #[proof]
+        fn test_range(c: char) {
+            assert(c as u32 <= 0x10FFFF);
+            assert(c as u32 != 0xD800);
+        }
+
+        fn test_lit() {
+            let c = 'a';
+            assert(c as u32 == 97);
+            assert(c < 'b');
+            let x: u8 = 0x41;
+            let d = x as char;
+            assert(d == 'A');
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_chars_fails code! {
+        #[proof]
+        fn test_range(c: char) {
+            assert(c as u32 < 0xD800); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_string code! {
+        use crate::pervasive::seq::*;
+        use crate::pervasive::string::*;
+
+        fn test() {
+            let mut s = String::new();
+            s.push('h');
+            s.push('i');
+            assert(s.len() == 2);
+            let c = s.get_char(1);
+            assert(c == 'i');
+            let t = s.as_str();
+            assert(t.view().index(0) == 'h');
+            let u = t.to_string();
+            let b = u.eq(&s);
+            assert(b);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_string_fails code! {
+        use crate::pervasive::seq::*;
+        use crate::pervasive::string::*;
+
+        fn test() {
+            let mut s = String::new();
+            s.push('h');
+            s.push('i');
+            let c = s.get_char(1);
+            assert(c == 'h'); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_parser DIGITS.to_string() + code_str! {
+        fn all_digits(s: &StrSlice) -> bool {
+            ensures(|r: bool|
+                r == forall(|i: int| 0 <= i && i < s.view().len() >>= is_digit(s.view().index(i))));
+
+            let len = s.len();
+            let mut ok = true;
+            let mut i: usize = 0;
+            while i < len {
+                invariant([
+                    len == s.view().len(),
+                    i <= len,
+                    ok == forall(|j: int| 0 <= j && j < i >>= is_digit(s.view().index(j))),
+                ]);
+
+                let c = s.get_char(i);
+                if c < '0' || c > '9' {
+                    ok = false;
+                }
+                i = i + 1;
+            }
+            ok
+        }
+
+        fn digit_value(c: char) -> u32 {
+            requires(is_digit(c));
+            ensures(|r: u32| [
+                r < 10,
+                r == c as u32 - '0' as u32,
+            ]);
+
+            c as u32 - '0' as u32
+        }
+
+        fn test() {
+            let mut s = String::new();
+            s.push('4');
+            s.push('2');
+            let t = s.as_str();
+            assert(t.view().index(0) == '4' && t.view().index(1) == '2');
+            let b = all_digits(&t);
+            assert(b);
+            let d = digit_value(s.get_char(0));
+            assert(d == 4);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_parser_fails DIGITS.to_string() + code_str! {
+        fn digit_value(c: char) -> u32 {
+            requires('0' <= c);
+            ensures(|r: u32| r < 10); // FAILS
+
+            c as u32 - '0' as u32
+        }
+    } => Err(err) => assert_one_fails(err)
+}
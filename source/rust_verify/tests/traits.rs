@@ -3,26 +3,6 @@
 mod common;
 use common::*;
 
-test_verify_one_file! {
-    #[test] test_not_yet_supported_1 code! {
-        trait T1 {}
-        trait T2 {
-            // need to add A: T1 to termination checking before supporting this
-            fn f<A: T1>(a: &A) {
-            }
-        }
-    } => Err(err) => assert_vir_error(err)
-}
-
-test_verify_one_file! {
-    #[test] test_not_yet_supported_2 code! {
-        trait T1 {}
-        // need to add A: T1 to termination checking before supporting this
-        trait T2<A: T1> {
-        }
-    } => Err(err) => assert_vir_error(err)
-}
-
 test_verify_one_file! {
     #[test] test_not_yet_supported_3 code! {
         trait T1 {}
@@ -33,25 +13,6 @@ test_verify_one_file! {
     } => Err(err) => assert_vir_error(err)
 }
 
-test_verify_one_file! {
-    #[test] test_not_yet_supported_4 code! {
-        trait T1 {}
-        trait T2 {
-            fn f(&self) {
-                no_method_body()
-            }
-        }
-        struct S2<A> {
-            a: A,
-        }
-        impl<A: T1> T2 for S2<A> {
-            // might need to add A: T1 to termination checking before supporting this
-            fn f(&self) {
-            }
-        }
-    } => Err(err) => assert_vir_error(err)
-}
-
 test_verify_one_file! {
     #[test] test_not_yet_supported_5 code! {
         trait T1 {
@@ -224,38 +185,6 @@ test_verify_one_file! {
     } => Err(err) => assert_vir_error(err)
 }
 
-test_verify_one_file! {
-    #[test] test_ill_formed_8 code! {
-        trait T1 {
-            fn f(&self) {
-                no_method_body()
-            }
-        }
-        struct S {}
-        impl T1 for S {
-            fn f(&self) {
-                requires(true); // no requires allowed
-            }
-        }
-    } => Err(err) => assert_vir_error(err)
-}
-
-test_verify_one_file! {
-    #[test] test_ill_formed_9 code! {
-        trait T1 {
-            fn f(&self) {
-                no_method_body()
-            }
-        }
-        struct S {}
-        impl T1 for S {
-            fn f(&self) {
-                ensures(true); // no ensures allowed
-            }
-        }
-    } => Err(err) => assert_vir_error(err)
-}
-
 test_verify_one_file! {
     #[test] test_mode_matches_1 code! {
         trait T1 {
@@ -731,6 +660,165 @@ test_verify_one_file! {
     } => Err(err) => assert_fails(err, 2)
 }
 
+const REFINE: &str = code_str! {
+    trait T {
+        fn f(&self, x: u64) -> u64 {
+            requires(x < 10);
+            ensures(|r: u64| r < 20);
+            no_method_body()
+        }
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_refine_ok REFINE.to_string() + code_str! {
+        struct S {}
+        impl T for S {
+            fn f(&self, x: u64) -> u64 {
+                requires(x < 100);
+                ensures(|y: u64| y == x + 1);
+                x + 1
+            }
+        }
+        fn test1(s: &S) {
+            let y = s.f(50);
+            assert(y == 51);
+        }
+        fn test2<A: T>(a: &A) {
+            let y = a.f(5);
+            assert(y < 20);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_refine_generic_caller_uses_trait_spec REFINE.to_string() + code_str! {
+        struct S {}
+        impl T for S {
+            fn f(&self, x: u64) -> u64 {
+                requires(x < 100);
+                ensures(|y: u64| y == x + 1);
+                x + 1
+            }
+        }
+        fn test<A: T>(a: &A) {
+            let y = a.f(50); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_refine_requires_fails REFINE.to_string() + code_str! {
+        struct S {}
+        impl T for S {
+            fn f(&self, x: u64) -> u64 {
+                requires(x < 5); // FAILS
+                ensures(|y: u64| y == x + 1);
+                x + 1
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_refine_ensures_fails code! {
+        trait T {
+            fn f(&self, x: u64) -> u64 {
+                requires(x < 10);
+                ensures(|r: u64| r < 20); // FAILS
+                no_method_body()
+            }
+        }
+        struct S {}
+        impl T for S {
+            fn f(&self, x: u64) -> u64 {
+                requires(x < 10);
+                ensures(|y: u64| y == x + 15);
+                x + 15
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_refine_body_fails REFINE.to_string() + code_str! {
+        struct S {}
+        impl T for S {
+            fn f(&self, x: u64) -> u64 {
+                requires(x < 10);
+                ensures(|y: u64| y == x + 1); // FAILS
+                x + 2
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_refine_requires_only REFINE.to_string() + code_str! {
+        struct S {}
+        impl T for S {
+            fn f(&self, x: u64) -> u64 {
+                requires(x < 100); // ensures inherited from T
+                if x < 19 { x + 1 } else { 0 }
+            }
+        }
+        fn test(s: &S) {
+            let y = s.f(50);
+            assert(y < 20);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_refine_requires_only_fails code! {
+        trait T {
+            fn f(&self, x: u64) -> u64 {
+                requires(x < 10);
+                ensures(|r: u64| r < 20); // FAILS
+                no_method_body()
+            }
+        }
+        struct S {}
+        impl T for S {
+            fn f(&self, x: u64) -> u64 {
+                requires(x < 100); // ensures inherited from T
+                x + 1
+            }
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_refine_ensures_only REFINE.to_string() + code_str! {
+        struct S {}
+        impl T for S {
+            fn f(&self, x: u64) -> u64 {
+                ensures(|y: u64| y == x + 1); // requires inherited from T
+                x + 1
+            }
+        }
+        fn test(s: &S) {
+            let y = s.f(5);
+            assert(y == 6);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_refine_ensures_only_fails REFINE.to_string() + code_str! {
+        struct S {}
+        impl T for S {
+            fn f(&self, x: u64) -> u64 {
+                ensures(|y: u64| y == x + 1); // requires inherited from T
+                x + 1
+            }
+        }
+        fn test(s: &S) {
+            let y = s.f(50); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
 test_verify_one_file! {
     #[test] test_multiple code! {
         trait T1 {
@@ -989,3 +1077,441 @@ test_verify_one_file! {
         }
     } => Err(err) => assert_fails(err, 3)
 }
+
+const BOUNDS: &str = code_str! {
+    trait T1 {
+        #[spec]
+        fn g(&self) -> u64 { no_method_body() }
+
+        fn get(&self) -> u64 {
+            ensures(|r: u64| r == self.g());
+            no_method_body()
+        }
+    }
+
+    struct X { x: u64 }
+
+    impl T1 for X {
+        #[spec]
+        fn g(&self) -> u64 { self.x }
+
+        fn get(&self) -> u64 { self.x }
+    }
+};
+
+test_verify_one_file! {
+    #[test] test_bounds_method_type_param BOUNDS.to_string() + code_str! {
+        trait T2 {
+            fn f<A: T1>(&self, a: &A) -> u64 {
+                ensures(|r: u64| r == a.g());
+                no_method_body()
+            }
+        }
+
+        struct S {}
+
+        impl T2 for S {
+            fn f<B: T1>(&self, a: &B) -> u64 {
+                a.get()
+            }
+        }
+
+        fn test() {
+            let s = S {};
+            let x = X { x: 7 };
+            let r = s.f(&x);
+            assert(r == 7);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_trait_type_param BOUNDS.to_string() + code_str! {
+        trait T2<A: T1> {
+            fn f(&self, a: &A) -> u64 {
+                ensures(|r: u64| r == a.g());
+                no_method_body()
+            }
+        }
+
+        struct S {}
+
+        impl T2<X> for S {
+            fn f(&self, a: &X) -> u64 {
+                a.get()
+            }
+        }
+
+        fn p<A: T1, B: T2<A>>(a: &A, b: &B) {
+            let r = b.f(a);
+            assert(r == a.g());
+            assert(r == 7); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_impl_type_param BOUNDS.to_string() + code_str! {
+        trait T2 {
+            fn f(&self) -> u64 {
+                no_method_body()
+            }
+        }
+
+        struct S2<A> {
+            a: A,
+        }
+
+        impl<A: T1> T2 for S2<A> {
+            fn f(&self) -> u64 {
+                ensures(|r: u64| r == self.a.g());
+                self.a.get()
+            }
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_where_clause BOUNDS.to_string() + code_str! {
+        fn p<A>(a: &A) -> u64
+        where
+            A: T1,
+        {
+            ensures(|r: u64| r == a.g());
+            a.get()
+        }
+
+        fn test() {
+            let x = X { x: 7 };
+            let r = p(&x);
+            assert(r == 7);
+            assert(r == 8); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_where_clause_unsupported BOUNDS.to_string() + code_str! {
+        fn p<A>(a: &A)
+        where
+            X: T1,
+        {
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_termination_fail code! {
+        trait T1 {
+            #[spec]
+            fn f1(&self) -> bool { no_method_body() }
+        }
+
+        trait T2<A: T1> {
+            #[spec]
+            fn f2(&self, a: &A) -> bool { no_method_body() }
+        }
+
+        struct S {}
+
+        #[spec]
+        fn g<B: T2<S>>(b: &B) -> bool {
+            true
+        }
+
+        impl T1 for S {
+            #[spec]
+            fn f1(&self) -> bool {
+                // S: T1 depends on S: T2<S>, which depends on S: T1
+                g(self)
+            }
+        }
+
+        impl T2<S> for S {
+            #[spec]
+            fn f2(&self, a: &S) -> bool {
+                true
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_termination_trait_type_param_fail code! {
+        trait T1 {
+            #[spec]
+            fn f1(&self) -> bool { no_method_body() }
+        }
+
+        trait T2<A: T1> {
+            #[spec]
+            fn f2(&self, a: &A) -> bool { no_method_body() }
+        }
+
+        struct S {}
+        struct U {}
+
+        #[spec]
+        fn call_f2<B: T2<S>>(b: &B, a: &S) -> bool {
+            b.f2(a)
+        }
+
+        impl T2<S> for U {
+            #[spec]
+            fn f2(&self, a: &S) -> bool {
+                // U: T2<S> depends on S: T1
+                a.f1()
+            }
+        }
+
+        impl T1 for S {
+            #[spec]
+            fn f1(&self) -> bool {
+                call_f2(&U {}, self)
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_termination_where_clause_fail code! {
+        trait T {
+            #[spec]
+            fn f(&self) -> bool { no_method_body() }
+        }
+
+        #[spec]
+        fn rec<A>(x: &A) -> bool
+        where
+            A: T,
+        {
+            x.f()
+        }
+
+        struct S {}
+
+        impl T for S {
+            #[spec]
+            fn f(&self) -> bool {
+                rec(self)
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_termination_assoc_type_fail code! {
+        trait Store {
+            type Item;
+
+            #[spec]
+            fn view(&self) -> bool { no_method_body() }
+        }
+
+        trait T {
+            #[spec]
+            fn f(&self) -> bool { no_method_body() }
+        }
+
+        #[spec]
+        fn call_f<A: T>(a: &A) -> bool {
+            a.f()
+        }
+
+        struct S {}
+        struct U {}
+
+        impl Store for S {
+            type Item = U;
+
+            #[spec]
+            fn view(&self) -> bool {
+                call_f::<<S as Store>::Item>(&U {})
+            }
+        }
+
+        impl T for U {
+            #[spec]
+            fn f(&self) -> bool {
+                (S {}).view()
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_termination_impl_type_param_fail code! {
+        trait T1 {
+            #[spec]
+            fn f1(&self) -> bool { no_method_body() }
+        }
+
+        trait T2 {
+            #[spec]
+            fn f2(&self) -> bool { no_method_body() }
+        }
+
+        #[spec]
+        fn call_f1<B: T1>(b: &B) -> bool {
+            b.f1()
+        }
+
+        struct D<A> {
+            a: A,
+        }
+
+        impl<A: T2> T1 for D<A> {
+            #[spec]
+            fn f1(&self) -> bool {
+                self.a.f2()
+            }
+        }
+
+        struct E {}
+
+        impl T2 for E {
+            #[spec]
+            fn f2(&self) -> bool {
+                // building the dictionary for D<E>: T1 requires E: T2
+                call_f1(&D { a: E {} })
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_impl_type_param_recursive_ok code! {
+        trait T {
+            #[spec]
+            fn f(&self) -> bool { no_method_body() }
+        }
+
+        struct D<A> {
+            a: A,
+        }
+
+        impl<A: T> T for D<A> {
+            #[spec]
+            fn f(&self) -> bool {
+                self.a.f()
+            }
+        }
+
+        struct E {}
+
+        impl T for E {
+            #[spec]
+            fn f(&self) -> bool {
+                true
+            }
+        }
+
+        #[spec]
+        fn call_f<B: T>(b: &B) -> bool {
+            b.f()
+        }
+
+        #[spec]
+        fn test() -> bool {
+            call_f(&D { a: D { a: E {} } })
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_bounds_impl_type_param_unsupported code! {
+        trait T1 {
+            #[spec]
+            fn f1(&self) -> bool { no_method_body() }
+        }
+
+        trait T2<A> {
+            #[spec]
+            fn f2(&self, a: &A) -> bool { no_method_body() }
+        }
+
+        struct S {}
+
+        impl<A: T1> T2<A> for S {
+            #[spec]
+            fn f2(&self, a: &A) -> bool {
+                a.f1()
+            }
+        }
+    } => Err(err) => assert_vir_error(err)
+}
+
+test_verify_one_file! {
+    #[test] test_assoc_type_ok code! {
+        trait Store {
+            type Item;
+
+            #[spec]
+            fn view(&self) -> Self::Item { no_method_body() }
+
+            fn get(&self) -> Self::Item {
+                ensures(|r: Self::Item| equal(r, self.view()));
+                no_method_body()
+            }
+        }
+
+        struct S { x: u64 }
+
+        impl Store for S {
+            type Item = u64;
+
+            #[spec]
+            fn view(&self) -> u64 { self.x }
+
+            fn get(&self) -> u64 { self.x }
+        }
+
+        fn p<A: Store>(a: &A) -> A::Item {
+            ensures(|r: A::Item| equal(r, a.view()));
+            a.get()
+        }
+
+        fn test() {
+            let s = S { x: 7 };
+            let r = p(&s);
+            assert(r == 7);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_assoc_type_fail code! {
+        trait Store {
+            type Item;
+
+            #[spec]
+            fn view(&self) -> Self::Item { no_method_body() }
+        }
+
+        struct S { x: u64 }
+
+        impl Store for S {
+            type Item = u64;
+
+            #[spec]
+            fn view(&self) -> u64 { self.x }
+        }
+
+        fn p<A: Store>(a1: &A, a2: &A) {
+            assert(equal(a1.view(), a2.view())); // FAILS
+        }
+
+        fn test(s1: &S, s2: &S) {
+            requires(s1.x == s2.x);
+            assert(s1.view() == s2.view());
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_assoc_type_bounds_unsupported code! {
+        trait Store {
+            type Item: Copy;
+        }
+    } => Err(err) => assert_vir_error(err)
+}
@@ -0,0 +1,158 @@
+#![feature(rustc_private)]
+#[macro_use]
+mod common;
+use common::*;
+
+test_verify_one_file! {
+    #[test] test_push_loop code! {
+        use crate::pervasive::vec::*;
+
+        fn test(n: usize) -> Vec<usize> {
+            ensures(|v: Vec<usize>| [
+                v.len() == n,
+                forall(|i: int| 0 <= i && i < n >>= v.index(i) == i),
+            ]);
+
+            let mut v: Vec<usize> = Vec::new();
+            for i in 0..n {
+                invariant([
+                    v.len() == i,
+                    forall(|j: int| 0 <= j && j < i >>= v.index(j) == j),
+                ]);
+                v.push(i);
+            }
+            v
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_ops code! {
+        use crate::pervasive::vec::*;
+
+        fn test() {
+            let mut v: Vec<u64> = Vec::with_capacity(10);
+            v.push(1);
+            v.push(2);
+            v.push(3);
+            assert(v.len() == 3);
+            v.insert(1, 5);
+            assert(v.len() == 4);
+            assert(v.index(1) == 5 && v.index(2) == 2);
+            let x = v.remove(0);
+            assert(x == 1);
+            assert(v.len() == 3 && v.index(0) == 5);
+            v.swap(0, 2);
+            assert(v.index(0) == 3 && v.index(2) == 5);
+            v.set(1, 7);
+            let y = v.pop();
+            assert(y == 5);
+            assert(v.len() == 2 && v.index(1) == 7);
+            v.truncate(1);
+            assert(v.len() == 1 && v.index(0) == 3);
+            v.truncate(4);
+            assert(v.len() == 1);
+            v.clear();
+            assert(v.len() == 0);
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_pop_loop code! {
+        use crate::pervasive::vec::*;
+
+        fn reverse(v0: Vec<u64>) -> Vec<u64> {
+            ensures(|r: Vec<u64>| [
+                r.len() == v0.len(),
+                forall(|i: int| 0 <= i && i < v0.len() >>= r.index(i) == v0.index(v0.len() - 1 - i)),
+            ]);
+
+            let mut v = v0;
+            let mut r: Vec<u64> = Vec::new();
+            while v.len() > 0 {
+                invariant([
+                    v.len() + r.len() == v0.len(),
+                    forall(|i: int| 0 <= i && i < v.len() >>= v.index(i) == v0.index(i)),
+                    forall(|i: int| 0 <= i && i < r.len() >>= r.index(i) == v0.index(v0.len() - 1 - i)),
+                ]);
+                let x = v.pop();
+                r.push(x);
+            }
+            r
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_remove_loop code! {
+        use crate::pervasive::vec::*;
+
+        fn remove_all(v: Vec<u64>) -> Vec<u64> {
+            ensures(|r: Vec<u64>| r.len() == 0);
+
+            let mut r = v;
+            while r.len() > 0 {
+                r.remove(0);
+            }
+            r
+        }
+    } => Ok(())
+}
+
+test_verify_one_file! {
+    #[test] test_pop_empty_fails code! {
+        use crate::pervasive::vec::*;
+
+        fn test() {
+            let mut v: Vec<u64> = Vec::new();
+            v.push(1);
+            v.pop();
+            v.pop(); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_insert_fails code! {
+        use crate::pervasive::vec::*;
+
+        fn test() {
+            let mut v: Vec<u64> = Vec::new();
+            v.push(1);
+            v.insert(1, 2);
+            v.insert(3, 3); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_set_fails code! {
+        use crate::pervasive::vec::*;
+
+        fn test() {
+            let mut v: Vec<u64> = Vec::new();
+            v.push(1);
+            v.push(2);
+            v.set(0, 3);
+            assert(v.index(0) == 3);
+            assert(v.index(1) == 1); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
+
+test_verify_one_file! {
+    #[test] test_update_by_value code! {
+        use crate::pervasive::vec::*;
+
+        fn test() {
+            let mut v: Vec<u64> = Vec::new();
+            v.push(1);
+            v.push(2);
+            let v2 = v.update(1, 5);
+            assert(v2.len() == 2);
+            assert(v2.index(0) == 1 && v2.index(1) == 5);
+            let v3 = v2.update(2, 5); // FAILS
+        }
+    } => Err(err) => assert_one_fails(err)
+}
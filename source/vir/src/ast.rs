@@ -62,6 +62,8 @@ pub enum IntRange {
     USize,
     /// Rust's isize type
     ISize,
+    /// Rust's char type: Unicode scalar values 0..=0xD7FF and 0xE000..=0x10FFFF
+    Char,
 }
 
 /// Rust type, but without Box, Rc, Arc, etc.
@@ -79,10 +81,20 @@ pub enum TypX {
     Lambda(Typs, Typ),
     /// Datatype (concrete or abstract) applied to type arguments
     Datatype(Path, Typs),
+    /// Fixed-size array [typ; len], encoded as pervasive::seq::Seq<typ> whose length is len
+    /// (len is a ConstInt or a const generic TypParam)
+    Array(Typ, Typ),
     /// Boxed for SMT encoding (unrelated to Rust Box type), can be unboxed:
     Boxed(Typ),
     /// Type parameter (inherently SMT-boxed, and cannot be unboxed)
+    /// (also used for const generic parameters, such as N in struct S<const N: usize>)
     TypParam(Ident),
+    /// Constant integer argument to a const generic parameter, such as 3 in S<3>
+    /// (non-negative integer of arbitrary size, like Constant::Nat)
+    ConstInt(Arc<String>),
+    /// Associated type <typs[0] as trait_path<typs[1..]>>::name, where typs[0] is the Self type
+    /// (inherently SMT-boxed, and cannot be unboxed)
+    Projection(Path, Typs, Ident),
     /// Type of type identifiers
     TypeId,
     /// AIR type, used internally during translation
@@ -265,11 +277,25 @@ pub enum CallTarget {
     /// Call a dynamically computed FnSpec (no type arguments allowed),
     /// where the function type is specified by the GenericBound of typ_param.
     FnSpec(Expr),
+    /// Call a dynamically computed exec closure (no type arguments allowed),
+    /// relying on the closure's requires/ensures (see ClosureSpec)
+    FnExec(Expr),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClosureSpec {
+    /// f.requires(args)
+    Requires,
+    /// f.ensures(args, ret)
+    Ensures,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum VarAt {
     Pre,
+    /// Final value of a returned &mut at the end of the borrow (prophecy).
+    /// Note: ast_simplify replaces this with Var of an extra parameter.
+    Final,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -278,6 +304,23 @@ pub enum InvAtomicity {
     NonAtomic,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopInvariantKind {
+    /// Holds on entry and at the start of each iteration, but not necessarily after a break
+    InvariantExceptBreak,
+    /// Holds on entry, at the start of each iteration, and when the loop exits
+    InvariantAndEnsures,
+    /// Holds when the loop exits (either via break or, for while loops, when the condition fails)
+    Ensures,
+}
+
+#[derive(Clone, Debug)]
+pub struct LoopInvariant {
+    pub kind: LoopInvariantKind,
+    pub inv: Expr,
+}
+pub type LoopInvariants = Arc<Vec<LoopInvariant>>;
+
 /// Expression, similar to rustc_hir::Expr
 pub type Expr = Arc<SpannedTyped<ExprX>>;
 pub type Exprs = Arc<Vec<Expr>>;
@@ -293,6 +336,8 @@ pub enum ExprX {
     VarAt(Ident, VarAt),
     /// Use of a const variable.  Note: ast_simplify replaces this with Call.
     ConstVar(Fun),
+    /// Use of a const generic parameter as an integer value
+    ConstParam(Ident),
     /// Mutable reference (location)
     Loc(Expr),
     /// Call to a function passing some expression arguments
@@ -314,9 +359,22 @@ pub enum ExprX {
     Quant(Quant, Binders<Typ>, Expr),
     /// Specification closure
     Closure(Binders<Typ>, Expr),
+    /// Exec closure with parameters, requires, ensures (naming the return value ret), and body
+    ExecClosure {
+        params: Binders<Typ>,
+        require: Exprs,
+        ensure: Exprs,
+        ret: Binder<Typ>,
+        body: Expr,
+    },
+    /// Precondition or postcondition of an exec closure, applied to the arguments
+    /// (for ClosureSpec::Ensures, the last argument is the return value)
+    ClosureSpec(ClosureSpec, Expr, Exprs),
     /// Choose specification values satisfying a condition, compute body
     Choose { params: Binders<Typ>, cond: Expr, body: Expr },
-    /// Assign to local variable
+    /// Assign to local variable,
+    /// or write through the &mut returned by a call (`*f(...) = e`)
+    /// Note: ast_simplify replaces the latter with a Call that passes the final value
     Assign(Expr, Expr),
     /// Reveal definition of an opaque function with some integer fuel amount
     Fuel(Fun, u32),
@@ -334,8 +392,27 @@ pub enum ExprX {
     If(Expr, Expr, Option<Expr>),
     /// Match (Note: ast_simplify replaces Match with other expressions)
     Match(Expr, Arms),
-    /// While loop, with invariants
-    While { cond: Expr, body: Expr, invs: Exprs },
+    /// Loop, with optional label, optional while condition, optional step, and invariants
+    /// (the step runs after the body and before each continue, as in a for loop)
+    Loop {
+        label: Option<Ident>,
+        cond: Option<Expr>,
+        body: Expr,
+        step: Option<Expr>,
+        invs: LoopInvariants,
+    },
+    /// For loop over the integer range start..end, with optional label and invariants
+    /// (Note: ast_simplify replaces ForRange with Loop)
+    ForRange {
+        label: Option<Ident>,
+        var: Binder<Typ>,
+        start: Expr,
+        end: Expr,
+        body: Expr,
+        invs: LoopInvariants,
+    },
+    /// Break or continue, targeting the innermost loop or the loop with the given label
+    BreakOrContinue { label: Option<Ident>, is_break: bool },
     /// Open invariant
     OpenInvariant(Expr, Binder<Typ>, Expr, InvAtomicity),
     /// Return from function
@@ -365,7 +442,7 @@ pub struct ParamX {
     pub name: Ident,
     pub typ: Typ,
     pub mode: Mode,
-    /// An &mut parameter
+    /// An &mut parameter, or (for FunctionX::ret) an &mut return value
     pub is_mut: bool,
 }
 
@@ -377,6 +454,9 @@ pub enum GenericBoundX {
     /// Spec function type (t1, ..., tn) -> t0.
     /// Note: ast_simplify removes FnSpec type parameters, using a Datatype to represent FnSpec.
     FnSpec(Typs, Typ),
+    /// Const generic parameter (const N: t), where t is an unsigned integer type.
+    /// N is a type parameter, and its value, of type t, is available as ExprX::ConstParam.
+    Const(Typ),
 }
 
 pub type TypBounds = Arc<Vec<(Ident, GenericBound)>>;
@@ -400,6 +480,10 @@ pub struct FunctionAttrsX {
     pub bit_vector: bool,
     /// Is atomic (i.e., can be inside an invariant block)
     pub atomic: bool,
+    /// SMT resource limit (in the same units as --rlimit) for this function's queries
+    pub rlimit: Option<u32>,
+    /// SMT solver options set just for this function's queries
+    pub smt_options: Vec<(String, String)>,
 }
 
 /// Function specification of its invariant mask
@@ -515,6 +599,21 @@ pub struct TraitX {
     pub name: Path,
     pub typ_params: TypPositiveBounds,
     pub methods: Arc<Vec<Fun>>,
+    /// Names of the trait's associated types (type Item;)
+    pub assoc_typs: Idents,
+}
+
+/// Definition of an associated type in an impl of a trait for a datatype
+/// (impl<typ_params> trait_path<trait_typ_args> for self_typ { type name = typ; })
+pub type AssocTypeImpl = Arc<Spanned<AssocTypeImplX>>;
+#[derive(Clone, Debug)]
+pub struct AssocTypeImplX {
+    pub name: Ident,
+    pub typ_params: TypBounds,
+    pub self_typ: Typ,
+    pub trait_path: Path,
+    pub trait_typ_args: Typs,
+    pub typ: Typ,
 }
 
 /// An entire crate
@@ -527,6 +626,8 @@ pub struct KrateX {
     pub datatypes: Vec<Datatype>,
     /// All traits in the crate
     pub traits: Vec<Trait>,
+    /// All associated type definitions in the crate's trait impls
+    pub assoc_type_impls: Vec<AssocTypeImpl>,
     /// List of all modules in the crate
     pub module_ids: Vec<Path>,
 }
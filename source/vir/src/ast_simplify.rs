@@ -1,13 +1,18 @@
 //! VIR-AST -> VIR-AST transformation to simplify away some complicated features
 
 use crate::ast::{
-    BinaryOp, Binder, CallTarget, Constant, Datatype, DatatypeTransparency, DatatypeX, Expr, ExprX,
-    Field, Function, GenericBound, GenericBoundX, Ident, Krate, KrateX, Mode, Path, Pattern,
-    PatternX, SpannedTyped, Stmt, StmtX, Typ, TypX, UnaryOp, UnaryOpr, VirErr, Visibility,
+    Arm, ArmX, AssocTypeImpl, BinaryOp, Binder, CallTarget, Constant, Datatype,
+    DatatypeTransparency, DatatypeX, Expr, ExprX, Field, Function, FunctionX, GenericBound,
+    GenericBoundX, Ident, IntRange, Krate, KrateX, LoopInvariant, LoopInvariantKind, Mode, ParamX,
+    Params, Path, Pattern, PatternX, SpannedTyped, Stmt, StmtX, Typ, TypX, UnaryOp, UnaryOpr,
+    VarAt, VirErr, Visibility,
 };
-use crate::ast_util::{err_str, err_string};
+use crate::ast_util::{err_str, err_string, get_variant, is_unit};
+use crate::ast_visitor::VisitorScopeMap;
 use crate::context::GlobalCtx;
-use crate::def::{prefix_tuple_field, prefix_tuple_param, prefix_tuple_variant, Spanned};
+use crate::def::{
+    prefix_tuple_field, prefix_tuple_param, prefix_tuple_variant, Spanned, FINAL_VALUE,
+};
 use crate::util::vec_map_result;
 use air::ast::Span;
 use air::ast_util::ident_binder;
@@ -52,7 +57,7 @@ struct LocalCtxt {
 
 fn is_small_expr(expr: &Expr) -> bool {
     match &expr.x {
-        ExprX::Const(_) | ExprX::Var(_) | ExprX::VarAt(..) => true,
+        ExprX::Const(_) | ExprX::Var(_) | ExprX::VarAt(..) | ExprX::ConstParam(_) => true,
         ExprX::Unary(UnaryOp::Not | UnaryOp::Clip(_), e) => is_small_expr(e),
         ExprX::UnaryOpr(UnaryOpr::Box(_) | UnaryOpr::Unbox(_), e) => is_small_expr(e),
         ExprX::Loc(_) => panic!("expr contains a location"),
@@ -108,6 +113,7 @@ fn small_loc_or_temp(state: &mut State, expr: &Expr) -> SmallLocOrTemp {
     let contains_loc = match &expr.x {
         ExprX::Const(..) => None,
         ExprX::ConstVar(..) => None,
+        ExprX::ConstParam(..) => None,
         ExprX::Var(..) => None,
         ExprX::VarLoc(..) => Some((vec![], expr.clone())),
         ExprX::VarAt(..) => None,
@@ -168,6 +174,8 @@ fn small_loc_or_temp(state: &mut State, expr: &Expr) -> SmallLocOrTemp {
         ExprX::Closure(params, body) => small_loc_or_temp(state, body)
             .map_expr(|b| expr.new_x(ExprX::Closure(params.clone(), b)))
             .to_opt(),
+        ExprX::ExecClosure { .. } => None,
+        ExprX::ClosureSpec(..) => None,
         ExprX::Choose { .. } => None,
         ExprX::Assign(e1, e2) => {
             let (stmts, exprs, contains_loc) =
@@ -195,7 +203,9 @@ fn small_loc_or_temp(state: &mut State, expr: &Expr) -> SmallLocOrTemp {
         | ExprX::AssertBV(..)
         | ExprX::Header(_)
         | ExprX::Match(..)
-        | ExprX::While { .. }
+        | ExprX::Loop { .. }
+        | ExprX::ForRange { .. }
+        | ExprX::BreakOrContinue { .. }
         | ExprX::Return(..)
         | ExprX::Block(..)
         | ExprX::OpenInvariant(..) => {
@@ -219,6 +229,7 @@ fn keep_bound(bound: &GenericBound) -> bool {
     match &**bound {
         GenericBoundX::Traits(_) => true,
         GenericBoundX::FnSpec(..) => false,
+        GenericBoundX::Const(_) => true,
     }
 }
 
@@ -416,6 +427,71 @@ fn simplify_one_expr(ctx: &GlobalCtx, state: &mut State, expr: &Expr) -> Result<
                 err_str(&expr.span, "not yet implemented: zero-arm match expressions")
             }
         }
+        ExprX::ForRange { label, var, start, end, body, invs } => {
+            // Translate into a loop over a mutable counter:
+            //   let start = ...; let end = ...; let mut var = start;
+            //   loop while var < end, stepping var = var + 1 after the body and at each continue,
+            //   with the invariants start <= var && (start <= end ==> var <= end)
+            let range = match &*var.a {
+                TypX::Int(range) => *range,
+                _ => return err_str(&expr.span, "for loop variable must have an integer type"),
+            };
+            let t_bool = Arc::new(TypX::Bool);
+            let mk_bool = |x: ExprX| SpannedTyped::new(&expr.span, &t_bool, x);
+            let mut decls: Vec<Stmt> = Vec::new();
+            let mut bounds: Vec<Expr> = Vec::new();
+            for e in [start, end] {
+                // the bounds are evaluated once, before the loop
+                if let ExprX::Const(_) = &e.x {
+                    bounds.push(e.clone());
+                } else {
+                    let (temp_decl, temp) = temp_expr(state, e);
+                    decls.push(temp_decl);
+                    bounds.push(temp);
+                }
+            }
+            let (start, end) = (&bounds[0], &bounds[1]);
+            let patternx = PatternX::Var { name: var.name.clone(), mutable: true };
+            let pattern = SpannedTyped::new(&expr.span, &var.a, patternx);
+            let decl = StmtX::Decl { pattern, mode: Mode::Exec, init: Some(start.clone()) };
+            decls.push(Spanned::new(expr.span.clone(), decl));
+
+            let x = SpannedTyped::new(&expr.span, &var.a, ExprX::Var(var.name.clone()));
+            let cond = mk_bool(ExprX::Binary(BinaryOp::Lt, x.clone(), end.clone()));
+            let lower = mk_bool(ExprX::Binary(BinaryOp::Le, start.clone(), x.clone()));
+            let nonempty = mk_bool(ExprX::Binary(BinaryOp::Le, start.clone(), end.clone()));
+            let upper = mk_bool(ExprX::Binary(BinaryOp::Le, x.clone(), end.clone()));
+            let upper = mk_bool(ExprX::Binary(BinaryOp::Implies, nonempty, upper));
+            let mut loop_invs: Vec<LoopInvariant> = Vec::new();
+            for inv in [lower, upper] {
+                loop_invs.push(LoopInvariant { kind: LoopInvariantKind::InvariantAndEnsures, inv });
+            }
+            loop_invs.extend(invs.iter().cloned());
+
+            let one = SpannedTyped::new(
+                &expr.span,
+                &var.a,
+                ExprX::Const(Constant::Nat(Arc::new("1".to_string()))),
+            );
+            let add = SpannedTyped::new(&expr.span, &var.a, ExprX::Binary(BinaryOp::Add, x, one));
+            let add = match range {
+                IntRange::Int => add,
+                _ => SpannedTyped::new(&expr.span, &var.a, ExprX::Unary(UnaryOp::Clip(range), add)),
+            };
+            let loc = SpannedTyped::new(&expr.span, &var.a, ExprX::VarLoc(var.name.clone()));
+            let step = SpannedTyped::new(&expr.span, &expr.typ, ExprX::Assign(loc, add));
+
+            let loopx = ExprX::Loop {
+                label: label.clone(),
+                cond: Some(cond),
+                body: body.clone(),
+                step: Some(step),
+                invs: Arc::new(loop_invs),
+            };
+            let loop_expr = SpannedTyped::new(&expr.span, &expr.typ, loopx);
+            let block = ExprX::Block(Arc::new(decls), Some(loop_expr));
+            Ok(SpannedTyped::new(&expr.span, &expr.typ, block))
+        }
         _ => Ok(expr.clone()),
     }
 }
@@ -453,7 +529,7 @@ fn simplify_one_typ(local: &LocalCtxt, state: &mut State, typ: &Typ) -> Result<T
                 );
             }
             match &*local.bounds[x] {
-                GenericBoundX::Traits(_) => Ok(typ.clone()),
+                GenericBoundX::Traits(_) | GenericBoundX::Const(_) => Ok(typ.clone()),
                 GenericBoundX::FnSpec(ts, tr) => Ok(Arc::new(TypX::Lambda(ts.clone(), tr.clone()))),
             }
         }
@@ -461,6 +537,248 @@ fn simplify_one_typ(local: &LocalCtxt, state: &mut State, typ: &Typ) -> Result<T
     }
 }
 
+// Current value of a location (x.f.g for the location x.f.g)
+fn loc_value(loc: &Expr) -> Result<Expr, VirErr> {
+    match &loc.x {
+        ExprX::VarLoc(x) => Ok(loc.new_x(ExprX::Var(x.clone()))),
+        ExprX::UnaryOpr(op @ UnaryOpr::Field { .. }, e1) => {
+            Ok(loc.new_x(ExprX::UnaryOpr(op.clone(), loc_value(e1)?)))
+        }
+        _ => err_str(&loc.span, "not yet supported: &mut to this location"),
+    }
+}
+
+// Assign a value to a location, where assigning to a field updates the whole variable:
+//   x.f.g = value ==> x = X { f: F { g: value, ..x.f }, ..x }
+fn loc_assign(ctx: &GlobalCtx, loc: &Expr, value: &Expr) -> Result<Expr, VirErr> {
+    match &loc.x {
+        ExprX::VarLoc(_) => {
+            let unit = Arc::new(TypX::Tuple(Arc::new(vec![])));
+            Ok(SpannedTyped::new(&loc.span, &unit, ExprX::Assign(loc.clone(), value.clone())))
+        }
+        ExprX::UnaryOpr(UnaryOpr::Field { datatype, variant, field }, parent) => {
+            let parent_value = loc_value(parent)?;
+            let fields = &get_variant(&ctx.datatypes[datatype], variant).a;
+            let mut binders: Vec<Binder<Expr>> = Vec::new();
+            for f in fields.iter() {
+                if f.name == *field {
+                    binders.push(ident_binder(&f.name, value));
+                } else {
+                    let op = UnaryOpr::Field {
+                        datatype: datatype.clone(),
+                        variant: variant.clone(),
+                        field: f.name.clone(),
+                    };
+                    let exprx = ExprX::UnaryOpr(op, parent_value.clone());
+                    let field_exp = SpannedTyped::new(&loc.span, &f.a.0, exprx);
+                    binders.push(ident_binder(&f.name, &field_exp));
+                }
+            }
+            let ctorx = ExprX::Ctor(datatype.clone(), variant.clone(), Arc::new(binders), None);
+            let ctor = SpannedTyped::new(&loc.span, &parent.typ, ctorx);
+            loc_assign(ctx, parent, &ctor)
+        }
+        _ => err_str(&loc.span, "not yet supported: &mut to this location"),
+    }
+}
+
+// Pass borrowed fields as temporary variables, and write the temporaries back after the call:
+//   f(&mut x.g) ==> { let mut tmp = x.g; let r = f(&mut tmp); x = X { g: tmp, ..x }; r }
+fn call_field_borrows(ctx: &GlobalCtx, state: &mut State, call: &Expr) -> Result<Expr, VirErr> {
+    let (target, args) = match &call.x {
+        ExprX::Call(target, args) => (target, args),
+        _ => panic!("expected Call"),
+    };
+    let mut decls: Vec<Stmt> = Vec::new();
+    let mut writebacks: Vec<Stmt> = Vec::new();
+    let mut new_args: Vec<Expr> = Vec::new();
+    for arg in args.iter() {
+        match &arg.x {
+            ExprX::Loc(loc) if !matches!(loc.x, ExprX::VarLoc(_)) => {
+                let temp = state.next_temp();
+                let patternx = PatternX::Var { name: temp.clone(), mutable: true };
+                let pattern = SpannedTyped::new(&loc.span, &loc.typ, patternx);
+                let decl = StmtX::Decl { pattern, mode: Mode::Exec, init: Some(loc_value(loc)?) };
+                decls.push(Spanned::new(loc.span.clone(), decl));
+                let temp_loc = SpannedTyped::new(&loc.span, &loc.typ, ExprX::VarLoc(temp.clone()));
+                new_args.push(arg.new_x(ExprX::Loc(temp_loc)));
+                let temp_var = SpannedTyped::new(&loc.span, &loc.typ, ExprX::Var(temp));
+                let writeback = loc_assign(ctx, loc, &temp_var)?;
+                writebacks.push(Spanned::new(loc.span.clone(), StmtX::Expr(writeback)));
+            }
+            _ => new_args.push(arg.clone()),
+        }
+    }
+    if decls.len() == 0 {
+        return Ok(call.clone());
+    }
+    let call = call.new_x(ExprX::Call(target.clone(), Arc::new(new_args)));
+    let result = if is_unit(&call.typ) {
+        decls.push(Spanned::new(call.span.clone(), StmtX::Expr(call.clone())));
+        None
+    } else {
+        let (temp_decl, temp) = temp_expr(state, &call);
+        decls.push(temp_decl);
+        Some(temp)
+    };
+    decls.extend(writebacks.into_iter());
+    Ok(call.new_x(ExprX::Block(Arc::new(decls), result)))
+}
+
+// In a function returning &mut, return the current value of the borrowed location
+// and store the final value of the borrow (passed as a parameter) into the location:
+//   &mut x.g ==> { let tmp = x.g; x = X { g: fin, ..x }; tmp }
+//   f(args) ==> f(args, fin)
+fn mut_ret_result(
+    ctx: &GlobalCtx,
+    state: &mut State,
+    params: &Params,
+    fin: &Expr,
+    expr: &Expr,
+) -> Result<Expr, VirErr> {
+    match &expr.x {
+        ExprX::Var(x) if params.iter().any(|p| p.x.is_mut && p.x.name == *x) => {
+            let loc = expr.new_x(ExprX::VarLoc(x.clone()));
+            mut_ret_result(ctx, state, params, fin, &expr.new_x(ExprX::Loc(loc)))
+        }
+        ExprX::Loc(loc) => {
+            let (temp_decl, temp) = temp_expr(state, &loc_value(loc)?);
+            let assign = loc_assign(ctx, loc, fin)?;
+            let stmts = vec![temp_decl, Spanned::new(expr.span.clone(), StmtX::Expr(assign))];
+            Ok(expr.new_x(ExprX::Block(Arc::new(stmts), Some(temp))))
+        }
+        ExprX::Call(CallTarget::Static(x, typs), args) if ctx.mut_ret_funs.contains(x) => {
+            let mut args = (**args).clone();
+            args.push(fin.clone());
+            let target = CallTarget::Static(x.clone(), typs.clone());
+            call_field_borrows(ctx, state, &expr.new_x(ExprX::Call(target, Arc::new(args))))
+        }
+        ExprX::Block(stmts, Some(e1)) => {
+            let e1 = mut_ret_result(ctx, state, params, fin, e1)?;
+            Ok(expr.new_x(ExprX::Block(stmts.clone(), Some(e1))))
+        }
+        ExprX::If(e0, e1, Some(e2)) => {
+            let e1 = mut_ret_result(ctx, state, params, fin, e1)?;
+            let e2 = mut_ret_result(ctx, state, params, fin, e2)?;
+            Ok(expr.new_x(ExprX::If(e0.clone(), e1, Some(e2))))
+        }
+        ExprX::Match(e0, arms) => {
+            let arms = vec_map_result(arms, |arm| -> Result<Arm, VirErr> {
+                let body = mut_ret_result(ctx, state, params, fin, &arm.x.body)?;
+                let armx =
+                    ArmX { pattern: arm.x.pattern.clone(), guard: arm.x.guard.clone(), body };
+                Ok(Spanned::new(arm.span.clone(), armx))
+            })?;
+            Ok(expr.new_x(ExprX::Match(e0.clone(), Arc::new(arms))))
+        }
+        // already handled by simplify_mut_ref_expr
+        ExprX::Return(_) => Ok(expr.clone()),
+        _ => err_str(&expr.span, "not yet supported: this form of &mut return value"),
+    }
+}
+
+fn simplify_mut_ref_expr(
+    ctx: &GlobalCtx,
+    state: &mut State,
+    params: &Params,
+    fin: Option<&Expr>,
+    expr: &Expr,
+) -> Result<Expr, VirErr> {
+    match &expr.x {
+        ExprX::Call(CallTarget::Static(x, _), _) if ctx.mut_ret_funs.contains(x) => {
+            // handled by the enclosing Assign or return
+            Ok(expr.clone())
+        }
+        ExprX::Call(CallTarget::Static(..), _) => call_field_borrows(ctx, state, expr),
+        ExprX::Assign(lhs, rhs) => match &lhs.x {
+            ExprX::Call(CallTarget::Static(x, typs), args) if ctx.mut_ret_funs.contains(x) => {
+                // *f(args) = rhs ==> { let tmp = rhs; f(args, tmp); }
+                // (Rust evaluates rhs before the call)
+                let mut stmts: Vec<Stmt> = Vec::new();
+                let rhs = if let ExprX::Const(_) = &rhs.x {
+                    rhs.clone()
+                } else {
+                    let (temp_decl, temp) = temp_expr(state, rhs);
+                    stmts.push(temp_decl);
+                    temp
+                };
+                let mut args = (**args).clone();
+                args.push(rhs);
+                let target = CallTarget::Static(x.clone(), typs.clone());
+                let call = lhs.new_x(ExprX::Call(target, Arc::new(args)));
+                let call = call_field_borrows(ctx, state, &call)?;
+                stmts.push(Spanned::new(expr.span.clone(), StmtX::Expr(call)));
+                Ok(expr.new_x(ExprX::Block(Arc::new(stmts), None)))
+            }
+            _ => Ok(expr.clone()),
+        },
+        ExprX::Return(Some(e1)) => match fin {
+            Some(fin) => {
+                let e1 = mut_ret_result(ctx, state, params, fin, e1)?;
+                Ok(expr.new_x(ExprX::Return(Some(e1))))
+            }
+            None => Ok(expr.clone()),
+        },
+        _ => Ok(expr.clone()),
+    }
+}
+
+// Desugar &mut return values and &mut borrows of fields:
+// - a function returning &mut takes the final value of the borrow as an extra parameter,
+//   and fin(r) in the ensures refers to this parameter
+// - the caller passes the assigned value for *f(args) = rhs as the extra argument
+// - borrows of fields become borrows of temporary variables
+fn simplify_mut_refs(
+    ctx: &GlobalCtx,
+    state: &mut State,
+    functionx: &mut FunctionX,
+) -> Result<(), VirErr> {
+    let fin = if functionx.ret.x.is_mut {
+        let ret = functionx.ret.clone();
+        let name = Arc::new(FINAL_VALUE.to_string());
+        let fin = SpannedTyped::new(&ret.span, &ret.x.typ, ExprX::Var(name.clone()));
+        let paramx = ParamX { name, typ: ret.x.typ.clone(), mode: ret.x.mode, is_mut: false };
+        let mut params = (*functionx.params).clone();
+        params.push(Spanned::new(ret.span.clone(), paramx));
+        functionx.params = Arc::new(params);
+        let ensure = vec_map_result(&functionx.ensure, |e| {
+            let mut map: VisitorScopeMap = ScopeMap::new();
+            crate::ast_visitor::map_expr_visitor_env(
+                e,
+                &mut map,
+                &mut (),
+                &|_, _, expr| match &expr.x {
+                    ExprX::VarAt(_, VarAt::Final) => Ok(fin.clone()),
+                    _ => Ok(expr.clone()),
+                },
+                &|_, _, stmt| Ok(vec![stmt.clone()]),
+                &|_, typ| Ok(typ.clone()),
+            )
+        })?;
+        functionx.ensure = Arc::new(ensure);
+        Some(fin)
+    } else {
+        None
+    };
+    if let Some(body) = &functionx.body {
+        let params = functionx.params.clone();
+        let mut map: VisitorScopeMap = ScopeMap::new();
+        let mut body = crate::ast_visitor::map_expr_visitor_env(
+            body,
+            &mut map,
+            state,
+            &|state, _, expr| simplify_mut_ref_expr(ctx, state, &params, fin.as_ref(), expr),
+            &|_, _, stmt| Ok(vec![stmt.clone()]),
+            &|_, typ| Ok(typ.clone()),
+        )?;
+        if let Some(fin) = &fin {
+            body = mut_ret_result(ctx, state, &params, fin, &body)?;
+        }
+        functionx.body = Some(body);
+    }
+    Ok(())
+}
+
 fn simplify_function(
     ctx: &GlobalCtx,
     state: &mut State,
@@ -468,11 +786,12 @@ fn simplify_function(
 ) -> Result<Function, VirErr> {
     state.reset_for_function();
     let mut functionx = function.x.clone();
+    simplify_mut_refs(ctx, state, &mut functionx)?;
     let mut local =
         LocalCtxt { span: function.span.clone(), typ_params: Vec::new(), bounds: HashMap::new() };
     for (x, bound) in functionx.typ_bounds.iter() {
         match &**bound {
-            GenericBoundX::Traits(_) => local.typ_params.push(x.clone()),
+            GenericBoundX::Traits(_) | GenericBoundX::Const(_) => local.typ_params.push(x.clone()),
             GenericBoundX::FnSpec(..) => {}
         }
         // simplify types in bounds and disallow recursive bounds like F: FnSpec(F, F) -> F
@@ -516,6 +835,27 @@ fn simplify_datatype(state: &mut State, datatype: &Datatype) -> Result<Datatype,
     })
 }
 
+fn simplify_assoc_type_impl(
+    state: &mut State,
+    assoc: &AssocTypeImpl,
+) -> Result<AssocTypeImpl, VirErr> {
+    let mut local =
+        LocalCtxt { span: assoc.span.clone(), typ_params: Vec::new(), bounds: HashMap::new() };
+    for (x, bound) in assoc.x.typ_params.iter() {
+        local.typ_params.push(x.clone());
+        local.bounds.insert(x.clone(), bound.clone());
+    }
+    let ft = |state: &mut State, typ: &Typ| simplify_one_typ(&local, state, typ);
+    let mut assocx = assoc.x.clone();
+    assocx.self_typ = crate::ast_visitor::map_typ_visitor_env(&assocx.self_typ, state, &ft)?;
+    let trait_typ_args = vec_map_result(&*assocx.trait_typ_args, |t| {
+        crate::ast_visitor::map_typ_visitor_env(t, state, &ft)
+    })?;
+    assocx.trait_typ_args = Arc::new(trait_typ_args);
+    assocx.typ = crate::ast_visitor::map_typ_visitor_env(&assocx.typ, state, &ft)?;
+    Ok(Spanned::new(assoc.span.clone(), assocx))
+}
+
 /*
 fn mk_fun_decl(
     span: &Span,
@@ -551,10 +891,12 @@ fn mk_fun_decl(
 */
 
 pub fn simplify_krate(ctx: &mut GlobalCtx, krate: &Krate) -> Result<Krate, VirErr> {
-    let KrateX { functions, datatypes, traits, module_ids } = &**krate;
+    let KrateX { functions, datatypes, traits, assoc_type_impls, module_ids } = &**krate;
     let mut state = State::new();
     let functions = vec_map_result(functions, |f| simplify_function(ctx, &mut state, f))?;
     let mut datatypes = vec_map_result(&datatypes, |d| simplify_datatype(&mut state, d))?;
+    let assoc_type_impls =
+        vec_map_result(assoc_type_impls, |a| simplify_assoc_type_impl(&mut state, a))?;
 
     // Add a generic datatype to represent each tuple arity
     for (arity, path) in state.tuple_typs {
@@ -586,7 +928,7 @@ pub fn simplify_krate(ctx: &mut GlobalCtx, krate: &Krate) -> Result<Krate, VirEr
 
     let traits = traits.clone();
     let module_ids = module_ids.clone();
-    let krate = Arc::new(KrateX { functions, datatypes, traits, module_ids });
+    let krate = Arc::new(KrateX { functions, datatypes, traits, assoc_type_impls, module_ids });
     *ctx = crate::context::GlobalCtx::new(&krate, ctx.no_span.clone())?;
     Ok(krate)
 }
@@ -1,18 +1,19 @@
 use crate::ast::{
-    BinaryOp, CallTarget, Constant, Expr, ExprX, Fun, Function, Ident, Mode, PatternX,
-    SpannedTyped, Stmt, StmtX, Typ, TypX, Typs, UnaryOp, UnaryOpr, VarAt, VirErr,
+    BinaryOp, CallTarget, ClosureSpec, Constant, Expr, ExprX, Fun, Function, Ident, IntRange,
+    LoopInvariantKind, Mode, PatternX, SpannedTyped, Stmt, StmtX, Typ, TypX, Typs, UnaryOp,
+    UnaryOpr, VarAt, VirErr,
 };
-use crate::ast_util::{err_str, err_string};
+use crate::ast_util::{conjoin, err_str, err_string};
 use crate::context::Ctx;
 use crate::def::Spanned;
 use crate::sst::{
-    Bnd, BndX, Dest, Exp, ExpX, Exps, LocalDecl, LocalDeclX, ParPurpose, Pars, Stm, StmX,
-    UniqueIdent,
+    Bnd, BndX, Dest, Exp, ExpX, Exps, LocalDecl, LocalDeclX, LoopInv, ParPurpose, Pars, SourceVar,
+    SourceVarX, Stm, StmX, UniqueIdent,
 };
 use crate::sst_visitor::{map_exp_visitor, map_stm_exp_visitor};
 use crate::util::{vec_map, vec_map_result};
 use air::ast::{Binder, BinderX, Binders, Quant, Span};
-use air::errors::error_with_label;
+use air::errors::{error, error_with_label};
 use air::scope_map::ScopeMap;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -20,11 +21,23 @@ use std::sync::Arc;
 type Arg = (Exp, Typ);
 type Args = Arc<Vec<Arg>>;
 
+struct LoopInfo {
+    label: Option<Ident>,
+    invs: Vec<LoopInv>,
+    // statements to run before jumping back to the loop head (at the end of the body or a continue)
+    step: Vec<Stm>,
+    // set when we see a break that exits this loop
+    has_break: bool,
+}
+
 pub(crate) struct State {
     // Counter to generate temporary variables
     next_var: u64,
     // Collect all local variable declarations
     pub(crate) local_decls: Vec<LocalDecl>,
+    // Collect the parameters and local variables declared in the source code,
+    // in order of declaration, with their unique ids
+    pub(crate) source_vars: Vec<SourceVar>,
     // Rename local variables when needed, using unique integers, to avoid collisions.
     // This is only needed for statement-level declarations (Some(unique_int)),
     // not for expression-level bindings (None).
@@ -36,6 +49,8 @@ pub(crate) struct State {
     dont_rename: HashSet<UniqueIdent>,
     // If we allow return expressions, this is the return variable and ensures clauses:
     pub(crate) ret_post: Option<(Option<UniqueIdent>, Exps)>,
+    // Loops enclosing the current expression, innermost last, used for break and continue
+    loops: Vec<LoopInfo>,
 }
 
 impl State {
@@ -45,10 +60,12 @@ impl State {
         State {
             next_var: 0,
             local_decls: Vec::new(),
+            source_vars: Vec::new(),
             rename_map,
             rename_counters: HashMap::new(),
             dont_rename: HashSet::new(),
             ret_post: None,
+            loops: Vec::new(),
         }
     }
 
@@ -95,6 +112,11 @@ impl State {
         self.rename_map.insert(x.0.clone(), x.1).expect("declare var");
     }
 
+    pub(crate) fn record_source_var(&mut self, ident: &UniqueIdent, span: &Span, typ: &Typ) {
+        let var = SourceVarX { ident: ident.clone(), span: span.clone(), typ: typ.clone() };
+        self.source_vars.push(Arc::new(var));
+    }
+
     pub(crate) fn declare_binders<A: Clone>(&mut self, binders: &Binders<A>) {
         for binder in binders.iter() {
             self.declare_expression_var(&binder.name);
@@ -167,8 +189,8 @@ fn expr_get_call(
     expr: &Expr,
 ) -> Result<Option<(Vec<Stm>, Fun, Typs, bool, Args)>, VirErr> {
     match &expr.x {
-        ExprX::Call(CallTarget::FnSpec(..), _) => {
-            panic!("internal error: CallTarget::FnSpec")
+        ExprX::Call(CallTarget::FnSpec(..) | CallTarget::FnExec(..), _) => {
+            panic!("internal error: CallTarget::FnSpec/FnExec")
         }
         ExprX::Call(CallTarget::Static(x, typs), args) => {
             let mut stms: Vec<Stm> = Vec::new();
@@ -301,6 +323,7 @@ fn is_small_exp_or_loc(exp: &Exp) -> bool {
     match &exp.x {
         ExpX::Const(_) => true,
         ExpX::Var(..) => true,
+        ExpX::ConstParam(..) => true,
         ExpX::Old(..) => true,
         ExpX::Loc(..) => true,
         ExpX::Unary(UnaryOp::Not | UnaryOp::Clip(_), e) => is_small_exp_or_loc(e),
@@ -336,22 +359,46 @@ fn stm_call(
     stms_to_one_stm(span, stms)
 }
 
+// Put a non-small exp into a temporary variable, as in stm_call
+fn small_exp_or_temp(state: &mut State, stms: &mut Vec<Stm>, exp: Exp) -> Exp {
+    if is_small_exp_or_loc(&exp) {
+        exp
+    } else {
+        let (temp, temp_var) = state.next_temp(&exp.span, &exp.typ);
+        let temp_id = state.declare_new_var(&temp, &exp.typ, false, false);
+        stms.push(init_var(&exp.span, &temp_id, &exp));
+        temp_var
+    }
+}
+
+fn assume_has_typ(span: &Span, x: &UniqueIdent, typ: &Typ) -> Stm {
+    let xvar = SpannedTyped::new(span, typ, ExpX::Var(x.clone()));
+    let has_typx = ExpX::UnaryOpr(UnaryOpr::HasType(typ.clone()), xvar);
+    let has_typ = SpannedTyped::new(span, &Arc::new(TypX::Bool), has_typx);
+    Spanned::new(span.clone(), StmX::Assume(has_typ))
+}
+
 fn if_to_stm(
     state: &mut State,
     expr: &Expr,
     stms0: &mut Vec<Stm>,
     e0: Exp,
     mut stms1: Vec<Stm>,
-    e1: &Exp,
+    e1: Option<&Exp>,
     mut stms2: Vec<Stm>,
-    e2: &Exp,
+    e2: Option<&Exp>,
 ) -> Exp {
     // If statement, put results from e1/e2 in a temp variable, return temp variable
     let (temp, temp_var) = state.next_temp(&expr.span, &expr.typ);
     let temp_id = state.declare_new_var(&temp, &expr.typ, false, false);
     // if e0 { stms1; temp = e1; } else { stms2; temp = e2; }
-    stms1.push(init_var(&expr.span, &temp_id, &e1));
-    stms2.push(init_var(&expr.span, &temp_id, &e2));
+    // (a branch with no value always exits early, so it doesn't need to assign temp)
+    if let Some(e1) = e1 {
+        stms1.push(init_var(&expr.span, &temp_id, e1));
+    }
+    if let Some(e2) = e2 {
+        stms2.push(init_var(&expr.span, &temp_id, e2));
+    }
     let stm1 = stms_to_one_stm(&expr.span, stms1);
     let stm2 = stms_to_one_stm(&expr.span, stms2);
     let if_stmt = StmX::If(e0, stm1, Some(stm2));
@@ -360,6 +407,19 @@ fn if_to_stm(
     temp_var
 }
 
+/// Does expr always exit early, via return, break, or continue?
+fn expr_must_exit(expr: &Expr) -> bool {
+    match &expr.x {
+        ExprX::Return(_) | ExprX::BreakOrContinue { .. } => true,
+        ExprX::Block(_, Some(e)) => expr_must_exit(e),
+        ExprX::Block(stmts, None) => match stmts.last().map(|stmt| &stmt.x) {
+            Some(StmtX::Expr(e)) => expr_must_exit(e),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 pub(crate) fn expr_to_stm_opt(
     ctx: &Ctx,
     state: &mut State,
@@ -384,7 +444,9 @@ pub(crate) fn expr_to_stm_opt(
             }
             Ok((vec![], Some(mk_exp(ExpX::VarAt(state.get_var_unique_id(&x), VarAt::Pre)))))
         }
+        ExprX::VarAt(_, VarAt::Final) => panic!("VarAt::Final should already be removed"),
         ExprX::ConstVar(..) => panic!("ConstVar should already be removed"),
+        ExprX::ConstParam(x) => Ok((vec![], Some(mk_exp(ExpX::ConstParam(x.clone()))))),
         ExprX::Loc(expr1) => {
             let (stms, e0) = expr_to_stm(ctx, state, expr1)?;
             Ok((stms, Some(mk_exp(ExpX::Loc(e0)))))
@@ -416,6 +478,34 @@ pub(crate) fn expr_to_stm_opt(
             let call = ExpX::CallLambda(expr.typ.clone(), e0, args);
             Ok((vec![], Some(mk_exp(call))))
         }
+        ExprX::Call(CallTarget::FnExec(e0), args) => {
+            // assert(f.requires(args));
+            // assume(has_type(tmp) && f.ensures(args, tmp));
+            // tmp
+            let bool_typ = Arc::new(TypX::Bool);
+            let (mut stms, f) = expr_to_stm(ctx, state, e0)?;
+            let f = small_exp_or_temp(state, &mut stms, f);
+            let mut small_args: Vec<Exp> = Vec::new();
+            for arg in args.iter() {
+                let (mut stms0, e) = expr_to_stm(ctx, state, arg)?;
+                stms.append(&mut stms0);
+                small_args.push(small_exp_or_temp(state, &mut stms, e));
+            }
+            let reqx =
+                ExpX::ClosureSpec(ClosureSpec::Requires, f.clone(), Arc::new(small_args.clone()));
+            let req = SpannedTyped::new(&expr.span, &bool_typ, reqx);
+            let error = error("precondition not satisfied", &expr.span);
+            stms.push(Spanned::new(expr.span.clone(), StmX::Assert(Some(error), req)));
+
+            let (temp, temp_var) = state.next_temp(&expr.span, &expr.typ);
+            let temp_id = state.declare_new_var(&temp, &expr.typ, false, false);
+            stms.push(assume_has_typ(&expr.span, &temp_id, &expr.typ));
+            small_args.push(temp_var.clone());
+            let ensx = ExpX::ClosureSpec(ClosureSpec::Ensures, f, Arc::new(small_args));
+            let ens = SpannedTyped::new(&expr.span, &bool_typ, ensx);
+            stms.push(Spanned::new(expr.span.clone(), StmX::Assume(ens)));
+            Ok((stms, Some(temp_var)))
+        }
         ExprX::Call(CallTarget::Static(..), _) => {
             let (mut stms, x, typs, ret, args) = expr_get_call(ctx, state, expr)?.expect("Call");
             if function_can_be_exp(ctx, expr, &x)? {
@@ -480,13 +570,12 @@ pub(crate) fn expr_to_stm_opt(
                     //   if e1 { true } else { stmts2; e2 }
                     let bx = ExpX::Const(Constant::Bool(other));
                     let b = SpannedTyped::new(&expr.span, &Arc::new(TypX::Bool), bx);
+                    let (b, e2) = (Some(&b), Some(&e2));
                     if proceed_on {
-                        let temp_var =
-                            if_to_stm(state, expr, &mut stms1, e1, stms2, &e2, vec![], &b);
+                        let temp_var = if_to_stm(state, expr, &mut stms1, e1, stms2, e2, vec![], b);
                         temp_var
                     } else {
-                        let temp_var =
-                            if_to_stm(state, expr, &mut stms1, e1, vec![], &b, stms2, &e2);
+                        let temp_var = if_to_stm(state, expr, &mut stms1, e1, vec![], b, stms2, e2);
                         temp_var
                     }
                 }
@@ -521,7 +610,7 @@ pub(crate) fn expr_to_stm_opt(
 
             // Parameters and return types must be boxed, so insert necessary box/unboxing
             match &*body.typ {
-                TypX::TypParam(_) | TypX::Boxed(_) => {}
+                TypX::TypParam(_) | TypX::Projection(..) | TypX::Boxed(_) => {}
                 _ => {
                     let boxed_typ = Arc::new(TypX::Boxed(body.typ.clone()));
                     let boxx = ExpX::UnaryOpr(UnaryOpr::Box(body.typ.clone()), exp);
@@ -532,7 +621,7 @@ pub(crate) fn expr_to_stm_opt(
             let mut boxed_params: Vec<Binder<Typ>> = Vec::new();
             for p in params.iter() {
                 match &*p.a {
-                    TypX::TypParam(_) | TypX::Boxed(_) => {
+                    TypX::TypParam(_) | TypX::Projection(..) | TypX::Boxed(_) => {
                         boxed_params.push(p.clone());
                     }
                     _ => {
@@ -554,6 +643,87 @@ pub(crate) fn expr_to_stm_opt(
             let bnd = Spanned::new(body.span.clone(), BndX::Lambda(Arc::new(boxed_params)));
             Ok((vec![], Some(mk_exp(ExpX::Bind(bnd, exp)))))
         }
+        ExprX::ExecClosure { params, require, ensure, ret, body } => {
+            // deadend {
+            //   assume(require)
+            //   ret = body
+            //   assert(ensure);
+            // }
+            // f = arbitrary closure value
+            // assume(forall params. f.requires(params) == require)
+            // assume(forall params, ret. f.ensures(params, ret) == ensure)
+            let mut stms: Vec<Stm> = Vec::new();
+
+            // Check the body in a dead-end, outside any enclosing loops or function returns
+            let loops = std::mem::take(&mut state.loops);
+            let ret_post = state.ret_post.take();
+            state.push_scope();
+            let mut body_stms: Vec<Stm> = Vec::new();
+            for param in params.iter() {
+                let x = state.declare_new_var(&param.name, &param.a, false, true);
+                state.record_source_var(&x, &expr.span, &param.a);
+                body_stms.push(assume_has_typ(&expr.span, &x, &param.a));
+            }
+            for req in require.iter() {
+                let req_exp = expr_to_exp_state(ctx, state, req)?;
+                body_stms.push(Spanned::new(req.span.clone(), StmX::Assume(req_exp)));
+            }
+            let (mut stms1, e1) = expr_to_stm_opt(ctx, state, body)?;
+            body_stms.append(&mut stms1);
+            let ret_id = state.declare_new_var(&ret.name, &ret.a, false, true);
+            if let Some(e1) = e1 {
+                body_stms.push(init_var(&body.span, &ret_id, &e1));
+            }
+            for ens in ensure.iter() {
+                let ens_exp = expr_to_exp_state(ctx, state, ens)?;
+                let error = error("postcondition not satisfied", &ens.span);
+                body_stms.push(Spanned::new(ens.span.clone(), StmX::Assert(Some(error), ens_exp)));
+            }
+            state.pop_scope();
+            state.loops = loops;
+            state.ret_post = ret_post;
+            let block = Spanned::new(expr.span.clone(), StmX::Block(Arc::new(body_stms)));
+            stms.push(Spanned::new(expr.span.clone(), StmX::DeadEnd(block)));
+
+            // The closure value itself is arbitrary, except for its requires/ensures
+            let (temp, f) = state.next_temp(&expr.span, &expr.typ);
+            state.declare_new_var(&temp, &expr.typ, false, false);
+            let bool_typ = Arc::new(TypX::Bool);
+            let mut binders: Vec<Binder<Typ>> = (**params).clone();
+            let mut args: Vec<Exp> = vec_map(&binders, |b| {
+                SpannedTyped::new(&expr.span, &b.a, ExpX::Var((b.name.clone(), None)))
+            });
+            for (spec, conds) in [(ClosureSpec::Requires, require), (ClosureSpec::Ensures, ensure)]
+            {
+                if spec == ClosureSpec::Ensures {
+                    binders.push(ret.clone());
+                    args.push(SpannedTyped::new(
+                        &expr.span,
+                        &ret.a,
+                        ExpX::Var((ret.name.clone(), None)),
+                    ));
+                }
+                state.push_scope();
+                state.declare_binders(&Arc::new(binders.clone()));
+                let cond = expr_to_exp_state(ctx, state, &conjoin(&expr.span, conds))?;
+                state.pop_scope();
+                let specx = ExpX::ClosureSpec(spec, f.clone(), Arc::new(args.clone()));
+                let spec_exp = SpannedTyped::new(&expr.span, &bool_typ, specx);
+                let eqx = ExpX::Binary(BinaryOp::Eq(Mode::Spec), spec_exp.clone(), cond);
+                let eq = SpannedTyped::new(&expr.span, &bool_typ, eqx);
+                let trigs = Arc::new(vec![Arc::new(vec![spec_exp])]);
+                let bndx = BndX::Quant(Quant::Forall, Arc::new(binders.clone()), trigs);
+                let bnd = Spanned::new(expr.span.clone(), bndx);
+                let forall = SpannedTyped::new(&expr.span, &bool_typ, ExpX::Bind(bnd, eq));
+                stms.push(Spanned::new(expr.span.clone(), StmX::Assume(forall)));
+            }
+            Ok((stms, Some(f)))
+        }
+        ExprX::ClosureSpec(spec, e0, args) => {
+            let e0 = expr_to_exp_state(ctx, state, e0)?;
+            let args = Arc::new(vec_map_result(args, |e| expr_to_exp_state(ctx, state, e))?);
+            Ok((vec![], Some(mk_exp(ExpX::ClosureSpec(*spec, e0, args)))))
+        }
         ExprX::Choose { params, cond, body } => {
             state.push_scope();
             state.declare_binders(&params);
@@ -593,6 +763,7 @@ pub(crate) fn expr_to_stm_opt(
             let mut body: Vec<Stm> = Vec::new();
             for var in vars.iter() {
                 let x = state.declare_new_var(&var.name, &var.a, false, true);
+                state.record_source_var(&x, &expr.span, &var.a);
                 let xvarx = ExpX::Var(x);
                 let xvar = SpannedTyped::new(&expr.span, &Arc::new(TypX::Bool), xvarx);
                 let has_typx = ExpX::UnaryOpr(UnaryOpr::HasType(var.a.clone()), xvar);
@@ -627,6 +798,13 @@ pub(crate) fn expr_to_stm_opt(
             Ok((stms, None))
         }
         ExprX::AssertBV(e) => {
+            // char has no fixed bit width
+            crate::ast_visitor::expr_visitor_check(e, &mut |e: &Expr| match &*e.typ {
+                TypX::Int(IntRange::Char) => {
+                    err_str(&e.span, "char is not supported for bit-vector reasoning")
+                }
+                _ => Ok(()),
+            })?;
             let expr = expr_to_exp_state(ctx, state, &e)?;
             let assert = Spanned::new(e.span.clone(), StmX::AssertBV(expr));
             Ok((vec![assert], None))
@@ -648,7 +826,19 @@ pub(crate) fn expr_to_stm_opt(
                 }
                 (Some(e1), Some(e2)) => {
                     // If statement, put results from e1/e2 in a temp variable, return temp variable
-                    let temp_var = if_to_stm(state, expr, &mut stms0, e0, stms1, &e1, stms2, &e2);
+                    let (e1, e2) = (Some(&e1), Some(&e2));
+                    let temp_var = if_to_stm(state, expr, &mut stms0, e0, stms1, e1, stms2, e2);
+                    Ok((stms0, Some(temp_var)))
+                }
+                (Some(e1), None) if expr_must_exit(expr2) => {
+                    // expr2 exits early (e.g. return from e?), so only expr1 provides a value
+                    let e1 = Some(&e1);
+                    let temp_var = if_to_stm(state, expr, &mut stms0, e0, stms1, e1, stms2, None);
+                    Ok((stms0, Some(temp_var)))
+                }
+                (None, Some(e2)) if expr_must_exit(expr1) => {
+                    let e2 = Some(&e2);
+                    let temp_var = if_to_stm(state, expr, &mut stms0, e0, stms1, None, stms2, e2);
                     Ok((stms0, Some(temp_var)))
                 }
                 (None, None) => {
@@ -667,23 +857,89 @@ pub(crate) fn expr_to_stm_opt(
         ExprX::Match(..) => {
             panic!("internal error: Match should have been simplified by ast_simplify")
         }
-        ExprX::While { cond, body, invs } => {
-            let (stms0, e0) = expr_to_stm(ctx, state, cond)?;
-            let (stms1, e1) = expr_to_stm_opt(ctx, state, body)?;
+        ExprX::ForRange { .. } => {
+            panic!("internal error: ForRange should have been simplified by ast_simplify")
+        }
+        ExprX::Loop { label, cond, body, step, invs } => {
+            let cond = match cond {
+                None => None,
+                Some(cond) => {
+                    let (stms0, e0) = expr_to_stm(ctx, state, cond)?;
+                    Some((Arc::new(stms0), e0))
+                }
+            };
+            let invs = vec_map_result(invs, |inv| -> Result<LoopInv, VirErr> {
+                let (at_entry, at_exit) = match inv.kind {
+                    LoopInvariantKind::InvariantExceptBreak => (true, false),
+                    LoopInvariantKind::InvariantAndEnsures => (true, true),
+                    LoopInvariantKind::Ensures => (false, true),
+                };
+                let inv = expr_to_exp_state(ctx, state, &inv.inv)?;
+                Ok(LoopInv { inv, at_entry, at_exit })
+            })?;
+            let step = match step {
+                None => vec![],
+                Some(step) => {
+                    let (stms, e) = expr_to_stm_opt(ctx, state, step)?;
+                    check_no_exp(&e)?;
+                    stms
+                }
+            };
+            let loop_info =
+                LoopInfo { label: label.clone(), invs: invs.clone(), step, has_break: false };
+            state.loops.push(loop_info);
+            let body_stms = expr_to_stm_opt(ctx, state, body);
+            let loop_info = state.loops.pop().expect("loop info");
+            let (mut stms1, e1) = body_stms?;
             check_no_exp(&e1)?;
-            let invs = Arc::new(vec_map_result(invs, |e| expr_to_exp_state(ctx, state, e))?);
-            let while_stm = Spanned::new(
+            stms1.extend(loop_info.step.iter().cloned());
+            let loop_stm = Spanned::new(
                 expr.span.clone(),
-                StmX::While {
-                    cond_stms: Arc::new(stms0),
-                    cond_exp: e0,
+                StmX::Loop {
+                    cond,
                     body: stms_to_one_stm(&body.span, stms1),
-                    invs,
+                    invs: Arc::new(invs),
+                    has_break: loop_info.has_break,
                     typ_inv_vars: Arc::new(vec![]),
                     modified_vars: Arc::new(vec![]),
                 },
             );
-            Ok((vec![while_stm], None))
+            Ok((vec![loop_stm], None))
+        }
+        ExprX::BreakOrContinue { label, is_break } => {
+            let loop_info = match label {
+                None => state.loops.last_mut(),
+                Some(_) => state.loops.iter_mut().rev().find(|l| &l.label == label),
+            };
+            let loop_info = match loop_info {
+                None => {
+                    return err_str(&expr.span, "break/continue must be inside a verified loop");
+                }
+                Some(loop_info) => loop_info,
+            };
+            let mut stms: Vec<Stm> = Vec::new();
+            if *is_break {
+                loop_info.has_break = true;
+            } else {
+                stms.extend(loop_info.step.iter().cloned());
+            }
+            for inv in loop_info.invs.iter() {
+                let msg = match (*is_break, inv.at_entry, inv.at_exit) {
+                    (true, true, true) => "invariant not satisfied at break",
+                    (true, false, true) => "loop ensures not satisfied at break",
+                    (false, true, _) => "invariant not satisfied at continue",
+                    _ => continue,
+                };
+                let label = if *is_break { "at this break" } else { "at this continue" };
+                let error = error_with_label(msg.to_string(), &expr.span, label.to_string())
+                    .secondary_label(&inv.inv.span, "failed this condition".to_string());
+                let assert = StmX::Assert(Some(error), inv.inv.clone());
+                stms.push(Spanned::new(expr.span.clone(), assert));
+            }
+            let expx = ExpX::Const(Constant::Bool(false));
+            let exp = SpannedTyped::new(&expr.span, &Arc::new(TypX::Bool), expx);
+            stms.push(Spanned::new(expr.span.clone(), StmX::Assume(exp)));
+            Ok((stms, None))
         }
         ExprX::OpenInvariant(inv, binder, body, atomicity) => {
             // Evaluate `inv`
@@ -704,6 +960,7 @@ pub(crate) fn expr_to_stm_opt(
                 /* mutable */ true,
                 /* maybe_need_rename */ true,
             );
+            state.record_source_var(&ident, &expr.span, &binder.a);
             let body_stm = expr_to_one_stm(ctx, state, body)?;
             state.pop_scope();
 
@@ -835,6 +1092,7 @@ pub(crate) fn stmt_to_stm(
 
             let ident = state.alloc_unique_var(&name);
             let typ = pattern.typ.clone();
+            state.record_source_var(&ident, &pattern.span, &typ);
             let decl = Arc::new(LocalDeclX { ident, typ, mutable: *mutable });
 
             if let Some(init) = init {
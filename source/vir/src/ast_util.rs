@@ -48,12 +48,24 @@ pub fn types_equal(typ1: &Typ, typ2: &Typ) -> bool {
         (TypX::Datatype(p1, typs1), TypX::Datatype(p2, typs2)) => {
             p1 == p2 && n_types_equal(typs1, typs2)
         }
+        (TypX::Array(t1, len1), TypX::Array(t2, len2)) => {
+            types_equal(t1, t2) && types_equal(len1, len2)
+        }
         (TypX::Boxed(t1), TypX::Boxed(t2)) => types_equal(t1, t2),
         (TypX::TypParam(x1), TypX::TypParam(x2)) => x1 == x2,
+        (TypX::ConstInt(c1), TypX::ConstInt(c2)) => c1 == c2,
+        (TypX::Projection(p1, typs1, x1), TypX::Projection(p2, typs2, x2)) => {
+            p1 == p2 && n_types_equal(typs1, typs2) && x1 == x2
+        }
         _ => false,
     }
 }
 
+/// The Seq<typ> type that encodes an array type [typ; len]
+pub fn array_seq_typ(typ: &Typ) -> Typ {
+    Arc::new(TypX::Datatype(crate::def::seq_type_path(), Arc::new(vec![typ.clone()])))
+}
+
 pub fn n_types_equal(typs1: &Typs, typs2: &Typs) -> bool {
     typs1.len() == typs2.len() && typs1.iter().zip(typs2.iter()).all(|(t1, t2)| types_equal(t1, t2))
 }
@@ -161,14 +173,18 @@ pub fn pars_to_binders(pars: &Pars) -> Binders<Typ> {
     Arc::new(vec_map(&**pars, par_to_binder))
 }
 
+pub fn is_unit(typ: &Typ) -> bool {
+    match &**typ {
+        TypX::Tuple(ts) if ts.len() == 0 => true,
+        TypX::Datatype(path, _) if path == &crate::def::prefix_tuple_type(0) => true,
+        _ => false,
+    }
+}
+
 impl FunctionX {
     // unit return values are treated as no return value
     pub fn has_return(&self) -> bool {
-        match &*self.ret.x.typ {
-            TypX::Tuple(ts) if ts.len() == 0 => false,
-            TypX::Datatype(path, _) if path == &crate::def::prefix_tuple_type(0) => false,
-            _ => true,
-        }
+        !is_unit(&self.ret.x.typ)
     }
 
     pub fn typ_params(&self) -> Idents {
@@ -1,7 +1,7 @@
 use crate::ast::{
     Arm, ArmX, CallTarget, Datatype, DatatypeX, Expr, ExprX, Field, Function, FunctionKind,
-    FunctionX, GenericBound, GenericBoundX, Ident, MaskSpec, Param, ParamX, Pattern, PatternX,
-    SpannedTyped, Stmt, StmtX, Typ, TypX, UnaryOpr, Variant, VirErr,
+    FunctionX, GenericBound, GenericBoundX, Ident, LoopInvariant, MaskSpec, Param, ParamX, Pattern,
+    PatternX, SpannedTyped, Stmt, StmtX, Typ, TypX, UnaryOpr, Variant, VirErr,
 };
 use crate::ast_util::err_str;
 use crate::def::Spanned;
@@ -37,6 +37,7 @@ where
         VisitorControlFlow::Recurse => {
             match &**typ {
                 TypX::Bool | TypX::Int(_) | TypX::TypParam(_) | TypX::TypeId | TypX::Air(_) => (),
+                TypX::ConstInt(_) => (),
                 TypX::Tuple(ts) => {
                     for t in ts.iter() {
                         expr_visitor_control_flow!(typ_visitor_dfs(t, ft));
@@ -48,11 +49,15 @@ where
                     }
                     expr_visitor_control_flow!(typ_visitor_dfs(tr, ft));
                 }
-                TypX::Datatype(_path, ts) => {
+                TypX::Datatype(_path, ts) | TypX::Projection(_path, ts, _) => {
                     for t in ts.iter() {
                         expr_visitor_control_flow!(typ_visitor_dfs(t, ft));
                     }
                 }
+                TypX::Array(t, len) => {
+                    expr_visitor_control_flow!(typ_visitor_dfs(t, ft));
+                    expr_visitor_control_flow!(typ_visitor_dfs(len, ft));
+                }
                 TypX::Boxed(t) => {
                     expr_visitor_control_flow!(typ_visitor_dfs(t, ft));
                 }
@@ -68,6 +73,7 @@ where
 {
     match &**typ {
         TypX::Bool | TypX::Int(_) | TypX::TypParam(_) | TypX::TypeId | TypX::Air(_) => ft(env, typ),
+        TypX::ConstInt(_) => ft(env, typ),
         TypX::Tuple(ts) => {
             let ts = vec_map_result(&**ts, |t| map_typ_visitor_env(t, env, ft))?;
             ft(env, &Arc::new(TypX::Tuple(Arc::new(ts))))
@@ -81,6 +87,15 @@ where
             let ts = vec_map_result(&**ts, |t| map_typ_visitor_env(t, env, ft))?;
             ft(env, &Arc::new(TypX::Datatype(path.clone(), Arc::new(ts))))
         }
+        TypX::Projection(path, ts, x) => {
+            let ts = vec_map_result(&**ts, |t| map_typ_visitor_env(t, env, ft))?;
+            ft(env, &Arc::new(TypX::Projection(path.clone(), Arc::new(ts), x.clone())))
+        }
+        TypX::Array(t, len) => {
+            let t = map_typ_visitor_env(t, env, ft)?;
+            let len = map_typ_visitor_env(len, env, ft)?;
+            ft(env, &Arc::new(TypX::Array(t, len)))
+        }
         TypX::Boxed(t) => {
             let t = map_typ_visitor_env(t, env, ft)?;
             ft(env, &Arc::new(TypX::Boxed(t)))
@@ -164,14 +179,15 @@ where
                 | ExprX::Var(_)
                 | ExprX::VarLoc(_)
                 | ExprX::VarAt(_, _)
-                | ExprX::ConstVar(..) => (),
+                | ExprX::ConstVar(..)
+                | ExprX::ConstParam(..) => (),
                 ExprX::Loc(e) => {
                     expr_visitor_control_flow!(expr_visitor_dfs(e, map, mf));
                 }
                 ExprX::Call(target, es) => {
                     match target {
                         CallTarget::Static(_, _) => (),
-                        CallTarget::FnSpec(fun) => {
+                        CallTarget::FnSpec(fun) | CallTarget::FnExec(fun) => {
                             expr_visitor_control_flow!(expr_visitor_dfs(fun, map, mf));
                         }
                     }
@@ -221,6 +237,29 @@ where
                     expr_visitor_control_flow!(expr_visitor_dfs(body, map, mf));
                     map.pop_scope();
                 }
+                ExprX::ExecClosure { params, require, ensure, ret, body } => {
+                    map.push_scope(true);
+                    for binder in params.iter() {
+                        let _ = map.insert(binder.name.clone(), binder.a.clone());
+                    }
+                    for e in require.iter() {
+                        expr_visitor_control_flow!(expr_visitor_dfs(e, map, mf));
+                    }
+                    map.push_scope(true);
+                    let _ = map.insert(ret.name.clone(), ret.a.clone());
+                    for e in ensure.iter() {
+                        expr_visitor_control_flow!(expr_visitor_dfs(e, map, mf));
+                    }
+                    map.pop_scope();
+                    expr_visitor_control_flow!(expr_visitor_dfs(body, map, mf));
+                    map.pop_scope();
+                }
+                ExprX::ClosureSpec(_, fun, es) => {
+                    expr_visitor_control_flow!(expr_visitor_dfs(fun, map, mf));
+                    for e in es.iter() {
+                        expr_visitor_control_flow!(expr_visitor_dfs(e, map, mf));
+                    }
+                }
                 ExprX::Choose { params, cond, body } => {
                     map.push_scope(true);
                     for binder in params.iter() {
@@ -269,12 +308,28 @@ where
                         map.pop_scope();
                     }
                 }
-                ExprX::While { cond, body, invs } => {
-                    expr_visitor_control_flow!(expr_visitor_dfs(cond, map, mf));
+                ExprX::Loop { label: _, cond, body, step, invs } => {
+                    if let Some(cond) = cond {
+                        expr_visitor_control_flow!(expr_visitor_dfs(cond, map, mf));
+                    }
+                    expr_visitor_control_flow!(expr_visitor_dfs(body, map, mf));
+                    if let Some(step) = step {
+                        expr_visitor_control_flow!(expr_visitor_dfs(step, map, mf));
+                    }
+                    for inv in invs.iter() {
+                        expr_visitor_control_flow!(expr_visitor_dfs(&inv.inv, map, mf));
+                    }
+                }
+                ExprX::ForRange { label: _, var, start, end, body, invs } => {
+                    expr_visitor_control_flow!(expr_visitor_dfs(start, map, mf));
+                    expr_visitor_control_flow!(expr_visitor_dfs(end, map, mf));
+                    map.push_scope(true);
+                    let _ = map.insert(var.name.clone(), var.a.clone());
                     expr_visitor_control_flow!(expr_visitor_dfs(body, map, mf));
                     for inv in invs.iter() {
-                        expr_visitor_control_flow!(expr_visitor_dfs(inv, map, mf));
+                        expr_visitor_control_flow!(expr_visitor_dfs(&inv.inv, map, mf));
                     }
+                    map.pop_scope();
                 }
                 ExprX::OpenInvariant(inv, binder, body, _atomicity) => {
                     expr_visitor_control_flow!(expr_visitor_dfs(inv, map, mf));
@@ -283,6 +338,7 @@ where
                     expr_visitor_control_flow!(expr_visitor_dfs(body, map, mf));
                     map.pop_scope();
                 }
+                ExprX::BreakOrContinue { .. } => (),
                 ExprX::Return(e1) => match e1 {
                     None => (),
                     Some(e) => expr_visitor_control_flow!(expr_visitor_dfs(e, map, mf)),
@@ -415,6 +471,7 @@ where
         ExprX::VarLoc(x) => ExprX::VarLoc(x.clone()),
         ExprX::VarAt(x, at) => ExprX::VarAt(x.clone(), at.clone()),
         ExprX::ConstVar(x) => ExprX::ConstVar(x.clone()),
+        ExprX::ConstParam(x) => ExprX::ConstParam(x.clone()),
         ExprX::Loc(e) => ExprX::Loc(map_expr_visitor_env(e, map, env, fe, fs, ft)?),
         ExprX::Call(target, es) => {
             let target = match target {
@@ -426,6 +483,10 @@ where
                     let fun = map_expr_visitor_env(fun, map, env, fe, fs, ft)?;
                     CallTarget::FnSpec(fun)
                 }
+                CallTarget::FnExec(fun) => {
+                    let fun = map_expr_visitor_env(fun, map, env, fe, fs, ft)?;
+                    CallTarget::FnExec(fun)
+                }
             };
             let mut exprs: Vec<Expr> = Vec::new();
             for e in es.iter() {
@@ -494,6 +555,36 @@ where
             map.pop_scope();
             ExprX::Closure(Arc::new(params), body)
         }
+        ExprX::ExecClosure { params, require, ensure, ret, body } => {
+            let params =
+                vec_map_result(&**params, |b| b.map_result(|t| map_typ_visitor_env(t, env, ft)))?;
+            let ret = ret.map_result(|t| map_typ_visitor_env(t, env, ft))?;
+            map.push_scope(true);
+            for binder in params.iter() {
+                let _ = map.insert(binder.name.clone(), binder.a.clone());
+            }
+            let require =
+                vec_map_result(&**require, |e| map_expr_visitor_env(e, map, env, fe, fs, ft))?;
+            map.push_scope(true);
+            let _ = map.insert(ret.name.clone(), ret.a.clone());
+            let ensure =
+                vec_map_result(&**ensure, |e| map_expr_visitor_env(e, map, env, fe, fs, ft))?;
+            map.pop_scope();
+            let body = map_expr_visitor_env(body, map, env, fe, fs, ft)?;
+            map.pop_scope();
+            ExprX::ExecClosure {
+                params: Arc::new(params),
+                require: Arc::new(require),
+                ensure: Arc::new(ensure),
+                ret,
+                body,
+            }
+        }
+        ExprX::ClosureSpec(op, fun, es) => {
+            let fun = map_expr_visitor_env(fun, map, env, fe, fs, ft)?;
+            let es = vec_map_result(&**es, |e| map_expr_visitor_env(e, map, env, fe, fs, ft))?;
+            ExprX::ClosureSpec(*op, fun, Arc::new(es))
+        }
         ExprX::Choose { params, cond, body } => {
             let params =
                 vec_map_result(&**params, |b| b.map_result(|t| map_typ_visitor_env(t, env, ft)))?;
@@ -553,12 +644,34 @@ where
             });
             ExprX::Match(expr1, Arc::new(arms?))
         }
-        ExprX::While { cond, body, invs } => {
-            let cond = map_expr_visitor_env(cond, map, env, fe, fs, ft)?;
+        ExprX::Loop { label, cond, body, step, invs } => {
+            let cond =
+                cond.as_ref().map(|e| map_expr_visitor_env(e, map, env, fe, fs, ft)).transpose()?;
+            let body = map_expr_visitor_env(body, map, env, fe, fs, ft)?;
+            let step =
+                step.as_ref().map(|e| map_expr_visitor_env(e, map, env, fe, fs, ft)).transpose()?;
+            let invs = Arc::new(vec_map_result(invs, |inv| -> Result<LoopInvariant, VirErr> {
+                let e = map_expr_visitor_env(&inv.inv, map, env, fe, fs, ft)?;
+                Ok(LoopInvariant { kind: inv.kind, inv: e })
+            })?);
+            ExprX::Loop { label: label.clone(), cond, body, step, invs }
+        }
+        ExprX::ForRange { label, var, start, end, body, invs } => {
+            let start = map_expr_visitor_env(start, map, env, fe, fs, ft)?;
+            let end = map_expr_visitor_env(end, map, env, fe, fs, ft)?;
+            let var = var.map_result(|t| map_typ_visitor_env(t, env, ft))?;
+            map.push_scope(true);
+            let _ = map.insert(var.name.clone(), var.a.clone());
             let body = map_expr_visitor_env(body, map, env, fe, fs, ft)?;
-            let invs =
-                Arc::new(vec_map_result(invs, |e| map_expr_visitor_env(e, map, env, fe, fs, ft))?);
-            ExprX::While { cond, body, invs }
+            let invs = Arc::new(vec_map_result(invs, |inv| -> Result<LoopInvariant, VirErr> {
+                let e = map_expr_visitor_env(&inv.inv, map, env, fe, fs, ft)?;
+                Ok(LoopInvariant { kind: inv.kind, inv: e })
+            })?);
+            map.pop_scope();
+            ExprX::ForRange { label: label.clone(), var, start, end, body, invs }
+        }
+        ExprX::BreakOrContinue { label, is_break } => {
+            ExprX::BreakOrContinue { label: label.clone(), is_break: *is_break }
         }
         ExprX::Return(e1) => {
             let e1 = match e1 {
@@ -656,6 +769,9 @@ where
             let typ = map_typ_visitor_env(typ, env, ft)?;
             Ok(Arc::new(GenericBoundX::FnSpec(typs, typ)))
         }
+        GenericBoundX::Const(typ) => {
+            Ok(Arc::new(GenericBoundX::Const(map_typ_visitor_env(typ, env, ft)?)))
+        }
     }
 }
 
@@ -9,7 +9,8 @@ fn check_expr_simplified(expr: &Expr) -> Result<(), ()> {
         ExprX::ConstVar(_)
         | ExprX::UnaryOpr(UnaryOpr::TupleField { .. }, _)
         | ExprX::Tuple(_)
-        | ExprX::Match(..) => Err(()),
+        | ExprX::Match(..)
+        | ExprX::ForRange { .. } => Err(()),
         _ => Ok(()),
     }
 }
@@ -25,7 +26,7 @@ fn check_typ_simplified(typ: &Typ) -> Result<(), ()> {
 pub fn check_krate_simplified(krate: &Krate) {
     check_krate(krate);
 
-    let KrateX { functions, datatypes, traits: _, module_ids: _ } = &**krate;
+    let KrateX { functions, datatypes, traits: _, assoc_type_impls: _, module_ids: _ } = &**krate;
 
     for function in functions {
         let FunctionX { require, ensure, decrease, body, typ_bounds, params, ret, .. } =
@@ -41,6 +42,10 @@ pub fn check_krate_simplified(krate: &Krate) {
         for (_, bound) in typ_bounds.iter() {
             match &**bound {
                 GenericBoundX::Traits(_) => {}
+                GenericBoundX::Const(typ) => {
+                    typ_visitor_check(typ, &mut check_typ_simplified)
+                        .expect("function typ bound uses node that should have been simplified");
+                }
                 GenericBoundX::FnSpec(typs, typ) => {
                     let all_typs = typs.iter().chain(std::iter::once(typ));
                     for typ in all_typs {
@@ -103,7 +108,8 @@ fn expr_no_loc_in_spec(
 
 /// Panics if the ast uses nodes that should have been removed by ast_simplify
 pub fn check_krate(krate: &Krate) {
-    let KrateX { functions, datatypes: _, traits: _, module_ids: _ } = &**krate;
+    let KrateX { functions, datatypes: _, traits: _, assoc_type_impls: _, module_ids: _ } =
+        &**krate;
 
     for function in functions {
         let FunctionX { require, ensure, decrease, body, .. } = &function.x;
@@ -1,11 +1,11 @@
 use crate::ast::{
-    Datatype, Fun, Function, FunctionKind, GenericBound, IntRange, Krate, Mode, Path, Trait, TypX,
-    Variants, VirErr,
+    Datatype, Fun, Function, FunctionKind, GenericBound, Ident, IntRange, Krate, Mode, Path, Trait,
+    TypX, Variants, VirErr,
 };
 use crate::datatype_to_air::is_datatype_transparent;
 use crate::def::FUEL_ID;
 use crate::poly::MonoTyp;
-use crate::recursion::Node;
+use crate::recursion::{ImplBounds, Node};
 use crate::scc::Graph;
 use crate::sst_to_air::fun_to_air_ident;
 use crate::util::vec_map;
@@ -17,8 +17,12 @@ use std::sync::Arc;
 // Context for across all modules
 pub struct GlobalCtx {
     pub(crate) chosen_triggers: std::cell::RefCell<Vec<(Span, Vec<Vec<String>>)>>, // diagnostics
+    // Source location of each quantifier id generated for a user-written quantifier
+    pub(crate) qids: std::cell::RefCell<HashMap<Ident, Span>>, // diagnostics
     pub(crate) datatypes: HashMap<Path, Variants>,
     pub(crate) fun_bounds: HashMap<Fun, Vec<GenericBound>>,
+    // Functions that return &mut
+    pub(crate) mut_ret_funs: HashSet<Fun>,
     // Used for synthesized AST nodes that have no relation to any location in the original code:
     pub(crate) no_span: Span,
     pub func_call_graph: Graph<Node>,
@@ -74,7 +78,10 @@ fn datatypes_invs(
                     match &*field.a.0 {
                         // Should be kept in sync with vir::sst_to_air::typ_invariant
                         TypX::Int(IntRange::Int) => {}
-                        TypX::Int(_) | TypX::TypParam(_) => {
+                        TypX::Int(_)
+                        | TypX::TypParam(_)
+                        | TypX::Projection(..)
+                        | TypX::Array(..) => {
                             roots.insert(container_path.clone());
                         }
                         TypX::Lambda(..) => {
@@ -110,6 +117,8 @@ impl GlobalCtx {
     pub fn new(krate: &Krate, no_span: Span) -> Result<Self, VirErr> {
         let chosen_triggers: std::cell::RefCell<Vec<(Span, Vec<Vec<String>>)>> =
             std::cell::RefCell::new(Vec::new());
+        let qids: std::cell::RefCell<HashMap<Ident, Span>> =
+            std::cell::RefCell::new(HashMap::new());
         let datatypes: HashMap<Path, Variants> =
             krate.datatypes.iter().map(|d| (d.x.path.clone(), d.x.variants.clone())).collect();
         let mut func_map: HashMap<Fun, Function> = HashMap::new();
@@ -118,27 +127,44 @@ impl GlobalCtx {
             func_map.insert(function.x.name.clone(), function.clone());
         }
         let mut method_map: HashMap<(Fun, Path), Fun> = HashMap::new();
+        let mut impl_bounds: HashMap<(Path, Path), ImplBounds> = HashMap::new();
         for function in krate.functions.iter() {
-            if let FunctionKind::TraitMethodImpl { method, datatype, .. } = &function.x.kind {
+            if let FunctionKind::TraitMethodImpl { method, trait_path, datatype, .. } =
+                &function.x.kind
+            {
                 let key = (method.clone(), datatype.clone());
                 assert!(!method_map.contains_key(&key));
                 method_map.insert(key, function.x.name.clone());
+                let bounds = crate::recursion::trait_impl_bounds(&func_map, function)?;
+                impl_bounds.insert((datatype.clone(), trait_path.clone()), bounds);
             }
         }
         let mut fun_bounds: HashMap<Fun, Vec<GenericBound>> = HashMap::new();
+        let mut mut_ret_funs: HashSet<Fun> = HashSet::new();
         let mut func_call_graph: Graph<Node> = Graph::new();
         for f in &krate.functions {
             let bounds = vec_map(&f.x.typ_bounds, |(_, bound)| bound.clone());
             fun_bounds.insert(f.x.name.clone(), bounds);
+            if f.x.ret.x.is_mut {
+                mut_ret_funs.insert(f.x.name.clone());
+            }
             func_call_graph.add_node(Node::Fun(f.x.name.clone()));
-            crate::recursion::expand_call_graph(&func_map, &method_map, &mut func_call_graph, f)?;
+            crate::recursion::expand_call_graph(
+                &func_map,
+                &method_map,
+                &impl_bounds,
+                &mut func_call_graph,
+                f,
+            )?;
         }
         func_call_graph.compute_sccs();
         let func_call_sccs = func_call_graph.sort_sccs();
         Ok(GlobalCtx {
             chosen_triggers,
+            qids,
             datatypes,
             fun_bounds,
+            mut_ret_funs,
             no_span,
             func_call_graph,
             func_call_sccs,
@@ -150,6 +176,10 @@ impl GlobalCtx {
     pub fn get_chosen_triggers(&self) -> Vec<(Span, Vec<Vec<String>>)> {
         self.chosen_triggers.borrow().clone()
     }
+
+    pub fn get_qid_span(&self, qid: &str) -> Option<Span> {
+        self.qids.borrow().get(&Arc::new(qid.to_string())).cloned()
+    }
 }
 
 impl Ctx {
@@ -1,12 +1,13 @@
 use crate::ast::{
-    DatatypeTransparency, Field, GenericBoundX, Ident, Idents, Mode, Path, Typ, TypX, Variants,
+    ClosureSpec, DatatypeTransparency, Field, GenericBoundX, Ident, Idents, Mode, Path, Typ, TypX,
+    Variants,
 };
 use crate::ast_util::is_visible_to_of_owner;
 use crate::context::Ctx;
 use crate::def::{
-    prefix_box, prefix_lambda_type, prefix_tuple_param, prefix_type_id, prefix_unbox,
-    suffix_local_stmt_id, variant_field_ident, variant_field_ident_internal, variant_ident,
-    Spanned,
+    prefix_box, prefix_closure_spec, prefix_lambda_type, prefix_tuple_param, prefix_type_id,
+    prefix_unbox, suffix_local_stmt_id, variant_field_ident, variant_field_ident_internal,
+    variant_ident, Spanned,
 };
 use crate::func_to_air::{func_bind, func_bind_trig, func_def_args};
 use crate::sst::{Par, ParPurpose, ParX};
@@ -134,19 +135,27 @@ fn datatype_or_fun_to_air_commands(
     let has = expr_has_type(&id, &x_var);
     let has_box = expr_has_type(&id, &box_x);
     let vpolytyp = Arc::new(TypX::Boxed(datatyp.clone()));
+    let qid = |kind: &str| format!("{}_{}", path_to_air_ident(dpath), kind);
 
     if declare_box {
         // box axiom:
         //   forall x. x == unbox(box(x))
         // trigger on box(x)
-        let bind = func_bind(ctx, &Arc::new(vec![]), &x_params(&datatyp), &box_x, false);
+        let bind = func_bind(
+            ctx,
+            &qid("box_axiom"),
+            &Arc::new(vec![]),
+            &x_params(&datatyp),
+            &box_x,
+            false,
+        );
         let forall = mk_bind_expr(&bind, &mk_eq(&x_var, &unbox_box_x));
         axiom_commands.push(Arc::new(CommandX::Global(Arc::new(DeclX::Axiom(forall)))));
 
         // unbox axiom:
         //   forall typs, x. has_type(x, T(typs)) => x == box(unbox(x))
         // trigger on has_type(x, T(typs))
-        let bind = func_bind(ctx, tparams, &x_params(&vpolytyp), &has, false);
+        let bind = func_bind(ctx, &qid("unbox_axiom"), tparams, &x_params(&vpolytyp), &has, false);
         let forall = mk_bind_expr(&bind, &mk_implies(&has, &mk_eq(&x_var, &box_unbox_x)));
         axiom_commands.push(Arc::new(CommandX::Global(Arc::new(DeclX::Axiom(forall)))));
     }
@@ -182,15 +191,28 @@ fn datatype_or_fun_to_air_commands(
         //   has_type(box(mk_fun(x)), FUN(typ1...typn, tret))
         // trigger on has_type(box(mk_fun(x)), FUN(typ1...typn, tret))
         let inner_trigs = vec![has_app.clone()];
-        let inner_bind =
-            func_bind_trig(ctx, &Arc::new(vec![]), &Arc::new(params.clone()), &inner_trigs, false);
+        let inner_bind = func_bind_trig(
+            ctx,
+            &qid("apply_inner"),
+            &Arc::new(vec![]),
+            &Arc::new(params.clone()),
+            &inner_trigs,
+            false,
+        );
         let inner_imply = mk_implies(&mk_and(&pre), &has_app);
         let inner_forall = mk_bind_expr(&inner_bind, &inner_imply);
         let mk_fun = str_apply(crate::def::MK_FUN, &vec![x_var.clone()]);
         let box_mk_fun = ident_apply(&prefix_box(&dpath), &vec![mk_fun]);
         let has_box_mk_fun = expr_has_type(&id, &box_mk_fun);
         let trigs = vec![has_box_mk_fun.clone()];
-        let bind = func_bind_trig(ctx, tparams, &Arc::new(vec![x_param(&datatyp)]), &trigs, false);
+        let bind = func_bind_trig(
+            ctx,
+            &qid("constructor"),
+            tparams,
+            &Arc::new(vec![x_param(&datatyp)]),
+            &trigs,
+            false,
+        );
         let imply = mk_implies(&inner_forall, &has_box_mk_fun);
         let forall = mk_bind_expr(&bind, &imply);
         let axiom = Arc::new(DeclX::Axiom(forall));
@@ -203,7 +225,7 @@ fn datatype_or_fun_to_air_commands(
         params.push(x_param(&datatyp));
         pre.insert(0, has_box.clone());
         let trigs = vec![app, has_box.clone()];
-        let bind = func_bind_trig(ctx, tparams, &Arc::new(params), &trigs, false);
+        let bind = func_bind_trig(ctx, &qid("apply"), tparams, &Arc::new(params), &trigs, false);
         let imply = mk_implies(&mk_and(&pre), &has_app);
         let forall = mk_bind_expr(&bind, &imply);
         let axiom = Arc::new(DeclX::Axiom(forall));
@@ -231,7 +253,14 @@ fn datatype_or_fun_to_air_commands(
                     pre.push(inv);
                 }
             }
-            let bind = func_bind(ctx, tparams, &params, &has_ctor, false);
+            let bind = func_bind(
+                ctx,
+                &qid(&format!("constructor_{}", variant.name)),
+                tparams,
+                &params,
+                &has_ctor,
+                false,
+            );
             let imply = mk_implies(&mk_and(&pre), &has_ctor);
             let forall = mk_bind_expr(&bind, &imply);
             let axiom = Arc::new(DeclX::Axiom(forall));
@@ -256,7 +285,14 @@ fn datatype_or_fun_to_air_commands(
                 Arc::new(DeclX::Fun(id, Arc::new(vec![dtyp.clone()]), typ_to_air(ctx, typ)));
             field_commands.push(Arc::new(CommandX::Global(decl_field)));
             let trigs = vec![xfield.clone()];
-            let bind = func_bind_trig(ctx, &Arc::new(vec![]), &x_params(&datatyp), &trigs, false);
+            let bind = func_bind_trig(
+                ctx,
+                &qid(&format!("accessor_{}_{}", variant.name, field.name)),
+                &Arc::new(vec![]),
+                &x_params(&datatyp),
+                &trigs,
+                false,
+            );
             let eq = mk_eq(&xfield, &xfield_internal);
             let forall = mk_bind_expr(&bind, &eq);
             let axiom = Arc::new(DeclX::Axiom(forall));
@@ -268,7 +304,14 @@ fn datatype_or_fun_to_air_commands(
                     //   forall typs, x. has_type(x, T(typs)) => inv_f(unbox(x).f)
                     // trigger on unbox(x).f, has_type(x, T(typs))
                     let trigs = vec![xfield_unbox.clone(), has.clone()];
-                    let bind = func_bind_trig(ctx, tparams, &x_params(&vpolytyp), &trigs, false);
+                    let bind = func_bind_trig(
+                        ctx,
+                        &qid(&format!("invariant_{}_{}", variant.name, field.name)),
+                        tparams,
+                        &x_params(&vpolytyp),
+                        &trigs,
+                        false,
+                    );
                     let imply = mk_implies(&has, &inv_f);
                     let forall = mk_bind_expr(&bind, &imply);
                     let axiom = Arc::new(DeclX::Axiom(forall));
@@ -283,7 +326,8 @@ fn datatype_or_fun_to_air_commands(
         // or type is completely abstract to us), then has_type always holds:
         //   forall typs, x. has_type(box(x), T(typs))
         // trigger on has_type(box(x), T(typs))
-        let bind = func_bind(ctx, tparams, &x_params(&datatyp), &has_box, false);
+        let bind =
+            func_bind(ctx, &qid("has_type_always"), tparams, &x_params(&datatyp), &has_box, false);
         let forall = mk_bind_expr(&bind, &has_box);
         axiom_commands.push(Arc::new(CommandX::Global(Arc::new(DeclX::Axiom(forall)))));
     }
@@ -342,6 +386,23 @@ pub fn datatypes_to_air(ctx: &Ctx, datatypes: &crate::ast::Datatypes) -> Command
             true,
             false,
         );
+
+        // requires/ensures of exec closures with lambda_n_params parameters:
+        // closure_req%n(f, arg1, ..., argn) and closure_ens%n(f, arg1, ..., argn, ret)
+        let fun_typ = Arc::new(air::ast::TypX::Lambda);
+        let poly_typ = str_typ(crate::def::POLY);
+        for (spec, n_args) in
+            [(ClosureSpec::Requires, *lambda_n_params), (ClosureSpec::Ensures, lambda_n_params + 1)]
+        {
+            let mut typs = vec![fun_typ.clone()];
+            typs.extend((0..n_args).map(|_| poly_typ.clone()));
+            let decl = Arc::new(DeclX::Fun(
+                prefix_closure_spec(spec, *lambda_n_params),
+                Arc::new(typs),
+                Arc::new(air::ast::TypX::Bool),
+            ));
+            field_commands.push(Arc::new(CommandX::Global(decl)));
+        }
     }
 
     for monotyp in &ctx.mono_abstract_datatypes {
@@ -382,6 +443,7 @@ pub fn datatypes_to_air(ctx: &Ctx, datatypes: &crate::ast::Datatypes) -> Command
         for (name, bound, _strict_pos) in datatype.x.typ_params.iter() {
             match &**bound {
                 GenericBoundX::Traits(ts) if ts.len() == 0 => {}
+                GenericBoundX::Const(_) => {}
                 _ => panic!("datatype type parameter bounds"),
             }
             tparams.push(name.clone());
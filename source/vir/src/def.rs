@@ -1,4 +1,4 @@
-use crate::ast::{Fun, FunX, InvAtomicity, Path, PathX};
+use crate::ast::{ClosureSpec, Fun, FunX, InvAtomicity, Path, PathX};
 use crate::sst::UniqueIdent;
 use crate::util::vec_map;
 use air::ast::{Ident, Span};
@@ -49,10 +49,13 @@ const PREFIX_PRE_VAR: &str = "pre%";
 const PREFIX_BOX: &str = "Poly%";
 const PREFIX_UNBOX: &str = "%Poly%";
 const PREFIX_TYPE_ID: &str = "TYPE%";
+const PREFIX_PROJECTION: &str = "proj%%";
 const PREFIX_TUPLE_TYPE: &str = "tuple%";
 const PREFIX_TUPLE_PARAM: &str = "T%";
 const PREFIX_TUPLE_FIELD: &str = "field%";
 const PREFIX_LAMBDA_TYPE: &str = "fun%";
+const PREFIX_CLOSURE_REQ: &str = "closure_req%";
+const PREFIX_CLOSURE_ENS: &str = "closure_ens%";
 const PREFIX_SNAPSHOT: &str = "snap%";
 const KRATE_SEPARATOR: &str = "!";
 const PATH_SEPARATOR: &str = ".";
@@ -66,6 +69,11 @@ const MONOTYPE_APP_BEGIN: &str = "<";
 const MONOTYPE_APP_END: &str = ">";
 const DECREASE_AT_ENTRY: &str = "decrease%init";
 const TRAIT_SELF_TYPE_PARAM: &str = "Self%";
+// Every quantifier id generated by the verifier, including the literal ids in prelude.rs,
+// starts with PREFIX_QID_INTERNAL; the ids of quantifiers written by the user start with
+// PREFIX_QID_USER
+const PREFIX_QID_INTERNAL: &str = "internal_";
+const PREFIX_QID_USER: &str = "user_";
 
 pub const SUFFIX_SNAP_MUT: &str = "_mutation";
 pub const SUFFIX_SNAP_JOIN: &str = "_join";
@@ -83,14 +91,19 @@ pub const FUEL_BOOL: &str = "fuel_bool";
 pub const FUEL_BOOL_DEFAULT: &str = "fuel_bool_default";
 pub const FUEL_DEFAULTS: &str = "fuel_defaults";
 pub const RETURN_VALUE: &str = "%return";
+pub const TRY_VALUE: &str = "try%value";
+pub const TRY_RESIDUAL: &str = "try%residual";
+pub const FINAL_VALUE: &str = "%final";
 pub const U_HI: &str = "uHi";
 pub const I_LO: &str = "iLo";
 pub const I_HI: &str = "iHi";
 pub const U_CLIP: &str = "uClip";
 pub const I_CLIP: &str = "iClip";
 pub const NAT_CLIP: &str = "nClip";
+pub const CHAR_CLIP: &str = "charClip";
 pub const U_INV: &str = "uInv";
 pub const I_INV: &str = "iInv";
+pub const CHAR_INV: &str = "charInv";
 pub const ARCH_SIZE: &str = "SZ";
 pub const SNAPSHOT_CALL: &str = "CALL";
 pub const SNAPSHOT_PRE: &str = "PRE";
@@ -103,8 +116,11 @@ pub const TYPE: &str = "Type";
 pub const TYPE_ID_BOOL: &str = "BOOL";
 pub const TYPE_ID_INT: &str = "INT";
 pub const TYPE_ID_NAT: &str = "NAT";
+pub const TYPE_ID_CHAR: &str = "CHAR";
 pub const TYPE_ID_UINT: &str = "UINT";
 pub const TYPE_ID_SINT: &str = "SINT";
+pub const TYPE_ID_CONST_INT: &str = "CONST_INT";
+pub const CONST_INT: &str = "const_int";
 pub const HAS_TYPE: &str = "has_type";
 pub const AS_TYPE: &str = "as_type";
 pub const MK_FUN: &str = "mk_fun";
@@ -210,6 +226,10 @@ pub fn prefix_type_id(path: &Path) -> Ident {
     Arc::new(PREFIX_TYPE_ID.to_string() + &path_to_string(path))
 }
 
+pub fn prefix_projection(trait_path: &Path, name: &Ident) -> Ident {
+    Arc::new(PREFIX_PROJECTION.to_string() + &path_to_string(trait_path) + name)
+}
+
 pub fn prefix_tuple_type(i: usize) -> Path {
     let ident = Arc::new(format!("{}{}", PREFIX_TUPLE_TYPE, i));
     Arc::new(PathX { krate: None, segments: Arc::new(vec![ident]) })
@@ -232,6 +252,14 @@ pub fn prefix_lambda_type(i: usize) -> Path {
     Arc::new(PathX { krate: None, segments: Arc::new(vec![ident]) })
 }
 
+pub fn prefix_closure_spec(spec: ClosureSpec, i: usize) -> Ident {
+    let prefix = match spec {
+        ClosureSpec::Requires => PREFIX_CLOSURE_REQ,
+        ClosureSpec::Ensures => PREFIX_CLOSURE_ENS,
+    };
+    Arc::new(format!("{}{}", prefix, i))
+}
+
 pub fn prefix_type_id_fun(i: usize) -> Ident {
     prefix_type_id(&prefix_lambda_type(i))
 }
@@ -341,6 +369,33 @@ pub fn monotyp_apply(datatype: &Path, args: &Vec<Path>) -> Path {
     }
 }
 
+/// Quantifier id for axioms generated internally (e.g. function definitions, datatype axioms)
+pub fn internal_qid(name: &str) -> Ident {
+    Arc::new(format!("{}{}", PREFIX_QID_INTERNAL, name))
+}
+
+/// Quantifier id for a quantifier written by the user, derived from its source location
+/// (so that the id is stable across unrelated changes elsewhere in the crate)
+pub fn user_qid(span: &Span) -> Ident {
+    let mut name = PREFIX_QID_USER.to_string();
+    let loc = match span.as_string.find(" (") {
+        Some(i) => &span.as_string[..i],
+        None => &span.as_string[..],
+    };
+    for c in loc.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else if !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    Arc::new(name)
+}
+
+pub fn is_user_qid(qid: &str) -> bool {
+    qid.starts_with(PREFIX_QID_USER)
+}
+
 pub fn snapshot_ident(name: &str) -> Ident {
     Arc::new(format!("{}{}", PREFIX_SNAPSHOT, name))
 }
@@ -428,3 +483,56 @@ pub fn fn_namespace_name(atomicity: InvAtomicity) -> Fun {
         trait_path: None,
     })
 }
+
+pub fn seq_type_path() -> Path {
+    Arc::new(PathX {
+        krate: None,
+        segments: Arc::new(vec![
+            Arc::new("pervasive".to_string()),
+            Arc::new("seq".to_string()),
+            Arc::new("Seq".to_string()),
+        ]),
+    })
+}
+
+pub fn fn_seq_name(name: &str) -> Fun {
+    let mut path = seq_type_path();
+    Arc::make_mut(&mut Arc::make_mut(&mut path).segments).push(Arc::new(name.to_string()));
+    Arc::new(FunX { path, trait_path: None })
+}
+
+pub fn option_type_path() -> Path {
+    Arc::new(PathX {
+        krate: None,
+        segments: Arc::new(vec![
+            Arc::new("pervasive".to_string()),
+            Arc::new("option".to_string()),
+            Arc::new("Option".to_string()),
+        ]),
+    })
+}
+
+pub fn result_type_path() -> Path {
+    Arc::new(PathX {
+        krate: None,
+        segments: Arc::new(vec![
+            Arc::new("pervasive".to_string()),
+            Arc::new("result".to_string()),
+            Arc::new("Result".to_string()),
+        ]),
+    })
+}
+
+pub fn fn_array_name(name: &str) -> Fun {
+    Arc::new(FunX {
+        path: Arc::new(PathX {
+            krate: None,
+            segments: Arc::new(vec![
+                Arc::new("pervasive".to_string()),
+                Arc::new("array".to_string()),
+                Arc::new(name.to_string()),
+            ]),
+        }),
+        trait_path: None,
+    })
+}
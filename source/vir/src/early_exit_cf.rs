@@ -1,4 +1,4 @@
-use crate::ast::{CallTarget, Expr, ExprX, VirErr};
+use crate::ast::{CallTarget, Expr, ExprX, Ident, VirErr};
 use crate::ast_visitor::{expr_visitor_dfs, VisitorControlFlow, VisitorScopeMap};
 use air::ast::Span;
 use air::errors::error;
@@ -6,7 +6,9 @@ use air::scope_map::ScopeMap;
 
 #[derive(Copy, Clone, Debug)]
 enum StatementType {
-    Return, // TODO add Break and Continue when they are supported
+    Return,
+    Break,
+    Continue,
 }
 
 #[derive(Clone, Debug)]
@@ -25,6 +27,19 @@ pub fn assert_no_early_exit_in_inv_block(inv_span: &Span, expr: &Expr) -> Result
     }
 }
 
+pub fn assert_no_early_exit_in_exec_closure(
+    closure_span: &Span,
+    body: &Expr,
+) -> Result<(), VirErr> {
+    let v = expr_get_early_exits(body);
+    if v.len() == 0 {
+        Ok(())
+    } else {
+        Err(error("exec closure body cannot return, break, or continue early", closure_span)
+            .primary_label(&v[0].span, "would exit from here"))
+    }
+}
+
 /// Walk the AST and find all return/break/continue statements that would cause an
 /// 'early exit' from the expression. Does *not* recurse into nested Invariant blocks,
 /// to avoid quadratic behavior, and to avoid doubling up the errors.
@@ -32,17 +47,16 @@ pub fn assert_no_early_exit_in_inv_block(inv_span: &Span, expr: &Expr) -> Result
 fn expr_get_early_exits(expr: &Expr) -> Vec<EarlyExitInst> {
     let mut v = Vec::new();
     let mut scope_map = ScopeMap::new();
-    expr_get_early_exits_rec(expr, false, &mut scope_map, &mut v);
+    expr_get_early_exits_rec(expr, &[], &mut scope_map, &mut v);
     v
 }
 
-/// While recursing, we keep track of whether we entered a loop or not; then we can know
+/// While recursing, we keep track of the labels of the loops we entered; then we can know
 /// if a break/continue would cause an exit at the high-level expr.
-/// (Well, it will be useful when we implement break/continue, anyway.)
 
 fn expr_get_early_exits_rec(
     expr: &Expr,
-    in_loop: bool,
+    in_loops: &[Option<Ident>],
     scope_map: &mut VisitorScopeMap,
     results: &mut Vec<EarlyExitInst>,
 ) {
@@ -53,9 +67,11 @@ fn expr_get_early_exits_rec(
             | ExprX::VarLoc(..)
             | ExprX::VarAt(..)
             | ExprX::ConstVar(..)
+            | ExprX::ConstParam(..)
             | ExprX::Loc(..)
             | ExprX::Call(CallTarget::Static(..), _)
             | ExprX::Call(CallTarget::FnSpec(..), _)
+            | ExprX::Call(CallTarget::FnExec(..), _)
             | ExprX::Tuple(..)
             | ExprX::Ctor(..)
             | ExprX::Unary(..)
@@ -67,15 +83,44 @@ fn expr_get_early_exits_rec(
             | ExprX::Block(..) => VisitorControlFlow::Recurse,
             ExprX::Quant(..)
             | ExprX::Closure(..)
+            | ExprX::ExecClosure { .. }
+            | ExprX::ClosureSpec(..)
             | ExprX::Choose { .. }
             | ExprX::AssertBV(..)
             | ExprX::Fuel(..)
             | ExprX::Header(..)
             | ExprX::Admit
             | ExprX::Forall { .. } => VisitorControlFlow::Return,
-            ExprX::While { cond, body, invs: _ } => {
-                expr_get_early_exits_rec(cond, in_loop, scope_map, results);
-                expr_get_early_exits_rec(body, true, scope_map, results);
+            ExprX::Loop { label, cond, body, step, invs: _ } => {
+                if let Some(cond) = cond {
+                    expr_get_early_exits_rec(cond, in_loops, scope_map, results);
+                }
+                if let Some(step) = step {
+                    expr_get_early_exits_rec(step, in_loops, scope_map, results);
+                }
+                let mut in_loops = in_loops.to_vec();
+                in_loops.push(label.clone());
+                expr_get_early_exits_rec(body, &in_loops, scope_map, results);
+                VisitorControlFlow::Return
+            }
+            ExprX::ForRange { label, var: _, start, end, body, invs: _ } => {
+                expr_get_early_exits_rec(start, in_loops, scope_map, results);
+                expr_get_early_exits_rec(end, in_loops, scope_map, results);
+                let mut in_loops = in_loops.to_vec();
+                in_loops.push(label.clone());
+                expr_get_early_exits_rec(body, &in_loops, scope_map, results);
+                VisitorControlFlow::Return
+            }
+            ExprX::BreakOrContinue { label, is_break } => {
+                let exits = match label {
+                    None => in_loops.len() == 0,
+                    Some(label) => !in_loops.iter().any(|l| l.as_ref() == Some(label)),
+                };
+                if exits {
+                    let statement =
+                        if *is_break { StatementType::Break } else { StatementType::Continue };
+                    results.push(EarlyExitInst { span: expr.span.clone(), _statement: statement });
+                }
                 VisitorControlFlow::Return
             }
             ExprX::Return(_) => {
@@ -86,7 +131,7 @@ fn expr_get_early_exits_rec(
                 VisitorControlFlow::Recurse
             }
             ExprX::OpenInvariant(inv, _binder, _body, _atomicity) => {
-                expr_get_early_exits_rec(inv, in_loop, scope_map, results);
+                expr_get_early_exits_rec(inv, in_loops, scope_map, results);
                 // Skip checking nested loops to avoid quadratic behavior:
                 VisitorControlFlow::Return
             }
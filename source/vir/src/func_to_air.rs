@@ -1,14 +1,15 @@
 use crate::ast::{
-    Function, FunctionKind, GenericBoundX, Ident, Idents, Mode, Param, ParamX, Params,
+    Function, FunctionKind, GenericBoundX, Ident, Idents, MaskSpec, Mode, Param, ParamX, Params,
     SpannedTyped, Typ, TypX, Typs, VirErr,
 };
 use crate::context::Ctx;
 use crate::def::{
-    prefix_ensures, prefix_fuel_id, prefix_fuel_nat, prefix_pre_var, prefix_recursive_fun,
-    prefix_requires, suffix_global_id, suffix_local_stmt_id, suffix_typ_param_id, SnapPos, Spanned,
-    FUEL_BOOL, FUEL_BOOL_DEFAULT, FUEL_LOCAL, FUEL_TYPE, SUCC, ZERO,
+    internal_qid, prefix_ensures, prefix_fuel_id, prefix_fuel_nat, prefix_pre_var,
+    prefix_recursive_fun, prefix_requires, suffix_global_id, suffix_local_stmt_id,
+    suffix_typ_param_id, SnapPos, Spanned, FUEL_BOOL, FUEL_BOOL_DEFAULT, FUEL_LOCAL, FUEL_TYPE,
+    SUCC, ZERO,
 };
-use crate::sst::{BndX, ExpX, Par, ParPurpose, ParX, Pars};
+use crate::sst::{BndX, Exp, ExpX, Par, ParPurpose, ParX, Pars, SourceVar, Stm, StmX};
 use crate::sst_to_air::{exp_to_expr, fun_to_air_ident, typ_invariant, typ_to_air, ExprCtxt};
 use crate::util::{vec_map, vec_map_result};
 use air::ast::{
@@ -19,12 +20,13 @@ use air::ast_util::{
     bool_typ, ident_apply, ident_binder, ident_var, mk_and, mk_bind_expr, mk_eq, mk_implies,
     str_apply, str_ident, str_typ, str_var, string_apply,
 };
-use air::errors::ErrorLabel;
+use air::errors::{error, ErrorLabel};
 use std::sync::Arc;
 
 // binder for forall (typ_params params)
 pub(crate) fn func_bind_trig(
     ctx: &Ctx,
+    qid: &str,
     typ_params: &Idents,
     params: &Pars,
     trig_exprs: &Vec<Expr>,
@@ -47,18 +49,19 @@ pub(crate) fn func_bind_trig(
     }
     let trigger: Trigger = Arc::new(trig_exprs.clone());
     let triggers: Triggers = Arc::new(vec![trigger]);
-    Arc::new(BindX::Quant(Quant::Forall, Arc::new(binders), triggers))
+    Arc::new(BindX::Quant(Quant::Forall, Arc::new(binders), triggers, Some(internal_qid(qid))))
 }
 
 // binder for forall (typ_params params)
 pub(crate) fn func_bind(
     ctx: &Ctx,
+    qid: &str,
     typ_params: &Idents,
     params: &Pars,
     trig_expr: &Expr,
     add_fuel: bool,
 ) -> Bind {
-    func_bind_trig(ctx, typ_params, params, &vec![trig_expr.clone()], add_fuel)
+    func_bind_trig(ctx, qid, typ_params, params, &vec![trig_expr.clone()], add_fuel)
 }
 
 // arguments for function call f(typ_args, params)
@@ -96,7 +99,7 @@ fn func_def_quant(
     let f_args = func_def_typs_args(typ_args, params);
     let f_app = string_apply(name, &Arc::new(f_args));
     let f_eq = Arc::new(ExprX::Binary(BinaryOp::Eq, f_app.clone(), body));
-    Ok(mk_bind_expr(&func_bind(ctx, typ_params, params, &f_app, false), &f_eq))
+    Ok(mk_bind_expr(&func_bind(ctx, &name, typ_params, params, &f_app, false), &f_eq))
 }
 
 fn func_body_to_air(
@@ -138,6 +141,12 @@ fn func_body_to_air(
         let self_typ = Arc::new(TypX::Datatype(datatype.clone(), datatype_typ_args.clone()));
         let mut typ_args = vec![self_typ];
         typ_args.append(&mut (**trait_typ_args).clone());
+        // The method's own type parameters come last in both the declaration and the impl
+        let num_method_typs = ctx.func_map[method].x.typ_bounds.len() - typ_args.len();
+        let typ_params = function.x.typ_params();
+        for x in typ_params[typ_params.len() - num_method_typs..].iter() {
+            typ_args.push(Arc::new(TypX::TypParam(x.clone())));
+        }
         (method.clone(), typ_args)
     } else {
         let typ_args = vec_map(&function.x.typ_params(), |x| Arc::new(TypX::TypParam(x.clone())));
@@ -172,8 +181,11 @@ fn func_body_to_air(
         let rec_f_def = ident_apply(&rec_f, &args_def);
         let eq_zero = mk_eq(&rec_f_fuel, &rec_f_zero);
         let eq_body = mk_eq(&rec_f_succ, &body_expr);
-        let bind_zero = func_bind(ctx, &function.x.typ_params(), &pars, &rec_f_fuel, true);
-        let bind_body = func_bind(ctx, &function.x.typ_params(), &pars, &rec_f_succ, true);
+        let typ_params = function.x.typ_params();
+        let qid_zero = format!("{}_fuel_to_zero", rec_f);
+        let qid_body = format!("{}_fuel_to_body", rec_f);
+        let bind_zero = func_bind(ctx, &qid_zero, &typ_params, &pars, &rec_f_fuel, true);
+        let bind_body = func_bind(ctx, &qid_body, &typ_params, &pars, &rec_f_succ, true);
         let forall_zero = mk_bind_expr(&bind_zero, &eq_zero);
         let forall_body = mk_bind_expr(&bind_body, &eq_body);
         let fuel_nat_decl = Arc::new(DeclX::Const(fuel_nat_f, str_typ(FUEL_TYPE)));
@@ -394,6 +406,7 @@ pub fn func_decl_to_air(
                 let e_forall = mk_bind_expr(
                     &func_bind(
                         ctx,
+                        &format!("{}_pre_post", name),
                         &function.x.typ_params(),
                         &params_to_pars(&function.x.params, false),
                         &f_app,
@@ -407,9 +420,11 @@ pub fn func_decl_to_air(
         }
         Mode::Exec | Mode::Proof => {
             if let FunctionKind::TraitMethodImpl { .. } = &function.x.kind {
-                // For a trait method implementation, we inherit the trait requires/ensures,
-                // so we can just return here.
-                return Ok((Arc::new(decl_commands), Arc::new(check_commands)));
+                if !crate::traits::has_own_spec(function) {
+                    // For a trait method implementation, we inherit the trait requires/ensures,
+                    // so we can just return here.
+                    return Ok((Arc::new(decl_commands), Arc::new(check_commands)));
+                }
             }
 
             let msg = match &function.x.attrs.custom_req_err {
@@ -477,15 +492,15 @@ pub fn func_decl_to_air(
                 let mut binders: Vec<Binder<Typ>> = Vec::new();
                 for (name, bound) in function.x.typ_bounds.iter() {
                     match &**bound {
-                        GenericBoundX::Traits(ts) if ts.len() == 0 => {
+                        GenericBoundX::Traits(ts) if ts.len() > 0 => {
+                            todo!()
+                        }
+                        GenericBoundX::Traits(_) | GenericBoundX::Const(_) => {
                             vars.push(crate::def::suffix_typ_param_id(&name));
                             let typ = Arc::new(TypX::TypeId);
                             let bind = BinderX { name: name.clone(), a: typ };
                             binders.push(Arc::new(bind));
                         }
-                        GenericBoundX::Traits(_) => {
-                            todo!()
-                        }
                         GenericBoundX::FnSpec(..) => {}
                     }
                 }
@@ -510,34 +525,69 @@ pub fn func_decl_to_air(
     Ok((Arc::new(decl_commands), Arc::new(check_commands)))
 }
 
+/// Substitution of the type parameters of a trait method declaration
+/// with the type arguments used by a trait method implementation:
+/// Self and the trait's type parameters become the impl's self type and trait type arguments,
+/// and the method's own type parameters become the implementation's method type parameters
+/// (which may have different names)
+fn trait_method_impl_typ_substs(ctx: &Ctx, function: &Function) -> Vec<(Ident, Typ)> {
+    if let FunctionKind::TraitMethodImpl {
+        method,
+        trait_path,
+        trait_typ_args,
+        datatype,
+        datatype_typ_args,
+    } = &function.x.kind
+    {
+        let self_typ = Arc::new(TypX::Datatype(datatype.clone(), datatype_typ_args.clone()));
+        let mut trait_typ_substs: Vec<(Ident, Typ)> =
+            vec![(crate::def::trait_self_type_param(), self_typ)];
+        let tr = &ctx.trait_map[trait_path];
+        assert!(tr.x.typ_params.len() == trait_typ_args.len());
+        for ((x, _, _), t) in tr.x.typ_params.iter().zip(trait_typ_args.iter()) {
+            trait_typ_substs.push((x.clone(), t.clone()));
+        }
+        let decl_typ_params = ctx.func_map[method].x.typ_params();
+        let impl_typ_params = function.x.typ_params();
+        let num_method_typs = decl_typ_params.len() - trait_typ_substs.len();
+        let decl_method_typ_params = &decl_typ_params[decl_typ_params.len() - num_method_typs..];
+        let impl_method_typ_params = &impl_typ_params[impl_typ_params.len() - num_method_typs..];
+        for (x, y) in decl_method_typ_params.iter().zip(impl_method_typ_params.iter()) {
+            trait_typ_substs.push((x.clone(), Arc::new(TypX::TypParam(y.clone()))));
+        }
+        trait_typ_substs
+    } else {
+        panic!("internal error: expected trait method implementation")
+    }
+}
+
 pub fn func_def_to_air(
     ctx: &Ctx,
     function: &Function,
-) -> Result<(Commands, Vec<(Span, SnapPos)>), VirErr> {
+) -> Result<(Commands, Vec<(Span, SnapPos)>, Vec<SourceVar>), VirErr> {
     match (function.x.mode, function.x.ret.as_ref(), function.x.body.as_ref()) {
         (Mode::Exec, _, Some(body)) | (Mode::Proof, _, Some(body)) => {
-            let (trait_typ_substs, req_ens_function) = if let FunctionKind::TraitMethodImpl {
-                method,
-                trait_path,
-                trait_typ_args,
-                datatype,
-                datatype_typ_args,
-            } = &function.x.kind
-            {
-                // Inherit requires/ensures from trait method declaration
-                let self_typ =
-                    Arc::new(TypX::Datatype(datatype.clone(), datatype_typ_args.clone()));
-                let mut trait_typ_substs: Vec<(Ident, Typ)> =
-                    vec![(crate::def::trait_self_type_param(), self_typ)];
-                let tr = &ctx.trait_map[trait_path];
-                assert!(tr.x.typ_params.len() == trait_typ_args.len());
-                for ((x, _, _), t) in tr.x.typ_params.iter().zip(trait_typ_args.iter()) {
-                    trait_typ_substs.push((x.clone(), t.clone()));
-                }
-                (trait_typ_substs, &ctx.func_map[method])
-            } else {
-                (vec![], function)
-            };
+            let ((req_typ_substs, req_function), (ens_typ_substs, ens_function)) =
+                if let FunctionKind::TraitMethodImpl { method, .. } = &function.x.kind {
+                    // Use the implementation's own requires and ensures if it has them
+                    // (checked against the trait's requires/ensures by trait_impl_refinement_to_air),
+                    // and otherwise inherit requires and ensures from trait method declaration
+                    let trait_typ_substs = trait_method_impl_typ_substs(ctx, function);
+                    let decl = &ctx.func_map[method];
+                    let req = if function.x.require.len() > 0 {
+                        (vec![], function)
+                    } else {
+                        (trait_typ_substs.clone(), decl)
+                    };
+                    let ens = if function.x.ensure.len() > 0 {
+                        (vec![], function)
+                    } else {
+                        (trait_typ_substs, decl)
+                    };
+                    (req, ens)
+                } else {
+                    ((vec![], function), (vec![], function))
+                };
 
             let mut state = crate::ast_to_sst::State::new();
             let mut ens_params = (*function.x.params).clone();
@@ -551,15 +601,16 @@ pub fn func_def_to_air(
             };
 
             let ens_params = Arc::new(ens_params);
-            let reqs = vec_map_result(&*req_ens_function.x.require, |e| {
+            let reqs = vec_map_result(&*req_function.x.require, |e| {
                 crate::ast_to_sst::expr_to_exp(ctx, &params_to_pars(&function.x.params, true), e)
             })?;
-            let enss = vec_map_result(&*req_ens_function.x.ensure, |e| {
+            let enss = vec_map_result(&*ens_function.x.ensure, |e| {
                 crate::ast_to_sst::expr_to_exp(ctx, &params_to_pars(&ens_params, true), e)
             })?;
             let enss = Arc::new(enss);
             for param in function.x.params.iter() {
-                state.declare_new_var(&param.x.name, &param.x.typ, param.x.is_mut, false);
+                let x = state.declare_new_var(&param.x.name, &param.x.typ, param.x.is_mut, false);
+                state.record_source_var(&x, &param.span, &param.x.typ);
             }
 
             // AST --> SST
@@ -579,7 +630,8 @@ pub fn func_def_to_air(
 
             let (commands, snap_map) = crate::sst_to_air::body_stm_to_air(
                 ctx,
-                &trait_typ_substs,
+                &req_typ_substs,
+                &ens_typ_substs,
                 &function.x.typ_params(),
                 &function.x.params,
                 &state.local_decls,
@@ -592,8 +644,93 @@ pub fn func_def_to_air(
             );
 
             state.finalize();
-            Ok((commands, snap_map))
+            if crate::traits::has_own_spec(function) {
+                let mut commands = (*commands).clone();
+                commands.extend(trait_impl_refinement_to_air(ctx, function)?.iter().cloned());
+                return Ok((Arc::new(commands), snap_map, state.source_vars));
+            }
+            Ok((commands, snap_map, state.source_vars))
         }
-        _ => Ok((Arc::new(vec![]), vec![])),
+        _ => Ok((Arc::new(vec![]), vec![], vec![])),
+    }
+}
+
+/// Check that the requires/ensures of a trait method implementation refine
+/// the requires/ensures of the trait method declaration:
+/// - the trait's requires imply the implementation's requires
+/// - the trait's requires and the implementation's ensures imply the trait's ensures
+/// (requires or ensures that the implementation inherits from the trait need no check)
+fn trait_impl_refinement_to_air(ctx: &Ctx, function: &Function) -> Result<Commands, VirErr> {
+    let method = if let FunctionKind::TraitMethodImpl { method, .. } = &function.x.kind {
+        method
+    } else {
+        panic!("internal error: expected trait method implementation")
+    };
+    let decl = &ctx.func_map[method];
+    let trait_typ_substs = trait_method_impl_typ_substs(ctx, function);
+
+    let mut state = crate::ast_to_sst::State::new();
+    let mut ens_params = (*function.x.params).clone();
+    if function.x.has_return() {
+        let ParamX { name, typ, .. } = &function.x.ret.x;
+        ens_params.push(function.x.ret.clone());
+        state.declare_new_var(name, typ, false, false);
+    }
+    for param in function.x.params.iter() {
+        state.declare_new_var(&param.x.name, &param.x.typ, param.x.is_mut, false);
+    }
+    let req_pars = params_to_pars(&function.x.params, true);
+    let ens_pars = params_to_pars(&Arc::new(ens_params), true);
+    let trait_reqs =
+        vec_map_result(&*decl.x.require, |e| crate::ast_to_sst::expr_to_exp(ctx, &req_pars, e))?;
+    let trait_enss =
+        vec_map_result(&*decl.x.ensure, |e| crate::ast_to_sst::expr_to_exp(ctx, &ens_pars, e))?;
+    let impl_reqs = vec_map_result(&*function.x.require, |e| {
+        crate::ast_to_sst::expr_to_exp(ctx, &req_pars, e)
+    })?;
+    let impl_enss =
+        vec_map_result(&*function.x.ensure, |e| crate::ast_to_sst::expr_to_exp(ctx, &ens_pars, e))?;
+
+    let span = &function.span;
+    let mut commands: Vec<Command> = Vec::new();
+    let mut queries: Vec<(Stm, Vec<Exp>)> = Vec::new();
+    // assume trait requires; assert implementation requires
+    let mut stms: Vec<Stm> = Vec::new();
+    for req in impl_reqs.iter() {
+        let error = error(
+            "requires of trait method implementation is not implied by the trait's requires",
+            &req.span,
+        );
+        stms.push(Spanned::new(req.span.clone(), StmX::Assert(Some(error), req.clone())));
+    }
+    if impl_reqs.len() > 0 {
+        queries.push((Spanned::new(span.clone(), StmX::Block(Arc::new(stms))), vec![]));
     }
+    // assume trait requires, implementation requires and ensures; assert trait ensures
+    let mut stms: Vec<Stm> = Vec::new();
+    for exp in impl_reqs.iter().chain(impl_enss.iter()) {
+        stms.push(Spanned::new(exp.span.clone(), StmX::Assume(exp.clone())));
+    }
+    if impl_enss.len() > 0 {
+        queries.push((Spanned::new(span.clone(), StmX::Block(Arc::new(stms))), trait_enss));
+    }
+    for (stm, enss) in queries {
+        let (query_commands, _snap_map) = crate::sst_to_air::body_stm_to_air(
+            ctx,
+            &trait_typ_substs,
+            &trait_typ_substs,
+            &function.x.typ_params(),
+            &function.x.params,
+            &Arc::new(state.local_decls.clone()),
+            &function.x.attrs.hidden,
+            &trait_reqs,
+            &enss,
+            &MaskSpec::NoSpec,
+            function.x.mode,
+            &stm,
+        );
+        commands.extend(query_commands.iter().cloned());
+    }
+    state.finalize();
+    Ok(Arc::new(commands))
 }
@@ -1,4 +1,7 @@
-use crate::ast::{Expr, ExprX, Exprs, Fun, HeaderExprX, Ident, MaskSpec, Stmt, StmtX, Typ, VirErr};
+use crate::ast::{
+    Expr, ExprX, Exprs, Fun, HeaderExprX, Ident, LoopInvariant, LoopInvariantKind, LoopInvariants,
+    MaskSpec, Stmt, StmtX, Typ, VirErr,
+};
 use crate::ast_util::err_str;
 use std::sync::Arc;
 
@@ -150,3 +153,33 @@ pub fn read_header(body: &mut Expr) -> Result<Header, VirErr> {
         _ => read_header_block(&mut vec![]),
     }
 }
+
+/// Read the header of a loop body:
+/// invariant(...) holds at loop entry and at the start of each iteration,
+/// and ensures(...) holds whenever the loop exits.
+/// If there is no ensures, the invariant also holds whenever the loop exits.
+pub fn read_loop_header(body: &mut Expr) -> Result<LoopInvariants, VirErr> {
+    let header = read_header(body)?;
+    if header.require.len() > 0 {
+        return err_str(&body.span, "requires is not allowed in a loop (use invariant instead)");
+    }
+    if header.decrease.len() > 0 {
+        return err_str(&body.span, "decreases is not supported for loops");
+    }
+    if header.ensure_id_typ.is_some() {
+        return err_str(&body.span, "loop ensures cannot bind a return value");
+    }
+    let inv_kind = if header.ensure.len() == 0 {
+        LoopInvariantKind::InvariantAndEnsures
+    } else {
+        LoopInvariantKind::InvariantExceptBreak
+    };
+    let mut invs: Vec<LoopInvariant> = Vec::new();
+    for inv in header.invariant.iter() {
+        invs.push(LoopInvariant { kind: inv_kind, inv: inv.clone() });
+    }
+    for inv in header.ensure.iter() {
+        invs.push(LoopInvariant { kind: LoopInvariantKind::Ensures, inv: inv.clone() });
+    }
+    Ok(Arc::new(invs))
+}
@@ -67,7 +67,7 @@ impl MaskSet {
                     "cannot show invariant namespace is in the mask given by the function signature".to_string(),
                     span,
                     "invariant opened here".to_string());
-                results.push(Arc::new(StmtX::Assert(error, equals_one)));
+                results.push(Arc::new(StmtX::Assert(None, error, equals_one)));
             }
             SetBase::Full => {}
         }
@@ -80,7 +80,7 @@ impl MaskSet {
                 "this invariant".to_string(),
             )
             .primary_label(span, "might be the same as this invariant".to_string());
-            results.push(Arc::new(StmtX::Assert(error, not_equal)));
+            results.push(Arc::new(StmtX::Assert(None, error, not_equal)));
         }
     }
 
@@ -105,7 +105,7 @@ impl MaskSet {
                 SetBase::Empty => {
                     let fa = mk_false();
                     let error = error("callee may open invariants that caller cannot", call_span);
-                    results.push(Arc::new(StmtX::Assert(error, fa)));
+                    results.push(Arc::new(StmtX::Assert(None, error, fa)));
                 }
                 SetBase::Full => {
                     for e in &other.minus {
@@ -120,7 +120,7 @@ impl MaskSet {
                             "invariant opened here".to_string(),
                         )
                         .primary_label(call_span, "might be opened again in this call".to_string());
-                        results.push(Arc::new(StmtX::Assert(error, equals_one)));
+                        results.push(Arc::new(StmtX::Assert(None, error, equals_one)));
                     }
                 }
             },
@@ -39,6 +39,7 @@ mod early_exit_cf;
 pub mod func_to_air;
 pub mod headers;
 mod inv_masks;
+pub mod model_to_rust;
 pub mod modes;
 pub mod poly;
 mod prelude;
@@ -0,0 +1,227 @@
+//! Render the values that the SMT model assigns to a function's variables as Rust values.
+//! This undoes the datatype encoding of datatype_to_air (constructors, testers, accessors)
+//! and the boxing introduced by poly (Poly values are unboxed according to their VIR type).
+
+use crate::ast::{Ident, IntRange, Path, Typ, TypX, Typs};
+use crate::context::Ctx;
+use crate::def::{
+    path_to_string, positional_field_ident, prefix_tuple_type, prefix_unbox,
+    suffix_local_unique_id, variant_field_ident_internal, variant_ident, UNBOX_BOOL, UNBOX_INT,
+};
+use crate::sst::SourceVar;
+use air::ast::{Expr, Span};
+use air::ast_util::{ident_apply, ident_var, str_apply};
+use air::model::{Model, ModelExpr};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Nested datatype values deeper than this are elided as ".."
+const MAX_DEPTH: usize = 8;
+
+/// Value of a variable at the failed assertion
+#[derive(Debug)]
+pub struct ModelValue {
+    /// The variable's name in the source code
+    pub name: Ident,
+    /// The span of the variable's declaration
+    pub span: Span,
+    /// The variable's value, rendered as a Rust value (None if it cannot be rendered)
+    pub value: Option<String>,
+}
+
+fn subst_typ(substs: &HashMap<Ident, Typ>, typ: &Typ) -> Typ {
+    let ft = |_: &mut (), t: &Typ| match &**t {
+        TypX::TypParam(x) if substs.contains_key(x) => Ok(substs[x].clone()),
+        _ => Ok(t.clone()),
+    };
+    crate::ast_visitor::map_typ_visitor_env(typ, &mut (), &ft).unwrap()
+}
+
+fn render_int(range: &IntRange, n: String) -> String {
+    match range {
+        IntRange::Int | IntRange::Nat => n,
+        IntRange::U(bits) => format!("{}u{}", n, bits),
+        IntRange::I(bits) => format!("{}i{}", n, bits),
+        IntRange::USize => format!("{}usize", n),
+        IntRange::ISize => format!("{}isize", n),
+        IntRange::Char => match n.parse::<u32>().ok().and_then(std::char::from_u32) {
+            Some(c) => format!("{:?}", c),
+            None => n,
+        },
+    }
+}
+
+struct Renderer<'a> {
+    ctx: &'a Ctx,
+    air_context: &'a mut air::context::Context,
+}
+
+impl<'a> Renderer<'a> {
+    fn eval(&mut self, expr: &Expr) -> Option<ModelExpr> {
+        self.air_context.eval_model_expr(expr)
+    }
+
+    fn is_transparent(&self, path: &Path) -> bool {
+        self.ctx.datatype_is_transparent.get(path) == Some(&true)
+    }
+
+    fn unbox(&self, typ: &Typ, expr: &Expr) -> Option<Expr> {
+        match &**typ {
+            TypX::Bool => Some(str_apply(UNBOX_BOOL, &vec![expr.clone()])),
+            TypX::Int(_) => Some(str_apply(UNBOX_INT, &vec![expr.clone()])),
+            TypX::Datatype(path, _) if self.is_transparent(path) => {
+                Some(ident_apply(&prefix_unbox(path), &vec![expr.clone()]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Render the value of expr, which has type typ, or None if it cannot be rendered
+    fn render(&mut self, typ: &Typ, expr: &Expr, depth: usize) -> Option<String> {
+        if depth > MAX_DEPTH {
+            return Some("..".to_string());
+        }
+        match &**typ {
+            TypX::Bool => self
+                .eval(expr)
+                .filter(|e| e.is_atom("true") || e.is_atom("false"))
+                .map(|e| e.to_string()),
+            TypX::Int(range) => {
+                self.eval(expr).and_then(|e| e.as_int()).map(|n| render_int(range, n))
+            }
+            TypX::Datatype(path, typ_args) => self.render_datatype(path, typ_args, expr, depth),
+            TypX::Boxed(t) => {
+                let unboxed = self.unbox(t, expr)?;
+                self.render(t, &unboxed, depth)
+            }
+            TypX::Tuple(_)
+            | TypX::Lambda(..)
+            | TypX::Array(..)
+            | TypX::TypParam(_)
+            | TypX::ConstInt(_)
+            | TypX::Projection(..)
+            | TypX::TypeId
+            | TypX::Air(_) => None,
+        }
+    }
+
+    fn render_datatype(
+        &mut self,
+        path: &Path,
+        typ_args: &Typs,
+        expr: &Expr,
+        depth: usize,
+    ) -> Option<String> {
+        if !self.is_transparent(path) {
+            return None;
+        }
+        let datatype = self.ctx.datatype_map.get(path)?.clone();
+        let mut substs: HashMap<Ident, Typ> = HashMap::new();
+        for ((x, _, _), t) in datatype.x.typ_params.iter().zip(typ_args.iter()) {
+            substs.insert(x.clone(), t.clone());
+        }
+
+        // Find the constructor with the SMT tester is-Variant
+        let mut found = None;
+        for variant in datatype.x.variants.iter() {
+            let is_variant = Arc::new(format!("is-{}", variant_ident(path, &variant.name)));
+            if self.eval(&ident_apply(&is_variant, &vec![expr.clone()]))?.is_atom("true") {
+                found = Some(variant.clone());
+                break;
+            }
+        }
+        let variant = found?;
+
+        let mut fields: Vec<(Ident, String)> = Vec::new();
+        for field in variant.a.iter() {
+            let id = variant_field_ident_internal(path, &variant.name, &field.name, true);
+            let field_expr = ident_apply(&id, &vec![expr.clone()]);
+            // fields declared with a type parameter as their type hold boxed values
+            let typ = match &*field.a.0 {
+                TypX::TypParam(_) | TypX::Projection(..) => {
+                    Arc::new(TypX::Boxed(subst_typ(&substs, &field.a.0)))
+                }
+                _ => subst_typ(&substs, &field.a.0),
+            };
+            let value = self.render(&typ, &field_expr, depth + 1);
+            fields.push((field.name.clone(), value.unwrap_or_else(|| "?".to_string())));
+        }
+
+        let positional: Vec<Ident> = (0..fields.len()).map(positional_field_ident).collect();
+        if path == &prefix_tuple_type(fields.len()) {
+            let args: Vec<String> = fields.into_iter().map(|(_, v)| v).collect();
+            let comma = if args.len() == 1 { "," } else { "" };
+            return Some(format!("({}{})", args.join(", "), comma));
+        }
+        let positional = fields.iter().map(|(x, _)| x).eq(positional.iter());
+        let args: Vec<String> = if positional {
+            fields.into_iter().map(|(_, v)| v).collect()
+        } else {
+            fields.into_iter().map(|(x, v)| format!("{}: {}", x, v)).collect()
+        };
+        let type_name = match path.segments.last() {
+            Some(x) => x.to_string(),
+            None => path_to_string(path),
+        };
+        let ctor = if datatype.x.variants.len() == 1 && *variant.name == type_name {
+            type_name
+        } else {
+            format!("{}::{}", type_name, variant.name)
+        };
+        Some(if args.len() == 0 {
+            ctor
+        } else if positional {
+            format!("{}({})", ctor, args.join(", "))
+        } else {
+            format!("{} {{ {} }}", ctor, args.join(", "))
+        })
+    }
+}
+
+/// Render the values of the source variables, where consts maps each variable's AIR name
+/// to the SMT constant that holds its value (or None if the variable has no value)
+fn render_vars(
+    ctx: &Ctx,
+    air_context: &mut air::context::Context,
+    source_vars: &Vec<SourceVar>,
+    consts: &dyn Fn(&Ident) -> Option<Ident>,
+) -> Vec<ModelValue> {
+    let mut renderer = Renderer { ctx, air_context };
+    let mut values: Vec<ModelValue> = Vec::new();
+    for var in source_vars.iter() {
+        if let Some(c) = consts(&suffix_local_unique_id(&var.ident)) {
+            let value = renderer.render(&var.typ, &ident_var(&c), 0);
+            values.push(ModelValue { name: var.ident.0.clone(), span: var.span.clone(), value });
+        }
+    }
+    values
+}
+
+/// Render the values of a function's source variables (as returned by func_def_to_air)
+/// at the assertion that failed.
+/// Must be called while air_context still holds the model of the failed query.
+pub fn failed_assertion_values(
+    ctx: &Ctx,
+    air_context: &mut air::context::Context,
+    air_model: &Model,
+    source_vars: &Vec<SourceVar>,
+) -> Vec<ModelValue> {
+    let consts: HashMap<Ident, Ident> = air_model.failed_assertion_vars().into_iter().collect();
+    render_vars(ctx, air_context, source_vars, &|x| consts.get(x).cloned())
+}
+
+/// Render the values of a function's source variables (as returned by func_def_to_air)
+/// at the snapshot sid.
+/// Returns an empty list if the failed query has no such snapshot.
+/// Must be called while air_context still holds the model of the failed query.
+pub fn snapshot_values(
+    ctx: &Ctx,
+    air_context: &mut air::context::Context,
+    air_model: &Model,
+    source_vars: &Vec<SourceVar>,
+    sid: &Ident,
+) -> Vec<ModelValue> {
+    render_vars(ctx, air_context, source_vars, &|x| {
+        air_model.translate_variable(sid, x).map(Arc::new)
+    })
+}
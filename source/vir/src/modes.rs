@@ -188,6 +188,11 @@ fn add_pattern(typing: &mut Typing, mode: Mode, pattern: &Pattern) -> Result<(),
 fn check_expr(typing: &mut Typing, outer_mode: Mode, expr: &Expr) -> Result<Mode, VirErr> {
     match &expr.x {
         ExprX::Const(_) => Ok(Mode::Exec),
+        ExprX::ConstParam(_) => {
+            // const generic parameters are compile-time values, usable in any mode
+            typing.erasure_modes.var_modes.push((expr.span.clone(), Mode::Exec));
+            Ok(Mode::Exec)
+        }
         ExprX::Var(x) | ExprX::VarLoc(x) | ExprX::VarAt(x, _) => {
             let mode = mode_join(outer_mode, typing.get(x).1);
             if typing.in_forall_stmt && mode == Mode::Proof {
@@ -273,6 +278,20 @@ fn check_expr(typing: &mut Typing, outer_mode: Mode, expr: &Expr) -> Result<Mode
             }
             Ok(Mode::Spec)
         }
+        ExprX::Call(CallTarget::FnExec(e0), es) => {
+            if outer_mode != Mode::Exec {
+                return err_str(&expr.span, "cannot call an exec closure in spec/proof code");
+            }
+            match &mut typing.atomic_insts {
+                None => {}
+                Some(ai) => ai.add_non_atomic(&expr.span),
+            }
+            check_expr_has_mode(typing, Mode::Exec, e0, Mode::Exec)?;
+            for arg in es.iter() {
+                check_expr_has_mode(typing, Mode::Exec, arg, Mode::Exec)?;
+            }
+            Ok(Mode::Exec)
+        }
         ExprX::Tuple(es) => {
             let modes = vec_map_result(es, |e| check_expr(typing, outer_mode, e))?;
             Ok(modes.into_iter().fold(outer_mode, mode_join))
@@ -351,6 +370,45 @@ fn check_expr(typing: &mut Typing, outer_mode: Mode, expr: &Expr) -> Result<Mode
             typing.vars.pop_scope();
             Ok(Mode::Spec)
         }
+        ExprX::ExecClosure { params, require, ensure, ret, body } => {
+            if outer_mode != Mode::Exec {
+                return err_str(&expr.span, "exec closures are only allowed in exec code");
+            }
+            typing.vars.push_scope(true);
+            for binder in params.iter() {
+                typing.insert(&expr.span, &binder.name, false, Mode::Exec);
+            }
+            for req in require.iter() {
+                check_expr_has_mode(typing, Mode::Spec, req, Mode::Spec)?;
+            }
+            typing.vars.push_scope(true);
+            typing.insert(&expr.span, &ret.name, false, Mode::Exec);
+            for ens in ensure.iter() {
+                check_expr_has_mode(typing, Mode::Spec, ens, Mode::Spec)?;
+            }
+            typing.vars.pop_scope();
+
+            let mut inner_atomic_insts = None;
+            swap(&mut inner_atomic_insts, &mut typing.atomic_insts);
+            let mut inner_ret_mode = None;
+            swap(&mut inner_ret_mode, &mut typing.ret_mode);
+
+            check_expr_has_mode(typing, Mode::Exec, body, Mode::Exec)?;
+
+            swap(&mut inner_ret_mode, &mut typing.ret_mode);
+            swap(&mut inner_atomic_insts, &mut typing.atomic_insts);
+
+            typing.vars.pop_scope();
+            typing.erasure_modes.var_modes.push((expr.span.clone(), Mode::Exec));
+            Ok(Mode::Exec)
+        }
+        ExprX::ClosureSpec(_, e0, es) => {
+            check_expr_has_mode(typing, Mode::Spec, e0, Mode::Spec)?;
+            for arg in es.iter() {
+                check_expr_has_mode(typing, Mode::Spec, arg, Mode::Spec)?;
+            }
+            Ok(Mode::Spec)
+        }
         ExprX::Choose { params, cond, body } => {
             typing.vars.push_scope(true);
             for binder in params.iter() {
@@ -381,6 +439,14 @@ fn check_expr(typing: &mut Typing, outer_mode: Mode, expr: &Expr) -> Result<Mode
                     check_expr_has_mode(typing, outer_mode, rhs, x_mode)?;
                     Ok(x_mode)
                 }
+                ExprX::Call(..) => {
+                    // *f(...) = rhs, where f returns &mut
+                    let lhs_mode = check_expr(typing, outer_mode, lhs)?;
+                    typing.erasure_modes.var_modes.push((lhs.span.clone(), lhs_mode));
+
+                    check_expr_has_mode(typing, outer_mode, rhs, lhs_mode)?;
+                    Ok(lhs_mode)
+                }
                 _ => panic!("expected VarLoc, found {:?}", &lhs),
             }
         }
@@ -453,16 +519,43 @@ fn check_expr(typing: &mut Typing, outer_mode: Mode, expr: &Expr) -> Result<Mode
             }
             Ok(final_mode)
         }
-        ExprX::While { cond, body, invs } => {
+        ExprX::Loop { label: _, cond, body, step, invs } => {
             // We could also allow this for proof, if we check it for termination
             match &mut typing.atomic_insts {
                 None => {}
                 Some(ai) => ai.add_loop(&expr.span),
             }
-            check_expr_has_mode(typing, outer_mode, cond, Mode::Exec)?;
+            if let Some(cond) = cond {
+                check_expr_has_mode(typing, outer_mode, cond, Mode::Exec)?;
+            }
+            check_expr_has_mode(typing, outer_mode, body, Mode::Exec)?;
+            if let Some(step) = step {
+                check_expr_has_mode(typing, outer_mode, step, Mode::Exec)?;
+            }
+            for inv in invs.iter() {
+                check_expr_has_mode(typing, Mode::Spec, &inv.inv, Mode::Spec)?;
+            }
+            Ok(Mode::Exec)
+        }
+        ExprX::ForRange { label: _, var, start, end, body, invs } => {
+            match &mut typing.atomic_insts {
+                None => {}
+                Some(ai) => ai.add_loop(&expr.span),
+            }
+            check_expr_has_mode(typing, outer_mode, start, Mode::Exec)?;
+            check_expr_has_mode(typing, outer_mode, end, Mode::Exec)?;
+            typing.vars.push_scope(true);
+            typing.insert(&expr.span, &var.name, /* mutable */ false, Mode::Exec);
             check_expr_has_mode(typing, outer_mode, body, Mode::Exec)?;
             for inv in invs.iter() {
-                check_expr_has_mode(typing, Mode::Spec, inv, Mode::Spec)?;
+                check_expr_has_mode(typing, Mode::Spec, &inv.inv, Mode::Spec)?;
+            }
+            typing.vars.pop_scope();
+            Ok(Mode::Exec)
+        }
+        ExprX::BreakOrContinue { label: _, is_break: _ } => {
+            if outer_mode != Mode::Exec {
+                return err_str(&expr.span, "break and continue are only allowed in exec code");
             }
             Ok(Mode::Exec)
         }
@@ -71,9 +71,10 @@ Therefore, the expression Unbox(Box(1)) explicitly introduces a superfluous Unbo
 
 use crate::ast::{
     BinaryOp, CallTarget, Datatype, DatatypeX, Expr, ExprX, Exprs, Function, FunctionKind,
-    FunctionX, Ident, IntRange, Krate, KrateX, MaskSpec, Mode, Param, ParamX, Path, PatternX,
-    SpannedTyped, Stmt, StmtX, Typ, TypX, UnaryOp, UnaryOpr,
+    FunctionX, Ident, IntRange, Krate, KrateX, LoopInvariant, MaskSpec, Mode, Param, ParamX, Path,
+    PatternX, SpannedTyped, Stmt, StmtX, Typ, TypX, UnaryOp, UnaryOpr,
 };
+use crate::ast_util::array_seq_typ;
 use crate::context::Ctx;
 use crate::def::Spanned;
 use crate::util::vec_map;
@@ -88,6 +89,7 @@ pub type MonoTyps = Arc<Vec<MonoTyp>>;
 pub enum MonoTypX {
     Bool,
     Int(IntRange),
+    ConstInt(Arc<String>),
     Datatype(Path, MonoTyps),
 }
 
@@ -99,6 +101,7 @@ pub(crate) fn typ_as_mono(typ: &Typ) -> Option<MonoTyp> {
     match &**typ {
         TypX::Bool => Some(Arc::new(MonoTypX::Bool)),
         TypX::Int(range) => Some(Arc::new(MonoTypX::Int(*range))),
+        TypX::ConstInt(c) => Some(Arc::new(MonoTypX::ConstInt(c.clone()))),
         TypX::Datatype(path, typs) => {
             let mut monotyps: Vec<MonoTyp> = Vec::new();
             for typ in typs.iter() {
@@ -110,6 +113,7 @@ pub(crate) fn typ_as_mono(typ: &Typ) -> Option<MonoTyp> {
             }
             Some(Arc::new(MonoTypX::Datatype(path.clone(), Arc::new(monotyps))))
         }
+        TypX::Array(t, _) => typ_as_mono(&array_seq_typ(t)),
         _ => None,
     }
 }
@@ -118,6 +122,7 @@ pub(crate) fn monotyp_to_typ(monotyp: &MonoTyp) -> Typ {
     match &**monotyp {
         MonoTypX::Bool => Arc::new(TypX::Bool),
         MonoTypX::Int(range) => Arc::new(TypX::Int(*range)),
+        MonoTypX::ConstInt(c) => Arc::new(TypX::ConstInt(c.clone())),
         MonoTypX::Datatype(path, typs) => {
             let typs = vec_map(&**typs, monotyp_to_typ);
             Arc::new(TypX::Datatype(path.clone(), Arc::new(typs)))
@@ -136,7 +141,9 @@ pub(crate) fn typ_is_poly(ctx: &Ctx, typ: &Typ) -> bool {
                 typ_as_mono(typ).is_none()
             }
         }
-        TypX::Boxed(_) | TypX::TypParam(_) => true,
+        TypX::Array(..) => typ_as_mono(typ).is_none(),
+        TypX::Boxed(_) | TypX::TypParam(_) | TypX::Projection(..) => true,
+        TypX::ConstInt(_) => panic!("internal error: ConstInt is not the type of a value"),
         TypX::TypeId => panic!("internal error: TypeId created too soon"),
         TypX::Air(_) => panic!("internal error: Air type created too soon"),
     }
@@ -157,7 +164,15 @@ fn coerce_typ_to_native(ctx: &Ctx, typ: &Typ) -> Typ {
                 }
             }
         }
-        TypX::Boxed(_) | TypX::TypParam(_) => typ.clone(),
+        TypX::Array(..) => {
+            if typ_as_mono(typ).is_none() {
+                Arc::new(TypX::Boxed(typ.clone()))
+            } else {
+                typ.clone()
+            }
+        }
+        TypX::Boxed(_) | TypX::TypParam(_) | TypX::Projection(..) => typ.clone(),
+        TypX::ConstInt(_) => panic!("internal error: ConstInt is not the type of a value"),
         TypX::TypeId => panic!("internal error: TypeId created too soon"),
         TypX::Air(_) => panic!("internal error: Air type created too soon"),
     }
@@ -167,8 +182,9 @@ fn coerce_typ_to_poly(_ctx: &Ctx, typ: &Typ) -> Typ {
     match &**typ {
         TypX::Bool | TypX::Int(_) | TypX::Lambda(..) => Arc::new(TypX::Boxed(typ.clone())),
         TypX::Tuple(_) => panic!("internal error: Tuple should be removed by ast_simplify"),
-        TypX::Datatype(..) => Arc::new(TypX::Boxed(typ.clone())),
-        TypX::Boxed(_) | TypX::TypParam(_) => typ.clone(),
+        TypX::Datatype(..) | TypX::Array(..) => Arc::new(TypX::Boxed(typ.clone())),
+        TypX::Boxed(_) | TypX::TypParam(_) | TypX::Projection(..) => typ.clone(),
+        TypX::ConstInt(_) => panic!("internal error: ConstInt is not the type of a value"),
         TypX::TypeId => panic!("internal error: TypeId created too soon"),
         TypX::Air(_) => panic!("internal error: Air type created too soon"),
     }
@@ -176,7 +192,9 @@ fn coerce_typ_to_poly(_ctx: &Ctx, typ: &Typ) -> Typ {
 
 fn coerce_expr_to_native(ctx: &Ctx, expr: &Expr) -> Expr {
     match &*expr.typ {
-        TypX::Bool | TypX::Int(_) | TypX::Lambda(..) | TypX::Datatype(..) => expr.clone(),
+        TypX::Bool | TypX::Int(_) | TypX::Lambda(..) | TypX::Datatype(..) | TypX::Array(..) => {
+            expr.clone()
+        }
         TypX::Tuple(_) => panic!("internal error: Tuple should be removed by ast_simplify"),
         TypX::Boxed(typ) => {
             if typ_is_poly(ctx, typ) {
@@ -187,7 +205,8 @@ fn coerce_expr_to_native(ctx: &Ctx, expr: &Expr) -> Expr {
                 SpannedTyped::new(&expr.span, typ, exprx)
             }
         }
-        TypX::TypParam(_) => expr.clone(),
+        TypX::TypParam(_) | TypX::Projection(..) => expr.clone(),
+        TypX::ConstInt(_) => panic!("internal error: ConstInt is not the type of a value"),
         TypX::TypeId => panic!("internal error: TypeId created too soon"),
         TypX::Air(_) => panic!("internal error: Air type created too soon"),
     }
@@ -200,14 +219,16 @@ fn coerce_expr_to_poly(ctx: &Ctx, expr: &Expr) -> Expr {
         {
             expr.clone()
         }
-        TypX::Bool | TypX::Int(_) | TypX::Lambda(..) | TypX::Datatype(..) => {
+        TypX::Array(..) if typ_as_mono(&expr.typ).is_none() => expr.clone(),
+        TypX::Bool | TypX::Int(_) | TypX::Lambda(..) | TypX::Datatype(..) | TypX::Array(..) => {
             let op = UnaryOpr::Box(expr.typ.clone());
             let exprx = ExprX::UnaryOpr(op, expr.clone());
             let typ = Arc::new(TypX::Boxed(expr.typ.clone()));
             SpannedTyped::new(&expr.span, &typ, exprx)
         }
         TypX::Tuple(_) => panic!("internal error: Tuple should be removed by ast_simplify"),
-        TypX::Boxed(_) | TypX::TypParam(_) => expr.clone(),
+        TypX::Boxed(_) | TypX::TypParam(_) | TypX::Projection(..) => expr.clone(),
+        TypX::ConstInt(_) => panic!("internal error: ConstInt is not the type of a value"),
         TypX::TypeId => panic!("internal error: TypeId created too soon"),
         TypX::Air(_) => panic!("internal error: Air type created too soon"),
     }
@@ -235,6 +256,7 @@ fn poly_expr(ctx: &Ctx, state: &mut State, expr: &Expr) -> Expr {
             SpannedTyped::new(&expr.span, &state.types[x], ExprX::VarAt(x.clone(), *at))
         }
         ExprX::ConstVar(..) => panic!("ConstVar should already be removed"),
+        ExprX::ConstParam(_) => expr.clone(),
         ExprX::Call(target, exprs) => match target {
             CallTarget::Static(name, _) => {
                 let function = &ctx.func_map[name].x;
@@ -268,6 +290,16 @@ fn poly_expr(ctx: &Ctx, state: &mut State, expr: &Expr) -> Expr {
                 let typ = coerce_typ_to_poly(ctx, &expr.typ);
                 mk_expr_typ(&typ, ExprX::Call(target, Arc::new(exprs)))
             }
+            CallTarget::FnExec(e) => {
+                let target =
+                    CallTarget::FnExec(coerce_expr_to_native(ctx, &poly_expr(ctx, state, e)));
+                let exprs = exprs
+                    .iter()
+                    .map(|e| coerce_expr_to_poly(ctx, &poly_expr(ctx, state, e)))
+                    .collect();
+                let typ = coerce_typ_to_poly(ctx, &expr.typ);
+                mk_expr_typ(&typ, ExprX::Call(target, Arc::new(exprs)))
+            }
         },
         ExprX::Tuple(_) => panic!("internal error: ast_simplify should remove Tuple"),
         ExprX::Ctor(path, variant, binders, update) => {
@@ -373,6 +405,37 @@ fn poly_expr(ctx: &Ctx, state: &mut State, expr: &Expr) -> Expr {
             state.types.pop_scope();
             mk_expr(ExprX::Closure(Arc::new(bs), e1))
         }
+        ExprX::ExecClosure { params, require, ensure, ret, body } => {
+            let mut bs: Vec<Binder<Typ>> = Vec::new();
+            state.types.push_scope(true);
+            for binder in params.iter() {
+                let typ = coerce_typ_to_poly(ctx, &binder.a);
+                let _ = state.types.insert(binder.name.clone(), typ.clone());
+                bs.push(binder.new_a(typ));
+            }
+            let require: Vec<Expr> = require
+                .iter()
+                .map(|e| coerce_expr_to_native(ctx, &poly_expr(ctx, state, e)))
+                .collect();
+            state.types.push_scope(true);
+            let ret = ret.new_a(coerce_typ_to_poly(ctx, &ret.a));
+            let _ = state.types.insert(ret.name.clone(), ret.a.clone());
+            let ensure: Vec<Expr> = ensure
+                .iter()
+                .map(|e| coerce_expr_to_native(ctx, &poly_expr(ctx, state, e)))
+                .collect();
+            state.types.pop_scope();
+            let body = coerce_expr_to_poly(ctx, &poly_expr(ctx, state, body));
+            state.types.pop_scope();
+            let (params, require, ensure) = (Arc::new(bs), Arc::new(require), Arc::new(ensure));
+            mk_expr(ExprX::ExecClosure { params, require, ensure, ret, body })
+        }
+        ExprX::ClosureSpec(spec, e0, es) => {
+            let e0 = coerce_expr_to_native(ctx, &poly_expr(ctx, state, e0));
+            let es: Vec<Expr> =
+                es.iter().map(|e| coerce_expr_to_poly(ctx, &poly_expr(ctx, state, e))).collect();
+            mk_expr(ExprX::ClosureSpec(*spec, e0, Arc::new(es)))
+        }
         ExprX::Choose { params, cond, body } => {
             let mut bs: Vec<Binder<Typ>> = Vec::new();
             state.types.push_scope(true);
@@ -423,13 +486,19 @@ fn poly_expr(ctx: &Ctx, state: &mut State, expr: &Expr) -> Expr {
             mk_expr_typ(&e1.typ, ExprX::If(e0, e1.clone(), Some(e2)))
         }
         ExprX::Match(..) => panic!("Match should already be removed"),
-        ExprX::While { cond, body, invs } => {
-            let cond = coerce_expr_to_native(ctx, &poly_expr(ctx, state, cond));
+        ExprX::Loop { label, cond, body, step, invs } => {
+            let cond = cond.as_ref().map(|e| coerce_expr_to_native(ctx, &poly_expr(ctx, state, e)));
             let body = poly_expr(ctx, state, body);
-            let invs = invs.iter().map(|e| coerce_expr_to_native(ctx, &poly_expr(ctx, state, e)));
+            let step = step.as_ref().map(|e| poly_expr(ctx, state, e));
+            let invs = invs.iter().map(|inv| LoopInvariant {
+                kind: inv.kind,
+                inv: coerce_expr_to_native(ctx, &poly_expr(ctx, state, &inv.inv)),
+            });
             let invs = Arc::new(invs.collect());
-            mk_expr(ExprX::While { cond, body, invs })
+            mk_expr(ExprX::Loop { label: label.clone(), cond, body, step, invs })
         }
+        ExprX::ForRange { .. } => panic!("ForRange should already be removed"),
+        ExprX::BreakOrContinue { .. } => expr.clone(),
         ExprX::OpenInvariant(inv, binder, body, atomicity) => {
             let inv = coerce_expr_to_poly(ctx, &poly_expr(ctx, state, inv));
             state.types.push_scope(true);
@@ -622,11 +691,12 @@ fn poly_datatype(ctx: &Ctx, datatype: &Datatype) -> Datatype {
 }
 
 pub fn poly_krate_for_module(ctx: &mut Ctx, krate: &Krate) -> Krate {
-    let KrateX { functions, datatypes, traits, module_ids } = &**krate;
+    let KrateX { functions, datatypes, traits, assoc_type_impls, module_ids } = &**krate;
     let kratex = KrateX {
         functions: functions.iter().map(|f| poly_function(ctx, f)).collect(),
         datatypes: datatypes.iter().map(|d| poly_datatype(ctx, d)).collect(),
         traits: traits.clone(),
+        assoc_type_impls: assoc_type_impls.clone(),
         module_ids: module_ids.clone(),
     };
     ctx.func_map = HashMap::new();
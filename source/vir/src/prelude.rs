@@ -22,8 +22,10 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
     let u_clip = str_to_node(U_CLIP);
     let i_clip = str_to_node(I_CLIP);
     let nat_clip = str_to_node(NAT_CLIP);
+    let char_clip = str_to_node(CHAR_CLIP);
     let u_inv = str_to_node(U_INV);
     let i_inv = str_to_node(I_INV);
+    let char_inv = str_to_node(CHAR_INV);
     let arch_size = str_to_node(ARCH_SIZE);
     let check_decrease_int =
         str_to_node(&suffix_global_id(&fun_to_air_ident(&check_decrease_int())));
@@ -38,8 +40,11 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
     let type_id_bool = str_to_node(TYPE_ID_BOOL);
     let type_id_int = str_to_node(TYPE_ID_INT);
     let type_id_nat = str_to_node(TYPE_ID_NAT);
+    let type_id_char = str_to_node(TYPE_ID_CHAR);
     let type_id_uint = str_to_node(TYPE_ID_UINT);
     let type_id_sint = str_to_node(TYPE_ID_SINT);
+    let type_id_const_int = str_to_node(TYPE_ID_CONST_INT);
+    let const_int = str_to_node(CONST_INT);
     let has_type = str_to_node(HAS_TYPE);
     let as_type = str_to_node(AS_TYPE);
     let mk_fun = str_to_node(MK_FUN);
@@ -64,6 +69,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
             (forall ((id [FuelId])) (!
                 (= ([fuel_bool] id) ([fuel_bool_default] id))
                 :pattern (([fuel_bool] id))
+                :qid internal_prelude_fuel_defaults
             ))
         ))
 
@@ -77,8 +83,11 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
         (declare-const [type_id_bool] [typ])
         (declare-const [type_id_int] [typ])
         (declare-const [type_id_nat] [typ])
+        (declare-const [type_id_char] [typ])
         (declare-fun [type_id_uint] (Int) [typ])
         (declare-fun [type_id_sint] (Int) [typ])
+        (declare-fun [type_id_const_int] (Int) [typ])
+        (declare-fun [const_int] ([typ]) Int)
         (declare-fun [has_type] ([Poly] [typ]) Bool)
         (declare-fun [as_type] ([Poly] [typ]) Poly)
         (declare-fun [mk_fun] (Fun) Fun)
@@ -93,18 +102,27 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 )
             )
             :pattern (([as_type] x t))
+            :qid internal_prelude_as_type
         )))
         (axiom (forall ((x Fun)) (!
             (= (mk_fun x) x)
             :pattern (([mk_fun] x))
+            :qid internal_prelude_mk_fun
+        )))
+        (axiom (forall ((x Int)) (!
+            (= x ([const_int] ([type_id_const_int] x)))
+            :pattern (([type_id_const_int] x))
+            :qid internal_prelude_type_id_const_int
         )))
         (axiom (forall ((x Bool)) (!
             (= x ([unbox_bool] ([box_bool] x)))
             :pattern (([box_bool] x))
+            :qid internal_prelude_box_unbox_bool
         )))
         (axiom (forall ((x Int)) (!
             (= x ([unbox_int] ([box_int] x)))
             :pattern (([box_int] x))
+            :qid internal_prelude_box_unbox_int
         )))
         (axiom (forall ((x [Poly])) (!
             (=>
@@ -112,6 +130,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 (= x ([box_bool] ([unbox_bool] x)))
             )
             :pattern (([has_type] x [type_id_bool]))
+            :qid internal_prelude_unbox_box_bool
         )))
         (axiom (forall ((x [Poly])) (!
             (=>
@@ -119,6 +138,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 (= x ([box_int] ([unbox_int] x)))
             )
             :pattern (([has_type] x [type_id_int]))
+            :qid internal_prelude_unbox_box_int
         )))
         (axiom (forall ((x [Poly])) (!
             (=>
@@ -126,6 +146,15 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 (= x ([box_int] ([unbox_int] x)))
             )
             :pattern (([has_type] x [type_id_nat]))
+            :qid internal_prelude_unbox_box_nat
+        )))
+        (axiom (forall ((x [Poly])) (!
+            (=>
+                ([has_type] x [type_id_char])
+                (= x ([box_int] ([unbox_int] x)))
+            )
+            :pattern (([has_type] x [type_id_char]))
+            :qid internal_prelude_unbox_box_char
         )))
         (axiom (forall ((bits Int) (x [Poly])) (!
             (=>
@@ -133,6 +162,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 (= x ([box_int] ([unbox_int] x)))
             )
             :pattern (([has_type] x ([type_id_uint] bits)))
+            :qid internal_prelude_unbox_box_uint
         )))
         (axiom (forall ((bits Int) (x [Poly])) (!
             (=>
@@ -140,6 +170,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 (= x ([box_int] ([unbox_int] x)))
             )
             :pattern (([has_type] x ([type_id_sint] bits)))
+            :qid internal_prelude_unbox_box_sint
         )))
 
         // Integers
@@ -168,12 +199,14 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
         (declare-fun [nat_clip] (Int) Int)
         (declare-fun [u_clip] (Int Int) Int)
         (declare-fun [i_clip] (Int Int) Int)
+        (declare-fun [char_clip] (Int) Int)
         (axiom (forall ((i Int)) (!
             (and
                 (<= 0 ([nat_clip] i))
                 (=> (<= 0 i) (= i ([nat_clip] i)))
             )
             :pattern (([nat_clip] i))
+            :qid internal_prelude_nat_clip
         )))
         (axiom (forall ((bits Int) (i Int)) (!
             (and
@@ -184,6 +217,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 )
             )
             :pattern (([u_clip] bits i))
+            :qid internal_prelude_u_clip
         )))
         (axiom (forall ((bits Int) (i Int)) (!
             (and
@@ -194,25 +228,48 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 )
             )
             :pattern (([i_clip] bits i))
+            :qid internal_prelude_i_clip
+        )))
+        (axiom (forall ((i Int)) (!
+            (and
+                ([char_inv] ([char_clip] i))
+                (=> ([char_inv] i) (= i ([char_clip] i)))
+            )
+            :pattern (([char_clip] i))
+            :qid internal_prelude_char_clip
         )))
         // type invariants inv(num_bits, value)
         (declare-fun [u_inv] (Int Int) Bool)
         (declare-fun [i_inv] (Int Int) Bool)
+        (declare-fun [char_inv] (Int) Bool)
         (axiom (forall ((bits Int) (i Int)) (!
             (= ([u_inv] bits i)
                 (and (<= 0 i) (< i ([u_hi] bits))
             ))
             :pattern (([u_inv] bits i))
+            :qid internal_prelude_u_inv
         )))
         (axiom (forall ((bits Int) (i Int)) (!
             (= ([i_inv] bits i)
                 (and (<= ([i_lo] bits) i) (< i ([i_hi] bits))
             ))
             :pattern (([i_inv] bits i))
+            :qid internal_prelude_i_inv
+        )))
+        (axiom (forall ((i Int)) (!
+            (= ([char_inv] i)
+                (or
+                    (and (<= 0 i) (<= i {str_to_node(&0xD7FFu32.to_string())}))
+                    (and (<= {str_to_node(&0xE000u32.to_string())} i) (<= i {str_to_node(&0x10FFFFu32.to_string())}))
+                )
+            )
+            :pattern (([char_inv] i))
+            :qid internal_prelude_char_inv
         )))
         (axiom (forall ((x Int)) (!
             ([has_type] ([box_int] x) [type_id_int])
             :pattern (([has_type] ([box_int] x) [type_id_int]))
+            :qid internal_prelude_has_type_int
         )))
         (axiom (forall ((x Int)) (!
             (=>
@@ -220,6 +277,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 ([has_type] ([box_int] x) [type_id_nat])
             )
             :pattern (([has_type] ([box_int] x) [type_id_nat]))
+            :qid internal_prelude_has_type_nat
         )))
         (axiom (forall ((bits Int) (x Int)) (!
             (=>
@@ -227,6 +285,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 ([has_type] ([box_int] x) ([type_id_uint] bits))
             )
             :pattern (([has_type] ([box_int] x) ([type_id_uint] bits)))
+            :qid internal_prelude_has_type_uint
         )))
         (axiom (forall ((bits Int) (x Int)) (!
             (=>
@@ -234,6 +293,15 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 ([has_type] ([box_int] x) ([type_id_sint] bits))
             )
             :pattern (([has_type] ([box_int] x) ([type_id_sint] bits)))
+            :qid internal_prelude_has_type_sint
+        )))
+        (axiom (forall ((x Int)) (!
+            (=>
+                ([char_inv] x)
+                ([has_type] ([box_int] x) [type_id_char])
+            )
+            :pattern (([has_type] ([box_int] x) [type_id_char]))
+            :qid internal_prelude_has_type_char
         )))
         (axiom (forall ((x Poly)) (!
             (=>
@@ -241,6 +309,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 (<= 0 ([unbox_int] x))
             )
             :pattern (([has_type] x [type_id_nat]))
+            :qid internal_prelude_unbox_int_nat
         )))
         (axiom (forall ((bits Int) (x Poly)) (!
             (=>
@@ -248,6 +317,7 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 ([u_inv] bits ([unbox_int] x))
             )
             :pattern (([has_type] x ([type_id_uint] bits)))
+            :qid internal_prelude_unbox_int_uint
         )))
         (axiom (forall ((bits Int) (x Poly)) (!
             (=>
@@ -255,6 +325,15 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 ([i_inv] bits ([unbox_int] x))
             )
             :pattern (([has_type] x ([type_id_sint] bits)))
+            :qid internal_prelude_unbox_int_sint
+        )))
+        (axiom (forall ((x Poly)) (!
+            (=>
+                ([has_type] x [type_id_char])
+                ([char_inv] ([unbox_int] x))
+            )
+            :pattern (([has_type] x [type_id_char]))
+            :qid internal_prelude_unbox_int_char
         )))
 
         // Decreases
@@ -267,11 +346,13 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
                 )
             )
             :pattern (([check_decrease_int] cur prev otherwise))
+            :qid internal_prelude_check_decrease_int
         )))
         (declare-fun [height] (Poly) Int)
         (axiom (forall ((x Poly)) (!
             (<= 0 ([height] x))
             :pattern (([height] x))
+            :qid internal_prelude_height
         )))
 
         // BitVector
@@ -287,6 +368,8 @@ pub(crate) fn prelude_nodes() -> Vec<Node> {
 
 pub(crate) fn datatype_height_axiom(typ_name1: &Path, typ_name2: &Path, field: &Ident) -> Node {
     let height = str_to_node(&suffix_global_id(&fun_to_air_ident(&height())));
+    let qid = format!("{}_height_{}", path_to_air_ident(typ_name1), field);
+    let qid = str_to_node(&internal_qid(&qid));
     let field = str_to_node(field.as_str());
     let typ1 = str_to_node(path_to_air_ident(typ_name1).as_str());
     let box_t1 = str_to_node(prefix_box(typ_name1).as_str());
@@ -298,6 +381,7 @@ pub(crate) fn datatype_height_axiom(typ_name1: &Path, typ_name2: &Path, field: &
                 ([height] ([box_t1] x))
             )
             :pattern (([height] ([box_t2] ([field] x))))
+            :qid [qid]
         )))
     )
 }
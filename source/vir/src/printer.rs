@@ -36,6 +36,7 @@ fn bound_to_node(bound: &GenericBound) -> Node {
         GenericBoundX::FnSpec(typs, ret) => {
             nodes!(fnspec {typs_to_node(typs)} {typ_to_node(ret)})
         }
+        GenericBoundX::Const(typ) => nodes!(const_param {typ_to_node(typ)}),
     }
 }
 
@@ -59,6 +60,7 @@ fn int_range_to_node(int_range: &IntRange) -> Node {
         IntRange::I(bits) => nodes!(I {str_to_node(&format!("{}", bits))}),
         IntRange::USize => node!(USize),
         IntRange::ISize => node!(ISize),
+        IntRange::Char => node!(Char),
     }
 }
 
@@ -69,8 +71,13 @@ fn typ_to_node(typ: &Typ) -> Node {
         TypX::Tuple(typs) => nodes!(Tuple {typs_to_node(typs)}),
         TypX::Lambda(typs, ret) => nodes!(Lambda {typs_to_node(typs)} {typ_to_node(ret)}),
         TypX::Datatype(path, typs) => nodes!(Datatype {path_to_node(path)} {typs_to_node(typs)}),
+        TypX::Array(typ, len) => nodes!(Array {typ_to_node(typ)} {typ_to_node(len)}),
         TypX::Boxed(btyp) => nodes!(Boxed {typ_to_node(btyp)}),
         TypX::TypParam(ident) => nodes!(TypParam {str_to_node(ident)}),
+        TypX::ConstInt(val) => nodes!(ConstInt {str_to_node(val)}),
+        TypX::Projection(path, typs, ident) => {
+            nodes!(Projection {path_to_node(path)} {typs_to_node(typs)} {str_to_node(ident)})
+        }
         TypX::TypeId => nodes!(TypeId),
         TypX::Air(_air_typ) => nodes!({ str_to_node("AirTyp") }),
     }
@@ -185,6 +192,21 @@ fn exprs_to_node(exprs: &Exprs) -> Node {
     Node::List(exprs.iter().map(expr_to_node).collect())
 }
 
+fn loop_invariants_to_node(invs: &LoopInvariants) -> Node {
+    Node::List(
+        invs.iter()
+            .map(|inv| {
+                let kind = match inv.kind {
+                    LoopInvariantKind::InvariantExceptBreak => "invariant_except_break",
+                    LoopInvariantKind::InvariantAndEnsures => "invariant",
+                    LoopInvariantKind::Ensures => "ensures",
+                };
+                nodes!({str_to_node(kind)} {expr_to_node(&inv.inv)})
+            })
+            .collect(),
+    )
+}
+
 fn expr_to_node(expr: &Expr) -> Node {
     let node = match &expr.x {
         ExprX::Const(cnst) => nodes!(
@@ -201,10 +223,12 @@ fn expr_to_node(expr: &Expr) -> Node {
             nodes!(varat {str_to_node(ident)} {str_to_node(&format!("{:?}", var_at))})
         }
         ExprX::ConstVar(fun) => nodes!(constvar {fun_to_node(fun)}),
+        ExprX::ConstParam(ident) => nodes!(constparam {str_to_node(ident)}),
         ExprX::Loc(expr) => nodes!(loc {expr_to_node(expr)}),
         ExprX::Call(call_target, exprs) => nodes!(call {match call_target {
             CallTarget::Static(fun, typs) => nodes!(static {fun_to_node(fun)} {typs_to_node(typs)}),
             CallTarget::FnSpec(e) => nodes!(fnspec {expr_to_node(e)}),
+            CallTarget::FnExec(e) => nodes!(fnexec {expr_to_node(e)}),
         }} {exprs_to_node(exprs)}),
         ExprX::Tuple(exprs) => nodes!(tuple {exprs_to_node(exprs)}),
         ExprX::Ctor(path, ident, binders, expr) => {
@@ -264,6 +288,12 @@ fn expr_to_node(expr: &Expr) -> Node {
         ExprX::Closure(binders, expr) => {
             nodes!(closure {binders_node(binders, &typ_to_node)} {expr_to_node(expr)})
         }
+        ExprX::ExecClosure { params, require, ensure, ret, body } => {
+            nodes!(execclosure {binders_node(params, &typ_to_node)} {str_to_node(":require")} {exprs_to_node(require)} {str_to_node(":ensure")} {exprs_to_node(ensure)} {str_to_node(":ret")} {str_to_node(&ret.name)} {expr_to_node(body)})
+        }
+        ExprX::ClosureSpec(spec, e, args) => {
+            nodes!(closurespec {str_to_node(&format!("{:?}", spec).to_lowercase())} {expr_to_node(e)} {exprs_to_node(args)})
+        }
         ExprX::Choose { params, cond, body } => {
             nodes!(choose {binders_node(params, &typ_to_node)} {expr_to_node(cond)} {expr_to_node(body)})
         }
@@ -293,8 +323,45 @@ fn expr_to_node(expr: &Expr) -> Node {
             spanned_node(node, &arm.span)
         }).collect())})
         }
-        ExprX::While { cond, body, invs } => {
-            nodes!(while {expr_to_node(cond)} {expr_to_node(body)} {str_to_node(":invs")} {exprs_to_node(invs)})
+        ExprX::Loop { label, cond, body, step, invs } => {
+            let mut nodes = nodes_vec!(loop);
+            if let Some(label) = label {
+                nodes.push(str_to_node(":label"));
+                nodes.push(str_to_node(label));
+            }
+            if let Some(cond) = cond {
+                nodes.push(str_to_node(":cond"));
+                nodes.push(expr_to_node(cond));
+            }
+            nodes.push(expr_to_node(body));
+            if let Some(step) = step {
+                nodes.push(str_to_node(":step"));
+                nodes.push(expr_to_node(step));
+            }
+            nodes.push(str_to_node(":invs"));
+            nodes.push(loop_invariants_to_node(invs));
+            Node::List(nodes)
+        }
+        ExprX::ForRange { label, var, start, end, body, invs } => {
+            let mut nodes = nodes_vec!(for_range);
+            if let Some(label) = label {
+                nodes.push(str_to_node(":label"));
+                nodes.push(str_to_node(label));
+            }
+            nodes.push(binder_node(var, &typ_to_node));
+            nodes.push(expr_to_node(start));
+            nodes.push(expr_to_node(end));
+            nodes.push(expr_to_node(body));
+            nodes.push(str_to_node(":invs"));
+            nodes.push(loop_invariants_to_node(invs));
+            Node::List(nodes)
+        }
+        ExprX::BreakOrContinue { label, is_break } => {
+            let mut nodes = if *is_break { nodes_vec!(break) } else { nodes_vec!(continue) };
+            if let Some(label) = label {
+                nodes.push(str_to_node(label));
+            }
+            Node::List(nodes)
         }
         ExprX::OpenInvariant(e1, binder, e2, atomicity) => {
             nodes!(openinvariant {expr_to_node(e1)} {binder_node(binder, &typ_to_node)} {expr_to_node(e2)} {atomicity_to_node(*atomicity)})
@@ -384,6 +451,8 @@ fn function_to_node(function: &FunctionX) -> Node {
             autoview,
             bit_vector,
             atomic,
+            rlimit,
+            smt_options,
         } = &**attrs;
 
         let mut nodes = vec![
@@ -410,6 +479,15 @@ fn function_to_node(function: &FunctionX) -> Node {
         if *atomic {
             nodes.push(str_to_node("+atomic"));
         }
+        if let Some(rlimit) = rlimit {
+            nodes.push(str_to_node(":rlimit"));
+            nodes.push(str_to_node(&rlimit.to_string()));
+        }
+        for (option, value) in smt_options.iter() {
+            nodes.push(str_to_node(":smt_option"));
+            nodes.push(str_node(option));
+            nodes.push(str_node(value));
+        }
 
         Node::List(nodes)
     };
@@ -479,7 +557,7 @@ fn function_to_node(function: &FunctionX) -> Node {
 }
 
 pub fn write_krate(mut write: impl std::io::Write, vir_crate: &Krate) {
-    let KrateX { datatypes, functions, traits, module_ids } = &**vir_crate;
+    let KrateX { datatypes, functions, traits, assoc_type_impls, module_ids } = &**vir_crate;
     let mut nw = NodeWriter::new();
     for datatype in datatypes.iter() {
         writeln!(
@@ -508,6 +586,11 @@ pub fn write_krate(mut write: impl std::io::Write, vir_crate: &Krate) {
         writeln!(&mut write, "{}\n", nw.node_to_string_indent(&" ".to_string(), &t))
             .expect("cannot write to vir write");
     }
+    for a in assoc_type_impls.iter() {
+        let a = nodes!(assoc_type_impl {str_to_node(&a.x.name)} {typ_to_node(&a.x.self_typ)} {path_to_node(&a.x.trait_path)} {typs_to_node(&a.x.trait_typ_args)} {typ_to_node(&a.x.typ)});
+        writeln!(&mut write, "{}\n", nw.node_to_string_indent(&" ".to_string(), &a))
+            .expect("cannot write to vir write");
+    }
     for module_id in module_ids.iter() {
         let module_id_node = nodes!(module_id {path_to_node(module_id)});
         writeln!(&mut write, "{}\n", nw.node_to_string_indent(&" ".to_string(), &module_id_node))
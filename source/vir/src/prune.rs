@@ -8,9 +8,11 @@ use crate::ast::{
     CallTarget, Datatype, Expr, ExprX, Fun, Function, Ident, InvAtomicity, Krate, KrateX, Mode,
     Path, Stmt, Typ, TypX,
 };
-use crate::ast_util::{is_visible_to, is_visible_to_of_owner};
+use crate::ast_util::{array_seq_typ, is_visible_to, is_visible_to_of_owner};
 use crate::datatype_to_air::is_datatype_transparent;
-use crate::def::{datatype_invariant_path, fn_inv_name, fn_namespace_name, Spanned};
+use crate::def::{
+    datatype_invariant_path, fn_inv_name, fn_namespace_name, fn_seq_name, seq_type_path, Spanned,
+};
 use crate::poly::MonoTyp;
 use air::scope_map::ScopeMap;
 use std::collections::{HashMap, HashSet};
@@ -47,6 +49,17 @@ fn record_datatype(ctxt: &Ctxt, state: &mut State, typ: &Typ, path: &Path) {
     }
 }
 
+// Is typ expressible using only the datatypes reached from our module?
+fn typ_is_reached(state: &State, typ: &Typ) -> bool {
+    crate::ast_visitor::typ_visitor_check(typ, &mut |t: &Typ| match &**t {
+        TypX::Datatype(path, _) if !state.reached_datatypes.contains(path) => Err(()),
+        TypX::Array(..) if !state.reached_datatypes.contains(&seq_type_path()) => Err(()),
+        TypX::Lambda(..) => Err(()),
+        _ => Ok(()),
+    })
+    .is_ok()
+}
+
 fn reach<A: std::hash::Hash + std::cmp::Eq + Clone>(
     reached: &mut HashSet<A>,
     worklist: &mut Vec<A>,
@@ -82,6 +95,13 @@ fn traverse_reachable(ctxt: &Ctxt, state: &mut State) {
                     record_datatype(ctxt, state, t, path);
                     reach_datatype(ctxt, state, path);
                 }
+                TypX::Array(typ, _) => {
+                    // arrays are encoded as Seq, with the length given by Seq::len
+                    let path = seq_type_path();
+                    record_datatype(ctxt, state, &array_seq_typ(typ), &path);
+                    reach_datatype(ctxt, state, &path);
+                    reach_function(ctxt, state, &fn_seq_name("len"));
+                }
                 TypX::Lambda(typs, _) => {
                     state.lambda_types.insert(typs.len());
                 }
@@ -254,6 +274,16 @@ pub fn prune_krate_for_module(krate: &Krate, module: &Path) -> (Krate, Vec<MonoT
             .filter(|d| state.reached_datatypes.contains(&d.x.path))
             .collect(),
         traits: krate.traits.clone(),
+        assoc_type_impls: krate
+            .assoc_type_impls
+            .iter()
+            .filter(|a| {
+                typ_is_reached(&state, &a.x.self_typ)
+                    && typ_is_reached(&state, &a.x.typ)
+                    && a.x.trait_typ_args.iter().all(|t| typ_is_reached(&state, t))
+            })
+            .cloned()
+            .collect(),
         module_ids: krate.module_ids.clone(),
     };
     let mut lambda_types: Vec<usize> = state.lambda_types.into_iter().collect();
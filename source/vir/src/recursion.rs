@@ -1,6 +1,6 @@
 use crate::ast::{
     BinaryOp, CallTarget, Constant, ExprX, Fun, Function, FunctionKind, GenericBoundX, IntRange,
-    MaskSpec, Path, SpannedTyped, TypX, Typs, UnaryOp, UnaryOpr, VirErr,
+    MaskSpec, Path, SpannedTyped, Typ, TypX, Typs, UnaryOp, UnaryOpr, VirErr,
 };
 use crate::ast_to_sst::expr_to_exp;
 use crate::ast_util::err_str;
@@ -43,7 +43,7 @@ fn get_callee(ctx: &Ctx, target: &Fun, targs: &Typs) -> Option<Fun> {
     let fun = &ctx.func_map[target];
     if let FunctionKind::TraitMethodDecl { .. } = &fun.x.kind {
         match &*targs[0] {
-            TypX::TypParam(_) => None,
+            TypX::TypParam(_) | TypX::Projection(..) => None,
             TypX::Datatype(datatype, _) => {
                 Some(ctx.global.method_map[&(target.clone(), datatype.clone())].clone())
             }
@@ -71,7 +71,7 @@ fn is_recursive_call(ctxt: &Ctxt, target: &Fun, targs: &Typs) -> bool {
 fn height_of_exp(ctxt: &Ctxt, exp: &Exp) -> Exp {
     match &*exp.typ {
         TypX::Int(_) => exp.clone(),
-        TypX::Datatype(..) => {
+        TypX::Datatype(..) | TypX::Array(..) => {
             let arg = if crate::poly::typ_is_poly(ctxt.ctx, &exp.typ) {
                 exp.clone()
             } else {
@@ -158,6 +158,7 @@ fn terminates(ctxt: &Ctxt, exp: &Exp) -> Result<Exp, VirErr> {
         ExpX::Const(_) | ExpX::Var(..) | ExpX::VarAt(..) | ExpX::VarLoc(..) | ExpX::Old(..) => {
             Ok(bool_exp(ExpX::Const(Constant::Bool(true))))
         }
+        ExpX::ConstParam(_) => Ok(bool_exp(ExpX::Const(Constant::Bool(true)))),
         ExpX::Loc(e) => terminates(ctxt, e),
         ExpX::Call(x, targs, args) => {
             let mut e = if is_recursive_call(ctxt, x, targs) {
@@ -171,7 +172,7 @@ fn terminates(ctxt: &Ctxt, exp: &Exp) -> Result<Exp, VirErr> {
             }
             Ok(e)
         }
-        ExpX::CallLambda(_, f, args) => {
+        ExpX::CallLambda(_, f, args) | ExpX::ClosureSpec(_, f, args) => {
             let mut e = terminates(ctxt, f)?;
             for arg in args.iter().rev() {
                 let e_arg = terminates(ctxt, arg)?;
@@ -353,6 +354,7 @@ pub(crate) fn check_termination_exp(
     let (commands, _snap_map) = crate::sst_to_air::body_stm_to_air(
         ctx,
         &vec![],
+        &vec![],
         &function.x.typ_params(),
         &function.x.params,
         &Arc::new(local_decls),
@@ -419,9 +421,87 @@ pub(crate) fn check_termination_stm(
     Ok((decls, stm_block))
 }
 
+/// Trait bounds that D's implementation of T puts on D's type arguments,
+/// as (i, traits) for each bounded type parameter that is the i-th type argument of D
+pub(crate) type ImplBounds = Vec<(usize, Vec<Path>)>;
+
+/// Compute the ImplBounds for the implementation containing the trait method implementation function
+pub(crate) fn trait_impl_bounds(
+    func_map: &HashMap<Fun, Function>,
+    function: &Function,
+) -> Result<ImplBounds, VirErr> {
+    let (method, trait_typ_args, datatype_typ_args) = match &function.x.kind {
+        FunctionKind::TraitMethodImpl { method, trait_typ_args, datatype_typ_args, .. } => {
+            (method, trait_typ_args, datatype_typ_args)
+        }
+        _ => panic!("expected trait method implementation"),
+    };
+    let decl = match func_map.get(method) {
+        Some(decl) => decl,
+        // traits declared outside the crate do not appear in the call graph
+        None => return Ok(vec![]),
+    };
+    // decl type parameters = [Self, trait type parameters, method type parameters]
+    // function type parameters = [impl type parameters, method type parameters]
+    let num_method_params = decl.x.typ_bounds.len() - 1 - trait_typ_args.len();
+    let num_impl_params = function.x.typ_bounds.len() - num_method_params;
+    let mut bounds: ImplBounds = Vec::new();
+    for (x, tbound) in function.x.typ_bounds.iter().take(num_impl_params) {
+        match &**tbound {
+            GenericBoundX::Traits(traits) if traits.len() > 0 => {
+                let is_x = |t: &Typ| matches!(&**t, TypX::TypParam(y) if y == x);
+                match datatype_typ_args.iter().position(is_x) {
+                    Some(i) => bounds.push((i, traits.clone())),
+                    None => {
+                        return err_str(
+                            &function.span,
+                            "not yet supported: trait bounds on impl type parameters that are not type arguments of the implementing datatype",
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(bounds)
+}
+
+// Add from --> D: T, where the datatype typ = D<...> satisfies a bound A: T,
+// and recursively add from --> D2: T2 where D's implementation of T requires D2: T2
+// for one of D's type arguments D2
+fn add_datatype_trait_bound_edges(
+    impl_bounds: &HashMap<(Path, Path), ImplBounds>,
+    call_graph: &mut Graph<Node>,
+    from: &Node,
+    typ: &Typ,
+    trait_path: &Path,
+) {
+    // type parameters A: T are provided by the enclosing function's own bounds,
+    // and only datatypes implement the crate's traits
+    if let TypX::Datatype(datatype, typ_args) = &**typ {
+        let dt_node =
+            Node::DatatypeTraitBound { datatype: datatype.clone(), trait_path: trait_path.clone() };
+        call_graph.add_edge(from.clone(), dt_node);
+        if let Some(bounds) = impl_bounds.get(&(datatype.clone(), trait_path.clone())) {
+            for (i, traits) in bounds.iter() {
+                for tr in traits.iter() {
+                    add_datatype_trait_bound_edges(
+                        impl_bounds,
+                        call_graph,
+                        from,
+                        &typ_args[*i],
+                        tr,
+                    );
+                }
+            }
+        }
+    }
+}
+
 pub(crate) fn expand_call_graph(
     func_map: &HashMap<Fun, Function>,
     method_map: &HashMap<(Fun, Path), Fun>,
+    impl_bounds: &HashMap<(Path, Path), ImplBounds>,
     call_graph: &mut Graph<Node>,
     function: &Function,
 ) -> Result<(), VirErr> {
@@ -429,18 +509,38 @@ pub(crate) fn expand_call_graph(
     let f_node = Node::Fun(function.x.name.clone());
 
     // Add D: T --> f and D: T --> T where f is one of D's methods that implements T
-    if let FunctionKind::TraitMethodImpl { trait_path, datatype, .. } = function.x.kind.clone() {
+    // Add D: T --> D2: T2 where D's implementation of T<..., D2, ...> instantiates A: T2 with D2
+    if let FunctionKind::TraitMethodImpl { method, trait_path, trait_typ_args, datatype, .. } =
+        function.x.kind.clone()
+    {
         let t_node = Node::Trait(trait_path.clone());
         let dt_node = Node::DatatypeTraitBound { datatype, trait_path };
-        call_graph.add_edge(t_node, dt_node.clone());
-        call_graph.add_edge(dt_node, f_node.clone());
+        call_graph.add_edge(dt_node.clone(), t_node);
+        call_graph.add_edge(dt_node.clone(), f_node.clone());
+        if let Some(decl) = func_map.get(&method) {
+            // decl type parameters = [Self, trait type parameters, method type parameters]
+            let trait_bounds = decl.x.typ_bounds.iter().skip(1);
+            for ((_, tbound), targ) in trait_bounds.zip(trait_typ_args.iter()) {
+                if let GenericBoundX::Traits(traits) = &**tbound {
+                    for tr in traits {
+                        add_datatype_trait_bound_edges(impl_bounds, call_graph, &dt_node, targ, tr);
+                    }
+                }
+            }
+        }
+    }
+
+    // Add T --> f for each of T's method declarations f
+    if let FunctionKind::TraitMethodDecl { trait_path } = &function.x.kind {
+        call_graph.add_edge(Node::Trait(trait_path.clone()), f_node.clone());
     }
 
     // Add f --> T for any function f<A: T> with type parameter A: T
+    // (for method declarations, this includes the bounds on the trait's type parameters)
     for (_, tbound) in function.x.typ_bounds.iter() {
         if let GenericBoundX::Traits(traits) = &**tbound {
             for tr in traits {
-                call_graph.add_edge(Node::Trait(tr.clone()), f_node.clone());
+                call_graph.add_edge(f_node.clone(), Node::Trait(tr.clone()));
             }
         }
     }
@@ -500,26 +600,21 @@ pub(crate) fn expand_call_graph(
                         GenericBoundX::Traits(traits) => {
                             for tr in traits {
                                 match &**targ {
-                                    TypX::Datatype(datatype, _) => {
-                                        // f1 --> D: T
-                                        call_graph.add_edge(
-                                            f_node.clone(),
-                                            Node::DatatypeTraitBound {
-                                                datatype: datatype.clone(),
-                                                trait_path: tr.clone(),
-                                            },
+                                    TypX::Datatype(..) => {
+                                        // f1 --> D: T, and f1 --> D2: T2 for the bounds
+                                        // that D's implementation of T puts on D's arguments
+                                        add_datatype_trait_bound_edges(
+                                            impl_bounds,
+                                            call_graph,
+                                            &f_node,
+                                            targ,
+                                            tr,
                                         );
                                     }
-                                    TypX::TypParam(..) => match &function.x.kind {
-                                        FunctionKind::Static => {}
-                                        FunctionKind::TraitMethodDecl { .. } => {
-                                            return err_str(
-                                                &expr.span,
-                                                "not yet supported: trait type bounds",
-                                            );
-                                        }
-                                        FunctionKind::TraitMethodImpl { .. } => {}
-                                    },
+                                    TypX::TypParam(..) => {
+                                        // The bound A: T comes from one of f1's own bounds,
+                                        // for which f1 --> T was added above
+                                    }
                                     _ => {
                                         return err_str(
                                             &expr.span,
@@ -529,7 +624,7 @@ pub(crate) fn expand_call_graph(
                                 }
                             }
                         }
-                        GenericBoundX::FnSpec(..) => {}
+                        GenericBoundX::FnSpec(..) | GenericBoundX::Const(_) => {}
                     }
                 }
 
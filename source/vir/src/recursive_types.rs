@@ -1,6 +1,7 @@
 use crate::ast::{Datatype, FunctionKind, GenericBoundX, Ident, Krate, Path, Typ, TypX, VirErr};
-use crate::ast_util::{err_str, err_string, path_as_rust_name};
+use crate::ast_util::{array_seq_typ, err_str, err_string, path_as_rust_name};
 use crate::context::GlobalCtx;
+use crate::def::seq_type_path;
 use crate::recursion::Node;
 use crate::scc::Graph;
 use air::ast::Span;
@@ -45,7 +46,10 @@ fn check_well_founded_typ(
     typ: &Typ,
 ) -> Result<bool, VirErr> {
     match &**typ {
-        TypX::Bool | TypX::Int(_) | TypX::TypParam(_) | TypX::Lambda(..) => Ok(true),
+        TypX::Bool | TypX::Int(_) | TypX::TypParam(_) | TypX::Projection(..) | TypX::Lambda(..) => {
+            Ok(true)
+        }
+        TypX::ConstInt(_) => Ok(true),
         TypX::Boxed(_) | TypX::TypeId | TypX::Air(_) => {
             panic!("internal error: unexpected type in check_well_founded_typ")
         }
@@ -66,6 +70,9 @@ fn check_well_founded_typ(
             // the height of Foo<List> is unrelated to the height of List)
             check_well_founded(datatypes, datatypes_well_founded, path)
         }
+        TypX::Array(t, _) => {
+            check_well_founded_typ(datatypes, datatypes_well_founded, &array_seq_typ(t))
+        }
     }
 }
 
@@ -138,6 +145,7 @@ fn check_positive_uses(
             }
             Ok(())
         }
+        TypX::Array(t, _) => check_positive_uses(global, local, polarity, &array_seq_typ(t)),
         TypX::Boxed(t) => check_positive_uses(global, local, polarity, t),
         TypX::TypParam(x) => {
             let strictly_positive = local.tparams[x];
@@ -153,6 +161,14 @@ fn check_positive_uses(
                 ),
             }
         }
+        TypX::Projection(_, ts, _) => {
+            // We don't know how the associated type uses its arguments
+            for t in ts.iter() {
+                check_positive_uses(global, local, None, t)?;
+            }
+            Ok(())
+        }
+        TypX::ConstInt(_) => Ok(()),
         TypX::TypeId => Ok(()),
         TypX::Air(_) => Ok(()),
     }
@@ -174,6 +190,10 @@ pub(crate) fn check_recursive_types(krate: &Krate) -> Result<(), VirErr> {
                         type_graph.add_edge(datatype.x.path.clone(), path.clone());
                         Ok(t.clone())
                     }
+                    TypX::Array(..) => {
+                        type_graph.add_edge(datatype.x.path.clone(), seq_type_path());
+                        Ok(t.clone())
+                    }
                     _ => Ok(t.clone()),
                 };
                 crate::ast_visitor::map_typ_visitor_env(typ, &mut type_graph, &ft).unwrap();
@@ -190,25 +210,25 @@ pub(crate) fn check_recursive_types(krate: &Krate) -> Result<(), VirErr> {
             let (self_name, _) = typ_params.next().expect("self type parameter");
             assert!(self_name == &crate::def::trait_self_type_param());
         }
+        // Trait bounds on method type parameters and trait type parameters
+        // are checked for termination by check_traits
         for (_name, bound) in typ_params {
             match (&**bound, &function.x.kind) {
                 (GenericBoundX::Traits(ts), _) if ts.len() == 0 => {}
-                (GenericBoundX::Traits(_), FunctionKind::Static)
-                    if function.x.attrs.broadcast_forall =>
-                {
+                (GenericBoundX::Const(_), _) => {}
+                (GenericBoundX::Traits(_), _) if function.x.attrs.broadcast_forall => {
                     // See the todo!() in func_to_air.rs
                     return err_str(
                         &function.span,
                         "not yet supported: bounds on broadcast_forall function type parameters",
                     );
                 }
-                (_, FunctionKind::Static) => {}
-                _ => {
-                    // REVIEW: when we support bounds on method type parameters,
-                    // we'll need the appropriate termination checking.
+                (GenericBoundX::Traits(_), _) => {}
+                (GenericBoundX::FnSpec(..), FunctionKind::Static) => {}
+                (GenericBoundX::FnSpec(..), _) => {
                     return err_str(
                         &function.span,
-                        "not yet supported: bounds on method type parameters",
+                        "not yet supported: function bounds on method type parameters",
                     );
                 }
             }
@@ -218,13 +238,12 @@ pub(crate) fn check_recursive_types(krate: &Krate) -> Result<(), VirErr> {
     for tr in &krate.traits {
         for (_name, bound, _positive) in tr.x.typ_params.iter() {
             match &**bound {
-                GenericBoundX::Traits(ts) if ts.len() == 0 => {}
-                _ => {
-                    // REVIEW: when we support bounds on trait type parameters,
-                    // we'll need the appropriate termination checking.
-                    // (e.g. by including traits in the datatype graph
-                    // and checking bounded type variables occur in positive positions)
-                    return err_str(&tr.span, "not yet supported: bounds on trait type parameters");
+                GenericBoundX::Traits(_) | GenericBoundX::Const(_) => {}
+                GenericBoundX::FnSpec(..) => {
+                    return err_str(
+                        &tr.span,
+                        "not yet supported: function bounds on trait type parameters",
+                    );
                 }
             }
         }
@@ -235,6 +254,7 @@ pub(crate) fn check_recursive_types(krate: &Krate) -> Result<(), VirErr> {
         for (name, bound, positive) in datatype.x.typ_params.iter() {
             match &**bound {
                 GenericBoundX::Traits(ts) if ts.len() == 0 => {}
+                GenericBoundX::Const(_) => {}
                 _ => {
                     // REVIEW: when we support bounds on datatype parameters,
                     // we'll need the appropriate termination checking.
@@ -351,15 +371,42 @@ pub fn check_traits(krate: &Krate, ctx: &GlobalCtx) -> Result<(), VirErr> {
 
     // We extend the call graph to represent trait declarations (T) and datatypes implementing
     // traits (D: T) using Node::Trait(T) and Node::DatatypeTraitBound(D, T).
+    // Bounds on trait type parameters (trait T<A: T2>) and on method type parameters
+    // (fn f<A: T2>(...)) are treated as dictionary arguments to T's methods,
+    // so T's dictionary can only be defined after T2's dictionary.
+    // Likewise, an implementation of T<D2> for D instantiates A: T2 with D2: T2.
+
     // We add the following edges to the call graph (see recursion::expand_call_graph):
     //   - T --> f if the requires/ensures of T's method declarations call f
+    //   - T --> f for each of T's method declarations f
     //   - f --> T for any function f<A: T> with type parameter A: T
+    //     (including T's method declarations with bounds on T's type parameters)
     //   - D: T --> T
     //   - f --> D: T where one of f's expressions instantiates A: T with D: T.
     //   - D: T --> f where f is one of D's methods that implements T
+    //   - D: T --> D2: T2 where D's implementation of T instantiates A: T2 with D2: T2
+    //   - f --> D2: T2 where f instantiates A: T with D<..., D2, ...>
+    //     and D's implementation of T requires D2: T2 (impl<B: T2> T for D<..., B, ...>)
     // It is an error for Node::Trait(T) or Node::DatatypeTraitBound(D, T) to appear in a cycle in
     // the call graph.
 
+    // These edges still rule out cycles through trait dispatch:
+    // - Where clauses (where A: T) are merged into the bounds on A by rust_to_vir,
+    //   so they produce the same f --> T and f --> D: T edges as bounds written as <A: T>.
+    // - A call through a type parameter A: T or through an associated type projection
+    //   gets no edge to a method implementation, since the implementation comes from the
+    //   dictionary for A: T.
+    //   But any f that creates this dictionary has an edge f --> D: T to the dictionary,
+    //   and D: T has edges to all of D's method implementations.
+    //   Associated type projections cannot themselves carry dictionaries,
+    //   because bounds on associated types are not supported,
+    //   and rustc normalizes projections on datatypes to the datatypes that the impls define.
+    // - The dictionary for D<..., D2, ...>: T is built from the dictionaries that
+    //   D's implementation of T requires for D's type arguments,
+    //   so whoever builds it also depends on those dictionaries (f --> D2: T2 above).
+    //   Bounds on impl type parameters that are not type arguments of D are not supported,
+    //   since the call graph cannot tell how they are instantiated.
+
     for scc in &ctx.func_call_sccs {
         let scc_nodes = ctx.func_call_graph.get_scc_nodes(scc);
         let count = scc_nodes.len();
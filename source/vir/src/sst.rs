@@ -7,11 +7,11 @@
 //! SST is designed to make the translation to AIR as straightforward as possible.
 
 use crate::ast::{
-    BinaryOp, Constant, Fun, InvAtomicity, Mode, Path, SpannedTyped, Typ, Typs, UnaryOp, UnaryOpr,
-    VarAt,
+    BinaryOp, ClosureSpec, Constant, Fun, InvAtomicity, Mode, Path, SpannedTyped, Typ, Typs,
+    UnaryOp, UnaryOpr, VarAt,
 };
 use crate::def::Spanned;
-use air::ast::{Binders, Ident, Quant};
+use air::ast::{Binders, Ident, Quant, Span};
 use air::errors::Error;
 use std::sync::Arc;
 
@@ -38,12 +38,16 @@ pub enum ExpX {
     Var(UniqueIdent),
     VarLoc(UniqueIdent),
     VarAt(UniqueIdent, VarAt),
+    // value of a const generic parameter
+    ConstParam(Ident),
     Loc(Exp),
     // used only during sst_to_air to generate AIR Old
     Old(Ident, UniqueIdent),
     // call to spec function
     Call(Fun, Typs, Exps),
     CallLambda(Typ, Exp, Exps),
+    // requires/ensures of an exec closure
+    ClosureSpec(ClosureSpec, Exp, Exps),
     Ctor(Path, Ident, Binders<Exp>),
     Unary(UnaryOp, Exp),
     UnaryOpr(UnaryOpr, Exp),
@@ -78,6 +82,17 @@ pub struct Dest {
 
 pub type Stm = Arc<Spanned<StmX>>;
 pub type Stms = Arc<Vec<Stm>>;
+
+#[derive(Clone, Debug)]
+pub struct LoopInv {
+    pub inv: Exp,
+    // checked on entry and at the end of each iteration, assumed at the start of each iteration
+    pub at_entry: bool,
+    // checked whenever the loop exits, assumed after the loop
+    pub at_exit: bool,
+}
+pub type LoopInvs = Arc<Vec<LoopInv>>;
+
 #[derive(Debug)]
 pub enum StmX {
     // call to exec/proof function
@@ -94,11 +109,13 @@ pub enum StmX {
     Fuel(Fun, u32),
     DeadEnd(Stm),
     If(Exp, Stm, Option<Stm>),
-    While {
-        cond_stms: Stms,
-        cond_exp: Exp,
+    Loop {
+        // for while loops: statements to evaluate the condition, and the condition
+        cond: Option<(Stms, Exp)>,
         body: Stm,
-        invs: Exps,
+        invs: LoopInvs,
+        // true if some break exits the loop
+        has_break: bool,
         typ_inv_vars: Arc<Vec<(UniqueIdent, Typ)>>,
         modified_vars: Arc<Vec<UniqueIdent>>,
     },
@@ -113,3 +130,13 @@ pub struct LocalDeclX {
     pub typ: Typ,
     pub mutable: bool,
 }
+
+/// Parameter or local variable as declared in the source code,
+/// used to map the AIR variables of a counterexample back to source names
+pub type SourceVar = Arc<SourceVarX>;
+#[derive(Debug)]
+pub struct SourceVarX {
+    pub ident: UniqueIdent,
+    pub span: Span,
+    pub typ: Typ,
+}
@@ -1,17 +1,18 @@
 use crate::ast::{
-    BinaryOp, Fun, Ident, Idents, IntRange, InvAtomicity, MaskSpec, Mode, Params, Path, PathX,
-    SpannedTyped, Typ, TypX, Typs, UnaryOp, UnaryOpr, VarAt,
+    BinaryOp, ClosureSpec, Fun, Ident, Idents, IntRange, InvAtomicity, MaskSpec, Mode, Params,
+    Path, PathX, SpannedTyped, Typ, TypX, Typs, UnaryOp, UnaryOpr, VarAt,
 };
-use crate::ast_util::{bitwidth_from_type, get_field, get_variant};
+use crate::ast_util::{array_seq_typ, bitwidth_from_type, get_field, get_variant};
 use crate::context::Ctx;
 use crate::def::{fn_inv_name, fn_namespace_name};
 use crate::def::{
-    fun_to_string, path_to_string, prefix_box, prefix_ensures, prefix_fuel_id, prefix_lambda_type,
-    prefix_pre_var, prefix_requires, prefix_unbox, snapshot_ident, suffix_global_id,
-    suffix_local_expr_id, suffix_local_stmt_id, suffix_local_unique_id, suffix_typ_param_id,
-    variant_field_ident, variant_ident, SnapPos, SpanKind, Spanned, FUEL_BOOL, FUEL_BOOL_DEFAULT,
-    FUEL_DEFAULTS, FUEL_ID, FUEL_PARAM, FUEL_TYPE, POLY, SNAPSHOT_CALL, SNAPSHOT_PRE, SUCC,
-    SUFFIX_SNAP_JOIN, SUFFIX_SNAP_MUT, SUFFIX_SNAP_WHILE_BEGIN, SUFFIX_SNAP_WHILE_END,
+    fun_to_string, internal_qid, path_to_string, prefix_box, prefix_ensures, prefix_fuel_id,
+    prefix_lambda_type, prefix_pre_var, prefix_requires, prefix_unbox, snapshot_ident,
+    suffix_global_id, suffix_local_expr_id, suffix_local_stmt_id, suffix_local_unique_id,
+    suffix_typ_param_id, user_qid, variant_field_ident, variant_ident, SnapPos, SpanKind, Spanned,
+    FUEL_BOOL, FUEL_BOOL_DEFAULT, FUEL_DEFAULTS, FUEL_ID, FUEL_PARAM, FUEL_TYPE, POLY,
+    SNAPSHOT_CALL, SNAPSHOT_PRE, SUCC, SUFFIX_SNAP_JOIN, SUFFIX_SNAP_MUT, SUFFIX_SNAP_WHILE_BEGIN,
+    SUFFIX_SNAP_WHILE_END,
 };
 use crate::inv_masks::MaskSet;
 use crate::poly::{typ_as_mono, MonoTyp, MonoTypX};
@@ -24,7 +25,7 @@ use air::ast::{
 };
 use air::ast_util::{
     bool_typ, bv_typ, ident_apply, ident_binder, ident_typ, ident_var, int_typ, mk_and,
-    mk_bind_expr, mk_eq, mk_exists, mk_implies, mk_ite, mk_let, mk_not, mk_or, str_apply,
+    mk_bind_expr, mk_eq, mk_exists, mk_false, mk_implies, mk_ite, mk_let, mk_not, mk_or, str_apply,
     str_ident, str_typ, str_var, string_var,
 };
 use air::errors::{error, error_with_label};
@@ -45,7 +46,7 @@ pub(crate) fn path_to_air_ident(path: &Path) -> Ident {
 pub(crate) fn apply_range_fun(name: &str, range: &IntRange, exprs: Vec<Expr>) -> Expr {
     let mut args = exprs;
     match range {
-        IntRange::Int | IntRange::Nat => {}
+        IntRange::Int | IntRange::Nat | IntRange::Char => {}
         IntRange::U(range) | IntRange::I(range) => {
             let bits = Constant::Nat(Arc::new(range.to_string()));
             args.insert(0, Arc::new(ExprX::Const(bits)));
@@ -57,6 +58,17 @@ pub(crate) fn apply_range_fun(name: &str, range: &IntRange, exprs: Vec<Expr>) ->
     str_apply(name, &args)
 }
 
+fn clip_to_range(range: &IntRange, expr: Expr) -> Expr {
+    let f_name = match range {
+        IntRange::Int => return expr,
+        IntRange::Nat => crate::def::NAT_CLIP,
+        IntRange::U(_) | IntRange::USize => crate::def::U_CLIP,
+        IntRange::I(_) | IntRange::ISize => crate::def::I_CLIP,
+        IntRange::Char => crate::def::CHAR_CLIP,
+    };
+    apply_range_fun(&f_name, &range, vec![expr])
+}
+
 pub(crate) fn monotyp_to_path(typ: &MonoTyp) -> Path {
     let id = match &**typ {
         MonoTypX::Bool => str_ident("bool"),
@@ -67,7 +79,10 @@ pub(crate) fn monotyp_to_path(typ: &MonoTyp) -> Path {
             IntRange::I(n) => Arc::new(format!("i{}", n)),
             IntRange::USize => str_ident("usize"),
             IntRange::ISize => str_ident("isize"),
+            IntRange::Char => str_ident("char"),
         },
+        // "const" is a Rust keyword, so this can't collide with a user-defined name
+        MonoTypX::ConstInt(c) => Arc::new(format!("const{}", c)),
         MonoTypX::Datatype(path, typs) => {
             return crate::def::monotyp_apply(path, &typs.iter().map(monotyp_to_path).collect());
         }
@@ -91,8 +106,11 @@ pub(crate) fn typ_to_air(ctx: &Ctx, typ: &Typ) -> air::ast::Typ {
                 }
             }
         }
+        TypX::Array(t, _) => typ_to_air(ctx, &array_seq_typ(t)),
         TypX::Boxed(_) => str_typ(POLY),
         TypX::TypParam(_) => str_typ(POLY),
+        TypX::Projection(..) => str_typ(POLY),
+        TypX::ConstInt(_) => panic!("internal error: ConstInt is not the type of a value"),
         TypX::TypeId => str_typ(crate::def::TYPE),
         TypX::Air(t) => t.clone(),
     }
@@ -102,6 +120,7 @@ pub fn range_to_id(range: &IntRange) -> Expr {
     match range {
         IntRange::Int => str_var(crate::def::TYPE_ID_INT),
         IntRange::Nat => str_var(crate::def::TYPE_ID_NAT),
+        IntRange::Char => str_var(crate::def::TYPE_ID_CHAR),
         IntRange::U(_) | IntRange::USize => {
             apply_range_fun(crate::def::TYPE_ID_UINT, range, vec![])
         }
@@ -115,6 +134,7 @@ pub fn monotyp_to_id(typ: &MonoTyp) -> Expr {
     match &**typ {
         MonoTypX::Int(range) => range_to_id(range),
         MonoTypX::Bool => str_var(crate::def::TYPE_ID_BOOL),
+        MonoTypX::ConstInt(c) => const_int_id(c),
         MonoTypX::Datatype(path, typs) => {
             let f_name = crate::def::prefix_type_id(path);
             air::ast_util::ident_apply_or_var(&f_name, &Arc::new(vec_map(&**typs, monotyp_to_id)))
@@ -133,8 +153,11 @@ pub fn typ_to_id(typ: &Typ) -> Expr {
         TypX::Tuple(_) => panic!("internal error: Tuple should have been removed by ast_simplify"),
         TypX::Lambda(typs, typ) => fun_id(typs, typ),
         TypX::Datatype(path, typs) => datatype_id(path, typs),
+        TypX::Array(t, _) => typ_to_id(&array_seq_typ(t)),
         TypX::Boxed(typ) => typ_to_id(typ),
         TypX::TypParam(x) => ident_var(&suffix_typ_param_id(x)),
+        TypX::Projection(trait_path, typs, x) => projection_id(trait_path, typs, x),
+        TypX::ConstInt(c) => const_int_id(c),
         TypX::TypeId => panic!("internal error: typ_to_id of TypeId"),
         TypX::Air(_) => panic!("internal error: typ_to_id of Air"),
     }
@@ -147,6 +170,35 @@ pub(crate) fn fun_id(typs: &Typs, typ: &Typ) -> Expr {
     air::ast_util::ident_apply_or_var(&f_name, &Arc::new(typids))
 }
 
+fn const_int_id(c: &Arc<String>) -> Expr {
+    let value = Arc::new(ExprX::Const(Constant::Nat(c.clone())));
+    str_apply(crate::def::TYPE_ID_CONST_INT, &vec![value])
+}
+
+fn const_param_value(x: &Ident, range: &IntRange) -> Expr {
+    // The value of const generic parameter x is stored in x's type identifier.
+    // Clipping makes the value fit x's integer type, even for unconstrained x.
+    let id = ident_var(&suffix_typ_param_id(x));
+    clip_to_range(range, str_apply(crate::def::CONST_INT, &vec![id]))
+}
+
+// The length of an array [typ; len], where array is the Poly value of the array
+fn array_len_invariant(typ: &Typ, len: &Typ, array: Expr) -> Expr {
+    let seq_len = suffix_global_id(&fun_to_air_ident(&crate::def::fn_seq_name("len")));
+    let array_len = ident_apply(&seq_len, &vec![typ_to_id(typ), array]);
+    let len = match &**len {
+        TypX::ConstInt(c) => Arc::new(ExprX::Const(Constant::Nat(c.clone()))),
+        TypX::TypParam(x) => const_param_value(x, &IntRange::USize),
+        _ => panic!("internal error: array length must be a const generic argument"),
+    };
+    mk_eq(&array_len, &len)
+}
+
+pub(crate) fn projection_id(trait_path: &Path, typs: &Typs, name: &Ident) -> Expr {
+    let f_name = crate::def::prefix_projection(trait_path, name);
+    air::ast_util::ident_apply_or_var(&f_name, &Arc::new(vec_map(&**typs, typ_to_id)))
+}
+
 pub(crate) fn datatype_id(path: &Path, typs: &Typs) -> Expr {
     let f_name = crate::def::prefix_type_id(path);
     air::ast_util::ident_apply_or_var(&f_name, &Arc::new(vec_map(&**typs, typ_to_id)))
@@ -180,6 +232,7 @@ pub(crate) fn typ_invariant(ctx: &Ctx, typ: &Typ, expr: &Expr) -> Option<Expr> {
                 IntRange::Nat => panic!("internal error: Int"),
                 IntRange::U(_) | IntRange::USize => crate::def::U_INV,
                 IntRange::I(_) | IntRange::ISize => crate::def::I_INV,
+                IntRange::Char => crate::def::CHAR_INV,
             };
             Some(apply_range_fun(&f_name, &range, vec![expr.clone()]))
         }
@@ -203,11 +256,26 @@ pub(crate) fn typ_invariant(ctx: &Ctx, typ: &Typ, expr: &Expr) -> Option<Expr> {
                 }
             }
         }
-        TypX::Boxed(t) => Some(str_apply(crate::def::HAS_TYPE, &vec![expr.clone(), typ_to_id(t)])),
+        TypX::Array(t, len) => {
+            let boxed = try_box(ctx, expr.clone(), typ).expect("try_box array");
+            Some(array_len_invariant(t, len, boxed))
+        }
+        TypX::Boxed(t) => {
+            let has_type = str_apply(crate::def::HAS_TYPE, &vec![expr.clone(), typ_to_id(t)]);
+            match &**t {
+                TypX::Array(t, len) => {
+                    Some(mk_and(&vec![has_type, array_len_invariant(t, len, expr.clone())]))
+                }
+                _ => Some(has_type),
+            }
+        }
         TypX::TypParam(x) => Some(str_apply(
             crate::def::HAS_TYPE,
             &vec![expr.clone(), ident_var(&suffix_typ_param_id(&x))],
         )),
+        TypX::Projection(..) => {
+            Some(str_apply(crate::def::HAS_TYPE, &vec![expr.clone(), typ_to_id(typ)]))
+        }
         _ => None,
     }
 }
@@ -230,8 +298,11 @@ fn try_box(ctx: &Ctx, expr: Expr, typ: &Typ) -> Option<Expr> {
                 }
             }
         }
+        TypX::Array(t, _) => return try_box(ctx, expr, &array_seq_typ(t)),
         TypX::Boxed(_) => None,
         TypX::TypParam(_) => None,
+        TypX::Projection(..) => None,
+        TypX::ConstInt(_) => None,
         TypX::TypeId => None,
         TypX::Air(_) => None,
     };
@@ -256,8 +327,11 @@ fn try_unbox(ctx: &Ctx, expr: Expr, typ: &Typ) -> Option<Expr> {
         }
         TypX::Tuple(_) => None,
         TypX::Lambda(typs, _) => Some(prefix_unbox(&prefix_lambda_type(typs.len()))),
+        TypX::Array(t, _) => return try_unbox(ctx, expr, &array_seq_typ(t)),
         TypX::Boxed(_) => None,
         TypX::TypParam(_) => None,
+        TypX::Projection(..) => None,
+        TypX::ConstInt(_) => None,
         TypX::TypeId => None,
         TypX::Air(_) => None,
     };
@@ -326,6 +400,10 @@ pub(crate) fn exp_to_expr(ctx: &Ctx, exp: &Exp, expr_ctxt: ExprCtxt) -> Expr {
         }
         ExpX::Var(x) => string_var(&suffix_local_unique_id(x)),
         ExpX::VarLoc(x) => string_var(&suffix_local_unique_id(x)),
+        ExpX::ConstParam(x) => match &*exp.typ {
+            TypX::Int(range) => const_param_value(x, range),
+            _ => panic!("internal error: const generic parameter must have integer type"),
+        },
         ExpX::VarAt(x, VarAt::Pre) => match expr_ctxt {
             ExprCtxt::Spec => string_var(&prefix_pre_var(&suffix_local_unique_id(x))),
             ExprCtxt::Body => {
@@ -333,6 +411,7 @@ pub(crate) fn exp_to_expr(ctx: &Ctx, exp: &Exp, expr_ctxt: ExprCtxt) -> Expr {
             }
             ExprCtxt::BodyPre => string_var(&suffix_local_unique_id(x)),
         },
+        ExpX::VarAt(_, VarAt::Final) => panic!("internal error: VarAt::Final"),
         ExpX::Loc(e0) => exp_to_expr(ctx, e0, expr_ctxt),
         ExpX::Old(span, x) => Arc::new(ExprX::Old(span.clone(), suffix_local_unique_id(x))),
         ExpX::Call(x, typs, args) => {
@@ -348,6 +427,17 @@ pub(crate) fn exp_to_expr(ctx: &Ctx, exp: &Exp, expr_ctxt: ExprCtxt) -> Expr {
             let args = vec_map(args, |e| exp_to_expr(ctx, e, expr_ctxt));
             Arc::new(ExprX::ApplyLambda(typ_to_air(ctx, typ), e0, Arc::new(args)))
         }
+        ExpX::ClosureSpec(spec, e0, args) => {
+            let n_params = match spec {
+                ClosureSpec::Requires => args.len(),
+                ClosureSpec::Ensures => args.len() - 1,
+            };
+            let mut exprs: Vec<Expr> = vec![exp_to_expr(ctx, e0, expr_ctxt)];
+            for arg in args.iter() {
+                exprs.push(exp_to_expr(ctx, arg, expr_ctxt));
+            }
+            ident_apply(&crate::def::prefix_closure_spec(*spec, n_params), &exprs)
+        }
         ExpX::Ctor(path, variant, binders) => {
             let (variant, args) = ctor_to_apply(ctx, path, variant, binders);
             let args = args.map(|b| exp_to_expr(ctx, &b.a, expr_ctxt)).collect::<Vec<_>>();
@@ -363,17 +453,7 @@ pub(crate) fn exp_to_expr(ctx: &Ctx, exp: &Exp, expr_ctxt: ExprCtxt) -> Expr {
                 ))
             }
             UnaryOp::Trigger(_) => exp_to_expr(ctx, exp, expr_ctxt),
-            UnaryOp::Clip(IntRange::Int) => exp_to_expr(ctx, exp, expr_ctxt),
-            UnaryOp::Clip(range) => {
-                let expr = exp_to_expr(ctx, exp, expr_ctxt);
-                let f_name = match range {
-                    IntRange::Int => panic!("internal error: Int"),
-                    IntRange::Nat => crate::def::NAT_CLIP,
-                    IntRange::U(_) | IntRange::USize => crate::def::U_CLIP,
-                    IntRange::I(_) | IntRange::ISize => crate::def::I_CLIP,
-                };
-                apply_range_fun(&f_name, &range, vec![expr])
-            }
+            UnaryOp::Clip(range) => clip_to_range(range, exp_to_expr(ctx, exp, expr_ctxt)),
         },
         ExpX::UnaryOpr(op, exp) => match op {
             UnaryOpr::Box(typ) => {
@@ -513,7 +593,9 @@ pub(crate) fn exp_to_expr(ctx: &Ctx, exp: &Exp, expr_ctxt: ExprCtxt) -> Expr {
                 let triggers = vec_map(&*trigs, |trig| {
                     Arc::new(vec_map(trig, |x| exp_to_expr(ctx, x, expr_ctxt)))
                 });
-                air::ast_util::mk_quantifier(*quant, &binders, &triggers, &expr)
+                let qid = user_qid(&bnd.span);
+                ctx.global.qids.borrow_mut().insert(qid.clone(), bnd.span.clone());
+                air::ast_util::mk_quantifier(*quant, &binders, &triggers, Some(qid), &expr)
             }
             BndX::Lambda(binders) => {
                 let expr = exp_to_expr(ctx, exp, expr_ctxt);
@@ -754,29 +836,40 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
     match &stm.x {
         StmX::Call(x, typs, args, dest) => {
             let mut stmts: Vec<Stmt> = Vec::new();
+            // If the callee is a trait method whose implementation for Self has its own
+            // requires and/or ensures, use the implementation's (refined) requires and/or ensures
+            // (the parts that the implementation omits are inherited from the trait)
+            let refined = crate::traits::refined_method_impl(ctx, x, typs);
+            let (req_x, req_typs) = match &refined {
+                Some((f, ts)) if ctx.func_map[f].x.require.len() > 0 => (f.clone(), ts.clone()),
+                _ => (x.clone(), typs.clone()),
+            };
+            let (ens_x, ens_typs) = match &refined {
+                Some((f, ts)) if ctx.func_map[f].x.ensure.len() > 0 => (f.clone(), ts.clone()),
+                _ => (x.clone(), typs.clone()),
+            };
             let func = &ctx.func_map[x];
-            if func.x.require.len() > 0 {
-                let f_req = prefix_requires(&fun_to_air_ident(&func.x.name));
-                let mut req_args = vec_map(typs, typ_to_id);
+            let req_func = &ctx.func_map[&req_x];
+            if req_func.x.require.len() > 0 {
+                let f_req = prefix_requires(&fun_to_air_ident(&req_x));
+                let mut req_args = vec_map(&req_typs, typ_to_id);
                 for arg in args.iter() {
                     req_args.push(exp_to_expr(ctx, arg, expr_ctxt));
                 }
                 let e_req = Arc::new(ExprX::Apply(f_req, Arc::new(req_args)));
-                let description = match &func.x.attrs.custom_req_err {
+                let description = match &req_func.x.attrs.custom_req_err {
                     None => "precondition not satisfied".to_string(),
                     Some(s) => s.clone(),
                 };
                 let error = error(description, &stm.span);
-                stmts.push(Arc::new(StmtX::Assert(error, e_req)));
+                stmts.push(Arc::new(StmtX::Assert(None, error, e_req)));
             }
 
             let callee_mask_set = mask_set_from_spec(&func.x.mask_spec, func.x.mode);
             callee_mask_set.assert_is_contained_in(&state.mask, &stm.span, &mut stmts);
 
-            let typ_args: Vec<Expr> = vec_map(typs, typ_to_id);
-            if func.x.params.iter().any(|p| p.x.is_mut) && ctx.debug {
-                unimplemented!("&mut args are unsupported in debugger mode");
-            }
+            let typ_args: Vec<Expr> = vec_map(&ens_typs, typ_to_id);
+            let has_mut_args = func.x.params.iter().any(|p| p.x.is_mut);
             let mut call_snapshot = false;
             let mut ens_args_wo_typ = Vec::new();
             let mut havocs = Vec::new();
@@ -834,18 +927,18 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
                     let havoc = StmtX::Havoc(x.clone());
                     stmts.push(Arc::new(havoc));
                 }
-                if ctx.debug {
-                    // Add a snapshot after we modify the destination
-                    let sid = state.update_current_sid(SUFFIX_SNAP_MUT);
-                    // Update the snap_map so that it reflects the state _after_ the
-                    // statement takes effect.
-                    state.map_span(&stm, SpanKind::Full);
-                    let snapshot = Arc::new(StmtX::Snapshot(sid.clone()));
-                    stmts.push(snapshot);
-                }
             }
-            if ctx.funcs_with_ensure_predicate.contains(&func.x.name) {
-                let f_ens = prefix_ensures(&fun_to_air_ident(&func.x.name));
+            if ctx.debug && (dest.is_some() || has_mut_args) {
+                // Add a snapshot after we modify the destination and the &mut arguments
+                let sid = state.update_current_sid(SUFFIX_SNAP_MUT);
+                // Update the snap_map so that it reflects the state _after_ the
+                // statement takes effect.
+                state.map_span(&stm, SpanKind::Full);
+                let snapshot = Arc::new(StmtX::Snapshot(sid.clone()));
+                stmts.push(snapshot);
+            }
+            if ctx.funcs_with_ensure_predicate.contains(&ens_x) {
+                let f_ens = prefix_ensures(&fun_to_air_ident(&ens_x));
                 let e_ens = Arc::new(ExprX::Apply(f_ens, Arc::new(ens_args)));
                 stmts.push(Arc::new(StmtX::Assume(e_ens)));
             }
@@ -864,7 +957,7 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
             if ctx.debug {
                 state.map_span(&stm, SpanKind::Full);
             }
-            vec![Arc::new(StmtX::Assert(error, air_expr))]
+            vec![Arc::new(StmtX::Assert(None, error, air_expr))]
         }
         StmX::AssertBV(expr) => {
             let error = error_with_label(
@@ -874,7 +967,7 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
             );
             let local = state.local_bv_shared.clone();
             let air_expr = exp_to_bv_expr(&state, &expr);
-            let assertion = Arc::new(StmtX::Assert(error, air_expr));
+            let assertion = Arc::new(StmtX::Assert(None, error, air_expr));
             // this creates a separate query for the bv assertion
             let query = Arc::new(QueryX { local: Arc::new(local), assertion });
             state.commands.push(Arc::new(CommandX::CheckValid(query)));
@@ -932,13 +1025,35 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
             }
             stmts
         }
-        StmX::While { cond_stms, cond_exp, body, invs, typ_inv_vars, modified_vars } => {
-            let pos_cond = exp_to_expr(ctx, &cond_exp, expr_ctxt);
-            let neg_cond = Arc::new(ExprX::Unary(air::ast::UnaryOp::Not, pos_cond.clone()));
-            let pos_assume = Arc::new(StmtX::Assume(pos_cond));
-            let neg_assume = Arc::new(StmtX::Assume(neg_cond));
-            let invs: Vec<(Span, Expr)> =
-                invs.iter().map(|e| (e.span.clone(), exp_to_expr(ctx, e, expr_ctxt))).collect();
+        StmX::Loop { cond, body, invs, has_break, typ_inv_vars, modified_vars } => {
+            let conds = cond.as_ref().map(|(cond_stms, cond_exp)| {
+                let pos_cond = exp_to_expr(ctx, cond_exp, expr_ctxt);
+                let neg_cond = Arc::new(ExprX::Unary(air::ast::UnaryOp::Not, pos_cond.clone()));
+                let pos_assume = Arc::new(StmtX::Assume(pos_cond));
+                let neg_assume = Arc::new(StmtX::Assume(neg_cond));
+                (cond_stms, pos_assume, neg_assume)
+            });
+            let mut invs_entry: Vec<(Span, Expr)> = Vec::new();
+            let mut invs_exit: Vec<(Span, Expr)> = Vec::new();
+            // Exit invariants to check when the condition fails.
+            // (Invariants that hold at the start of each iteration also hold when the condition
+            // fails, so they only need checking if a break can also exit the loop.)
+            let mut invs_exit_check: Vec<(Span, Expr, &str)> = Vec::new();
+            for inv in invs.iter() {
+                let span = inv.inv.span.clone();
+                let expr = exp_to_expr(ctx, &inv.inv, expr_ctxt);
+                if inv.at_entry {
+                    invs_entry.push((span.clone(), expr.clone()));
+                }
+                if inv.at_exit {
+                    invs_exit.push((span.clone(), expr.clone()));
+                }
+                if inv.at_exit && !inv.at_entry {
+                    invs_exit_check.push((span, expr, "loop ensures not satisfied at loop exit"));
+                } else if inv.at_exit && *has_break {
+                    invs_exit_check.push((span, expr, "invariant not satisfied at loop exit"));
+                }
+            }
 
             let entry_snap_id = if ctx.debug {
                 // Add a snapshot to capture the start of the while loop
@@ -949,11 +1064,16 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
                 None
             };
 
-            let cond_stmts: Vec<Stmt> =
-                cond_stms.iter().map(|s| stm_to_stmts(ctx, state, s)).flatten().collect();
+            let cond_stmts: Vec<Stmt> = match &conds {
+                None => vec![],
+                Some((cond_stms, _, _)) => {
+                    cond_stms.iter().map(|s| stm_to_stmts(ctx, state, s)).flatten().collect()
+                }
+            };
             let mut air_body: Vec<Stmt> = Vec::new();
-            air_body.append(&mut cond_stmts.clone());
-            air_body.push(pos_assume);
+            if let Some((_, pos_assume, _)) = &conds {
+                air_body.push(pos_assume.clone());
+            }
             air_body.append(&mut stm_to_stmts(ctx, state, body));
 
             /*
@@ -980,15 +1100,30 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
                     local.push(Arc::new(DeclX::Axiom(expr)));
                 }
             }
-            for (_, inv) in invs.iter() {
+            for (_, inv) in invs_entry.iter() {
                 local.push(Arc::new(DeclX::Axiom(inv.clone())));
             }
-            for (span, inv) in invs.iter() {
+            for (span, inv) in invs_entry.iter() {
                 let error = error("invariant not satisfied at end of loop body", span);
-                let inv_stmt = StmtX::Assert(error, inv.clone());
+                let inv_stmt = StmtX::Assert(None, error, inv.clone());
                 air_body.push(Arc::new(inv_stmt));
             }
-            let assertion = one_stmt(air_body);
+            let mut air_stmts = cond_stmts.clone();
+            match &conds {
+                Some((_, _, neg_assume)) if invs_exit_check.len() > 0 => {
+                    // Check the exit invariants when the condition fails
+                    let mut air_exit: Vec<Stmt> = vec![neg_assume.clone()];
+                    for (span, inv, msg) in invs_exit_check.iter() {
+                        let error = error(*msg, span);
+                        air_exit.push(Arc::new(StmtX::Assert(None, error, inv.clone())));
+                    }
+                    let body_block = Arc::new(StmtX::Block(Arc::new(air_body)));
+                    let exit_block = Arc::new(StmtX::Block(Arc::new(air_exit)));
+                    air_stmts.push(Arc::new(StmtX::Switch(Arc::new(vec![body_block, exit_block]))));
+                }
+                _ => air_stmts.append(&mut air_body),
+            }
+            let assertion = one_stmt(air_stmts);
 
             let assertion = if !ctx.debug {
                 assertion
@@ -1004,11 +1139,12 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
             let query = Arc::new(QueryX { local: Arc::new(local), assertion });
             state.commands.push(Arc::new(CommandX::CheckValid(query)));
 
-            // At original site of while loop, assert invariant, havoc, assume invariant + neg_cond
+            // At original site of loop, assert invariant, havoc, assume invariant + neg_cond
+            // (or, if a break can exit the loop, assume the invariants that hold at exit)
             let mut stmts: Vec<Stmt> = Vec::new();
-            for (span, inv) in invs.iter() {
+            for (span, inv) in invs_entry.iter() {
                 let error = error("invariant not satisfied before loop", span);
-                let inv_stmt = StmtX::Assert(error, inv.clone());
+                let inv_stmt = StmtX::Assert(None, error, inv.clone());
                 stmts.push(Arc::new(inv_stmt));
             }
             for x in modified_vars.iter() {
@@ -1022,12 +1158,29 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
                     }
                 }
             }
-            for (_, inv) in invs.iter() {
-                let inv_stmt = StmtX::Assume(inv.clone());
-                stmts.push(Arc::new(inv_stmt));
+            if *has_break {
+                for (_, inv) in invs_exit.iter() {
+                    stmts.push(Arc::new(StmtX::Assume(inv.clone())));
+                }
+            } else {
+                for (_, inv) in invs_entry.iter() {
+                    let inv_stmt = StmtX::Assume(inv.clone());
+                    stmts.push(Arc::new(inv_stmt));
+                }
+                match &conds {
+                    None => {
+                        // A loop with no condition and no break never exits
+                        stmts.push(Arc::new(StmtX::Assume(mk_false())));
+                    }
+                    Some((_, _, neg_assume)) => {
+                        stmts.append(&mut cond_stmts.clone());
+                        stmts.push(neg_assume.clone());
+                        for (_, inv, _) in invs_exit_check.iter() {
+                            stmts.push(Arc::new(StmtX::Assume(inv.clone())));
+                        }
+                    }
+                }
             }
-            stmts.append(&mut cond_stmts.clone());
-            stmts.push(neg_assume);
             if ctx.debug {
                 // Add a snapshot for the state after we emerge from the while loop
                 let sid = state.update_current_sid(SUFFIX_SNAP_WHILE_END);
@@ -1075,7 +1228,7 @@ fn stm_to_stmts(ctx: &Ctx, state: &mut State, stm: &Stm) -> Vec<Stmt> {
             // given by `uid` which may have been assigned to since the start of the block.
             // so this may evaluate differently in the SMT.
             let error = error("Cannot show invariant holds at end of block", &body_stm.span);
-            stmts.push(Arc::new(StmtX::Assert(error, main_inv)));
+            stmts.push(Arc::new(StmtX::Assert(None, error, main_inv)));
 
             stmts
         }
@@ -1144,7 +1297,8 @@ fn set_fuel(local: &mut Vec<Decl>, hidden: &Vec<Fun>) {
         let trigger: Trigger = Arc::new(vec![fuel_bool.clone()]);
         let triggers: Triggers = Arc::new(vec![trigger]);
         let binders: Binders<air::ast::Typ> = Arc::new(vec![ident_binder(&id, &str_typ(FUEL_ID))]);
-        let bind = Arc::new(BindX::Quant(Quant::Forall, binders, triggers));
+        let qid = Some(internal_qid("set_fuel"));
+        let bind = Arc::new(BindX::Quant(Quant::Forall, binders, triggers, qid));
         let or = Arc::new(ExprX::Multi(air::ast::MultiOp::Or, Arc::new(disjuncts)));
         mk_bind_expr(&bind, &or)
     };
@@ -1153,7 +1307,8 @@ fn set_fuel(local: &mut Vec<Decl>, hidden: &Vec<Fun>) {
 
 pub fn body_stm_to_air(
     ctx: &Ctx,
-    trait_typ_substs: &Vec<(Ident, Typ)>,
+    req_typ_substs: &Vec<(Ident, Typ)>,
+    ens_typ_substs: &Vec<(Ident, Typ)>,
     typ_params: &Idents,
     params: &Params,
     local_decls: &Vec<LocalDecl>,
@@ -1170,8 +1325,12 @@ pub fn body_stm_to_air(
     let mut local_shared: Vec<Decl> = Vec::new();
     let mut local_bv_shared: Vec<Decl> = Vec::new();
 
-    let trait_typ_bind =
-        vec_map(trait_typ_substs, |(x, t)| ident_binder(&suffix_typ_param_id(x), &typ_to_id(t)));
+    // reqs and enss inherited from a trait method declaration refer to the trait's type parameters
+    let typ_bind = |substs: &Vec<(Ident, Typ)>| {
+        vec_map(substs, |(x, t)| ident_binder(&suffix_typ_param_id(x), &typ_to_id(t)))
+    };
+    let req_typ_bind = typ_bind(req_typ_substs);
+    let ens_typ_bind = typ_bind(ens_typ_substs);
 
     for x in typ_params.iter() {
         local_shared
@@ -1244,8 +1403,8 @@ pub fn body_stm_to_air(
 
     for ens in enss {
         let error = error("postcondition not satisfied", &ens.span);
-        let e = mk_let(&trait_typ_bind, &exp_to_expr(ctx, ens, ExprCtxt::Body));
-        let ens_stmt = StmtX::Assert(error, e);
+        let e = mk_let(&ens_typ_bind, &exp_to_expr(ctx, ens, ExprCtxt::Body));
+        let ens_stmt = StmtX::Assert(None, error, e);
         stmts.push(Arc::new(ens_stmt));
     }
     let assertion = one_stmt(stmts);
@@ -1259,7 +1418,7 @@ pub fn body_stm_to_air(
     }
 
     for req in reqs {
-        let e = mk_let(&trait_typ_bind, &exp_to_expr(ctx, req, ExprCtxt::BodyPre));
+        let e = mk_let(&req_typ_bind, &exp_to_expr(ctx, req, ExprCtxt::BodyPre));
         local.push(Arc::new(DeclX::Axiom(e)));
     }
 
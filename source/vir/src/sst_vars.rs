@@ -68,10 +68,17 @@ pub(crate) fn stm_assign(
             *assigned = pre_assigned;
             Spanned::new(stm.span.clone(), StmX::If(cond.clone(), lhs, rhs))
         }
-        StmX::While { cond_stms, cond_exp, body, invs, typ_inv_vars, modified_vars } => {
+        StmX::Loop { cond, body, invs, has_break, typ_inv_vars, modified_vars } => {
             let mut pre_modified = modified.clone();
             *modified = HashSet::new();
-            let cond_stms = stms_assign(assign_map, declared, assigned, modified, cond_stms);
+            let cond = match cond {
+                None => None,
+                Some((cond_stms, cond_exp)) => {
+                    let cond_stms =
+                        stms_assign(assign_map, declared, assigned, modified, cond_stms);
+                    Some((cond_stms, cond_exp.clone()))
+                }
+            };
 
             let pre_assigned = assigned.clone();
             let body = stm_assign(assign_map, declared, assigned, modified, body);
@@ -92,15 +99,15 @@ pub(crate) fn stm_assign(
             for x in assigned.iter() {
                 typ_inv_vars.push((x.clone(), declared[x].clone()));
             }
-            let while_x = StmX::While {
-                cond_stms,
-                cond_exp: cond_exp.clone(),
+            let loop_x = StmX::Loop {
+                cond,
                 body,
                 invs: invs.clone(),
+                has_break: *has_break,
                 typ_inv_vars: Arc::new(typ_inv_vars),
                 modified_vars: Arc::new(modified_vars),
             };
-            Spanned::new(stm.span.clone(), while_x)
+            Spanned::new(stm.span.clone(), loop_x)
         }
         StmX::OpenInvariant(inv, ident, ty, body_stm, atomicity) => {
             assigned.insert(ident.clone());
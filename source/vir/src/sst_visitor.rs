@@ -1,6 +1,6 @@
 use crate::ast::{Ident, SpannedTyped, VirErr};
 use crate::def::Spanned;
-use crate::sst::{BndX, Exp, ExpX, Stm, StmX, Trig, Trigs, UniqueIdent};
+use crate::sst::{BndX, Exp, ExpX, LoopInv, Stm, StmX, Trig, Trigs, UniqueIdent};
 use crate::util::vec_map;
 use crate::visitor::expr_visitor_control_flow;
 pub(crate) use crate::visitor::VisitorControlFlow;
@@ -46,7 +46,8 @@ where
                 | ExpX::Var(..)
                 | ExpX::VarAt(..)
                 | ExpX::Old(..)
-                | ExpX::VarLoc(..) => (),
+                | ExpX::VarLoc(..)
+                | ExpX::ConstParam(..) => (),
                 ExpX::Loc(e0) => {
                     expr_visitor_control_flow!(exp_visitor_dfs(e0, map, f));
                 }
@@ -55,7 +56,7 @@ where
                         expr_visitor_control_flow!(exp_visitor_dfs(e, map, f));
                     }
                 }
-                ExpX::CallLambda(_typ, e0, es) => {
+                ExpX::CallLambda(_, e0, es) | ExpX::ClosureSpec(_, e0, es) => {
                     expr_visitor_control_flow!(exp_visitor_dfs(e0, map, f));
                     for e in es.iter() {
                         expr_visitor_control_flow!(exp_visitor_dfs(e, map, f));
@@ -154,16 +155,18 @@ where
                         expr_visitor_control_flow!(stm_visitor_dfs(rhs, f));
                     }
                 }
-                StmX::While {
-                    cond_stms,
-                    cond_exp: _,
+                StmX::Loop {
+                    cond,
                     body,
                     invs: _,
+                    has_break: _,
                     typ_inv_vars: _,
                     modified_vars: _,
                 } => {
-                    for s in cond_stms.iter() {
-                        expr_visitor_control_flow!(stm_visitor_dfs(s, f));
+                    if let Some((cond_stms, _)) = cond {
+                        for s in cond_stms.iter() {
+                            expr_visitor_control_flow!(stm_visitor_dfs(s, f));
+                        }
                     }
                     expr_visitor_control_flow!(stm_visitor_dfs(body, f));
                 }
@@ -209,17 +212,12 @@ where
             StmX::If(exp, _s1, _s2) => {
                 expr_visitor_control_flow!(exp_visitor_dfs(exp, &mut ScopeMap::new(), f))
             }
-            StmX::While {
-                cond_stms: _,
-                cond_exp,
-                body: _,
-                invs,
-                typ_inv_vars: _,
-                modified_vars: _,
-            } => {
-                expr_visitor_control_flow!(exp_visitor_dfs(cond_exp, &mut ScopeMap::new(), f));
+            StmX::Loop { cond, body: _, invs, has_break: _, typ_inv_vars: _, modified_vars: _ } => {
+                if let Some((_, cond_exp)) = cond {
+                    expr_visitor_control_flow!(exp_visitor_dfs(cond_exp, &mut ScopeMap::new(), f));
+                }
                 for inv in invs.iter() {
-                    expr_visitor_control_flow!(exp_visitor_dfs(inv, &mut ScopeMap::new(), f));
+                    expr_visitor_control_flow!(exp_visitor_dfs(&inv.inv, &mut ScopeMap::new(), f));
                 }
             }
             StmX::OpenInvariant(inv, _ident, _ty, _body, _atomicity) => {
@@ -245,6 +243,7 @@ where
         ExpX::Var(..) => f(exp, map),
         ExpX::VarAt(..) => f(exp, map),
         ExpX::VarLoc(..) => f(exp, map),
+        ExpX::ConstParam(..) => f(exp, map),
         ExpX::Loc(e1) => {
             let expr1 = map_exp_visitor_bind(e1, map, f)?;
             let exp = exp_new(ExpX::Loc(expr1));
@@ -268,6 +267,15 @@ where
             let exp = exp_new(ExpX::CallLambda(typ.clone(), e0, Arc::new(exps)));
             f(&exp, map)
         }
+        ExpX::ClosureSpec(spec, e0, es) => {
+            let e0 = map_exp_visitor_bind(e0, map, f)?;
+            let mut exps: Vec<Exp> = Vec::new();
+            for e in es.iter() {
+                exps.push(map_exp_visitor_bind(e, map, f)?);
+            }
+            let exp = exp_new(ExpX::ClosureSpec(*spec, e0, Arc::new(exps)));
+            f(&exp, map)
+        }
         ExpX::Ctor(path, ident, binders) => {
             let mapped_binders = binders
                 .iter()
@@ -401,19 +409,25 @@ where
             let stm = Spanned::new(stm.span.clone(), StmX::If(cond.clone(), lhs, rhs));
             f(&stm)
         }
-        StmX::While { cond_stms, cond_exp, body, invs, typ_inv_vars, modified_vars } => {
-            let mut cs: Vec<Stm> = Vec::new();
-            for s in cond_stms.iter() {
-                cs.push(map_stm_visitor(s, f)?);
-            }
+        StmX::Loop { cond, body, invs, has_break, typ_inv_vars, modified_vars } => {
+            let cond = match cond {
+                None => None,
+                Some((cond_stms, cond_exp)) => {
+                    let mut cs: Vec<Stm> = Vec::new();
+                    for s in cond_stms.iter() {
+                        cs.push(map_stm_visitor(s, f)?);
+                    }
+                    Some((Arc::new(cs), cond_exp.clone()))
+                }
+            };
             let body = map_stm_visitor(body, f)?;
             let stm = Spanned::new(
                 stm.span.clone(),
-                StmX::While {
-                    cond_stms: Arc::new(cs),
-                    cond_exp: cond_exp.clone(),
+                StmX::Loop {
+                    cond,
                     body,
                     invs: invs.clone(),
+                    has_break: *has_break,
                     typ_inv_vars: typ_inv_vars.clone(),
                     modified_vars: modified_vars.clone(),
                 },
@@ -463,16 +477,18 @@ where
                 let exp = f(exp);
                 Spanned::new(span, StmX::If(exp, s1.clone(), s2.clone()))
             }
-            StmX::While { cond_stms, cond_exp, body, invs, typ_inv_vars, modified_vars } => {
-                let cond_exp = f(cond_exp);
-                let invs = Arc::new(vec_map(invs, f));
+            StmX::Loop { cond, body, invs, has_break, typ_inv_vars, modified_vars } => {
+                let cond =
+                    cond.as_ref().map(|(cond_stms, cond_exp)| (cond_stms.clone(), f(cond_exp)));
+                let invs =
+                    Arc::new(vec_map(invs, |inv| LoopInv { inv: f(&inv.inv), ..inv.clone() }));
                 Spanned::new(
                     span,
-                    StmX::While {
-                        cond_stms: cond_stms.clone(),
-                        cond_exp,
+                    StmX::Loop {
+                        cond,
                         body: body.clone(),
                         invs,
+                        has_break: *has_break,
                         typ_inv_vars: typ_inv_vars.clone(),
                         modified_vars: modified_vars.clone(),
                     },
@@ -1,6 +1,17 @@
-use crate::ast::{Fun, Function, FunctionKind, GenericBoundX, Krate, Path, VirErr};
-use crate::ast_util::err_string;
-use crate::def::Spanned;
+use crate::ast::{
+    AssocTypeImplX, ExprX, Fun, Function, FunctionKind, GenericBoundX, Ident, Krate, Path,
+    SpannedTyped, Typ, TypX, Typs, VirErr,
+};
+use crate::ast_util::{err_string, is_visible_to, types_equal};
+use crate::ast_visitor::VisitorScopeMap;
+use crate::context::Ctx;
+use crate::def::{prefix_projection, Spanned, TYPE};
+use crate::func_to_air::func_bind;
+use crate::sst_to_air::{projection_id, typ_to_id};
+use crate::util::{vec_map, vec_map_result};
+use air::ast::{Command, CommandX, Commands, DeclX};
+use air::ast_util::{mk_bind_expr, mk_eq, str_typ};
+use air::scope_map::ScopeMap;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
@@ -35,9 +46,30 @@ pub fn demote_foreign_traits(krate: &Krate) -> Result<Krate, VirErr> {
             let mut functionx = function.x.clone();
             if our_trait {
                 let decl = &func_map[method];
+                let ret_name = function.x.ret.x.name.clone();
+                let decl_ret_name = decl.x.ret.x.name.clone();
                 let mut retx = functionx.ret.x.clone();
-                retx.name = decl.x.ret.x.name.clone();
+                retx.name = decl_ret_name.clone();
                 functionx.ret = Spanned::new(functionx.ret.span.clone(), retx);
+                // The implementation's own ensures must refer to the renamed return value
+                let ensure = vec_map_result(&functionx.ensure, |e| {
+                    let mut map: VisitorScopeMap = ScopeMap::new();
+                    crate::ast_visitor::map_expr_visitor_env(
+                        e,
+                        &mut map,
+                        &mut (),
+                        &|_, map, expr| match &expr.x {
+                            ExprX::Var(x) if x == &ret_name && !map.contains_key(x) => {
+                                let var = ExprX::Var(decl_ret_name.clone());
+                                Ok(SpannedTyped::new(&expr.span, &expr.typ, var))
+                            }
+                            _ => Ok(expr.clone()),
+                        },
+                        &|_, _, stmt| Ok(vec![stmt.clone()]),
+                        &|_, typ| Ok(typ.clone()),
+                    )
+                })?;
+                functionx.ensure = Arc::new(ensure);
             } else {
                 functionx.kind = FunctionKind::Static;
             }
@@ -45,5 +77,128 @@ pub fn demote_foreign_traits(krate: &Krate) -> Result<Krate, VirErr> {
         }
     }
 
+    // Associated types of traits from other crates are left to rustc
+    kratex.assoc_type_impls.retain(|a| traits.contains(&a.x.trait_path));
+
     Ok(Arc::new(kratex))
 }
+
+/// Does function implement a trait method with its own requires and/or ensures?
+/// Such an implementation is verified against its own specification,
+/// which must refine the specification of the trait method declaration:
+/// the trait's requires must imply the implementation's requires,
+/// and the implementation's ensures must imply the trait's ensures.
+/// If the implementation declares only requires or only ensures,
+/// it inherits the other from the trait method declaration.
+pub(crate) fn has_own_spec(function: &Function) -> bool {
+    matches!(function.x.kind, FunctionKind::TraitMethodImpl { .. })
+        && function.x.require.len() + function.x.ensure.len() > 0
+}
+
+fn match_typ(pattern: &Typ, typ: &Typ, substs: &mut HashMap<Ident, Typ>) -> bool {
+    match (&**pattern, &**typ) {
+        (TypX::Boxed(p), _) => match_typ(p, typ, substs),
+        (_, TypX::Boxed(t)) => match_typ(pattern, t, substs),
+        (TypX::TypParam(x), _) => match substs.get(x) {
+            Some(t) => types_equal(t, typ),
+            None => {
+                substs.insert(x.clone(), typ.clone());
+                true
+            }
+        },
+        (TypX::Tuple(ps), TypX::Tuple(ts)) => match_typs(ps, ts, substs),
+        (TypX::Datatype(p1, ps), TypX::Datatype(p2, ts)) => p1 == p2 && match_typs(ps, ts, substs),
+        _ => types_equal(pattern, typ),
+    }
+}
+
+fn match_typs(patterns: &Typs, typs: &Typs, substs: &mut HashMap<Ident, Typ>) -> bool {
+    patterns.len() == typs.len()
+        && patterns.iter().zip(typs.iter()).all(|(p, t)| match_typ(p, t, substs))
+}
+
+/// For a call to the trait method declaration method with type arguments typs,
+/// if Self is a datatype whose implementation of the method has its own requires and/or ensures,
+/// return the implementation and its type arguments.
+/// (Callers may always use the trait method declaration's specification instead,
+/// since the implementation's specification refines it.)
+pub(crate) fn refined_method_impl(ctx: &Ctx, method: &Fun, typs: &Typs) -> Option<(Fun, Typs)> {
+    let decl = ctx.func_map.get(method)?;
+    if !matches!(decl.x.kind, FunctionKind::TraitMethodDecl { .. }) {
+        return None;
+    }
+    let datatype = match typs.first().map(|t| &**t) {
+        Some(TypX::Datatype(datatype, _)) => datatype,
+        Some(TypX::Boxed(t)) => match &**t {
+            TypX::Datatype(datatype, _) => datatype,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let impl_name = ctx.global.method_map.get(&(method.clone(), datatype.clone()))?;
+    let function = ctx.func_map.get(impl_name)?;
+    // The implementation's requires/ensures are only declared in modules that can see it
+    if !has_own_spec(function) || !is_visible_to(&function.x.visibility, &ctx.module) {
+        return None;
+    }
+    if let FunctionKind::TraitMethodImpl { trait_typ_args, datatype, datatype_typ_args, .. } =
+        &function.x.kind
+    {
+        // typs = [Self, trait type arguments, method type arguments]
+        // impl type parameters = [impl type parameters, method type parameters]
+        let num_method_typs = typs.len().checked_sub(1 + trait_typ_args.len())?;
+        let self_typ = Arc::new(TypX::Datatype(datatype.clone(), datatype_typ_args.clone()));
+        let mut patterns = vec![self_typ];
+        patterns.extend(trait_typ_args.iter().cloned());
+        let mut substs: HashMap<Ident, Typ> = HashMap::new();
+        let decl_typs = Arc::new(typs[..typs.len() - num_method_typs].to_vec());
+        if !match_typs(&Arc::new(patterns), &decl_typs, &mut substs) {
+            return None;
+        }
+        let typ_params = function.x.typ_params();
+        let num_impl_typs = typ_params.len().checked_sub(num_method_typs)?;
+        let mut impl_typs: Vec<Typ> = Vec::new();
+        for x in typ_params[..num_impl_typs].iter() {
+            impl_typs.push(substs.get(x)?.clone());
+        }
+        impl_typs.extend(typs[typs.len() - num_method_typs..].iter().cloned());
+        Some((impl_name.clone(), Arc::new(impl_typs)))
+    } else {
+        None
+    }
+}
+
+/// Declare a type identifier function for each associated type of each trait,
+/// and define it for each associated type definition in the trait's impls:
+///   (declare-fun proj%%T.Item (Type ...) Type)
+///   (axiom (forall ((A& Type) ...) (= (proj%%T.Item TYPE%D.(A&) ...) typ_id)))
+pub fn traits_to_air(ctx: &Ctx, krate: &Krate) -> Commands {
+    let mut commands: Vec<Command> = Vec::new();
+    for tr in krate.traits.iter() {
+        for name in tr.x.assoc_typs.iter() {
+            // Self and the trait's type parameters
+            let typs = vec![str_typ(TYPE); 1 + tr.x.typ_params.len()];
+            let f_name = prefix_projection(&tr.x.name, name);
+            let decl = Arc::new(DeclX::Fun(f_name, Arc::new(typs), str_typ(TYPE)));
+            commands.push(Arc::new(CommandX::Global(decl)));
+        }
+    }
+    for assoc in krate.assoc_type_impls.iter() {
+        let AssocTypeImplX { name, typ_params, self_typ, trait_path, trait_typ_args, typ } =
+            &assoc.x;
+        let mut typs = vec![self_typ.clone()];
+        typs.extend(trait_typ_args.iter().cloned());
+        let proj = projection_id(trait_path, &Arc::new(typs), name);
+        let eq = mk_eq(&proj, &typ_to_id(typ));
+        let typ_params = Arc::new(vec_map(&**typ_params, |(x, _)| x.clone()));
+        let axiom = if typ_params.len() == 0 {
+            eq
+        } else {
+            let qid = format!("{}_definition", prefix_projection(trait_path, name));
+            let bind = func_bind(ctx, &qid, &typ_params, &Arc::new(vec![]), &proj, false);
+            mk_bind_expr(&bind, &eq)
+        };
+        commands.push(Arc::new(CommandX::Global(Arc::new(DeclX::Axiom(axiom)))));
+    }
+    Arc::new(commands)
+}
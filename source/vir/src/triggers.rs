@@ -29,6 +29,7 @@ fn remove_boxing(exp: &Exp) -> Exp {
 fn check_trigger_expr(exp: &Exp, free_vars: &mut HashSet<Ident>) -> Result<(), VirErr> {
     match &exp.x {
         ExpX::Call(..)
+        | ExpX::ClosureSpec(..)
         | ExpX::UnaryOpr(UnaryOpr::Field { .. }, _)
         | ExpX::Unary(UnaryOp::Trigger(_), _) => {}
         // REVIEW: Z3 allows some arithmetic, but it's not clear we want to allow it
@@ -43,6 +44,7 @@ fn check_trigger_expr(exp: &Exp, free_vars: &mut HashSet<Ident>) -> Result<(), V
         &mut |exp, _scope_map| match &exp.x {
             ExpX::Const(_)
             | ExpX::CallLambda(..)
+            | ExpX::ClosureSpec(..)
             | ExpX::Ctor(..)
             | ExpX::Loc(..)
             | ExpX::VarLoc(..) => Ok(()),
@@ -64,7 +66,12 @@ fn check_trigger_expr(exp: &Exp, free_vars: &mut HashSet<Ident>) -> Result<(), V
                 Ok(())
             }
             ExpX::Var((_, Some(_))) => Ok(()),
+            ExpX::ConstParam(x) => {
+                free_vars.insert(crate::def::suffix_typ_param_id(x));
+                Ok(())
+            }
             ExpX::VarAt(_, VarAt::Pre) => Ok(()),
+            ExpX::VarAt(_, VarAt::Final) => panic!("internal error: VarAt::Final"),
             ExpX::Old(_, _) => panic!("internal error: Old"),
             ExpX::Unary(op, _) => match op {
                 UnaryOp::Trigger(_) | UnaryOp::Clip(_) | UnaryOp::BitNot => Ok(()),
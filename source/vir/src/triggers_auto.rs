@@ -1,5 +1,5 @@
 use crate::ast::{
-    BinaryOp, Constant, Fun, Ident, Path, Typ, TypX, UnaryOp, UnaryOpr, VarAt, VirErr,
+    BinaryOp, ClosureSpec, Constant, Fun, Ident, Path, Typ, TypX, UnaryOp, UnaryOpr, VarAt, VirErr,
 };
 use crate::ast_util::{err_str, path_as_rust_name};
 use crate::context::Ctx;
@@ -36,6 +36,8 @@ enum App {
     Call(Fun),
     // datatype constructor: (Path, Variant)
     Ctor(Path, Ident),
+    // requires/ensures of an exec closure: f.requires(args), f.ensures(args, ret)
+    ClosureSpec(ClosureSpec),
     // u64 is an id, assigned via a simple counter
     Other(u64),
     VarAt(UniqueIdent, VarAt),
@@ -71,12 +73,29 @@ impl std::fmt::Debug for TermX {
                 }
                 write!(f, ")")
             }
+            TermX::App(App::ClosureSpec(spec), es) => {
+                let spec = match spec {
+                    ClosureSpec::Requires => "requires",
+                    ClosureSpec::Ensures => "ensures",
+                };
+                write!(f, "{:?}.{}(", es[0], spec)?;
+                for i in 1..es.len() {
+                    write!(f, "{:?}", es[i])?;
+                    if i < es.len() - 1 {
+                        write!(f, ", ")?;
+                    }
+                }
+                write!(f, ")")
+            }
             TermX::App(App::Other(_), _) => {
                 write!(f, "_")
             }
             TermX::App(App::VarAt((x, _), VarAt::Pre), _) => {
                 write!(f, "old({})", x)
             }
+            TermX::App(App::VarAt((x, _), VarAt::Final), _) => {
+                write!(f, "fin({})", x)
+            }
         }
     }
 }
@@ -203,6 +222,11 @@ fn gather_terms(ctxt: &mut Ctxt, ctx: &Ctx, exp: &Exp, depth: u64) -> (bool, Ter
     let (is_pure, term) = match &exp.x {
         ExpX::Const(c) => (true, Arc::new(TermX::App(App::Const(c.clone()), Arc::new(vec![])))),
         ExpX::Var(x) => (true, Arc::new(TermX::Var(x.clone()))),
+        ExpX::ConstParam(x) => {
+            // const_int(x) is clipped to x's integer type, so we don't trigger on it
+            let x = (crate::def::suffix_typ_param_id(x), None);
+            (false, Arc::new(TermX::App(ctxt.other(), Arc::new(vec![Arc::new(TermX::Var(x))]))))
+        }
         ExpX::VarLoc(..) | ExpX::Loc(..) => panic!("unexpected Loc/VarLoc in quantifier"),
         ExpX::VarAt(x, _) => {
             (true, Arc::new(TermX::App(App::VarAt(x.clone(), VarAt::Pre), Arc::new(vec![]))))
@@ -241,6 +265,14 @@ fn gather_terms(ctxt: &mut Ctxt, ctx: &Ctx, exp: &Exp, depth: u64) -> (bool, Ter
             terms.insert(0, term0);
             (false, Arc::new(TermX::App(ctxt.other(), Arc::new(terms))))
         }
+        ExpX::ClosureSpec(spec, e0, es) => {
+            let (is_pures, terms): (Vec<bool>, Vec<Term>) = std::iter::once(e0)
+                .chain(es.iter())
+                .map(|e| gather_terms(ctxt, ctx, e, depth + 1))
+                .unzip();
+            let is_pure = is_pures.into_iter().all(|b| b);
+            (is_pure, Arc::new(TermX::App(App::ClosureSpec(*spec), Arc::new(terms))))
+        }
         ExpX::Ctor(path, variant, fields) => {
             let (variant, args) = crate::sst_to_air::ctor_to_apply(ctx, path, variant, fields);
             let (is_pures, terms): (Vec<bool>, Vec<Term>) =
@@ -1,10 +1,14 @@
 use crate::ast::{
-    CallTarget, Datatype, ExprX, Fun, FunX, Function, FunctionKind, Krate, MaskSpec, Mode, Path,
-    PathX, TypX, UnaryOpr, VirErr,
+    Binders, CallTarget, Datatype, Expr, ExprX, Fun, FunX, Function, FunctionKind, Krate, MaskSpec,
+    Mode, Path, PathX, Typ, TypX, UnaryOpr, VarAt, VirErr,
 };
 use crate::ast_util::{err_str, err_string};
+use crate::ast_visitor::{expr_visitor_dfs, VisitorControlFlow, VisitorScopeMap};
 use crate::datatype_to_air::is_datatype_transparent;
-use crate::early_exit_cf::assert_no_early_exit_in_inv_block;
+use crate::early_exit_cf::{
+    assert_no_early_exit_in_exec_closure, assert_no_early_exit_in_inv_block,
+};
+use air::scope_map::ScopeMap;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -13,6 +17,34 @@ struct Ctxt {
     pub(crate) dts: HashMap<Path, Datatype>,
 }
 
+// Local variables and (possibly nested) fields of local variables
+// (ast_simplify desugars borrows of fields into borrows of temporary variables)
+fn is_var_or_field_loc(expr: &Expr) -> bool {
+    match &expr.x {
+        ExprX::VarLoc(_) => true,
+        ExprX::UnaryOpr(UnaryOpr::Field { .. }, e1) => is_var_or_field_loc(e1),
+        _ => false,
+    }
+}
+
+// Exec closures may read, but not modify, the variables they capture
+fn check_exec_closure_body(params: &Binders<Typ>, body: &Expr) -> Result<(), VirErr> {
+    let mut scope_map: VisitorScopeMap = ScopeMap::new();
+    scope_map.push_scope(true);
+    for param in params.iter() {
+        let _ = scope_map.insert(param.name.clone(), param.a.clone());
+    }
+    match expr_visitor_dfs(body, &mut scope_map, &mut |scope_map, expr| match &expr.x {
+        ExprX::VarLoc(x) if !scope_map.contains_key(x) => VisitorControlFlow::Stop(expr.clone()),
+        _ => VisitorControlFlow::Recurse,
+    }) {
+        VisitorControlFlow::Stop(expr) => {
+            err_str(&expr.span, "exec closure cannot modify captured variables")
+        }
+        _ => Ok(()),
+    }
+}
+
 fn check_function(ctxt: &Ctxt, function: &Function) -> Result<(), VirErr> {
     if let FunctionKind::TraitMethodDecl { .. } = function.x.kind {
         if function.x.body.is_some() && function.x.mode != Mode::Exec {
@@ -32,12 +64,6 @@ fn check_function(ctxt: &Ctxt, function: &Function) -> Result<(), VirErr> {
     }
 
     if let FunctionKind::TraitMethodImpl { .. } = &function.x.kind {
-        if function.x.require.len() + function.x.ensure.len() != 0 {
-            return err_str(
-                &function.span,
-                "trait method implementation cannot declare requires/ensures; these can only be inherited from the trait declaration",
-            );
-        }
         if !matches!(function.x.mask_spec, MaskSpec::NoSpec) {
             return err_str(
                 &function.span,
@@ -52,6 +78,35 @@ fn check_function(ctxt: &Ctxt, function: &Function) -> Result<(), VirErr> {
         }
     }
 
+    if function.x.ret.x.is_mut {
+        if function.x.mode != Mode::Exec || function.x.ret.x.mode != Mode::Exec {
+            return err_str(&function.span, "only exec functions can return &mut");
+        }
+        if !function.x.has_return() {
+            return err_str(&function.span, "a function returning &mut must return a value");
+        }
+    }
+
+    // fin(x) is only allowed in ensures, for x the &mut return value
+    for expr in function.x.require.iter().chain(function.x.body.iter()) {
+        crate::ast_visitor::expr_visitor_check(expr, &mut |expr| match &expr.x {
+            ExprX::VarAt(_, VarAt::Final) => {
+                err_str(&expr.span, "fin(...) can only be used in ensures")
+            }
+            _ => Ok(()),
+        })?;
+    }
+    for ens in function.x.ensure.iter() {
+        crate::ast_visitor::expr_visitor_check(ens, &mut |expr| match &expr.x {
+            ExprX::VarAt(x, VarAt::Final)
+                if !function.x.ret.x.is_mut || *x != function.x.ret.x.name =>
+            {
+                err_str(&expr.span, "fin(...) can only be applied to an &mut return value")
+            }
+            _ => Ok(()),
+        })?;
+    }
+
     if function.x.attrs.atomic {
         if function.x.mode != Mode::Exec {
             return err_str(&function.span, "'atomic' only makes sense on an 'exec' function");
@@ -166,10 +221,7 @@ fn check_function(ctxt: &Ctxt, function: &Function) -> Result<(), VirErr> {
                         f.x.params.iter().zip(args.iter()).filter(|(p, _)| p.x.is_mut)
                     {
                         let ok = match &arg.x {
-                            ExprX::Loc(l) => match l.x {
-                                ExprX::VarLoc(_) => true,
-                                _ => false,
-                            },
+                            ExprX::Loc(l) => is_var_or_field_loc(l),
                             _ => false,
                         };
                         if !ok {
@@ -234,6 +286,10 @@ fn check_function(ctxt: &Ctxt, function: &Function) -> Result<(), VirErr> {
                 ExprX::OpenInvariant(_inv, _binder, body, _atomicity) => {
                     assert_no_early_exit_in_inv_block(&body.span, body)?;
                 }
+                ExprX::ExecClosure { params, body, .. } => {
+                    assert_no_early_exit_in_exec_closure(&expr.span, body)?;
+                    check_exec_closure_body(params, body)?;
+                }
                 _ => {}
             }
             Ok(())